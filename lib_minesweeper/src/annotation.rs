@@ -0,0 +1,126 @@
+#[cfg(feature = "serde")]
+use serde_derive::{Deserialize, Serialize};
+
+use crate::Board;
+use crate::MapElement::{Mine, Number};
+use crate::MapElementCellState::{Closed, Flagged, Open};
+use crate::Point;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Tag {
+    Red,
+    Blue,
+    Green,
+    Yellow,
+}
+
+impl Tag {
+    pub fn next(self) -> Tag {
+        match self {
+            Tag::Red => Tag::Blue,
+            Tag::Blue => Tag::Green,
+            Tag::Green => Tag::Yellow,
+            Tag::Yellow => Tag::Red,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Tag::Red => "red",
+            Tag::Blue => "blue",
+            Tag::Green => "green",
+            Tag::Yellow => "yellow",
+        }
+    }
+}
+
+// A note on some cells, like "one of these two is a mine", for working a
+// board out. It changes nothing about how the board plays.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Annotation {
+    pub cells: Vec<Point>,
+    pub tag: Tag,
+    pub note: String,
+}
+
+impl Annotation {
+    // A note for every pair of closed cells an open number has one mine
+    // left between, each pair once. These are the notes expert players
+    // keep in their heads.
+    pub fn suggest(board: &Board) -> Vec<Annotation> {
+        let mut suggested: Vec<Annotation> = vec![];
+        for p in board.points() {
+            let count = match board.at(&p) {
+                Some(Number { state: Open, count }) => *count as usize,
+                _ => continue,
+            };
+            let neighbours = board.neighbours(&p);
+            let flagged = neighbours
+                .iter()
+                .filter(|n| {
+                    matches!(
+                        board.at(n),
+                        Some(Number { state: Flagged, .. }) | Some(Mine { state: Flagged })
+                    )
+                })
+                .count();
+            let closed: Vec<Point> = neighbours
+                .into_iter()
+                .filter(|n| {
+                    matches!(
+                        board.at(n),
+                        Some(Number { state: Closed, .. }) | Some(Mine { state: Closed })
+                    )
+                })
+                .collect();
+            if count.checked_sub(flagged) != Some(1) || closed.len() != 2 {
+                continue;
+            }
+            if suggested.iter().any(|a| a.has_cells(&closed)) {
+                continue;
+            }
+            suggested.push(Annotation {
+                cells: closed,
+                tag: Tag::Red,
+                note: String::from("One of these two is a mine"),
+            });
+        }
+        suggested
+    }
+
+    // Whether it's on exactly `cells`, in any order.
+    pub fn has_cells(&self, cells: &[Point]) -> bool {
+        self.cells.len() == cells.len() && cells.iter().all(|p| self.cells.contains(p))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Adjacency;
+    use crate::BoardBuilder;
+    use crate::Move;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_suggest() {
+        // One mine in the corner of a 3x2 board, so opening the other end
+        // leaves it and the cell above it closed.
+        let board = BoardBuilder::new(3, 2)
+            .mines_at(vec![Point::new(0, 1)])
+            .adjacency(Adjacency::King)
+            .build()
+            .unwrap();
+        assert_eq!(Annotation::suggest(&board), vec![]);
+        let board = board.apply(&Move::Open(Point::new(2, 0))).unwrap();
+        // The 1 at (1, 0) has (0, 0) and (0, 1) closed around it, and so
+        // does the 1 at (1, 1), but the pair is only suggested once.
+        let suggested = Annotation::suggest(&board);
+        assert_eq!(suggested.len(), 1);
+        assert!(suggested[0].has_cells(&[Point::new(0, 1), Point::new(0, 0)]));
+        let board = board.apply(&Move::Flag(Point::new(0, 1))).unwrap();
+        assert_eq!(Annotation::suggest(&board), vec![]);
+    }
+}
@@ -0,0 +1,559 @@
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::fmt;
+
+use crate::mine_map;
+use crate::placed_mines;
+use crate::random_mines;
+use crate::Adjacency;
+use crate::Board;
+use crate::Cascade;
+use crate::MapElement::Mine;
+use crate::MapElement::Number;
+use crate::MapElementCellState;
+use crate::Point;
+use crate::Spacing;
+use crate::Symmetry;
+
+// How many boards to draw when looking for a big enough opening.
+const OPENING_ATTEMPTS: usize = 200;
+
+// How many cells, over all the boards drawn, looking for a big enough
+// opening may number and search. Each draw covers the whole board, so big
+// boards get fewer, down to one.
+const OPENING_CELLS: usize = 1_000_000;
+
+// The most cells a board can have, so sizes from files and other players
+// can't ask for more memory than there is.
+pub const MAX_CELLS: usize = 4_000_000;
+
+#[derive(Debug, PartialEq, Clone)]
+enum Mines {
+    Count(usize),
+    At(Vec<Point>),
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct BoardBuilder {
+    width: usize,
+    height: usize,
+    mines: Mines,
+    adjacency: Adjacency,
+    seed: Option<u64>,
+    symmetry: Symmetry,
+    spacing: Option<Spacing>,
+    min_opening: Option<usize>,
+    cascade: Cascade,
+    states: Vec<(Point, MapElementCellState)>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum BuildBoardError {
+    Empty,
+    TooBig { width: usize, height: usize },
+    TooManyMines { mines: usize, cells: usize },
+    OutOfBounds { point: Point },
+    NoSymmetricLayout { mines: usize },
+    TooSpread { distance: usize },
+}
+
+impl fmt::Display for BuildBoardError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BuildBoardError::Empty => write!(f, "board needs at least one row and column"),
+            BuildBoardError::TooBig { width, height } => {
+                write!(
+                    f,
+                    "{}x{} is more than the {} cells a board can have",
+                    width, height, MAX_CELLS
+                )
+            }
+            BuildBoardError::TooManyMines { mines, cells } => {
+                write!(f, "can't fit {} mines in {} cells", mines, cells)
+            }
+            BuildBoardError::OutOfBounds { point } => {
+                write!(
+                    f,
+                    "column {}, row {} is outside the board",
+                    point.x, point.y
+                )
+            }
+            BuildBoardError::NoSymmetricLayout { mines } => {
+                write!(f, "can't place {} mines symmetrically on this board", mines)
+            }
+            BuildBoardError::TooSpread { distance } => {
+                write!(f, "can't keep mines {} moves apart on this board", distance)
+            }
+        }
+    }
+}
+
+impl std::error::Error for BuildBoardError {}
+
+impl BoardBuilder {
+    pub fn new(width: usize, height: usize) -> BoardBuilder {
+        BoardBuilder {
+            width,
+            height,
+            mines: Mines::Count(0),
+            adjacency: Adjacency::Knight,
+            seed: None,
+            symmetry: Symmetry::None,
+            spacing: None,
+            min_opening: None,
+            cascade: Cascade::Zeros,
+            states: vec![],
+        }
+    }
+
+    // Places this many mines randomly, using the seed if one is given.
+    pub fn mine_count(mut self, mines: usize) -> BoardBuilder {
+        self.mines = Mines::Count(mines);
+        self
+    }
+
+    pub fn mines_at(mut self, points: Vec<Point>) -> BoardBuilder {
+        self.mines = Mines::At(points);
+        self
+    }
+
+    pub fn adjacency(mut self, adjacency: Adjacency) -> BoardBuilder {
+        self.adjacency = adjacency;
+        self
+    }
+
+    pub fn seed(mut self, seed: u64) -> BoardBuilder {
+        self.seed = Some(seed);
+        self
+    }
+
+    // Only applies to mines placed randomly.
+    pub fn symmetry(mut self, symmetry: Symmetry) -> BoardBuilder {
+        self.symmetry = symmetry;
+        self
+    }
+
+    // Only applies to mines placed randomly, see Spacing. Distances longer
+    // than the board are an error.
+    pub fn spacing(mut self, spacing: Spacing) -> BoardBuilder {
+        self.spacing = Some(spacing);
+        self
+    }
+
+    // Redraws randomly placed mines until clicking a zero opens at least
+    // this many cells, see Board::largest_opening. Gives up after a while,
+    // sooner on big boards, and keeps the board with the biggest opening.
+    pub fn min_opening(mut self, cells: usize) -> BoardBuilder {
+        self.min_opening = Some(cells);
+        self
+    }
+
+    pub fn cascade(mut self, cascade: Cascade) -> BoardBuilder {
+        self.cascade = cascade;
+        self
+    }
+
+    pub fn cell_state(mut self, point: Point, state: MapElementCellState) -> BoardBuilder {
+        self.states.push((point, state));
+        self
+    }
+
+    // The errors `build` can tell from the options alone, without placing
+    // any mines.
+    pub fn check(&self) -> Result<(), BuildBoardError> {
+        if self.width == 0 || self.height == 0 {
+            return Err(BuildBoardError::Empty);
+        }
+        let (width, height) = (self.width, self.height);
+        if width
+            .checked_mul(height)
+            .is_none_or(|cells| cells > MAX_CELLS)
+        {
+            return Err(BuildBoardError::TooBig { width, height });
+        }
+        if let Mines::Count(mines) = self.mines {
+            let cells = width * height;
+            if mines > cells {
+                return Err(BuildBoardError::TooManyMines { mines, cells });
+            }
+            if let Some(spacing) = self.spacing.filter(|s| !s.fits_board(width, height)) {
+                return Err(BuildBoardError::TooSpread {
+                    distance: spacing.distance,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    pub fn build(self) -> Result<Board, BuildBoardError> {
+        self.check()?;
+        let (width, height) = (self.width, self.height);
+        let in_bounds =
+            |p: &Point| p.x >= 0 && p.y >= 0 && (p.x as usize) < width && (p.y as usize) < height;
+
+        let mut board = match &self.mines {
+            Mines::Count(mines) => {
+                let seed = self.seed.unwrap_or_else(|| rand::thread_rng().gen());
+                let mut rng = StdRng::seed_from_u64(seed);
+                // Keeps drawing boards until one has a big enough opening,
+                // settling for the one with the biggest. No opening can be
+                // bigger than the cells without mines, and with no mines or
+                // nothing but mines every draw is the same board.
+                let cells = width * height;
+                let min_opening = self.min_opening.map(|min| min.min(cells - mines));
+                let draws = match *mines {
+                    0 => 1,
+                    mines if mines == cells => 1,
+                    _ => (OPENING_CELLS / cells).clamp(1, OPENING_ATTEMPTS),
+                };
+                let mut best: Option<(usize, Board)> = None;
+                for _ in 0..draws {
+                    let board = Board {
+                        seed: Some(seed),
+                        symmetry: self.symmetry,
+                        spacing: self.spacing,
+                        min_opening: self.min_opening,
+                        ..self.numbered(&self.random_points(*mines, &mut rng)?)
+                    };
+                    let min = match min_opening {
+                        Some(min) => min,
+                        None => {
+                            best = Some((0, board));
+                            break;
+                        }
+                    };
+                    let opening = board.largest_opening();
+                    if best.as_ref().is_none_or(|(most, _)| opening > *most) {
+                        best = Some((opening, board));
+                    }
+                    if opening >= min {
+                        break;
+                    }
+                }
+                best.unwrap().1
+            }
+            Mines::At(points) => {
+                if let Some(point) = points.iter().find(|p| !in_bounds(p)) {
+                    return Err(BuildBoardError::OutOfBounds { point: *point });
+                }
+                self.numbered(points)
+            }
+        };
+
+        for (point, state) in self.states {
+            if !in_bounds(&point) {
+                return Err(BuildBoardError::OutOfBounds { point });
+            }
+            let el = board.map.get_mut(point.x as usize, point.y as usize);
+            *el = match el {
+                Mine { .. } => Mine { state },
+                Number { count, .. } => Number {
+                    state,
+                    count: *count,
+                },
+            };
+        }
+        Ok(board.settled())
+    }
+
+    fn random_points(&self, mines: usize, rng: &mut StdRng) -> Result<Vec<Point>, BuildBoardError> {
+        let rand = |x, y| rng.gen_range(x, y);
+        match (self.symmetry, self.spacing) {
+            (Symmetry::None, None) => Ok(random_mines(self.width, self.height, mines, rand)),
+            (symmetry, spacing) => {
+                placed_mines(self.width, self.height, mines, symmetry, spacing, rand)
+                    .ok_or(BuildBoardError::NoSymmetricLayout { mines })
+            }
+        }
+    }
+
+    fn numbered(&self, points: &[Point]) -> Board {
+        Board {
+            adjacency: self.adjacency,
+            cascade: self.cascade,
+            ..Board::from_map(mine_map(self.width, self.height, points))
+        }
+        .with_numbers()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BoardState;
+    use crate::MapElementCellState::{Closed, Flagged, Open};
+    use crate::Transform;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_build_with_mines_at() {
+        let board = BoardBuilder::new(5, 2)
+            .mines_at(vec![Point::new(0, 0), Point::new(1, 1)])
+            .build()
+            .unwrap();
+        assert_eq!(board.to_string(), "*....\n.*...");
+        assert_eq!(
+            board.at(&Point::new(3, 0)),
+            Some(&Number {
+                state: Closed,
+                count: 1
+            })
+        );
+        assert_eq!(board.state, BoardState::Ready);
+        assert_eq!(board.seed, None);
+    }
+
+    #[test]
+    fn test_build_with_king_adjacency() {
+        let board = BoardBuilder::new(5, 2)
+            .mines_at(vec![Point::new(0, 0), Point::new(1, 1)])
+            .adjacency(Adjacency::King)
+            .build()
+            .unwrap();
+        assert_eq!(
+            board.at(&Point::new(1, 0)),
+            Some(&Number {
+                state: Closed,
+                count: 2
+            })
+        );
+        assert_eq!(
+            board.at(&Point::new(3, 0)),
+            Some(&Number {
+                state: Closed,
+                count: 0
+            })
+        );
+    }
+
+    #[test]
+    fn test_build_with_seed_is_repeatable() {
+        let build = || {
+            BoardBuilder::new(16, 16)
+                .mine_count(40)
+                .seed(42)
+                .build()
+                .unwrap()
+        };
+        let board = build();
+        assert_eq!(board, build());
+        assert_eq!(board.mines, 40);
+        assert_eq!(board.seed, Some(42));
+    }
+
+    #[test]
+    fn test_build_without_seed_records_one() {
+        let board = BoardBuilder::new(8, 8).mine_count(10).build().unwrap();
+        let seed = board.seed.unwrap();
+        let rebuilt = BoardBuilder::new(8, 8)
+            .mine_count(10)
+            .seed(seed)
+            .build()
+            .unwrap();
+        assert_eq!(board, rebuilt);
+    }
+
+    #[test]
+    fn test_build_symmetric() {
+        for (width, height, mines) in &[(8, 8, 10), (7, 9, 11), (7, 9, 62), (1, 1, 1)] {
+            for symmetry in &[Symmetry::Mirror, Symmetry::Rotational] {
+                let board = BoardBuilder::new(*width, *height)
+                    .mine_count(*mines)
+                    .symmetry(*symmetry)
+                    .seed(3)
+                    .build()
+                    .unwrap();
+                assert_eq!(board.mines, *mines);
+                assert_eq!(board.symmetry, *symmetry);
+                for y in 0..*height {
+                    for x in 0..*width {
+                        let (ix, iy) = symmetry.image(*width, *height, x, y);
+                        assert_eq!(
+                            matches!(board.at(&Point::new(x, y)), Some(Mine { .. })),
+                            matches!(board.at(&Point::new(ix, iy)), Some(Mine { .. }))
+                        );
+                    }
+                }
+                let image = match symmetry {
+                    Symmetry::Mirror => Transform::MirrorX,
+                    _ => Transform::Rotate180,
+                };
+                assert_eq!(
+                    board.transformed(image).position_hash(),
+                    board.position_hash()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_build_symmetric_needs_a_middle_for_odd_counts() {
+        assert_eq!(
+            BoardBuilder::new(8, 8)
+                .mine_count(9)
+                .symmetry(Symmetry::Rotational)
+                .build(),
+            Err(BuildBoardError::NoSymmetricLayout { mines: 9 })
+        );
+        let board = BoardBuilder::new(7, 8)
+            .mine_count(9)
+            .symmetry(Symmetry::Mirror)
+            .build()
+            .unwrap();
+        assert_eq!(board.mines, 9);
+    }
+
+    #[test]
+    fn test_build_spaced() {
+        for moves in &[Adjacency::King, Adjacency::Knight] {
+            let spacing = Spacing {
+                distance: 2,
+                moves: *moves,
+            };
+            let board = BoardBuilder::new(16, 16)
+                .mine_count(20)
+                .spacing(spacing)
+                .symmetry(Symmetry::Rotational)
+                .seed(5)
+                .build()
+                .unwrap();
+            assert_eq!(board.mines, 20);
+            assert_eq!(board.spacing, Some(spacing));
+            let mines: Vec<Point> = (0..16)
+                .flat_map(|y| (0..16).map(move |x| Point::new(x, y)))
+                .filter(|p| matches!(board.at(p), Some(Mine { .. })))
+                .collect();
+            let steps = Board {
+                adjacency: *moves,
+                ..board.clone()
+            };
+            for mine in &mines {
+                assert!(steps.neighbours(mine).iter().all(|p| !mines.contains(p)));
+            }
+        }
+    }
+
+    #[test]
+    fn test_build_spaced_falls_back_when_crowded() {
+        let board = BoardBuilder::new(4, 4)
+            .mine_count(12)
+            .spacing(Spacing {
+                distance: 3,
+                moves: Adjacency::Knight,
+            })
+            .seed(1)
+            .build()
+            .unwrap();
+        assert_eq!(board.mines, 12);
+    }
+
+    #[test]
+    fn test_build_spaced_wide_apart() {
+        let spaced = |distance| {
+            BoardBuilder::new(300, 200)
+                .mine_count(10)
+                .spacing(Spacing {
+                    distance,
+                    moves: Adjacency::Knight,
+                })
+                .seed(3)
+                .build()
+        };
+        assert_eq!(spaced(300).unwrap().mines, 10);
+        assert_eq!(
+            spaced(301),
+            Err(BuildBoardError::TooSpread { distance: 301 })
+        );
+    }
+
+    #[test]
+    fn test_build_with_min_opening() {
+        let build = |min_opening| {
+            BoardBuilder::new(16, 30)
+                .mine_count(99)
+                .min_opening(min_opening)
+                .seed(2)
+                .build()
+                .unwrap()
+        };
+        let board = build(40);
+        assert!(board.largest_opening() >= 40);
+        assert_eq!(board.min_opening, Some(40));
+        assert_eq!(board, build(40));
+        let plain = BoardBuilder::new(16, 30)
+            .mine_count(99)
+            .seed(2)
+            .build()
+            .unwrap();
+        assert_eq!(build(0).largest_opening(), plain.largest_opening());
+        assert!(build(10_000).largest_opening() >= board.largest_opening());
+    }
+
+    #[test]
+    fn test_build_with_min_opening_past_the_board() {
+        let board = BoardBuilder::new(300, 300)
+            .mine_count(20_000)
+            .min_opening(usize::MAX)
+            .seed(2)
+            .build()
+            .unwrap();
+        assert_eq!(board.min_opening, Some(usize::MAX));
+        let empty = BoardBuilder::new(8, 8)
+            .min_opening(100)
+            .seed(2)
+            .build()
+            .unwrap();
+        assert_eq!(empty.largest_opening(), 64);
+    }
+
+    #[test]
+    fn test_build_with_preset_states() {
+        let board = BoardBuilder::new(5, 2)
+            .mines_at(vec![Point::new(0, 0), Point::new(1, 1)])
+            .cell_state(Point::new(0, 0), Flagged)
+            .cell_state(Point::new(3, 0), Open)
+            .build()
+            .unwrap();
+        assert_eq!(board.to_string(), "F..1.\n.*...");
+        assert_eq!(board.state, BoardState::Playing);
+    }
+
+    #[test]
+    fn test_build_errors() {
+        assert_eq!(BoardBuilder::new(0, 3).build(), Err(BuildBoardError::Empty));
+        assert_eq!(
+            BoardBuilder::new(usize::MAX, 2).build(),
+            Err(BuildBoardError::TooBig {
+                width: usize::MAX,
+                height: 2
+            })
+        );
+        assert_eq!(
+            BoardBuilder::new(MAX_CELLS, 2).build(),
+            Err(BuildBoardError::TooBig {
+                width: MAX_CELLS,
+                height: 2
+            })
+        );
+        assert_eq!(
+            BoardBuilder::new(2, 2).mine_count(5).build(),
+            Err(BuildBoardError::TooManyMines { mines: 5, cells: 4 })
+        );
+        assert_eq!(
+            BoardBuilder::new(2, 2)
+                .mines_at(vec![Point::new(2, 0)])
+                .build(),
+            Err(BuildBoardError::OutOfBounds {
+                point: Point::new(2, 0)
+            })
+        );
+        assert_eq!(
+            BoardBuilder::new(2, 2)
+                .cell_state(Point { x: -1, y: 0 }, Open)
+                .build(),
+            Err(BuildBoardError::OutOfBounds {
+                point: Point { x: -1, y: 0 }
+            })
+        );
+    }
+}
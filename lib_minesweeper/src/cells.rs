@@ -0,0 +1,234 @@
+use std::fmt;
+use std::sync::Arc;
+
+#[cfg(feature = "serde")]
+use serde_derive::{Deserialize, Serialize};
+
+use crate::MapElement;
+use crate::MapElement::Number;
+use crate::MapElementCellState::Closed;
+use crate::Point;
+
+// Chunks are CHUNK cells wide and high.
+const CHUNK: usize = 32;
+
+// What cells in chunks that were never written to hold: closed, with no
+// mines around them.
+static EMPTY: MapElement = Number {
+    state: Closed,
+    count: 0,
+};
+
+// A board's cells, kept in square chunks that are only allocated once one
+// of their cells is written to. Copies share chunks until they're written
+// to, so a move only copies the chunks it changes, and a huge board stays
+// cheap to copy however little of it has been played. Serializes as rows of
+// cells, like the plain grid it replaced.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(from = "Vec<Vec<MapElement>>", into = "Vec<Vec<MapElement>>")
+)]
+pub(crate) struct Cells {
+    width: usize,
+    height: usize,
+    // Row by row, `width.div_ceil(CHUNK)` chunks to a row.
+    chunks: Vec<Option<Arc<Vec<MapElement>>>>,
+}
+
+impl Cells {
+    pub fn new(width: usize, height: usize) -> Cells {
+        let chunks = width.div_ceil(CHUNK) * height.div_ceil(CHUNK);
+        Cells {
+            width,
+            height,
+            chunks: vec![None; chunks],
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    // The chunk the cell is in, and where in the chunk.
+    fn index(&self, x: usize, y: usize) -> (usize, usize) {
+        let chunk = y / CHUNK * self.width.div_ceil(CHUNK) + x / CHUNK;
+        (chunk, y % CHUNK * CHUNK + x % CHUNK)
+    }
+
+    // `x` and `y` have to be on the board.
+    pub fn get(&self, x: usize, y: usize) -> &MapElement {
+        let (chunk, i) = self.index(x, y);
+        match &self.chunks[chunk] {
+            Some(cells) => &cells[i],
+            None => &EMPTY,
+        }
+    }
+
+    // Allocates the cell's chunk, or copies it if it's shared.
+    pub fn get_mut(&mut self, x: usize, y: usize) -> &mut MapElement {
+        let (chunk, i) = self.index(x, y);
+        let cells =
+            self.chunks[chunk].get_or_insert_with(|| Arc::new(vec![EMPTY.clone(); CHUNK * CHUNK]));
+        &mut Arc::make_mut(cells)[i]
+    }
+
+    // Leaves the chunk alone if the cell already holds `el`.
+    pub fn set(&mut self, x: usize, y: usize, el: MapElement) {
+        if *self.get(x, y) != el {
+            *self.get_mut(x, y) = el;
+        }
+    }
+
+    pub fn row(&self, y: usize) -> Vec<MapElement> {
+        (0..self.width).map(|x| self.get(x, y).clone()).collect()
+    }
+
+    pub fn rows(&self) -> Vec<Vec<MapElement>> {
+        (0..self.height).map(|y| self.row(y)).collect()
+    }
+
+    // Row by row.
+    pub fn iter(&self) -> impl Iterator<Item = &MapElement> + Clone + '_ {
+        (0..self.height).flat_map(move |y| (0..self.width).map(move |x| self.get(x, y)))
+    }
+
+    // The cells that differ from `other`'s, row by row. Only looks inside
+    // chunks the two don't share, so it's quick for boards a move apart.
+    pub fn changed(&self, other: &Cells) -> Vec<Point> {
+        let across = self.width.div_ceil(CHUNK);
+        let mut changed = vec![];
+        for (chunk, (a, b)) in self.chunks.iter().zip(&other.chunks).enumerate() {
+            if same_chunk(a, b) {
+                continue;
+            }
+            let (left, top) = (chunk % across * CHUNK, chunk / across * CHUNK);
+            for y in top..(top + CHUNK).min(self.height) {
+                for x in left..(left + CHUNK).min(self.width) {
+                    if self.get(x, y) != other.get(x, y) {
+                        changed.push(Point::new(x, y));
+                    }
+                }
+            }
+        }
+        changed.sort_by_key(|p| (p.y, p.x));
+        changed
+    }
+}
+
+fn same_chunk(a: &Option<Arc<Vec<MapElement>>>, b: &Option<Arc<Vec<MapElement>>>) -> bool {
+    match (a, b) {
+        (None, None) => true,
+        (Some(a), Some(b)) => Arc::ptr_eq(a, b) || a == b,
+        (Some(cells), None) | (None, Some(cells)) => cells.iter().all(|el| *el == EMPTY),
+    }
+}
+
+impl PartialEq for Cells {
+    fn eq(&self, other: &Cells) -> bool {
+        (self.width, self.height) == (other.width, other.height)
+            && self
+                .chunks
+                .iter()
+                .zip(&other.chunks)
+                .all(|(a, b)| same_chunk(a, b))
+    }
+}
+
+impl PartialEq<Vec<Vec<MapElement>>> for Cells {
+    fn eq(&self, rows: &Vec<Vec<MapElement>>) -> bool {
+        self.rows() == *rows
+    }
+}
+
+impl fmt::Debug for Cells {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.rows()).finish()
+    }
+}
+
+// As wide as the first row; cells missing from shorter rows are closed.
+impl From<Vec<Vec<MapElement>>> for Cells {
+    fn from(rows: Vec<Vec<MapElement>>) -> Cells {
+        let width = rows.first().map_or(0, |row| row.len());
+        let mut cells = Cells::new(width, rows.len());
+        for (y, row) in rows.into_iter().enumerate() {
+            for (x, el) in row.into_iter().take(width).enumerate() {
+                cells.set(x, y, el);
+            }
+        }
+        cells
+    }
+}
+
+impl From<Cells> for Vec<Vec<MapElement>> {
+    fn from(cells: Cells) -> Vec<Vec<MapElement>> {
+        cells.rows()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MapElement::Mine;
+    use crate::MapElementCellState::Open;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_chunks_allocated_on_write() {
+        let mut cells = Cells::new(1000, 1000);
+        assert!(cells.chunks.iter().all(Option::is_none));
+        cells.set(999, 999, EMPTY.clone());
+        assert!(cells.chunks.iter().all(Option::is_none));
+        cells.set(999, 999, Mine { state: Closed });
+        assert_eq!(cells.chunks.iter().filter(|c| c.is_some()).count(), 1);
+        assert_eq!(cells.get(999, 999), &Mine { state: Closed });
+        assert_eq!(cells.get(998, 999), &EMPTY);
+    }
+
+    #[test]
+    fn test_copies_share_chunks() {
+        let mut cells = Cells::new(100, 100);
+        cells.set(0, 0, Mine { state: Closed });
+        cells.set(99, 99, Mine { state: Closed });
+        let mut opened = cells.clone();
+        opened.set(
+            1,
+            0,
+            Number {
+                state: Open,
+                count: 1,
+            },
+        );
+        assert_eq!(opened.changed(&cells), vec![Point::new(1, 0)]);
+        let last = opened.chunks.len() - 1;
+        assert!(Arc::ptr_eq(
+            opened.chunks[last].as_ref().unwrap(),
+            cells.chunks[last].as_ref().unwrap()
+        ));
+        assert!(opened != cells);
+    }
+
+    #[test]
+    fn test_rows_round_trip() {
+        let rows = vec![
+            vec![Mine { state: Closed }, EMPTY.clone()],
+            vec![
+                EMPTY.clone(),
+                Number {
+                    state: Open,
+                    count: 1,
+                },
+            ],
+        ];
+        let cells = Cells::from(rows.clone());
+        assert_eq!(cells, rows);
+        assert_eq!(cells.iter().count(), 4);
+        assert_eq!(Vec::from(cells), rows);
+    }
+}
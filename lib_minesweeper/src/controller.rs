@@ -0,0 +1,428 @@
+#[cfg(feature = "serde")]
+use serde_derive::{Deserialize, Serialize};
+use std::time::Duration;
+
+use crate::Annotation;
+use crate::Board;
+use crate::BoardState;
+use crate::MapElement::{Mine, Number};
+use crate::MapElementCellState::{Closed, Open};
+use crate::Move;
+use crate::Point;
+use crate::Replay;
+use crate::RuleEvent;
+use crate::Rules;
+
+// Added to the clock when second chance forgives a mine.
+pub const SECOND_CHANCE_PENALTY: Duration = Duration::from_secs(10);
+// In casual mode, each mine dug costs this much more than the last.
+pub const CASUAL_PENALTY: Duration = Duration::from_secs(5);
+
+// What a plain click does.
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Mode {
+    Flagging,
+    Digging,
+}
+
+// What a click on a cell does. Modifier keys and the d-pad pick an action
+// regardless of the current mode; the secondary button does what the mode
+// doesn't.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Action {
+    FromMode,
+    AgainstMode,
+    Dig,
+    Flag,
+    Chord,
+}
+
+// What an action comes to once the mode's been taken into account.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Intent {
+    Dig,
+    Flag,
+    Chord,
+}
+
+// What a move played, for a frontend to show.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Played {
+    pub before: Board,
+    // The move as the replay has it, which is a flag on the mine when the
+    // rules forgave an open or a chord that hit one.
+    pub m: Move,
+    pub events: Vec<RuleEvent>,
+    // What the forgiven mine added to the clock, if there was one.
+    pub penalty: Option<Duration>,
+}
+
+// A game in progress, with no frontend attached: the board, the replay being
+// recorded and what the rules have done so far. Frontends turn the player's
+// input into calls on it and show what comes back. It decides what a click
+// plays and whether a new game moves on to another difficulty, but not
+// which one: the presets are the frontend's, as are the robot's strategies.
+// Loops of moves, like the robot's steps and auto-open, stay with the
+// frontend too, as it times and sends each move as it's played.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct GameController {
+    pub mode: Mode,
+    pub board: Board,
+    // Only boards built from a seed can be replayed, so imported ones have
+    // none.
+    pub replay: Option<Replay>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub rules: Rules,
+    // Forgive every mine dug rather than only the first, when the rules'
+    // second chance is on.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub casual: bool,
+    // The mines forgiven this game and what they've added to the clock.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub forgiven: Vec<Point>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub penalty: Duration,
+    // The mines the auto-flag rule flagged to finish the game.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub auto_flagged: Vec<Point>,
+    // The player's notes on the board, kept with the game.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub annotations: Vec<Annotation>,
+}
+
+impl GameController {
+    pub fn new(board: Board, mode: Mode) -> GameController {
+        GameController {
+            mode,
+            replay: Replay::for_board(&board),
+            board,
+            rules: Rules::default(),
+            casual: false,
+            forgiven: vec![],
+            penalty: Duration::default(),
+            auto_flagged: vec![],
+            annotations: vec![],
+        }
+    }
+
+    // Starts over on `board`, with the same mode and rules.
+    pub fn load(&mut self, board: Board) {
+        *self = GameController {
+            rules: self.rules,
+            casual: self.casual,
+            ..GameController::new(board, self.mode)
+        };
+    }
+
+    pub fn is_over(&self) -> bool {
+        matches!(self.board.state, BoardState::Won | BoardState::Failed)
+    }
+
+    // Whether nothing's been played yet, when a new game moves on to the
+    // next difficulty rather than starting this one again.
+    pub fn is_fresh(&self) -> bool {
+        self.board.state == BoardState::Ready
+    }
+
+    // Switches between digging and flagging, except in a finished game.
+    pub fn toggle_mode(&mut self) -> bool {
+        if self.is_over() {
+            return false;
+        }
+        self.mode = match self.mode {
+            Mode::Digging => Mode::Flagging,
+            Mode::Flagging => Mode::Digging,
+        };
+        true
+    }
+
+    // With `swapped` buttons, a plain click goes against the mode and the
+    // other button with it.
+    pub fn intent(&self, action: Action, swapped: bool) -> Intent {
+        let action = match (action, swapped) {
+            (Action::FromMode, true) => Action::AgainstMode,
+            (Action::AgainstMode, true) => Action::FromMode,
+            (action, _) => action,
+        };
+        match (action, self.mode) {
+            (Action::Dig, _)
+            | (Action::FromMode, Mode::Digging)
+            | (Action::AgainstMode, Mode::Flagging) => Intent::Dig,
+            (Action::Flag, _)
+            | (Action::FromMode, Mode::Flagging)
+            | (Action::AgainstMode, Mode::Digging) => Intent::Flag,
+            (Action::Chord, _) => Intent::Chord,
+        }
+    }
+
+    // The move a click on `p` plays, once its action is an intent.
+    pub fn move_for(&self, p: Point, intent: Intent) -> Move {
+        match intent {
+            Intent::Dig => Move::Open(p),
+            Intent::Flag => Move::Flag(p),
+            Intent::Chord => Move::Chord(p),
+        }
+    }
+
+    // Whether a chord on `p` does nothing for want of flags: it's an open
+    // number with closed cells around it, but not as many flags as its
+    // count.
+    pub fn needs_flags(&self, p: &Point) -> bool {
+        let board = &self.board;
+        board.chord_open(p).is_none()
+            && matches!(board.at(p), Some(Number { state: Open, .. }))
+            && board.neighbours(p).iter().any(|n| {
+                matches!(
+                    board.at(n),
+                    Some(Number { state: Closed, .. }) | Some(Mine { state: Closed })
+                )
+            })
+    }
+
+    // Adds a note, in place of any on the same cells. False if it's on no
+    // cells or on some off the board.
+    pub fn annotate(&mut self, annotation: Annotation) -> bool {
+        if annotation.cells.is_empty()
+            || annotation.cells.iter().any(|p| self.board.at(p).is_none())
+        {
+            return false;
+        }
+        self.annotations.retain(|a| !a.has_cells(&annotation.cells));
+        self.annotations.push(annotation);
+        true
+    }
+
+    // The rules as they stand for the next move: second chance only ever
+    // forgives one mine a game, unless it's casual.
+    fn rules_now(&self) -> Rules {
+        Rules {
+            second_chance: self.casual || self.rules.second_chance && self.forgiven.is_empty(),
+            ..self.rules
+        }
+    }
+
+    // Plays `m` and the rules it sets off, and records it in the replay.
+    // Assisted moves are the ones an assist played for the player. None if
+    // the move does nothing.
+    pub fn play(&mut self, m: Move, assisted: bool) -> Option<Played> {
+        let (board, events) = self.board.apply_with(&m, self.rules_now())?;
+        let before = std::mem::replace(&mut self.board, board);
+        let mut penalty = None;
+        let mut forgiven = None;
+        for event in &events {
+            match event {
+                RuleEvent::AutoFlagged(points) => self.auto_flagged = points.clone(),
+                RuleEvent::Forgiven(p) => {
+                    penalty = Some(self.forgive(*p));
+                    forgiven = Some(*p);
+                }
+            }
+        }
+        // Replays play by the plain rules, where this move would have lost,
+        // so they get the flag it became.
+        let (m, assisted) = match forgiven {
+            Some(mine) => (Move::Flag(mine), true),
+            None => (m, assisted),
+        };
+        match &mut self.replay {
+            Some(replay) if assisted => replay.record_assisted(m),
+            Some(replay) => replay.record(m),
+            None => {}
+        }
+        Some(Played {
+            before,
+            m,
+            events,
+            penalty,
+        })
+    }
+
+    // What forgiving the mine at `p` costs: the same each time for second
+    // chance, and more with each one in casual mode.
+    fn forgive(&mut self, p: Point) -> Duration {
+        self.forgiven.push(p);
+        let penalty = if self.casual {
+            CASUAL_PENALTY * self.forgiven.len() as u32
+        } else {
+            SECOND_CHANCE_PENALTY
+        };
+        self.penalty += penalty;
+        penalty
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Adjacency;
+    use crate::BoardBuilder;
+    use crate::Tag;
+    use pretty_assertions::assert_eq;
+
+    // A mine in the corner of a 4x1 strip, with a seed so it has a replay.
+    fn one_mine() -> GameController {
+        let board = BoardBuilder::new(4, 1)
+            .mines_at(vec![Point::new(0, 0)])
+            .adjacency(Adjacency::King)
+            .build()
+            .unwrap();
+        let mut game = GameController::new(board.clone(), Mode::Digging);
+        game.replay = Replay::for_board(&Board {
+            seed: Some(1),
+            ..board
+        });
+        game
+    }
+
+    #[test]
+    fn test_intent() {
+        let mut game = one_mine();
+        assert_eq!(game.intent(Action::FromMode, false), Intent::Dig);
+        assert_eq!(game.intent(Action::AgainstMode, false), Intent::Flag);
+        assert_eq!(game.intent(Action::FromMode, true), Intent::Flag);
+        assert_eq!(game.intent(Action::Chord, true), Intent::Chord);
+        assert!(game.toggle_mode());
+        assert_eq!(game.intent(Action::FromMode, false), Intent::Flag);
+        assert_eq!(game.intent(Action::Dig, false), Intent::Dig);
+    }
+
+    #[test]
+    fn test_move_for() {
+        let game = one_mine();
+        let p = Point::new(1, 0);
+        assert_eq!(game.move_for(p, Intent::Dig), Move::Open(p));
+        assert_eq!(game.move_for(p, Intent::Flag), Move::Flag(p));
+        assert_eq!(game.move_for(p, Intent::Chord), Move::Chord(p));
+    }
+
+    #[test]
+    fn test_is_fresh() {
+        let mut game = one_mine();
+        assert!(game.is_fresh());
+        game.play(Move::Flag(Point::new(0, 0)), false).unwrap();
+        assert!(!game.is_fresh());
+        game.load(one_mine().board);
+        assert!(game.is_fresh());
+    }
+
+    #[test]
+    fn test_needs_flags() {
+        let mut game = one_mine();
+        let one = Point::new(1, 0);
+        // Closed, so there's nothing to chord yet.
+        assert!(!game.needs_flags(&one));
+        game.play(Move::Open(one), false).unwrap();
+        assert!(game.needs_flags(&one));
+        game.play(Move::Flag(Point::new(0, 0)), false).unwrap();
+        assert!(!game.needs_flags(&one));
+        // Once it's chorded, there's nothing closed left around it.
+        game.play(Move::Chord(one), false).unwrap();
+        assert!(!game.needs_flags(&one));
+    }
+
+    #[test]
+    fn test_toggle_mode_only_while_playing() {
+        let mut game = one_mine();
+        game.play(Move::Open(Point::new(0, 0)), false).unwrap();
+        assert!(game.is_over());
+        assert!(!game.toggle_mode());
+        assert_eq!(game.mode, Mode::Digging);
+    }
+
+    #[test]
+    fn test_play_records_replay() {
+        let mut game = one_mine();
+        let played = game.play(Move::Open(Point::new(3, 0)), true).unwrap();
+        assert_eq!(played.before.state, BoardState::Ready);
+        assert_eq!(game.board.state, BoardState::Won);
+        let replay = game.replay.unwrap();
+        assert_eq!(replay.moves, vec![Move::Open(Point::new(3, 0))]);
+        assert!(replay.is_assisted(0));
+    }
+
+    #[test]
+    fn test_play_chord() {
+        let mut game = one_mine();
+        let one = Point::new(1, 0);
+        assert_eq!(game.play(Move::Chord(one), false), None);
+        game.play(Move::Open(one), false).unwrap();
+        assert_eq!(game.play(Move::Chord(one), false), None);
+        game.play(Move::Flag(Point::new(0, 0)), false).unwrap();
+        let played = game.play(Move::Chord(one), false).unwrap();
+        assert_eq!(played.m, Move::Chord(one));
+        assert_eq!(game.board.state, BoardState::Won);
+        let replay = game.replay.unwrap();
+        assert_eq!(replay.moves.last(), Some(&Move::Chord(one)));
+        assert_eq!(replay.moves.len(), 3);
+    }
+
+    #[test]
+    fn test_second_chance_takes_back_a_chord() {
+        let mut game = one_mine();
+        game.rules.second_chance = true;
+        let (mine, one, two) = (Point::new(0, 0), Point::new(1, 0), Point::new(2, 0));
+        game.play(Move::Open(one), false).unwrap();
+        game.play(Move::Flag(two), false).unwrap();
+        let played = game.play(Move::Chord(one), false).unwrap();
+        assert_eq!(played.m, Move::Flag(mine));
+        assert_eq!(game.board.state, BoardState::Playing);
+        assert_eq!(game.forgiven, vec![mine]);
+        assert_eq!(
+            game.board.at(&one),
+            Some(&Number {
+                state: Open,
+                count: 1
+            })
+        );
+        assert!(game.replay.unwrap().is_assisted(2));
+    }
+
+    #[test]
+    fn test_second_chance_forgives_once() {
+        let mut game = one_mine();
+        game.rules.second_chance = true;
+        let mine = Point::new(0, 0);
+        let played = game.play(Move::Open(mine), false).unwrap();
+        assert_eq!(played.m, Move::Flag(mine));
+        assert_eq!(played.penalty, Some(SECOND_CHANCE_PENALTY));
+        assert_eq!(game.board.state, BoardState::Playing);
+        assert_eq!(game.forgiven, vec![mine]);
+        assert!(game.replay.as_ref().unwrap().is_assisted(0));
+        assert!(!game.rules_now().second_chance);
+        game.load(one_mine().board);
+        assert_eq!(game.forgiven, vec![]);
+        assert!(game.rules_now().second_chance);
+    }
+
+    #[test]
+    fn test_casual_penalties_stack() {
+        let mut game = one_mine();
+        game.rules.second_chance = true;
+        game.casual = true;
+        assert_eq!(game.forgive(Point::new(0, 0)), CASUAL_PENALTY);
+        assert_eq!(game.forgive(Point::new(1, 0)), CASUAL_PENALTY * 2);
+        assert_eq!(game.penalty, CASUAL_PENALTY * 3);
+        assert!(game.rules_now().second_chance);
+    }
+
+    #[test]
+    fn test_annotate() {
+        let mut game = one_mine();
+        let note = |cells: Vec<Point>, text: &str| Annotation {
+            cells,
+            tag: Tag::Red,
+            note: String::from(text),
+        };
+        assert!(!game.annotate(note(vec![], "nothing")));
+        assert!(!game.annotate(note(vec![Point::new(4, 0)], "off the board")));
+        let pair = vec![Point::new(0, 0), Point::new(1, 0)];
+        assert!(game.annotate(note(pair.clone(), "one of these")));
+        assert!(game.annotate(note(pair.into_iter().rev().collect(), "still one")));
+        assert_eq!(game.annotations.len(), 1);
+        assert_eq!(game.annotations[0].note, "still one");
+        game.load(game.board.clone());
+        assert_eq!(game.annotations, vec![]);
+    }
+}
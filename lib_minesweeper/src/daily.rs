@@ -0,0 +1,87 @@
+#[cfg(feature = "serde")]
+use serde_derive::{Deserialize, Serialize};
+
+use crate::Adjacency;
+use crate::Board;
+use crate::BoardBuilder;
+use crate::Replay;
+
+// Daily boards have the same shape as the app's medium difficulty.
+const DAILY_WIDTH: usize = 16;
+const DAILY_HEIGHT: usize = 16;
+const DAILY_MINES: usize = 40;
+
+// The board everyone plays on a given day, which the scores server ranks
+// times on.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Daily {
+    pub day: u64,
+    pub width: usize,
+    pub height: usize,
+    pub mines: usize,
+    pub adjacency: Adjacency,
+    pub seed: u64,
+}
+
+// `day` counts days since the Unix epoch, in UTC.
+pub fn daily(day: u64) -> Daily {
+    Daily {
+        day,
+        width: DAILY_WIDTH,
+        height: DAILY_HEIGHT,
+        mines: DAILY_MINES,
+        adjacency: Adjacency::Knight,
+        seed: splitmix(day),
+    }
+}
+
+// splitmix64, so that consecutive days, or boards of a race, are unrelated.
+pub(crate) fn splitmix(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+impl Daily {
+    pub fn board(&self) -> Board {
+        BoardBuilder::new(self.width, self.height)
+            .mine_count(self.mines)
+            .adjacency(self.adjacency)
+            .seed(self.seed)
+            .build()
+            .unwrap()
+    }
+
+    // Only games with the usual cascade count, so everyone's times compare.
+    pub fn is_board_of(&self, replay: &Replay) -> bool {
+        replay.width == self.width
+            && replay.height == self.height
+            && replay.mines == self.mines
+            && replay.adjacency == self.adjacency
+            && replay.seed == self.seed
+            && replay.symmetry.is_none()
+            && replay.spacing.is_none()
+            && replay.min_opening.is_none()
+            && replay.cascade.is_zeros()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Cascade;
+
+    #[test]
+    fn test_daily_board_is_the_days() {
+        let d = daily(3);
+        let board = d.board();
+        assert_eq!(board.seed, Some(d.seed));
+        assert!(d.is_board_of(&Replay::for_board(&board).unwrap()));
+        assert!(!daily(4).is_board_of(&Replay::for_board(&board).unwrap()));
+        let mut replay = Replay::for_board(&board).unwrap();
+        replay.cascade = Cascade::Off;
+        assert!(!d.is_board_of(&replay));
+    }
+}
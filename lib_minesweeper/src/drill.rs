@@ -0,0 +1,383 @@
+#[cfg(feature = "serde")]
+use serde_derive::{Deserialize, Serialize};
+
+use crate::Board;
+use crate::Point;
+#[cfg(feature = "solver")]
+use crate::{
+    certain_mines, certain_safe, daily::splitmix, Adjacency, BasicSolver, BoardBuilder, BoardState,
+    BoardView, CellView, Move,
+};
+
+// Drill positions are small boards with a few cells opened at random.
+#[cfg(feature = "solver")]
+const WIDTH: usize = 7;
+#[cfg(feature = "solver")]
+const HEIGHT: usize = 7;
+#[cfg(feature = "solver")]
+const MINES: usize = 10;
+// Random positions tried for one that shows the pattern.
+#[cfg(feature = "solver")]
+const ATTEMPTS: u64 = 500;
+
+// The bits of knight-move reasoning the trainer quizzes on.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Pattern {
+    // A number whose flags already account for all of its mines.
+    Satisfied,
+    // A number with just as many closed cells as mines left to find.
+    Filled,
+    // A number whose closed cells are all next to another one too, so the
+    // difference between the two decides the other's remaining cells.
+    Subset,
+    // A cell right beside a number that a knight's move can't reach, so the
+    // number says nothing about it.
+    Trap,
+}
+
+impl Pattern {
+    pub const ALL: [Pattern; 4] = [
+        Pattern::Satisfied,
+        Pattern::Filled,
+        Pattern::Subset,
+        Pattern::Trap,
+    ];
+
+    // How the pattern is written in the reference and in links.
+    pub fn key(self) -> &'static str {
+        match self {
+            Pattern::Satisfied => "satisfied",
+            Pattern::Filled => "filled",
+            Pattern::Subset => "subset",
+            Pattern::Trap => "trap",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Pattern> {
+        Pattern::ALL.iter().copied().find(|p| p.key() == key)
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Pattern::Satisfied => "satisfied number",
+            Pattern::Filled => "filled number",
+            Pattern::Subset => "subset",
+            Pattern::Trap => "not a knight's move away",
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Answer {
+    Safe,
+    Mine,
+    // Nothing the player can see decides it.
+    Unknown,
+}
+
+// A position and a closed cell in it to say whether is safe, a mine, or
+// can't be told, with the numbers that decide it and why.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Drill {
+    pub pattern: Pattern,
+    pub board: Board,
+    pub target: Point,
+    pub answer: Answer,
+    pub sources: Vec<Point>,
+    pub why: String,
+}
+
+// The same drill for the same pattern and seed. None if no position in
+// ATTEMPTS shows the pattern.
+#[cfg(feature = "solver")]
+pub fn drill(pattern: Pattern, seed: u64) -> Option<Drill> {
+    (0..ATTEMPTS).find_map(|i| {
+        let seed = splitmix(seed.wrapping_add(i));
+        let board = position(seed)?;
+        let pick = splitmix(seed);
+        match pattern {
+            Pattern::Satisfied => satisfied(&flag_forced(&board), pick),
+            Pattern::Filled => filled(&board, pick),
+            Pattern::Subset => subset(&board, pick),
+            Pattern::Trap => trap(&board, pick),
+        }
+    })
+}
+
+// A few cells opened at random, stopping short of a finished game.
+#[cfg(feature = "solver")]
+fn position(seed: u64) -> Option<Board> {
+    let mut board = BoardBuilder::new(WIDTH, HEIGHT)
+        .mine_count(MINES)
+        .adjacency(Adjacency::Knight)
+        .seed(seed)
+        .build()
+        .ok()?;
+    for i in 0..3 + seed % 5 {
+        let p = board.pick_safe_cell(splitmix(seed ^ i))?;
+        board = board.cascade_open_item(&p)?;
+    }
+    Some(board).filter(|b| b.state == BoardState::Playing)
+}
+
+// Flags every mine a single number proves, as a player would have.
+#[cfg(feature = "solver")]
+fn flag_forced(board: &Board) -> Board {
+    BasicSolver
+        .forced_mines(&BoardView::new(board))
+        .iter()
+        .fold(board.clone(), |b, p| b.flag_item(p))
+}
+
+#[cfg(feature = "solver")]
+fn pick(cells: &[Point], pick: u64) -> Option<Point> {
+    if cells.is_empty() {
+        return None;
+    }
+    Some(cells[(pick % cells.len() as u64) as usize])
+}
+
+// "column 3, row 5", counting from 1 like the rest of the explanations.
+#[cfg(feature = "solver")]
+fn at(p: Point) -> String {
+    format!("column {}, row {}", p.x + 1, p.y + 1)
+}
+
+// A single number proves the cell, one way or the other.
+#[cfg(feature = "solver")]
+fn justified(pattern: Pattern, board: &Board, target: Point, m: Move, answer: Answer) -> Drill {
+    let deduction = BasicSolver
+        .justify(&BoardView::new(board), &m)
+        .expect("only cells the basic solver proves are picked");
+    Drill {
+        pattern,
+        board: board.clone(),
+        target,
+        answer,
+        sources: vec![deduction.source],
+        why: deduction.to_string(),
+    }
+}
+
+#[cfg(feature = "solver")]
+fn satisfied(board: &Board, seed: u64) -> Option<Drill> {
+    let target = pick(&BasicSolver.safe_cells(&BoardView::new(board)), seed)?;
+    let m = Move::Open(target);
+    Some(justified(
+        Pattern::Satisfied,
+        board,
+        target,
+        m,
+        Answer::Safe,
+    ))
+}
+
+#[cfg(feature = "solver")]
+fn filled(board: &Board, seed: u64) -> Option<Drill> {
+    let target = pick(&BasicSolver.forced_mines(&BoardView::new(board)), seed)?;
+    let m = Move::Flag(target);
+    Some(justified(Pattern::Filled, board, target, m, Answer::Mine))
+}
+
+// An open number's closed neighbours, and how many of its mines are still
+// to find among them.
+#[cfg(feature = "solver")]
+fn constraint(view: &BoardView, p: Point) -> Option<(Vec<Point>, usize, usize)> {
+    let count = match view.at(&p) {
+        Some(CellView::Open(count)) if count > 0 => count as usize,
+        _ => return None,
+    };
+    let neighbours = view.neighbours(&p);
+    let closed: Vec<Point> = neighbours
+        .iter()
+        .filter(|n| view.at(n) == Some(CellView::Closed))
+        .copied()
+        .collect();
+    let flagged = neighbours
+        .iter()
+        .filter(|n| view.at(n) == Some(CellView::Flagged))
+        .count();
+    Some((closed, count, count.checked_sub(flagged)?))
+}
+
+// Only for cells no single number proves by itself.
+#[cfg(feature = "solver")]
+fn subset(board: &Board, seed: u64) -> Option<Drill> {
+    let view = BoardView::new(board);
+    let basic = [
+        BasicSolver.safe_cells(&view),
+        BasicSolver.forced_mines(&view),
+    ]
+    .concat();
+    let mut drills = vec![];
+    for a in view.points() {
+        let (small, count_a, left_a) = match constraint(&view, a) {
+            Some(c) if !c.0.is_empty() => c,
+            _ => continue,
+        };
+        for b in view.points() {
+            let (big, count_b, left_b) = match constraint(&view, b) {
+                Some(c) => c,
+                None => continue,
+            };
+            let rest: Vec<Point> = big.iter().filter(|p| !small.contains(p)).copied().collect();
+            if rest.is_empty() || !small.iter().all(|p| big.contains(p)) {
+                continue;
+            }
+            let (answer, why) = if left_a == left_b {
+                let why = format!(
+                    "Every closed cell next to the {} at {} is next to the {} at {} too. \
+                     Both have {} mines left to find, so they're all in the {}'s cells, \
+                     and the {}'s other cells are safe.",
+                    count_a,
+                    at(a),
+                    count_b,
+                    at(b),
+                    left_a,
+                    count_a,
+                    count_b
+                );
+                (Answer::Safe, why)
+            } else if left_b >= left_a && left_b - left_a == rest.len() {
+                let why = format!(
+                    "Every closed cell next to the {} at {} is next to the {} at {} too. \
+                     They hold {} of the {}'s {} mines left to find, which leaves {} for \
+                     its {} other cells, so they're all mines.",
+                    count_a,
+                    at(a),
+                    count_b,
+                    at(b),
+                    left_a,
+                    count_b,
+                    left_b,
+                    rest.len(),
+                    rest.len()
+                );
+                (Answer::Mine, why)
+            } else {
+                continue;
+            };
+            for target in rest.iter().filter(|p| !basic.contains(p)) {
+                drills.push(Drill {
+                    pattern: Pattern::Subset,
+                    board: board.clone(),
+                    target: *target,
+                    answer,
+                    sources: vec![a, b],
+                    why: why.clone(),
+                });
+            }
+        }
+    }
+    if drills.is_empty() {
+        return None;
+    }
+    let i = (seed % drills.len() as u64) as usize;
+    Some(drills.swap_remove(i))
+}
+
+// A closed cell diagonally or straight next to an open number, which
+// nothing on the board decides.
+#[cfg(feature = "solver")]
+fn trap(board: &Board, seed: u64) -> Option<Drill> {
+    let view = BoardView::new(board);
+    let certain = [certain_safe(&view), certain_mines(&view)].concat();
+    let mut traps = vec![];
+    for p in view.points() {
+        match view.at(&p) {
+            Some(CellView::Open(count)) if count > 0 => {}
+            _ => continue,
+        }
+        let knight = view.neighbours(&p);
+        for target in board.surrounding_points(&p) {
+            if view.at(&target) == Some(CellView::Closed)
+                && !knight.contains(&target)
+                && !certain.contains(&target)
+            {
+                traps.push((p, target));
+            }
+        }
+    }
+    let (source, target) = *traps.get((seed % traps.len().max(1) as u64) as usize)?;
+    let count = match view.at(&source) {
+        Some(CellView::Open(count)) => count,
+        _ => unreachable!(),
+    };
+    Some(Drill {
+        pattern: Pattern::Trap,
+        board: board.clone(),
+        target,
+        answer: Answer::Unknown,
+        sources: vec![source],
+        why: format!(
+            "The {} at {} is right beside it, but a knight's move can't reach it, \
+             so that number says nothing about it. Nothing else on the board does either.",
+            count,
+            at(source)
+        ),
+    })
+}
+
+#[cfg(all(test, feature = "solver"))]
+mod tests {
+    use super::*;
+    use crate::MapElement;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_drills_are_right() {
+        for pattern in Pattern::ALL.iter().copied() {
+            for seed in 0..5 {
+                let drill = drill(pattern, seed).unwrap();
+                assert_eq!(drill.pattern, pattern);
+                let view = BoardView::new(&drill.board);
+                assert_eq!(view.at(&drill.target), Some(CellView::Closed));
+                let mine = matches!(drill.board.at(&drill.target), Some(MapElement::Mine { .. }));
+                match drill.answer {
+                    Answer::Safe => assert!(!mine && certain_safe(&view).contains(&drill.target)),
+                    Answer::Mine => assert!(mine && certain_mines(&view).contains(&drill.target)),
+                    Answer::Unknown => {
+                        assert!(!certain_safe(&view).contains(&drill.target));
+                        assert!(!certain_mines(&view).contains(&drill.target));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_drills_show_their_pattern() {
+        let satisfied = drill(Pattern::Satisfied, 1).unwrap();
+        let view = BoardView::new(&satisfied.board);
+        let source = satisfied.sources[0];
+        let flagged = view
+            .neighbours(&source)
+            .iter()
+            .filter(|n| view.at(n) == Some(CellView::Flagged))
+            .count();
+        assert!(flagged > 0);
+        let subset = drill(Pattern::Subset, 1).unwrap();
+        let view = BoardView::new(&subset.board);
+        assert!(!BasicSolver.safe_cells(&view).contains(&subset.target));
+        assert!(!BasicSolver.forced_mines(&view).contains(&subset.target));
+        assert_eq!(subset.sources.len(), 2);
+        let trap = drill(Pattern::Trap, 1).unwrap();
+        let source = trap.sources[0];
+        assert!(trap
+            .board
+            .surrounding_points(&source)
+            .contains(&trap.target));
+        assert!(!trap.board.neighbours(&source).contains(&trap.target));
+    }
+
+    #[test]
+    fn test_drills_are_repeatable() {
+        assert_eq!(drill(Pattern::Subset, 7), drill(Pattern::Subset, 7));
+        assert_ne!(
+            drill(Pattern::Filled, 7).map(|d| d.board),
+            drill(Pattern::Filled, 8).map(|d| d.board)
+        );
+    }
+}
@@ -0,0 +1,172 @@
+#[cfg(feature = "serde")]
+use serde_derive::{Deserialize, Serialize};
+
+use crate::Adjacency;
+use crate::Board;
+use crate::BoardBuilder;
+use crate::Point;
+
+const CATALOG: &str = include_str!("featured.txt");
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum FeaturedKind {
+    // Small boards where every move follows from a single number.
+    Teaching,
+    // Bigger boards that never take a guess.
+    NoGuess,
+    // Boards that take weighing several numbers together, but no guess.
+    BrutalButFair,
+}
+
+impl FeaturedKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            FeaturedKind::Teaching => "teaching",
+            FeaturedKind::NoGuess => "no-guess",
+            FeaturedKind::BrutalButFair => "brutal-but-fair",
+        }
+    }
+}
+
+// A seeded board picked out for being worth playing.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Featured {
+    pub kind: FeaturedKind,
+    pub name: String,
+    pub note: String,
+    pub width: usize,
+    pub height: usize,
+    pub mines: usize,
+    pub adjacency: Adjacency,
+    pub seed: u64,
+}
+
+// The boards shipped with the game, in catalog order.
+pub fn featured() -> Vec<Featured> {
+    CATALOG.lines().filter_map(parse_line).collect()
+}
+
+// `kind | name | width height mines adjacency | seed | note`. Blank lines,
+// comments and lines that don't parse are skipped.
+fn parse_line(line: &str) -> Option<Featured> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+    let fields: Vec<&str> = line.split('|').map(str::trim).collect();
+    let (kind, name, shape, seed, note) = match fields[..] {
+        [kind, name, shape, seed, note] => (kind, name, shape, seed, note),
+        _ => return None,
+    };
+    let kind = [
+        FeaturedKind::Teaching,
+        FeaturedKind::NoGuess,
+        FeaturedKind::BrutalButFair,
+    ]
+    .iter()
+    .copied()
+    .find(|k| k.name() == kind)?;
+    let shape: Vec<&str> = shape.split_whitespace().collect();
+    let (width, height, mines, adjacency) = match shape[..] {
+        [width, height, mines, adjacency] => (
+            width.parse().ok()?,
+            height.parse().ok()?,
+            mines.parse().ok()?,
+            match adjacency {
+                "king" => Adjacency::King,
+                "knight" => Adjacency::Knight,
+                _ => return None,
+            },
+        ),
+        _ => return None,
+    };
+    Some(Featured {
+        kind,
+        name: name.to_string(),
+        note: note.to_string(),
+        width,
+        height,
+        mines,
+        adjacency,
+        seed: seed.parse().ok()?,
+    })
+}
+
+impl Featured {
+    pub fn board(&self) -> Board {
+        BoardBuilder::new(self.width, self.height)
+            .mine_count(self.mines)
+            .adjacency(self.adjacency)
+            .seed(self.seed)
+            .build()
+            .unwrap()
+    }
+
+    // Where to click first for the board to play as its kind says.
+    pub fn start(&self) -> Option<Point> {
+        self.board().opening_start()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_every_line_parses() {
+        let lines = CATALOG
+            .lines()
+            .filter(|l| !l.trim().is_empty() && !l.starts_with('#'))
+            .count();
+        assert_eq!(featured().len(), lines);
+        assert_eq!(
+            parse_line("teaching | A | 8 8 10 king | 3 | Note"),
+            Some(Featured {
+                kind: FeaturedKind::Teaching,
+                name: String::from("A"),
+                note: String::from("Note"),
+                width: 8,
+                height: 8,
+                mines: 10,
+                adjacency: Adjacency::King,
+                seed: 3,
+            })
+        );
+        assert_eq!(parse_line("teaching | A | 8 8 10 rook | 3 | Note"), None);
+    }
+
+    #[test]
+    fn test_no_board_is_in_twice() {
+        // Not even turned round or flipped over.
+        let mut hashes: Vec<u64> = featured()
+            .iter()
+            .map(|f| f.board().canonical_hash())
+            .collect();
+        hashes.sort_unstable();
+        hashes.dedup();
+        assert_eq!(hashes.len(), featured().len());
+    }
+
+    #[cfg(feature = "solver")]
+    #[test]
+    fn test_boards_are_as_hard_as_their_kind() {
+        use crate::Rating;
+        for featured in featured() {
+            let expected = match featured.kind {
+                FeaturedKind::Teaching => vec![Rating::Casual],
+                FeaturedKind::NoGuess => vec![Rating::Casual, Rating::Tricky],
+                FeaturedKind::BrutalButFair => vec![Rating::Tricky],
+            };
+            let rating = Rating::of(&featured.board());
+            assert!(
+                expected.contains(&rating),
+                "{} is {:?}",
+                featured.name,
+                rating
+            );
+        }
+    }
+}
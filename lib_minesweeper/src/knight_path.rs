@@ -0,0 +1,173 @@
+use crate::Board;
+use crate::MapElement::Number;
+use crate::MapElementCellState::Open;
+use crate::Point;
+
+// Each cell on the path is worth this much, and a path through every open
+// cell twice as much.
+pub const POINTS_PER_CELL: usize = 10;
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum PathError {
+    // Only open safe cells can be landed on.
+    NotOpen,
+    Visited,
+    // The cell isn't a knight's move from the last one.
+    NotAKnightMove,
+}
+
+// A route through a board's open cells, hopping from each to the next like
+// a knight and never landing on the same one twice: the bonus round after a
+// win, or a knight's tour when it takes in every cell.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct KnightPath {
+    pub cells: Vec<Point>,
+}
+
+impl KnightPath {
+    pub fn new() -> KnightPath {
+        KnightPath::default()
+    }
+
+    // Checks `cells` as a path on `board`, and where the first bad one is.
+    pub fn validate(board: &Board, cells: &[Point]) -> Result<KnightPath, (usize, PathError)> {
+        let mut path = KnightPath::new();
+        for (i, p) in cells.iter().enumerate() {
+            path.extend(board, *p).map_err(|e| (i, e))?;
+        }
+        Ok(path)
+    }
+
+    // Adds `p` to the end of the path, if it can go there.
+    pub fn extend(&mut self, board: &Board, p: Point) -> Result<(), PathError> {
+        if !matches!(board.at(&p), Some(Number { state: Open, .. })) {
+            return Err(PathError::NotOpen);
+        }
+        if self.cells.contains(&p) {
+            return Err(PathError::Visited);
+        }
+        if let Some(last) = self.cells.last() {
+            if !board.surrounding_knight_points(last).contains(&p) {
+                return Err(PathError::NotAKnightMove);
+            }
+        }
+        self.cells.push(p);
+        Ok(())
+    }
+
+    // The cells the path can go on to. Any open one, before it's started.
+    pub fn next_cells(&self, board: &Board) -> Vec<Point> {
+        let candidates: Vec<Point> = match self.cells.last() {
+            Some(last) => board.surrounding_knight_points(last),
+            None => board.points().collect(),
+        };
+        candidates
+            .into_iter()
+            .filter(|p| matches!(board.at(p), Some(Number { state: Open, .. })))
+            .filter(|p| !self.cells.contains(p))
+            .collect()
+    }
+
+    // Whether the path has started and has nowhere left to go.
+    pub fn is_stuck(&self, board: &Board) -> bool {
+        !self.cells.is_empty() && self.next_cells(board).is_empty()
+    }
+
+    // Whether the path takes in every open cell of `board`.
+    pub fn is_tour(&self, board: &Board) -> bool {
+        !self.cells.is_empty() && self.cells.len() == board.stats().opened
+    }
+
+    pub fn score(&self, board: &Board) -> usize {
+        let score = self.cells.len() * POINTS_PER_CELL;
+        if self.is_tour(board) {
+            score * 2
+        } else {
+            score
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BoardBuilder;
+    use crate::Move;
+    use pretty_assertions::assert_eq;
+
+    // A 3x3 board with its mine in the middle, out of every knight's reach,
+    // so opening a corner wins it.
+    fn ring() -> Board {
+        let board = BoardBuilder::new(3, 3)
+            .mines_at(vec![Point::new(1, 1)])
+            .build()
+            .unwrap();
+        board.apply(&Move::Open(Point::new(0, 0))).unwrap()
+    }
+
+    #[test]
+    fn test_extend() {
+        let board = ring();
+        let mut path = KnightPath::new();
+        assert_eq!(
+            path.extend(&board, Point::new(1, 1)),
+            Err(PathError::NotOpen)
+        );
+        assert_eq!(path.extend(&board, Point::new(0, 0)), Ok(()));
+        assert_eq!(
+            path.extend(&board, Point::new(1, 0)),
+            Err(PathError::NotAKnightMove)
+        );
+        assert_eq!(path.extend(&board, Point::new(2, 1)), Ok(()));
+        assert_eq!(
+            path.extend(&board, Point::new(0, 0)),
+            Err(PathError::Visited)
+        );
+        assert_eq!(path.cells, vec![Point::new(0, 0), Point::new(2, 1)]);
+    }
+
+    #[test]
+    fn test_validate() {
+        let board = ring();
+        let cells = vec![Point::new(0, 0), Point::new(1, 2), Point::new(1, 0)];
+        assert_eq!(
+            KnightPath::validate(&board, &cells),
+            Err((2, PathError::NotAKnightMove))
+        );
+        assert_eq!(
+            KnightPath::validate(&board, &cells[..2])
+                .unwrap()
+                .cells
+                .len(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_tour() {
+        let board = ring();
+        assert_eq!(board.stats().opened, 8);
+        // The ring of eight cells around the middle, a knight's move apart.
+        let cells: Vec<Point> = [
+            (0, 0),
+            (2, 1),
+            (0, 2),
+            (1, 0),
+            (2, 2),
+            (0, 1),
+            (2, 0),
+            (1, 2),
+        ]
+        .iter()
+        .map(|(x, y)| Point::new(*x, *y))
+        .collect();
+        let path = KnightPath::validate(&board, &cells).unwrap();
+        assert!(path.is_tour(&board));
+        assert!(path.is_stuck(&board));
+        assert_eq!(path.score(&board), 8 * POINTS_PER_CELL * 2);
+        let part = KnightPath::validate(&board, &cells[..3]).unwrap();
+        assert!(!part.is_tour(&board));
+        assert_eq!(part.next_cells(&board), vec![Point::new(1, 0)]);
+        assert_eq!(part.score(&board), 3 * POINTS_PER_CELL);
+    }
+}
@@ -1,6 +1,107 @@
+//! Minesweeper where numbers count the mines a knight's move away, or the
+//! eight cells around as usual, with no frontend attached: boards are plain
+//! values that each move turns into the next.
+//!
+//! ```
+//! use lib_minesweeper::{BoardBuilder, BoardState, Move, Point};
+//!
+//! let board = BoardBuilder::new(8, 8).mine_count(10).seed(42).build().unwrap();
+//! let board = board.apply(&Move::Open(Point::new(0, 0))).unwrap();
+//! assert_ne!(board.state, BoardState::Ready);
+//! ```
+//!
+//! Features:
+//! - `serde` (default): Serialize and Deserialize for boards, moves and
+//!   replays.
+//! - `solver` (default): the robot strategies, mine odds and tournaments.
+//! - `parallel`: runs the solver's searches on every core, natively.
+//! - `cli` (default): the console game binary. Library users can turn
+//!   default features off and pick the others.
+//!
+//! Until 1.0, a minor version bump may break the API and a patch one won't.
+//! See CHANGELOG.md.
+
+#[cfg(feature = "serde")]
 use serde_derive::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+mod annotation;
+mod builder;
+mod cells;
+mod controller;
+mod daily;
+mod drill;
+mod featured;
+mod knight_path;
+#[cfg(feature = "solver")]
+mod no_guess;
+mod opening;
+mod placement;
+#[cfg(feature = "solver")]
+mod probability;
+mod race;
+#[cfg(feature = "solver")]
+mod rating;
+mod reference;
+#[cfg(feature = "solver")]
+mod repair;
+mod replay;
+mod rules;
+mod scenario;
+#[cfg(feature = "solver")]
+mod strategy;
+#[cfg(feature = "solver")]
+mod tournament;
+mod transform;
+
+use cells::Cells;
+use placement::placed_mines;
+
+pub use annotation::{Annotation, Tag};
+pub use builder::BoardBuilder;
+pub use builder::BuildBoardError;
+pub use builder::MAX_CELLS;
+pub use controller::{
+    Action, GameController, Intent, Mode, Played, CASUAL_PENALTY, SECOND_CHANCE_PENALTY,
+};
+pub use daily::{daily, Daily};
+#[cfg(feature = "solver")]
+pub use drill::drill;
+pub use drill::{Answer, Drill, Pattern};
+pub use featured::{featured, Featured, FeaturedKind};
+pub use knight_path::{KnightPath, PathError, POINTS_PER_CELL};
+#[cfg(feature = "solver")]
+pub use no_guess::NoGuessSearch;
+pub use opening::{opening_book, rank_openings, Opening};
+pub use placement::Spacing;
+#[cfg(feature = "solver")]
+pub use probability::{
+    analyse_loss, certain_mines, certain_safe, consistent_configurations, frontier_components,
+    mine_odds, win_chance, Loss, MineAssignment, MineOdds, SafestGuess,
+};
+pub use race::{race, race_code, Race, MAX_RACE_BOARDS};
+#[cfg(feature = "solver")]
+pub use rating::Rating;
+pub use reference::{reference, Reference};
+#[cfg(feature = "solver")]
+pub use repair::{make_solvable, Fix, Repair};
+pub use replay::Move;
+pub use replay::Replay;
+pub use replay::{MAX_REPLAY_CELLS, MAX_REPLAY_MOVES};
+pub use replay::{pack_moves, unpack_moves};
+pub use rules::{RuleEvent, Rules};
+pub use scenario::{
+    parse_scenarios, scenarios, Scenario, ScenarioKind, ScenarioRun, ScenarioStep, Trigger,
+};
+#[cfg(feature = "solver")]
+pub use strategy::{BasicSolver, BoardView, CellView, Deduction, Guessing, Strategy};
+#[cfg(feature = "solver")]
+pub use tournament::{play, Standing, Tournament};
+pub use transform::Transform;
 
-#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum MapElement {
     Mine {
         state: MapElementCellState,
@@ -10,20 +111,32 @@ pub enum MapElement {
         count: i32,
     },
 }
-#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum MapElementCellState {
     Closed,
     Open,
     Flagged,
 }
 
+// One cell of a board written with Board::from_fn.
+#[derive(Debug, PartialEq, Clone)]
+pub enum CellSpec {
+    Mine(MapElementCellState),
+    // Numbered from the mines around it.
+    Safe(MapElementCellState),
+    // With a count of its own, whatever the mines around it.
+    Number(MapElementCellState, i32),
+}
+
 use MapElement::Mine;
 use MapElement::Number;
 use MapElementCellState::Closed;
 use MapElementCellState::Flagged;
 use MapElementCellState::Open;
 
-#[derive(Debug, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[derive(Debug, PartialEq, PartialOrd, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Point {
     pub x: i32,
     pub y: i32,
@@ -37,7 +150,8 @@ impl Point {
     }
 }
 
-#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum BoardState {
     NotReady,
     Ready,
@@ -46,36 +160,308 @@ pub enum BoardState {
     Failed,
 }
 
-#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+// Which cells count as neighbours when numbering and cascading.
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Adjacency {
+    King,
+    Knight,
+}
+
+// How randomly placed mines mirror each other. Cells on the mirror line, or
+// in the middle of a board turned half way, are their own image.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Symmetry {
+    #[default]
+    None,
+    // Left to right.
+    Mirror,
+    // The same when turned upside down.
+    Rotational,
+}
+
+impl Symmetry {
+    pub fn is_none(&self) -> bool {
+        *self == Symmetry::None
+    }
+
+    // Where the mine at (x, y) has its twin.
+    fn image(self, width: usize, height: usize, x: usize, y: usize) -> (usize, usize) {
+        match self {
+            Symmetry::None => (x, y),
+            Symmetry::Mirror => (width - 1 - x, y),
+            Symmetry::Rotational => (width - 1 - x, height - 1 - y),
+        }
+    }
+}
+
+// How far opening a cell spreads on its own.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Cascade {
+    // Through every zero opened, to the numbers around them.
+    #[default]
+    Zeros,
+    // As Zeros, and on through each number opened that already has its
+    // mines flagged, as a chord on it would. A wrong flag can lose the game.
+    Chording,
+    // Only the cell clicked opens.
+    Off,
+}
+
+impl Cascade {
+    pub fn is_zeros(&self) -> bool {
+        *self == Cascade::Zeros
+    }
+
+    pub fn next(self) -> Cascade {
+        match self {
+            Cascade::Zeros => Cascade::Chording,
+            Cascade::Chording => Cascade::Off,
+            Cascade::Off => Cascade::Zeros,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Cascade::Zeros => "through zeros",
+            Cascade::Chording => "through zeros and chords",
+            Cascade::Off => "off",
+        }
+    }
+}
+
+// Which clock a game was timed with. Leaderboards rank each separately.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Timing {
+    // From the first move to the last, pauses and all.
+    #[default]
+    RealTime,
+    // Only while the board is being played: not while paused or in a menu.
+    GameTime,
+}
+
+impl Timing {
+    pub fn next(self) -> Timing {
+        match self {
+            Timing::RealTime => Timing::GameTime,
+            Timing::GameTime => Timing::RealTime,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Timing::RealTime => "real time",
+            Timing::GameTime => "game time",
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct BoardStats {
+    pub closed: usize,
+    pub opened: usize,
+    pub flagged: usize,
+    pub mines: usize,
+    pub mines_remaining: i32,
+    // fraction of the safe cells that have been opened, between 0 and 1
+    pub progress: f64,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Board {
-    map: Vec<Vec<MapElement>>,
+    map: Cells,
     missing_points: i32,
     pub width: usize,
     pub height: usize,
     pub mines: usize,
     pub state: BoardState,
+    pub adjacency: Adjacency,
+    // seed the mines were placed with, if they were placed randomly
+    pub seed: Option<u64>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub symmetry: Symmetry,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub spacing: Option<Spacing>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub min_opening: Option<usize>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub cascade: Cascade,
 }
 
 impl Board {
+    #[deprecated(note = "use BoardBuilder or Board::from_fn, which also number the board")]
     pub fn new(map: Vec<Vec<MapElement>>) -> Board {
-        let mines = map
-            .iter()
-            .flat_map(|x| x.iter())
-            .filter(|x| matches!(x, Mine{..}))
-            .count();
-        let width = map.iter().next().unwrap().len();
-        let height = map.len();
+        Board::from_map(Cells::from(map))
+    }
+
+    // A board with each cell as `cell` says, for boards written in code such
+    // as puzzles and tests. Safe cells count the mines a knight's move away.
+    pub fn from_fn(width: usize, height: usize, mut cell: impl FnMut(Point) -> CellSpec) -> Board {
+        let mut map = Cells::new(width, height);
+        let mut counts = vec![];
+        for y in 0..height {
+            for x in 0..width {
+                let el = match cell(Point::new(x, y)) {
+                    CellSpec::Mine(state) => Mine { state },
+                    CellSpec::Safe(state) => Number { state, count: 0 },
+                    CellSpec::Number(state, count) => {
+                        counts.push((x, y, count));
+                        Number { state, count: 0 }
+                    }
+                };
+                map.set(x, y, el);
+            }
+        }
+        let mut board = Board::from_map(map).with_numbers();
+        for (x, y, count) in counts {
+            if let Number { count: c, .. } = board.map.get_mut(x, y) {
+                *c = count;
+            }
+        }
+        board.settled()
+    }
+
+    // The board with the cell at `p` made `cell`, for changing a position
+    // by hand. If a mine comes or goes, the numbers around it are counted
+    // again with the board's own adjacency. None off the board.
+    pub fn edit(&self, p: &Point, cell: CellSpec) -> Option<Board> {
+        let was_mine = matches!(self.at(p)?, Mine { .. });
+        let is_mine = matches!(cell, CellSpec::Mine(_));
+        let mut recount = if was_mine != is_mine {
+            self.neighbours(p)
+        } else {
+            vec![]
+        };
+        if matches!(cell, CellSpec::Safe(_)) {
+            recount.push(*p);
+        }
+        let mut map = self.map.clone();
+        let el = match cell {
+            CellSpec::Mine(state) => Mine { state },
+            CellSpec::Safe(state) => Number { state, count: 0 },
+            CellSpec::Number(state, count) => Number { state, count },
+        };
+        map.set(p.x as usize, p.y as usize, el);
+        for q in recount {
+            let mines = self
+                .neighbours(&q)
+                .iter()
+                .filter(|n| matches!(map.get(n.x as usize, n.y as usize), Mine { .. }))
+                .count() as i32;
+            if let Number { count, .. } = map.get_mut(q.x as usize, q.y as usize) {
+                *count = mines;
+            }
+        }
+        let mines = map.iter().filter(|el| matches!(el, Mine { .. })).count();
+        Some(
+            Board {
+                map,
+                mines,
+                ..self.clone()
+            }
+            .settled(),
+        )
+    }
+
+    fn from_map(map: Cells) -> Board {
+        let mines = map.iter().filter(|x| matches!(x, Mine { .. })).count();
+        let (width, height) = (map.width(), map.height());
         Board {
             width,
             height,
             mines,
             missing_points: (width as i32) * (height as i32) - (mines as i32),
             state: BoardState::NotReady,
+            adjacency: Adjacency::Knight,
+            seed: None,
+            symmetry: Symmetry::None,
+            spacing: None,
+            min_opening: None,
+            cascade: Cascade::Zeros,
+            map,
+        }
+    }
+
+    // Counts the mines around every number, which are all still 0.
+    fn with_numbers(self) -> Board {
+        let mut map = self.map.clone();
+        for p in self.points() {
+            if !matches!(self.at(&p), Some(Mine { .. })) {
+                continue;
+            }
+            for n in self.neighbours(&p) {
+                if let Number { count, .. } = map.get_mut(n.x as usize, n.y as usize) {
+                    *count += 1;
+                }
+            }
+        }
+        Board {
             map,
+            state: BoardState::Ready,
+            ..self
+        }
+    }
+
+    fn points(&self) -> impl Iterator<Item = Point> {
+        let width = self.width;
+        (0..self.height).flat_map(move |y| (0..width).map(move |x| Point::new(x, y)))
+    }
+
+    // Recomputes the bookkeeping that depends on cell states, for boards
+    // whose cells were set directly rather than through moves.
+    fn settled(self) -> Board {
+        let (missing_points, state) = self.settled_state();
+        Board {
+            missing_points,
+            state,
+            ..self
+        }
+    }
+
+    fn settled_state(&self) -> (i32, BoardState) {
+        let cells = self.map.iter();
+        let missing_points = cells
+            .clone()
+            .filter(|x| {
+                matches!(
+                    x,
+                    Number { state: Closed, .. } | Number { state: Flagged, .. }
+                )
+            })
+            .count() as i32;
+        let state = if cells.clone().any(|x| matches!(x, Mine { state: Open })) {
+            BoardState::Failed
+        } else if missing_points == 0 {
+            BoardState::Won
+        } else if cells.clone().any(|x| {
+            matches!(
+                x,
+                Mine { state: Flagged }
+                    | Number { state: Flagged, .. }
+                    | Number { state: Open, .. }
+            )
+        }) {
+            BoardState::Playing
+        } else {
+            BoardState::Ready
+        };
+        (missing_points, state)
+    }
+
+    pub fn row(&self, y: usize) -> Option<Vec<MapElement>> {
+        if y < self.height {
+            Some(self.map.row(y))
+        } else {
+            None
         }
     }
 
-    pub fn at(self: &Self, p: &Point) -> Option<&MapElement> {
+    pub fn at(&self, p: &Point) -> Option<&MapElement> {
         let width = self.width as i32;
         let height = self.height as i32;
         if p.x < 0 || p.x >= width || p.y < 0 || p.y >= height {
@@ -83,36 +469,34 @@ impl Board {
         } else {
             let x = p.x as usize;
             let y = p.y as usize;
-            Some(&self.map[y][x])
+            Some(self.map.get(x, y))
         }
     }
 
-    fn replace(self: &Self, p: &Point, el: MapElement) -> Board {
-        let was_closed = matches!(self.at(p), Some(Number { state: Closed, .. }));
-        let map = (0..self.height)
-            .map(|y| {
-                (0..self.width)
-                    .map(|x| {
-                        if Point::new(x, y) == *p {
-                            el.clone()
-                        } else {
-                            self.at(&Point::new(x, y)).unwrap().clone()
-                        }
-                    })
-                    .collect()
-            })
-            .collect();
-        let missing_points = if was_closed {
-            self.missing_points - 1
-        } else {
-            self.missing_points
-        };
+    fn replace(&self, p: &Point, el: MapElement) -> Board {
+        let opens_number = matches!(el, Number { state: Open, .. })
+            && !matches!(self.at(p), Some(Number { state: Open, .. }));
+        let mut map = self.map.clone();
+        map.set(p.x as usize, p.y as usize, el);
+        self.with_map(map, if opens_number { 1 } else { 0 })
+    }
+
+    // This board with `map` swapped in, after a move that opened `opened`
+    // numbers.
+    fn with_map(&self, map: Cells, opened: i32) -> Board {
+        let missing_points = self.missing_points - opened;
         Board {
             width: self.width,
             height: self.height,
             mines: self.mines,
             missing_points,
             map,
+            adjacency: self.adjacency,
+            seed: self.seed,
+            symmetry: self.symmetry,
+            spacing: self.spacing,
+            min_opening: self.min_opening,
+            cascade: self.cascade,
             state: match (missing_points, &self.state) {
                 (0, _) => BoardState::Won,
                 (_, BoardState::Ready) => BoardState::Playing,
@@ -121,7 +505,7 @@ impl Board {
         }
     }
 
-    pub fn flag_item(self: &Self, p: &Point) -> Board {
+    pub fn flag_item(&self, p: &Point) -> Board {
         match self.at(p) {
             Some(Mine { state }) => self.replace(
                 p,
@@ -144,36 +528,56 @@ impl Board {
                     count: *count,
                 },
             ),
-            None => unreachable!(),
+            // Off the board there's nothing to flag.
+            None => self.clone(),
         }
     }
 
-    pub fn cascade_open_item(self: &Self, p: &Point) -> Option<Board> {
-        match self.at(p).unwrap() {
+    pub fn cascade_open_item(&self, p: &Point) -> Option<Board> {
+        match self.at(p)? {
             Number { state: Open, .. }
             | Mine { state: Flagged, .. }
             | Number { state: Flagged, .. } => None,
-            Number {
-                state: Closed,
-                count,
-            } => {
-                let board = self.replace(
-                    p,
-                    Number {
-                        state: Open,
-                        count: *count,
-                    },
-                );
-                if *count == 0 {
-                    Some(
-                        board
-                            .surrounding_knight_points(&p)
-                            .iter()
-                            .fold(board, |b: Board, p| b.cascade_open_item(&p).unwrap_or(b)),
-                    )
-                } else {
-                    Some(board)
+            Number { state: Closed, .. } => {
+                // Opens the cascade with a work list rather than recursion, so
+                // big empty areas neither overflow the stack nor copy the map
+                // once per cell.
+                let mut map = self.map.clone();
+                let mut opened = 0;
+                let mut lost = false;
+                let mut pending = vec![*p];
+                while let Some(p) = pending.pop() {
+                    let (x, y) = (p.x as usize, p.y as usize);
+                    match *map.get(x, y) {
+                        Number {
+                            state: Closed,
+                            count,
+                        } => {
+                            map.set(x, y, Number { state: Open, count });
+                            opened += 1;
+                            let spreads = match self.cascade {
+                                Cascade::Zeros => count == 0,
+                                Cascade::Chording => self.flags_around(&map, &p) == count,
+                                Cascade::Off => false,
+                            };
+                            if spreads {
+                                pending.extend(self.neighbours(&p));
+                            }
+                        }
+                        // Only a chord past a wrong flag gets to a mine.
+                        Mine { state: Closed } => lost = true,
+                        _ => {}
+                    }
                 }
+                let board = self.with_map(map, opened);
+                Some(if lost {
+                    Board {
+                        state: BoardState::Failed,
+                        ..board
+                    }
+                } else {
+                    board
+                })
             }
             Mine { state: Open } | Mine { state: Closed } => Some(Board {
                 map: self.map.clone(),
@@ -181,12 +585,324 @@ impl Board {
                 height: self.height,
                 mines: self.mines,
                 missing_points: self.missing_points,
+                adjacency: self.adjacency,
+                seed: self.seed,
+                symmetry: self.symmetry,
+                spacing: self.spacing,
+                min_opening: self.min_opening,
+                cascade: self.cascade,
                 state: BoardState::Failed,
             }),
         }
     }
 
-    pub fn surrounding_points(self: &Self, p: &Point) -> Vec<Point> {
+    // Opens every unflagged neighbour of the open number at `p`, cascading
+    // from each, once it has as many flags around it as its count. A wrong
+    // flag means a mine gets opened and the board is lost. None if `p` isn't
+    // an open number, its flags don't add up or there's nothing left to open.
+    pub fn chord_open(&self, p: &Point) -> Option<Board> {
+        let count = match self.at(p)? {
+            Number { state: Open, count } => *count,
+            _ => return None,
+        };
+        if self.flags_around(&self.map, p) != count {
+            return None;
+        }
+        let mut board = self.clone();
+        for n in self.neighbours(p) {
+            if matches!(board.state, BoardState::Won | BoardState::Failed) {
+                break;
+            }
+            if let Some(next) = board.cascade_open_item(&n) {
+                board = next;
+            }
+        }
+        if board == *self {
+            return None;
+        }
+        Some(board)
+    }
+
+    pub fn stats(&self) -> BoardStats {
+        let cells = self.map.iter();
+        let count_state = |state: MapElementCellState| {
+            cells
+                .clone()
+                .filter(|x| match x {
+                    Mine { state: s } | Number { state: s, .. } => *s == state,
+                })
+                .count()
+        };
+        let closed = count_state(Closed);
+        let opened = count_state(Open);
+        let flagged = count_state(Flagged);
+        let safe_cells = self.width * self.height - self.mines;
+        BoardStats {
+            closed,
+            opened,
+            flagged,
+            mines: self.mines,
+            mines_remaining: self.mines as i32 - flagged as i32,
+            progress: if safe_cells == 0 {
+                1.0
+            } else {
+                opened as f64 / safe_cells as f64
+            },
+        }
+    }
+
+    // The 3BV: the fewest clicks that clear the board without flagging. One
+    // per opening, a connected patch of zeros that cascades along with the
+    // numbers around it, and one per number no opening reaches.
+    pub fn three_bv(&self) -> usize {
+        let index = |p: &Point| p.y as usize * self.width + p.x as usize;
+        let points: Vec<Point> = (0..self.height)
+            .flat_map(|y| (0..self.width).map(move |x| Point::new(x, y)))
+            .collect();
+        let count_at = |p: &Point| match self.at(p) {
+            Some(Number { count, .. }) => Some(*count),
+            _ => None,
+        };
+        let mut reached = vec![false; self.width * self.height];
+        let mut clicks = 0;
+        for p in points.iter().filter(|p| count_at(p) == Some(0)) {
+            if reached[index(p)] {
+                continue;
+            }
+            clicks += 1;
+            reached[index(p)] = true;
+            let mut opening = vec![*p];
+            while let Some(q) = opening.pop() {
+                for n in self.neighbours(&q) {
+                    if reached[index(&n)] || count_at(&n).is_none() {
+                        continue;
+                    }
+                    reached[index(&n)] = true;
+                    if count_at(&n) == Some(0) {
+                        opening.push(n);
+                    }
+                }
+            }
+        }
+        clicks
+            + points
+                .iter()
+                .filter(|p| count_at(p).is_some() && !reached[index(p)])
+                .count()
+    }
+
+    // FNV-1a over the board's shape and every cell's contents and state. The
+    // same on every platform and version, so it can be sent between players
+    // and kept in files; the seed and the game's state don't count.
+    pub fn position_hash(&self) -> u64 {
+        let mut bytes = vec![];
+        bytes.extend((self.width as u64).to_le_bytes());
+        bytes.extend((self.height as u64).to_le_bytes());
+        bytes.push(match self.adjacency {
+            Adjacency::King => 0,
+            Adjacency::Knight => 1,
+        });
+        for element in self.map.iter() {
+            // A tag byte tells mines from numbers, and numbers keep their
+            // whole count so no count can pass for another or for a mine.
+            let state = match element {
+                Mine { state } => {
+                    bytes.push(0);
+                    state
+                }
+                Number { state, count } => {
+                    bytes.push(1);
+                    bytes.extend(count.to_le_bytes());
+                    state
+                }
+            };
+            bytes.push(match state {
+                Closed => 0,
+                Open => 1,
+                Flagged => 2,
+            });
+        }
+        bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, b| {
+            (hash ^ u64::from(*b)).wrapping_mul(0x0100_0000_01b3)
+        })
+    }
+
+    // The cells that differ between this board and `other`, e.g. everything
+    // a move opened or flagged. Boards of different sizes differ everywhere.
+    pub fn changed_points(&self, other: &Board) -> Vec<Point> {
+        if (self.width, self.height) != (other.width, other.height) {
+            return other.points().collect();
+        }
+        self.map.changed(&other.map)
+    }
+
+    pub fn can_open_all_unflagged(&self) -> bool {
+        self.state == BoardState::Playing && self.stats().mines_remaining == 0
+    }
+
+    // Opens every closed cell in one go. Only allowed once as many flags as
+    // mines have been placed; if any of them was wrong, a mine is still closed
+    // and the game is lost.
+    pub fn open_all_unflagged(&self) -> Option<Board> {
+        if !self.can_open_all_unflagged() {
+            return None;
+        }
+        if self.map.iter().any(|x| matches!(x, Mine { state: Closed })) {
+            return Some(Board {
+                state: BoardState::Failed,
+                ..self.clone()
+            });
+        }
+        let mut map = self.map.clone();
+        for p in self.points() {
+            let (x, y) = (p.x as usize, p.y as usize);
+            if let Number {
+                state: Closed,
+                count,
+            } = *map.get(x, y)
+            {
+                map.set(x, y, Number { state: Open, count });
+            }
+        }
+        Some(Board {
+            map,
+            missing_points: 0,
+            state: BoardState::Won,
+            ..self.clone()
+        })
+    }
+
+    // How many cells the biggest cascade opens: a region of zeros and the
+    // numbers around it, as if every cell were closed. 0 without any zeros.
+    pub fn largest_opening(&self) -> usize {
+        self.biggest_opening().map_or(0, |(_, size)| size)
+    }
+
+    // A zero in the biggest opening, where a lucky first click lands.
+    pub fn opening_start(&self) -> Option<Point> {
+        self.biggest_opening().map(|(start, _)| start)
+    }
+
+    fn biggest_opening(&self) -> Option<(Point, usize)> {
+        let sizes = self.opening_sizes();
+        let mut biggest: Option<(Point, usize)> = None;
+        for (p, size) in self.points().zip(sizes) {
+            if !matches!(self.at(&p), Some(Number { count: 0, .. })) {
+                continue;
+            }
+            if biggest.is_none_or(|(_, most)| size > most) {
+                biggest = Some((p, size));
+            }
+        }
+        biggest
+    }
+
+    // How many cells a first click on each cell opens, in reading order: a
+    // zero's whole opening, 1 for any other number and 0 for a mine.
+    pub fn opening_sizes(&self) -> Vec<usize> {
+        let index = |p: &Point| p.y as usize * self.width + p.x as usize;
+        let mut sizes: Vec<usize> = self
+            .points()
+            .map(|p| match self.at(&p) {
+                Some(Number { .. }) => 1,
+                _ => 0,
+            })
+            .collect();
+        // The region each cell was last counted in, numbered from 1.
+        let mut region = vec![0; self.width * self.height];
+        for (id, start) in self.points().enumerate() {
+            let id = id + 1;
+            if region[index(&start)] != 0
+                || !matches!(self.at(&start), Some(Number { count: 0, .. }))
+            {
+                continue;
+            }
+            region[index(&start)] = id;
+            let (mut todo, mut zeros, mut size) = (vec![start], vec![start], 1);
+            while let Some(p) = todo.pop() {
+                for n in self.neighbours(&p) {
+                    if region[index(&n)] == id {
+                        continue;
+                    }
+                    region[index(&n)] = id;
+                    size += 1;
+                    if let Some(Number { count: 0, .. }) = self.at(&n) {
+                        todo.push(n);
+                        zeros.push(n);
+                    }
+                }
+            }
+            for zero in zeros {
+                sizes[index(&zero)] = size;
+            }
+        }
+        sizes
+    }
+
+    // The closed cells next to an open number, in reading order: the only
+    // ones the numbers say anything about. Flagged cells aren't included.
+    pub fn frontier(&self) -> Vec<Point> {
+        self.points()
+            .filter(|p| {
+                matches!(
+                    self.at(p),
+                    Some(Number { state: Closed, .. }) | Some(Mine { state: Closed })
+                )
+            })
+            .filter(|p| {
+                self.neighbours(p)
+                    .iter()
+                    .any(|n| matches!(self.at(n), Some(Number { state: Open, .. })))
+            })
+            .collect()
+    }
+
+    // A closed cell that isn't a mine, chosen by `pick` (any number, e.g. a
+    // random one), for a power-up that opens one. None once every safe cell
+    // is open.
+    pub fn pick_safe_cell(&self, pick: u64) -> Option<Point> {
+        self.pick_closed(pick, |el| matches!(el, Number { state: Closed, .. }))
+    }
+
+    // A closed cell, mine or not, chosen the same way.
+    pub fn pick_closed_cell(&self, pick: u64) -> Option<Point> {
+        self.pick_closed(pick, |el| {
+            matches!(el, Number { state: Closed, .. } | Mine { state: Closed })
+        })
+    }
+
+    fn pick_closed(&self, pick: u64, fits: impl Fn(&MapElement) -> bool) -> Option<Point> {
+        let cells: Vec<Point> = self
+            .points()
+            .filter(|p| self.at(p).is_some_and(&fits))
+            .collect();
+        if cells.is_empty() {
+            return None;
+        }
+        Some(cells[(pick % cells.len() as u64) as usize])
+    }
+
+    // The flags on `map` around `p`, as this board counts neighbours.
+    fn flags_around(&self, map: &Cells, p: &Point) -> i32 {
+        self.neighbours(p)
+            .iter()
+            .filter(|n| {
+                matches!(
+                    map.get(n.x as usize, n.y as usize),
+                    Mine { state: Flagged } | Number { state: Flagged, .. }
+                )
+            })
+            .count() as i32
+    }
+
+    pub fn neighbours(&self, p: &Point) -> Vec<Point> {
+        match self.adjacency {
+            Adjacency::King => self.surrounding_points(p),
+            Adjacency::Knight => self.surrounding_knight_points(p),
+        }
+    }
+
+    pub fn surrounding_points(&self, p: &Point) -> Vec<Point> {
         [p.x - 1, p.x, p.x + 1]
             .iter()
             .flat_map(|&x| {
@@ -200,7 +916,7 @@ impl Board {
             .collect()
     }
 
-    pub fn surrounding_knight_points(self: &Self, p: &Point) -> Vec<Point> {
+    pub fn surrounding_knight_points(&self, p: &Point) -> Vec<Point> {
         [-2i32, -1, 1, 2]
             .iter()
             .flat_map(|&x| {
@@ -216,80 +932,291 @@ impl Board {
 
 }
 
-pub fn create_board(
+fn random_mines(
     width: usize,
     height: usize,
     mines: usize,
     mut rand: impl FnMut(usize, usize) -> usize,
-) -> Board {
+) -> Vec<Point> {
     let mut points: Vec<Point> = Vec::with_capacity(mines);
+    let mut taken = vec![false; width * height];
     for _ in 0..mines {
         loop {
             let x = rand(0, width);
             let y = rand(0, height);
-            let p = Point::new(x, y);
-            if points.contains(&p) {
+            if taken[y * width + x] {
                 continue;
             }
-            points.push(p);
+            taken[y * width + x] = true;
+            points.push(Point::new(x, y));
             break;
         }
     }
+    points
+}
 
-    let map = (0..height)
-        .map(|y| {
-            (0..width)
-                .map(|x| {
-                    if points.contains(&Point::new(x, y)) {
-                        Mine { state: Closed }
-                    } else {
-                        Number {
-                            state: Closed,
-                            count: 0,
-                        }
-                    }
-                })
-                .collect()
-        })
-        .collect();
-    Board::new(map)
+fn mine_map(width: usize, height: usize, points: &[Point]) -> Cells {
+    let mut map = Cells::new(width, height);
+    for p in points {
+        map.set(p.x as usize, p.y as usize, Mine { state: Closed });
+    }
+    map
+}
+
+#[deprecated(note = "use BoardBuilder::mine_count")]
+pub fn create_board(
+    width: usize,
+    height: usize,
+    mines: usize,
+    rand: impl FnMut(usize, usize) -> usize,
+) -> Board {
+    let points = random_mines(width, height, mines, rand);
+    Board::from_map(mine_map(width, height, &points))
 }
 
+#[deprecated(note = "use BoardBuilder, which numbers the board when building it")]
 pub fn numbers_on_board(board: Board) -> Board {
-    let map = (0..board.height)
-        .map(|y| {
-            (0..board.width)
-                .map(|x| {
-                    let point = Point::new(x, y);
-                    match board.at(&point).unwrap() {
-                        Mine { state } => Mine {
-                            state: state.clone(),
-                        },
-                        Number { count: 0, state } => {
-                            let count = board
-                                .surrounding_knight_points(&point)
-                                .iter()
-                                .filter(|p| matches!(board.at(p), Some(Mine { .. })))
-                                .count() as i32;
-                            Number {
-                                state: state.clone(),
-                                count,
-                            }
-                        }
-                        _ => unreachable!(),
+    board.with_numbers()
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum ParseBoardError {
+    Empty,
+    UnevenRow { y: usize },
+    InvalidCell { x: usize, y: usize, c: char },
+    WrongCount { x: usize, y: usize },
+    InvalidLine { line: usize },
+    // The cells were read, but don't make a board, e.g. one too big.
+    Build(BuildBoardError),
+}
+
+impl fmt::Display for ParseBoardError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseBoardError::Empty => write!(f, "board has no rows"),
+            ParseBoardError::UnevenRow { y } => {
+                write!(f, "row {} is not as wide as the first row", y)
+            }
+            ParseBoardError::InvalidCell { x, y, c } => {
+                write!(f, "invalid cell '{}' at column {}, row {}", c, x, y)
+            }
+            ParseBoardError::WrongCount { x, y } => write!(
+                f,
+                "open number at column {}, row {} doesn't match the mines around it",
+                x, y
+            ),
+            ParseBoardError::InvalidLine { line } => write!(f, "line {} isn't a board cell", line),
+            ParseBoardError::Build(e) => write!(f, "cells don't make a board: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ParseBoardError {}
+
+type CsvCell = (usize, usize, bool, Option<usize>, MapElementCellState);
+
+fn parse_csv_cell(text: &str) -> Option<CsvCell> {
+    let fields: Vec<&str> = text.split(',').map(|f| f.trim()).collect();
+    if fields.len() != 5 {
+        return None;
+    }
+    let count = match fields[3] {
+        "" => None,
+        count => Some(count.parse().ok()?),
+    };
+    let state = match fields[4] {
+        "Closed" => Closed,
+        "Open" => Open,
+        "Flagged" => Flagged,
+        _ => return None,
+    };
+    Some((
+        fields[0].parse().ok()?,
+        fields[1].parse().ok()?,
+        fields[2].parse().ok()?,
+        count,
+        state,
+    ))
+}
+
+// One character per cell, one line per row:
+//   .  closed cell       *  closed mine
+//   f  flagged cell      F  flagged mine
+//   0-8 open number      X  open mine
+// King boards start with a `king` line. Knight boards have none, so text
+// written before there was a choice still reads the same.
+impl fmt::Display for Board {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.adjacency == Adjacency::King {
+            writeln!(f, "king")?;
+        }
+        for y in 0..self.height {
+            if y > 0 {
+                writeln!(f)?;
+            }
+            for x in 0..self.width {
+                let c = match self.map.get(x, y) {
+                    Number { state: Closed, .. } => '.',
+                    Number { state: Flagged, .. } => 'f',
+                    Number { state: Open, count } => {
+                        std::char::from_digit(*count as u32, 10).unwrap_or('?')
                     }
-                })
-                .collect()
-        })
-        .collect();
-    Board {
-        map,
-        state: BoardState::Ready,
-        ..board
+                    Mine { state: Closed } => '*',
+                    Mine { state: Flagged } => 'F',
+                    Mine { state: Open } => 'X',
+                };
+                write!(f, "{}", c)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Board {
+    type Err = ParseBoardError;
+
+    fn from_str(s: &str) -> Result<Board, ParseBoardError> {
+        let mut rows: Vec<&str> = s
+            .lines()
+            .map(|l| l.trim())
+            .filter(|l| !l.is_empty())
+            .collect();
+        let header = match rows.first() {
+            Some(&"king") => Some(Adjacency::King),
+            Some(&"knight") => Some(Adjacency::Knight),
+            _ => None,
+        };
+        if header.is_some() {
+            rows.remove(0);
+        }
+        let adjacency = header.unwrap_or(Adjacency::Knight);
+        let width = match rows.first() {
+            Some(row) => row.chars().count(),
+            None => return Err(ParseBoardError::Empty),
+        };
+
+        let mut open_counts = vec![];
+        let mut map = Vec::with_capacity(rows.len());
+        for (y, row) in rows.iter().enumerate() {
+            if row.chars().count() != width {
+                return Err(ParseBoardError::UnevenRow { y });
+            }
+            let mut map_row = Vec::with_capacity(width);
+            for (x, c) in row.chars().enumerate() {
+                let number = |state| Number { state, count: 0 };
+                map_row.push(match c {
+                    '.' => number(Closed),
+                    'f' => number(Flagged),
+                    '*' => Mine { state: Closed },
+                    'F' => Mine { state: Flagged },
+                    'X' => Mine { state: Open },
+                    '0'..='8' => {
+                        open_counts.push((x, y, c.to_digit(10).unwrap() as i32));
+                        number(Open)
+                    }
+                    c => return Err(ParseBoardError::InvalidCell { x, y, c }),
+                });
+            }
+            map.push(map_row);
+        }
+
+        let board = Board {
+            adjacency,
+            ..Board::from_map(Cells::from(map))
+        }
+        .with_numbers();
+        for (x, y, count) in open_counts {
+            if !matches!(board.at(&Point::new(x, y)), Some(Number { count: c, .. }) if *c == count)
+            {
+                return Err(ParseBoardError::WrongCount { x, y });
+            }
+        }
+
+        Ok(board.settled())
+    }
+}
+
+impl Board {
+    // The grid as the player sees it, for sharing a position without giving
+    // the mines away: like Display, but closed and flagged mines look like
+    // any other closed or flagged cell.
+    pub fn to_player_string(&self) -> String {
+        self.to_string().replace('*', ".").replace('F', "f")
+    }
+
+    // One row per cell, for loading into a spreadsheet. `count` is the number
+    // of mines around the cell, for mines too.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("x,y,mine,count,state\n");
+        for p in self.points() {
+            let (mine, count, state) = match self.map.get(p.x as usize, p.y as usize) {
+                Number { count, state } => (false, *count as usize, state),
+                Mine { state } => (true, self.count_neighbour_mines(&p), state),
+            };
+            csv += &format!("{},{},{},{},{:?}\n", p.x, p.y, mine, count, state);
+        }
+        csv
+    }
+
+    // Reads what `to_csv` writes. Counts may be left empty, and are
+    // recomputed either way; cells that aren't listed are closed.
+    pub fn from_csv(csv: &str) -> Result<Board, ParseBoardError> {
+        let mut cells = vec![];
+        for (line, text) in csv.lines().enumerate() {
+            let text = text.trim();
+            if text.is_empty() || (line == 0 && text.starts_with('x')) {
+                continue;
+            }
+            let cell =
+                parse_csv_cell(text).ok_or(ParseBoardError::InvalidLine { line: line + 1 })?;
+            cells.push(cell);
+        }
+        if cells.is_empty() {
+            return Err(ParseBoardError::Empty);
+        }
+        // Before the size is worked out, which a huge coordinate would
+        // overflow.
+        if let Some((x, y, ..)) = cells.iter().find(|c| c.0 >= MAX_CELLS || c.1 >= MAX_CELLS) {
+            return Err(ParseBoardError::Build(BuildBoardError::TooBig {
+                width: x.saturating_add(1),
+                height: y.saturating_add(1),
+            }));
+        }
+        let width = cells.iter().map(|c| c.0 + 1).max().unwrap_or(0);
+        let height = cells.iter().map(|c| c.1 + 1).max().unwrap_or(0);
+        let mines = cells
+            .iter()
+            .filter(|c| c.2)
+            .map(|c| Point::new(c.0, c.1))
+            .collect();
+        let mut builder = BoardBuilder::new(width, height).mines_at(mines);
+        for (x, y, _, _, state) in &cells {
+            builder = builder.cell_state(Point::new(*x, *y), state.clone());
+        }
+        let board = builder.build().map_err(ParseBoardError::Build)?;
+        for (x, y, _, count, _) in &cells {
+            let p = Point::new(*x, *y);
+            let actual = match board.at(&p) {
+                Some(Number { count, .. }) => *count as usize,
+                _ => board.count_neighbour_mines(&p),
+            };
+            if count.is_some_and(|c| c != actual) {
+                return Err(ParseBoardError::WrongCount { x: *x, y: *y });
+            }
+        }
+        Ok(board)
+    }
+
+    fn count_neighbour_mines(&self, p: &Point) -> usize {
+        self.neighbours(p)
+            .iter()
+            .filter(|n| matches!(self.at(n), Some(Mine { .. })))
+            .count()
     }
 }
 
 #[cfg(test)]
+#[allow(deprecated)]
 pub mod tests {
     use super::*;
     use pretty_assertions::assert_eq;
@@ -362,28 +1289,89 @@ pub mod tests {
         assert_eq!(map, expected_map);
     }
 
+    // Mines down the diagonal and every count still 0, for the tests of
+    // numbering.
+    fn diagonal_board(width: usize, height: usize) -> Board {
+        let board = Board::from_fn(width, height, |p| {
+            if p.x == p.y {
+                CellSpec::Mine(Closed)
+            } else {
+                CellSpec::Number(Closed, 0)
+            }
+        });
+        Board {
+            state: BoardState::NotReady,
+            ..board
+        }
+    }
+
     pub fn five_by_four_board() -> Board {
-        Board::new(make_map(
-            vec![
-                String::from("X0000"),
-                String::from("0X000"),
-                String::from("00X00"),
-                String::from("000X0"),
-            ],
-            vec![
-                String::from("CCCCC"),
-                String::from("CCCCC"),
-                String::from("CCCCC"),
-                String::from("CCCCC"),
-            ],
-        ))
+        diagonal_board(5, 4)
     }
 
     pub fn five_by_two_board() -> Board {
-        Board::new(make_map(
-            vec![String::from("X0000"), String::from("0X000")],
-            vec![String::from("CCCCC"), String::from("CCCCC")],
-        ))
+        diagonal_board(5, 2)
+    }
+
+    // The engine's first tests were written for King boards, before the
+    // game counted knight's moves, and still check those numbers.
+    fn king(board: Board) -> Board {
+        Board {
+            adjacency: Adjacency::King,
+            ..board
+        }
+    }
+
+    #[test]
+    fn test_edit() {
+        let board = Board::from_fn(3, 3, |_| CellSpec::Safe(Closed));
+        let count = |board: &Board, x, y| match board.at(&Point::new(x, y)) {
+            Some(Number { count, .. }) => *count,
+            _ => unreachable!(),
+        };
+        let mined = board
+            .edit(&Point::new(0, 0), CellSpec::Mine(Closed))
+            .unwrap();
+        assert_eq!(mined.mines, 1);
+        assert_eq!(mined.state, BoardState::Ready);
+        // A knight's move from the mine, and a king's.
+        assert_eq!(count(&mined, 1, 2), 1);
+        assert_eq!(count(&mined, 1, 1), 0);
+        let opened = mined.edit(&Point::new(1, 2), CellSpec::Safe(Open)).unwrap();
+        assert_eq!(opened.state, BoardState::Playing);
+        assert_eq!(count(&opened, 1, 2), 1);
+        let cleared = opened
+            .edit(&Point::new(0, 0), CellSpec::Safe(Closed))
+            .unwrap();
+        assert_eq!(cleared.mines, 0);
+        assert_eq!(count(&cleared, 1, 2), 0);
+        assert_eq!(board.edit(&Point::new(3, 0), CellSpec::Mine(Closed)), None);
+    }
+
+    #[test]
+    fn test_from_fn() {
+        let board = Board::from_fn(3, 3, |p| match (p.x, p.y) {
+            (0, 0) => CellSpec::Mine(Flagged),
+            (2, 2) => CellSpec::Number(Open, 5),
+            (0, 2) => CellSpec::Safe(Open),
+            _ => CellSpec::Safe(Closed),
+        });
+        assert_eq!(board.mines, 1);
+        assert_eq!(board.state, BoardState::Playing);
+        assert_eq!(board.at(&Point::new(0, 0)), Some(&Mine { state: Flagged }));
+        // A knight's move from the mine, and one that isn't.
+        let count = |x, y| match board.at(&Point::new(x, y)) {
+            Some(Number { count, .. }) => *count,
+            _ => unreachable!(),
+        };
+        assert_eq!(count(1, 2), 1);
+        assert_eq!(count(0, 2), 0);
+        assert_eq!(count(2, 2), 5);
+        let won = Board::from_fn(2, 1, |p| match p.x {
+            0 => CellSpec::Mine(Closed),
+            _ => CellSpec::Safe(Open),
+        });
+        assert_eq!(won.state, BoardState::Won);
     }
 
     #[test]
@@ -393,7 +1381,7 @@ pub mod tests {
         let mines = 4;
         let mut v = vec![3, 3, 2, 2, 1, 1, 0, 0];
         let rand = move |_start: usize, _end: usize| -> usize {
-            return v.pop().unwrap();
+            v.pop().unwrap()
         };
         let board = create_board(width, height, mines, rand);
         let expected_map = five_by_four_board().map;
@@ -408,7 +1396,7 @@ pub mod tests {
         let mines = 4;
         let mut v = vec![3, 3, 2, 2, 0, 0, 1, 1, 0, 0];
         let rand = move |_start: usize, _end: usize| -> usize {
-            return v.pop().unwrap();
+            v.pop().unwrap()
         };
         let board = create_board(width, height, mines, rand);
         let expected_map = five_by_four_board().map;
@@ -418,7 +1406,7 @@ pub mod tests {
 
     #[test]
     fn test_numbers_on_board() {
-        let board = numbers_on_board(five_by_four_board());
+        let board = numbers_on_board(king(five_by_four_board()));
         let expected_map = make_map(
             vec![
                 String::from("X2100"),
@@ -453,7 +1441,7 @@ pub mod tests {
 
     #[test]
     fn test_cascade_open_item() {
-        let board = numbers_on_board(five_by_two_board());
+        let board = numbers_on_board(king(five_by_two_board()));
         let board = board.cascade_open_item(&Point::new(3, 1)).unwrap();
         let expected_map = make_map(
             vec![String::from("X2100"), String::from("2X100")],
@@ -463,9 +1451,67 @@ pub mod tests {
         assert_eq!(board.state, BoardState::Playing);
     }
 
+    #[test]
+    fn test_cascade_policies() {
+        // A mine at the end of a 4x1 strip: a 1 beside it, then zeros.
+        let strip = |cascade| {
+            BoardBuilder::new(4, 1)
+                .mines_at(vec![Point::new(0, 0)])
+                .adjacency(Adjacency::King)
+                .cascade(cascade)
+                .build()
+                .unwrap()
+        };
+        let open = |board: Board, x| board.cascade_open_item(&Point::new(x, 0)).unwrap();
+        assert_eq!(open(strip(Cascade::Zeros), 3).state, BoardState::Won);
+        let single = open(strip(Cascade::Off), 3);
+        assert_eq!(
+            (single.stats().opened, single.state),
+            (1, BoardState::Playing)
+        );
+        let flagged = |cascade, x| strip(cascade).flag_item(&Point::new(x, 0));
+        assert_eq!(open(flagged(Cascade::Zeros, 0), 1).stats().opened, 1);
+        assert_eq!(
+            open(flagged(Cascade::Chording, 0), 1).state,
+            BoardState::Won
+        );
+        // The chord goes through a wrong flag onto the mine.
+        let lost = open(flagged(Cascade::Chording, 2), 1);
+        assert_eq!(lost.state, BoardState::Failed);
+    }
+
+    #[test]
+    fn test_chord_open() {
+        // A mine at each end of a 5x1 strip, with ones beside them and a
+        // zero in the middle. Nothing cascades on its own.
+        let strip = BoardBuilder::new(5, 1)
+            .mines_at(vec![Point::new(0, 0), Point::new(4, 0)])
+            .adjacency(Adjacency::King)
+            .cascade(Cascade::Off)
+            .build()
+            .unwrap();
+        let one = Point::new(1, 0);
+        assert_eq!(strip.chord_open(&one), None);
+        let opened = strip.cascade_open_item(&one).unwrap();
+        assert_eq!(opened.chord_open(&one), None);
+        assert_eq!(opened.chord_open(&Point::new(0, 0)), None);
+        let flagged = opened.flag_item(&Point::new(0, 0));
+        let chorded = flagged.chord_open(&one).unwrap();
+        assert_eq!(chorded.stats().opened, 2);
+        assert_eq!(chorded.state, BoardState::Playing);
+        assert_eq!(chorded.chord_open(&one), None);
+        // A wrong flag takes the chord onto a mine.
+        let wrong = strip
+            .cascade_open_item(&Point::new(3, 0))
+            .unwrap()
+            .flag_item(&Point::new(2, 0));
+        let lost = wrong.chord_open(&Point::new(3, 0)).unwrap();
+        assert_eq!(lost.state, BoardState::Failed);
+    }
+
     #[test]
     fn test_win_board() {
-        let board = numbers_on_board(five_by_two_board());
+        let board = numbers_on_board(king(five_by_two_board()));
         let board = board.cascade_open_item(&Point::new(3, 1)).unwrap();
         let board = board.cascade_open_item(&Point::new(0, 1)).unwrap();
         let board = board.cascade_open_item(&Point::new(1, 0)).unwrap();
@@ -479,7 +1525,7 @@ pub mod tests {
 
     #[test]
     fn test_flag() {
-        let board = numbers_on_board(five_by_two_board());
+        let board = numbers_on_board(king(five_by_two_board()));
         let board = board.flag_item(&Point::new(3, 1));
         let expected_map = make_map(
             vec![String::from("X2100"), String::from("2X100")],
@@ -491,7 +1537,7 @@ pub mod tests {
 
     #[test]
     fn test_flagging_again_unflags() {
-        let board = numbers_on_board(five_by_two_board());
+        let board = numbers_on_board(king(five_by_two_board()));
         let board = board.flag_item(&Point::new(3, 1));
         let board = board.flag_item(&Point::new(3, 1));
         let expected_map = make_map(
@@ -504,7 +1550,7 @@ pub mod tests {
 
     #[test]
     fn test_flagging_open_does_noting() {
-        let board = numbers_on_board(five_by_two_board());
+        let board = numbers_on_board(king(five_by_two_board()));
         let board = board.cascade_open_item(&Point::new(2, 0)).unwrap();
         let board = board.flag_item(&Point::new(2, 0));
         let expected_map = make_map(
@@ -514,4 +1560,345 @@ pub mod tests {
         assert_eq!(board.map, expected_map);
         assert_eq!(board.state, BoardState::Playing);
     }
+
+    #[test]
+    fn test_flagging_off_the_board_does_nothing() {
+        let board = numbers_on_board(king(five_by_two_board()));
+        assert_eq!(board.flag_item(&Point::new(5, 0)), board);
+        assert_eq!(board.flag_item(&Point { x: -1, y: 0 }), board);
+    }
+
+    #[test]
+    fn test_open_all_unflagged() {
+        let board = numbers_on_board(five_by_two_board());
+        let board = board.flag_item(&Point::new(0, 0));
+        let board = board.flag_item(&Point::new(1, 1));
+        let board = board.open_all_unflagged().unwrap();
+        let expected_map = make_map(
+            vec![String::from("X0010"), String::from("0X100")],
+            vec![String::from("FOOOO"), String::from("OFOOO")],
+        );
+        assert_eq!(board.map, expected_map);
+        assert_eq!(board.state, BoardState::Won);
+    }
+
+    #[test]
+    fn test_open_all_unflagged_with_wrong_flag_fails() {
+        let board = numbers_on_board(five_by_two_board());
+        let board = board.flag_item(&Point::new(0, 0));
+        let board = board.flag_item(&Point::new(2, 1));
+        let board = board.open_all_unflagged().unwrap();
+        assert_eq!(board.state, BoardState::Failed);
+    }
+
+    #[test]
+    fn test_open_all_unflagged_needs_all_flags() {
+        let board = numbers_on_board(five_by_two_board());
+        let board = board.flag_item(&Point::new(0, 0));
+        assert!(!board.can_open_all_unflagged());
+        assert_eq!(board.open_all_unflagged(), None);
+    }
+
+    #[test]
+    fn test_stats() {
+        let board = numbers_on_board(five_by_two_board());
+        let board = board.cascade_open_item(&Point::new(3, 1)).unwrap();
+        let board = board.flag_item(&Point::new(0, 0));
+        let board = board.flag_item(&Point::new(4, 1));
+        assert_eq!(
+            board.stats(),
+            BoardStats {
+                closed: 6,
+                opened: 2,
+                flagged: 2,
+                mines: 2,
+                mines_remaining: 0,
+                progress: 0.25,
+            }
+        );
+    }
+
+    #[test]
+    fn test_three_bv() {
+        let board = |width, mines: Vec<Point>| {
+            BoardBuilder::new(width, 3)
+                .mines_at(mines)
+                .adjacency(Adjacency::King)
+                .build()
+                .unwrap()
+        };
+        // The zeros all join up into one opening that reaches every number.
+        assert_eq!(board(3, vec![Point::new(0, 0)]).three_bv(), 1);
+        // No zeros at all, so every safe cell takes a click.
+        assert_eq!(board(2, vec![Point::new(0, 1)]).three_bv(), 5);
+        // Two openings, either side of a wall of mines.
+        let wall = (0..3).map(|y| Point::new(2, y)).collect();
+        assert_eq!(board(5, wall).three_bv(), 2);
+    }
+
+    #[test]
+    fn test_row() {
+        let board: Board = "F0...\n.*.0f".parse().unwrap();
+        let row = board.row(1).unwrap();
+        assert_eq!(row.len(), 5);
+        assert_eq!(Some(&row[1]), board.at(&Point::new(1, 1)));
+        assert_eq!(board.row(2), None);
+    }
+
+    #[test]
+    fn test_changed_points() {
+        let board = numbers_on_board(five_by_two_board());
+        let opened = board.cascade_open_item(&Point::new(3, 1)).unwrap();
+        assert_eq!(
+            board.changed_points(&opened),
+            vec![Point::new(1, 0), Point::new(3, 1)]
+        );
+        let flagged = opened.flag_item(&Point::new(0, 0));
+        assert_eq!(opened.changed_points(&flagged), vec![Point::new(0, 0)]);
+        assert_eq!(flagged.changed_points(&flagged), vec![]);
+        let other: Board = "..\n..".parse().unwrap();
+        assert_eq!(board.changed_points(&other).len(), 4);
+    }
+
+    #[test]
+    fn test_position_hash() {
+        let board = numbers_on_board(five_by_two_board());
+        assert_eq!(board.position_hash(), board.clone().position_hash());
+        // Pinned, as other clients and saved games rely on it.
+        assert_eq!(board.position_hash(), 621_488_710_815_091_477);
+        let opened = board.cascade_open_item(&Point::new(3, 1)).unwrap();
+        let flagged = opened.flag_item(&Point::new(0, 0));
+        assert!(opened.position_hash() != board.position_hash());
+        assert!(flagged.position_hash() != opened.position_hash());
+        let reseeded = Board {
+            seed: Some(7),
+            ..board.clone()
+        };
+        assert_eq!(reseeded.position_hash(), board.position_hash());
+        // Counts past a byte are told apart from smaller ones and mines.
+        let p = Point::new(1, 0);
+        let count = |count| {
+            board.replace(
+                &p,
+                Number {
+                    state: Closed,
+                    count,
+                },
+            )
+        };
+        assert!(count(256).position_hash() != count(0).position_hash());
+        assert!(
+            count(255).position_hash() != board.replace(&p, Mine { state: Closed }).position_hash()
+        );
+    }
+
+    #[test]
+    fn test_frontier() {
+        let board = numbers_on_board(five_by_two_board());
+        assert_eq!(board.frontier(), vec![]);
+        // The 1 only sees the mine a knight's move away, not the cells
+        // around it.
+        let opened = board.cascade_open_item(&Point::new(3, 0)).unwrap();
+        assert_eq!(opened.frontier(), vec![Point::new(1, 1)]);
+        let flagged = opened.flag_item(&Point::new(1, 1));
+        assert_eq!(flagged.frontier(), vec![]);
+    }
+
+    #[test]
+    fn test_pick_cells() {
+        let board = numbers_on_board(five_by_two_board());
+        let opened = board.cascade_open_item(&Point::new(3, 0)).unwrap();
+        let flagged = opened.flag_item(&Point::new(1, 1));
+        for pick in 0..20 {
+            let safe = flagged.pick_safe_cell(pick).unwrap();
+            assert!(matches!(
+                flagged.at(&safe),
+                Some(Number { state: Closed, .. })
+            ));
+            let closed = flagged.pick_closed_cell(pick).unwrap();
+            assert!(closed != Point::new(3, 0) && closed != Point::new(1, 1));
+        }
+        // Seven safe cells are closed, so picks wrap around after them.
+        assert_eq!(flagged.pick_safe_cell(0), flagged.pick_safe_cell(7));
+        let cleared = flagged
+            .flag_item(&Point::new(0, 0))
+            .open_all_unflagged()
+            .unwrap();
+        assert_eq!(cleared.pick_safe_cell(3), None);
+        assert_eq!(cleared.pick_closed_cell(3), None);
+    }
+
+    #[test]
+    fn test_largest_opening() {
+        let build = |adjacency| {
+            BoardBuilder::new(4, 1)
+                .mines_at(vec![Point::new(0, 0)])
+                .adjacency(adjacency)
+                .build()
+                .unwrap()
+        };
+        // The zeros at 2 and 3, and the 1 next to the mine.
+        assert_eq!(build(Adjacency::King).largest_opening(), 3);
+        // Knights can't move on a single row, so every zero opens alone.
+        assert_eq!(build(Adjacency::Knight).largest_opening(), 1);
+        let full = BoardBuilder::new(2, 2).mine_count(4).build().unwrap();
+        assert_eq!(full.largest_opening(), 0);
+        assert_eq!(full.opening_start(), None);
+        assert_eq!(
+            build(Adjacency::King).opening_start(),
+            Some(Point::new(2, 0))
+        );
+    }
+
+    #[test]
+    fn test_huge_board() {
+        let board = BoardBuilder::new(1000, 1000)
+            .mine_count(150_000)
+            .seed(1)
+            .build()
+            .unwrap();
+        let p = (0..1000)
+            .map(|x| Point::new(x, 500))
+            .find(|p| matches!(board.at(p), Some(Number { count: 0, .. })))
+            .unwrap();
+        let opened = board.cascade_open_item(&p).unwrap();
+        assert_eq!(opened.state, BoardState::Playing);
+        let closed = Point::new(999, 999);
+        assert!(matches!(
+            opened.at(&closed),
+            Some(Number { state: Closed, .. }) | Some(Mine { .. })
+        ));
+        let flagged = opened.flag_item(&closed);
+        assert_eq!(opened.changed_points(&flagged), vec![closed]);
+    }
+
+    #[test]
+    fn test_display() {
+        let board = numbers_on_board(five_by_two_board());
+        let board = board.cascade_open_item(&Point::new(3, 1)).unwrap();
+        let board = board.flag_item(&Point::new(0, 0));
+        let board = board.flag_item(&Point::new(4, 1));
+        assert_eq!(format!("{}", board), "F0...\n.*.0f");
+    }
+
+    #[test]
+    fn test_from_str() {
+        let board: Board = "F0...\n.*.0f".parse().unwrap();
+        let expected_map = make_map(
+            vec![String::from("X0010"), String::from("0X100")],
+            vec![String::from("FOCCC"), String::from("CCCOF")],
+        );
+        assert_eq!(board.map, expected_map);
+        assert_eq!(board.state, BoardState::Playing);
+        assert_eq!(board.mines, 2);
+    }
+
+    #[test]
+    fn test_display_from_str_round_trip() {
+        let board = numbers_on_board(five_by_four_board());
+        let board = board.cascade_open_item(&Point::new(4, 0)).unwrap();
+        let board = board.flag_item(&Point::new(2, 2));
+        let board = board.flag_item(&Point::new(0, 3));
+        let parsed: Board = board.to_string().parse().unwrap();
+        assert_eq!(parsed, board);
+        assert_eq!(parsed.to_string(), board.to_string());
+
+        let board = numbers_on_board(five_by_two_board());
+        let parsed: Board = board.to_string().parse().unwrap();
+        assert_eq!(parsed, board);
+
+        let king = BoardBuilder::new(3, 2)
+            .mines_at(vec![Point::new(1, 1)])
+            .adjacency(Adjacency::King)
+            .build()
+            .unwrap();
+        let king = king.cascade_open_item(&Point::new(0, 0)).unwrap();
+        assert_eq!(king.to_string(), "king\n1..\n.*.");
+        let parsed: Board = king.to_string().parse().unwrap();
+        assert_eq!(parsed.adjacency, Adjacency::King);
+        assert_eq!(parsed.map, king.map);
+        assert_eq!(parsed.to_string(), king.to_string());
+        let knight: Board = "knight\n..\n..".parse().unwrap();
+        assert_eq!(knight.adjacency, Adjacency::Knight);
+        assert_eq!("king".parse::<Board>(), Err(ParseBoardError::Empty));
+    }
+
+    #[test]
+    fn test_to_player_string() {
+        let board: Board = "F0.\n.*1".parse().unwrap();
+        assert_eq!(board.to_player_string(), "f0.\n..1");
+        let opened = board.cascade_open_item(&Point::new(2, 0)).unwrap();
+        assert_eq!(opened.to_player_string(), "f00\n0.1");
+    }
+
+    #[test]
+    fn test_to_csv() {
+        let board: Board = "F0.\n.*1".parse().unwrap();
+        assert_eq!(
+            board.to_csv(),
+            "x,y,mine,count,state\n\
+             0,0,true,0,Flagged\n\
+             1,0,false,0,Open\n\
+             2,0,false,0,Closed\n\
+             0,1,false,0,Closed\n\
+             1,1,true,0,Closed\n\
+             2,1,false,1,Open\n"
+        );
+    }
+
+    #[test]
+    fn test_from_csv() {
+        let board: Board = "F0.\n.*1".parse().unwrap();
+        assert_eq!(Board::from_csv(&board.to_csv()), Ok(board.clone()));
+        let sparse = "0,0,true,,Flagged\n1,0,false,,Open\n2,1,false,,Open\n1,1,true,,Closed";
+        assert_eq!(Board::from_csv(sparse), Ok(board));
+    }
+
+    #[test]
+    fn test_from_csv_errors() {
+        assert_eq!(
+            Board::from_csv("x,y,mine,count,state\n"),
+            Err(ParseBoardError::Empty)
+        );
+        assert_eq!(
+            Board::from_csv("0,0,true,0,Closed\n1,0,maybe,0,Closed"),
+            Err(ParseBoardError::InvalidLine { line: 2 })
+        );
+        assert_eq!(
+            Board::from_csv("0,0,true,,Closed\n2,1,false,3,Open"),
+            Err(ParseBoardError::WrongCount { x: 2, y: 1 })
+        );
+        assert_eq!(
+            Board::from_csv("0,0,true,,Closed\n2999,2999,false,,Closed"),
+            Err(ParseBoardError::Build(BuildBoardError::TooBig {
+                width: 3000,
+                height: 3000
+            }))
+        );
+        let huge = format!("0,0,true,,Closed\n{},0,false,,Closed", usize::MAX);
+        assert_eq!(
+            Board::from_csv(&huge),
+            Err(ParseBoardError::Build(BuildBoardError::TooBig {
+                width: usize::MAX,
+                height: 1
+            }))
+        );
+    }
+
+    #[test]
+    fn test_from_str_errors() {
+        assert_eq!("".parse::<Board>(), Err(ParseBoardError::Empty));
+        assert_eq!(
+            "...\n..".parse::<Board>(),
+            Err(ParseBoardError::UnevenRow { y: 1 })
+        );
+        assert_eq!(
+            "..?".parse::<Board>(),
+            Err(ParseBoardError::InvalidCell { x: 2, y: 0, c: '?' })
+        );
+        assert_eq!(
+            "*2..".parse::<Board>(),
+            Err(ParseBoardError::WrongCount { x: 1, y: 0 })
+        );
+    }
 }
@@ -1,10 +1,11 @@
 use colored::Colorize;
-use rand::Rng;
 use std::io;
+use std::time::Instant;
 
-use lib_minesweeper::create_board;
-use lib_minesweeper::numbers_on_board;
+use lib_minesweeper::opening_book;
+use lib_minesweeper::Adjacency;
 use lib_minesweeper::Board;
+use lib_minesweeper::BoardBuilder;
 use lib_minesweeper::BoardState;
 use lib_minesweeper::MapElement::Mine;
 use lib_minesweeper::MapElement::Number;
@@ -12,8 +13,26 @@ use lib_minesweeper::MapElementCellState::Closed;
 use lib_minesweeper::MapElementCellState::Flagged;
 use lib_minesweeper::MapElementCellState::Open;
 use lib_minesweeper::Point;
+#[cfg(feature = "solver")]
+use lib_minesweeper::{BasicSolver, Guessing, NoGuessSearch, SafestGuess, Tournament};
 
 fn main() {
+    #[cfg(feature = "solver")]
+    {
+        if std::env::args().nth(1).as_deref() == Some("tournament") {
+            return run_tournament();
+        }
+        if std::env::args().nth(1).as_deref() == Some("no-guess") {
+            return find_no_guess();
+        }
+    }
+    if std::env::args().nth(1).as_deref() == Some("openings") {
+        return print_openings();
+    }
+    if std::env::args().nth(1).as_deref() == Some("huge") {
+        return time_huge_board();
+    }
+
     //let height = rand::thread_rng().gen_range(5, 30);
     //let width = rand::thread_rng().gen_range(5, 30);
     //let mines = rand::thread_rng().gen_range((height * width) / 4, (height * width) * 5 / 10);
@@ -21,11 +40,10 @@ fn main() {
     let height = 8;
     let mines = 10;
 
-    let board = create_board(width, height, mines, |x, y| {
-        rand::thread_rng().gen_range(x, y)
-    });
-
-    let mut board = numbers_on_board(board);
+    let mut board = BoardBuilder::new(width, height)
+        .mine_count(mines)
+        .build()
+        .unwrap();
 
     loop {
         colorized_print_map(&board);
@@ -49,6 +67,109 @@ fn main() {
     }
 }
 
+// Plays the built-in strategies against each other on 100 seeded boards.
+#[cfg(feature = "solver")]
+fn run_tournament() {
+    let tournament = Tournament {
+        width: 8,
+        height: 8,
+        mines: 10,
+        adjacency: Adjacency::Knight,
+        seeds: (0..100).collect(),
+    };
+    for standing in tournament.run(&[&BasicSolver, &Guessing(BasicSolver), &SafestGuess]) {
+        println!(
+            "{:>10}: won {}/{}, {:.0}% cleared on average, {} moves",
+            standing.name,
+            standing.won,
+            standing.played,
+            standing.progress * 100_f64,
+            standing.moves
+        );
+    }
+}
+
+// Finds the first seed with a Hard knight board that can be won from the
+// middle without guessing. Build with `--features parallel` to use every
+// core.
+#[cfg(feature = "solver")]
+fn find_no_guess() {
+    let search = NoGuessSearch {
+        width: 16,
+        height: 30,
+        mines: 99,
+        adjacency: Adjacency::Knight,
+        start: Point::new(8, 15),
+    };
+    match search.find(0..10_000) {
+        Some(seed) => {
+            println!("seed {} is no-guess from {:?}", seed, search.start);
+            let board = search.board(seed);
+            colorized_print_map(&board.cascade_open_item(&search.start).unwrap());
+        }
+        None => println!("no seed below 10000 is no-guess"),
+    }
+}
+
+// Ranks first clicks on Easy knight boards over 1000 seeds, printing the
+// best ten.
+fn print_openings() {
+    let boards = (0..1000).map(|seed| {
+        BoardBuilder::new(8, 8)
+            .mine_count(10)
+            .adjacency(Adjacency::Knight)
+            .seed(seed)
+            .build()
+            .unwrap()
+    });
+    for opening in opening_book(boards).iter().take(10) {
+        println!(
+            "column {}, row {}: safe {:.1}% of the time, opens {:.1} cells on average",
+            opening.point.x + 1,
+            opening.point.y + 1,
+            opening.survival * 100_f64,
+            opening.expected_cascade
+        );
+    }
+}
+
+// Times what a move costs on a 1000x1000 board: building it, a cascade
+// through most of it, a flag, and working out which cells a move changed,
+// which is what the browser redraws. Drawing itself isn't timed here.
+fn time_huge_board() {
+    let timed = |what: &str, start: Instant| {
+        println!(
+            "{:>17}: {:.1}ms",
+            what,
+            start.elapsed().as_secs_f64() * 1000_f64
+        );
+    };
+    let start = Instant::now();
+    let board = BoardBuilder::new(1000, 1000)
+        .mine_count(150_000)
+        .seed(1)
+        .build()
+        .unwrap();
+    timed("build", start);
+    let zero = (0..1000)
+        .map(|x| Point::new(x, 500))
+        .find(|p| matches!(board.at(p), Some(Number { count: 0, .. })))
+        .unwrap();
+    let start = Instant::now();
+    let opened = board.cascade_open_item(&zero).unwrap();
+    timed("cascade", start);
+    println!("{:>17}: {}", "cells opened", opened.stats().opened);
+    let start = Instant::now();
+    let flagged = opened.flag_item(&Point::new(999, 999));
+    timed("flag", start);
+    let start = Instant::now();
+    opened.changed_points(&flagged);
+    timed("flag's changes", start);
+    let start = Instant::now();
+    board.changed_points(&opened);
+    timed("cascade's changes", start);
+}
+
 #[derive(Debug, PartialEq)]
 pub enum Operation {
     Open { point: Point },
@@ -62,7 +183,7 @@ fn process_line(line: String, board: &Board) -> Option<Operation> {
             let x = coord_reverse_mapping(*x);
             let y = coord_reverse_mapping(*y);
             let p = Point { x, y };
-            if matches!(board.at(&p), Some(_)) {
+            if board.at(&p).is_some() {
                 match op {
                     b'o' => Some(Operation::Open { point: p }),
                     b'f' => Some(Operation::Flag { point: p }),
@@ -98,11 +219,11 @@ fn print_board_state(board: &Board) {
         BoardState::Failed => print!("{}", "☠️  FAILED ☠️".red()),
         _ => unreachable!(),
     }
-    println!();
+    println!(", {} mines left", board.stats().mines_remaining);
 }
 
 fn colorized_print_map(board: &Board) {
-    print_board_state(&board);
+    print_board_state(board);
     let mut mapping = vec![];
     mapping.extend((b'0'..=b'9').map(char::from));
     mapping.extend((b'a'..=b'z').map(char::from));
@@ -112,8 +233,8 @@ fn colorized_print_map(board: &Board) {
     }
     println!();
     let is_done = matches!(board.state, BoardState::Failed | BoardState::Won);
-    for y in 0..board.height {
-        print!("{} ", mapping[y]);
+    for (y, row_label) in mapping.iter().enumerate().take(board.height) {
+        print!("{} ", row_label);
         for x in 0..board.width {
             let x = x as i32;
             let y = y as i32;
@@ -137,7 +258,7 @@ fn colorized_print_map(board: &Board) {
             };
             print!("{} ", c);
         }
-        print!("{}", mapping[y]);
+        print!("{}", row_label);
         println!();
     }
 
@@ -151,33 +272,13 @@ fn colorized_print_map(board: &Board) {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use lib_minesweeper::*;
     use pretty_assertions::assert_eq;
 
-    // TODO: I'm a dummy and couldn't figure out how to import this function and the next one from lib.rs
-    fn make_map(map: Vec<Vec<(bool, i32)>>) -> Vec<Vec<MapElement>> {
-        map.iter()
-            .map(|row| {
-                row.iter()
-                    .map(|(open, count)| match count {
-                        -1 => Mine {
-                            state: if *open { Open } else { Closed },
-                        },
-                        count => Number {
-                            state: if *open { Open } else { Closed },
-                            count: *count,
-                        },
-                    })
-                    .collect()
-            })
-            .collect()
-    }
-
     pub fn five_by_two_board() -> Board {
-        Board::new(make_map(vec![
-            vec![(false, -1), (false, 0), (false, 0), (false, 0), (false, 0)],
-            vec![(false, 0), (false, -1), (false, 0), (false, 0), (false, 0)],
-        ]))
+        BoardBuilder::new(5, 2)
+            .mines_at(vec![Point::new(0, 0), Point::new(1, 1)])
+            .build()
+            .unwrap()
     }
 
     #[test]
@@ -0,0 +1,124 @@
+use std::ops::Range;
+
+#[cfg(all(feature = "parallel", not(target_arch = "wasm32")))]
+use rayon::prelude::*;
+
+use crate::Adjacency;
+use crate::Board;
+use crate::BoardBuilder;
+use crate::Point;
+use crate::Rating;
+
+// Looks for boards that can be won from the first click without ever
+// guessing, by building one seed after another until one can. Most seeds
+// don't work out on big, dense boards, so with the `parallel` feature the
+// seeds are tried on every core.
+#[derive(Debug, PartialEq, Clone)]
+pub struct NoGuessSearch {
+    pub width: usize,
+    pub height: usize,
+    pub mines: usize,
+    pub adjacency: Adjacency,
+    // The first click. It's never a mine on the boards found.
+    pub start: Point,
+}
+
+impl NoGuessSearch {
+    pub fn board(&self, seed: u64) -> Board {
+        BoardBuilder::new(self.width, self.height)
+            .mine_count(self.mines)
+            .adjacency(self.adjacency)
+            .seed(seed)
+            .build()
+            .unwrap()
+    }
+
+    // Whether opening `start` and then only playing moves that are certain
+    // wins the board.
+    pub fn is_no_guess(&self, board: &Board) -> bool {
+        Rating::from_start(board, &self.start) != Rating::Brutal
+    }
+
+    // The first of `seeds` with a no-guess board.
+    #[cfg(not(all(feature = "parallel", not(target_arch = "wasm32"))))]
+    pub fn find(&self, mut seeds: Range<u64>) -> Option<u64> {
+        seeds.find(|seed| self.is_no_guess(&self.board(*seed)))
+    }
+
+    // Tries the seeds on every core, but still returns the first that works.
+    #[cfg(all(feature = "parallel", not(target_arch = "wasm32")))]
+    pub fn find(&self, seeds: Range<u64>) -> Option<u64> {
+        seeds
+            .into_par_iter()
+            .find_first(|seed| self.is_no_guess(&self.board(*seed)))
+    }
+
+    // Up to `count` of `seeds` with no-guess boards, in order, leaving out
+    // any board that's a turn or flip of one already found.
+    pub fn find_distinct(&self, seeds: Range<u64>, count: usize) -> Vec<u64> {
+        let mut found = vec![];
+        let mut layouts: Vec<Board> = vec![];
+        let mut next = seeds.start;
+        while found.len() < count {
+            let seed = match self.find(next..seeds.end) {
+                Some(seed) => seed,
+                None => break,
+            };
+            next = seed + 1;
+            let layout = self.board(seed).canonical_layout();
+            if !layouts.iter().any(|l| l.layout_eq(&layout)) {
+                layouts.push(layout);
+                found.push(seed);
+            }
+        }
+        found
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn search() -> NoGuessSearch {
+        NoGuessSearch {
+            width: 8,
+            height: 8,
+            mines: 10,
+            adjacency: Adjacency::Knight,
+            start: Point::new(4, 4),
+        }
+    }
+
+    #[test]
+    fn test_find_returns_first_no_guess_seed() {
+        let search = search();
+        let seed = search.find(0..1000).unwrap();
+        assert!(search.is_no_guess(&search.board(seed)));
+        assert!((0..seed).all(|seed| !search.is_no_guess(&search.board(seed))));
+        assert_eq!(search.find(seed..seed + 1), Some(seed));
+    }
+
+    #[test]
+    fn test_find_distinct() {
+        let search = search();
+        let seeds = search.find_distinct(0..1000, 3);
+        assert_eq!(seeds.len(), 3);
+        assert_eq!(seeds[0], search.find(0..1000).unwrap());
+        assert!(seeds.windows(2).all(|w| w[0] < w[1]));
+        assert!(seeds.iter().all(|s| search.is_no_guess(&search.board(*s))));
+        assert_eq!(
+            search.find_distinct(seeds[0]..seeds[0] + 1, 3),
+            vec![seeds[0]]
+        );
+    }
+
+    #[test]
+    fn test_mine_under_start_is_not_no_guess() {
+        let board = BoardBuilder::new(8, 8)
+            .mines_at(vec![Point::new(4, 4)])
+            .build()
+            .unwrap();
+        assert!(!search().is_no_guess(&board));
+    }
+}
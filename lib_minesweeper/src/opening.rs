@@ -0,0 +1,116 @@
+use crate::Board;
+use crate::Point;
+
+// How good a first click on one cell is, over one board or many.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Opening {
+    pub point: Point,
+    // How often the cell was safe, from 0 to 1.
+    pub survival: f64,
+    // The cells a click there opens on average, a mine counting as none.
+    pub expected_cascade: f64,
+}
+
+// Every cell of `board` as a first click, best first. Knows where the mines
+// are, so it's for editors and debugging rather than a hint.
+pub fn rank_openings(board: &Board) -> Vec<Opening> {
+    opening_book(std::iter::once(board.clone()))
+}
+
+// Every cell as a first click over `boards`, best first: by how much it
+// opens on average, then how often it's safe, then in reading order. Boards
+// of another size from the first are left out.
+pub fn opening_book(boards: impl IntoIterator<Item = Board>) -> Vec<Opening> {
+    let mut boards = boards.into_iter();
+    let first = match boards.next() {
+        Some(first) => first,
+        None => return vec![],
+    };
+    let (width, height) = (first.width, first.height);
+    let mut cascades = vec![0_usize; width * height];
+    let mut safe = vec![0_usize; width * height];
+    let mut played = 0;
+    for board in std::iter::once(first).chain(boards) {
+        if (board.width, board.height) != (width, height) {
+            continue;
+        }
+        for (i, size) in board.opening_sizes().into_iter().enumerate() {
+            cascades[i] += size;
+            safe[i] += (size > 0) as usize;
+        }
+        played += 1;
+    }
+    let mut openings: Vec<Opening> = (0..width * height)
+        .map(|i| Opening {
+            point: Point::new(i % width, i / width),
+            survival: safe[i] as f64 / played as f64,
+            expected_cascade: cascades[i] as f64 / played as f64,
+        })
+        .collect();
+    openings.sort_by(|a, b| {
+        (b.expected_cascade, b.survival)
+            .partial_cmp(&(a.expected_cascade, a.survival))
+            .unwrap()
+    });
+    openings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Adjacency;
+    use crate::BoardBuilder;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_rank_openings() {
+        let board = BoardBuilder::new(5, 5)
+            .mines_at(vec![Point::new(4, 0), Point::new(1, 1), Point::new(3, 1)])
+            .build()
+            .unwrap();
+        let ranked = rank_openings(&board);
+        assert_eq!(ranked.len(), 25);
+        let best = &ranked[0];
+        assert_eq!(best.survival, 1_f64);
+        assert_eq!(best.expected_cascade, board.largest_opening() as f64);
+        assert_eq!(Some(best.point), board.opening_start());
+        let worst = ranked.last().unwrap();
+        assert_eq!((worst.survival, worst.expected_cascade), (0_f64, 0_f64));
+        assert!(ranked
+            .windows(2)
+            .all(|w| w[0].expected_cascade >= w[1].expected_cascade));
+    }
+
+    #[test]
+    fn test_opening_book() {
+        let build = |seed| {
+            BoardBuilder::new(8, 8)
+                .mine_count(10)
+                .adjacency(Adjacency::Knight)
+                .seed(seed)
+                .build()
+                .unwrap()
+        };
+        let book = opening_book((0..50).map(build));
+        assert_eq!(book.len(), 64);
+        assert!(book
+            .iter()
+            .all(|o| o.survival > 0_f64 && o.survival <= 1_f64));
+        assert!(book[0].expected_cascade > book[63].expected_cascade);
+        // The same board every time is the same as ranking it.
+        assert_eq!(
+            opening_book(vec![build(3), build(3)]),
+            rank_openings(&build(3))
+        );
+        let other = BoardBuilder::new(4, 4)
+            .mine_count(2)
+            .seed(1)
+            .build()
+            .unwrap();
+        assert_eq!(
+            opening_book(vec![build(3), other]),
+            rank_openings(&build(3))
+        );
+        assert_eq!(opening_book(vec![]), vec![]);
+    }
+}
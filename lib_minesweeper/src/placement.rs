@@ -0,0 +1,394 @@
+#[cfg(feature = "serde")]
+use serde_derive::{Deserialize, Serialize};
+use std::cell::RefCell;
+
+use crate::Adjacency;
+use crate::Point;
+use crate::Symmetry;
+
+// How many times placement starts over after running out of room, before
+// trying the mines closer together.
+const ATTEMPTS: usize = 20;
+
+// Keeps randomly placed mines at least `distance` king or knight moves
+// apart, counting moves as if the board went on past its edges. Boards that
+// can't fit their mines that far apart get the largest distance below it
+// that fits, down to no spacing at all.
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Spacing {
+    pub distance: usize,
+    pub moves: Adjacency,
+}
+
+impl Spacing {
+    // How many of these moves it takes to get `dx` across and `dy` down.
+    fn apart(&self, dx: i32, dy: i32) -> usize {
+        let (x, y) = (dx.unsigned_abs() as i64, dy.unsigned_abs() as i64);
+        let (x, y) = (x.max(y), x.min(y));
+        let moves = match self.moves {
+            Adjacency::King => x,
+            // The usual closed form for knight moves on an open board, with
+            // the two short offsets it gets wrong.
+            Adjacency::Knight => match (x, y) {
+                (1, 0) => 3,
+                (2, 2) => 4,
+                _ if y > x - y => (x - y) - 2 * (x - 2 * y).div_euclid(3),
+                _ => (x - y) - 2 * (x - 2 * y).div_euclid(4),
+            },
+        };
+        moves as usize
+    }
+
+    // Whether a mine at one end of the offset keeps a mine out of the other.
+    // A cell is always too close to itself.
+    fn is_near(&self, dx: i32, dy: i32) -> bool {
+        self.apart(dx, dy) < self.distance.max(1)
+    }
+
+    // How far across or down a cell can be and still be too close.
+    fn reach(&self) -> usize {
+        let moves = self.distance.saturating_sub(1);
+        match self.moves {
+            Adjacency::King => moves,
+            Adjacency::Knight => 2 * moves,
+        }
+    }
+
+    // Whether the builder can try this spacing on a board this size, see
+    // BuildBoardError::TooSpread.
+    pub fn fits_board(&self, width: usize, height: usize) -> bool {
+        self.distance <= width.max(height)
+    }
+
+    // Whether placing the mines this far apart is worth trying. False when
+    // they don't fit however they're placed, see `too_tight`.
+    pub fn fits(&self, width: usize, height: usize, mines: usize) -> bool {
+        self.fits_board(width, height) && !self.too_tight(width, height, mines)
+    }
+
+    // Whether there's no room for the mines however they're placed. Every
+    // square `block` cells wide can only hold one, since any two cells in it
+    // are too close.
+    fn too_tight(&self, width: usize, height: usize, mines: usize) -> bool {
+        let block = match self.moves {
+            Adjacency::King => self.distance,
+            // No two cells in a square n wide are more than
+            // ceil(2(n-1)/3) + 2 knight moves apart.
+            Adjacency::Knight => 3 * self.distance.saturating_sub(3) / 2 + 1,
+        }
+        .clamp(1, width.max(height));
+        mines > width.div_ceil(block) * height.div_ceil(block)
+    }
+}
+
+// The cells that can still take a mine.
+struct Free {
+    width: usize,
+    height: usize,
+    free: Vec<bool>,
+    // How many are still free.
+    left: usize,
+    spacing: Option<Spacing>,
+}
+
+impl Free {
+    fn new(width: usize, height: usize, spacing: Option<Spacing>) -> Free {
+        Free {
+            width,
+            height,
+            free: vec![true; width * height],
+            left: width * height,
+            spacing,
+        }
+    }
+
+    fn is_free(&self, p: &Point) -> bool {
+        self.free[p.y as usize * self.width + p.x as usize]
+    }
+
+    fn is_near(&self, a: &Point, b: &Point) -> bool {
+        match self.spacing {
+            Some(spacing) => spacing.is_near(b.x - a.x, b.y - a.y),
+            None => a == b,
+        }
+    }
+
+    // Takes `p` and every cell too close to it.
+    fn take(&mut self, p: &Point) {
+        let reach = self
+            .spacing
+            .map_or(0, |s| s.reach().min(self.width.max(self.height))) as i32;
+        let xs = (p.x - reach).max(0)..=(p.x + reach).min(self.width as i32 - 1);
+        for y in (p.y - reach).max(0)..=(p.y + reach).min(self.height as i32 - 1) {
+            for x in xs.clone() {
+                let i = y as usize * self.width + x as usize;
+                if self.free[i] && self.is_near(p, &Point { x, y }) {
+                    self.free[i] = false;
+                    self.left -= 1;
+                }
+            }
+        }
+    }
+}
+
+// Places the mines in pairs of cells that are each other's image, plus
+// cells that are their own image when the count is odd or the board has
+// more of them than pairs would fill. Those get about their share of the
+// mines; with no symmetry, every cell is its own image. None if no mix of
+// the two adds up to `mines`, e.g. an odd count on an even board.
+pub(crate) fn placed_mines(
+    width: usize,
+    height: usize,
+    mines: usize,
+    symmetry: Symmetry,
+    spacing: Option<Spacing>,
+    mut rand: impl FnMut(usize, usize) -> usize,
+) -> Option<Vec<Point>> {
+    let (mut singles, mut pairs) = (vec![], vec![]);
+    for y in 0..height {
+        for x in 0..width {
+            let (ix, iy) = symmetry.image(width, height, x, y);
+            if (ix, iy) == (x, y) {
+                singles.push(Point::new(x, y));
+            } else if (iy, ix) > (y, x) {
+                pairs.push((Point::new(x, y), Point::new(ix, iy)));
+            }
+        }
+    }
+    let share = mines * singles.len() / (width * height);
+    let on_singles = (0..=singles.len().min(mines))
+        .filter(|n| (mines - n).is_multiple_of(2) && (mines - n) / 2 <= pairs.len())
+        .min_by_key(|n| (*n as i64 - share as i64).abs())?;
+
+    let mut attempt = |spacing: Option<Spacing>| {
+        if spacing.is_some_and(|s| s.too_tight(width, height, mines)) {
+            return None;
+        }
+        for _ in 0..ATTEMPTS {
+            let free = RefCell::new(Free::new(width, height, spacing));
+            let points = place(
+                &singles,
+                on_singles,
+                &mut rand,
+                || free.borrow().left > 0,
+                |p| {
+                    let mut free = free.borrow_mut();
+                    if !free.is_free(p) {
+                        return false;
+                    }
+                    free.take(p);
+                    true
+                },
+            )
+            .and_then(|mut points| {
+                let pairs = place(
+                    &pairs,
+                    (mines - on_singles) / 2,
+                    &mut rand,
+                    || free.borrow().left > 1,
+                    |(a, b)| {
+                        let mut free = free.borrow_mut();
+                        if !free.is_free(a) || !free.is_free(b) || free.is_near(a, b) {
+                            return false;
+                        }
+                        free.take(a);
+                        free.take(b);
+                        true
+                    },
+                )?;
+                points.extend(pairs.into_iter().flat_map(|(a, b)| vec![a, b]));
+                Some(points)
+            });
+            if points.is_some() {
+                return points;
+            }
+        }
+        None
+    };
+    // Without spacing the first attempt always fits, and so does a distance
+    // of 1.
+    let requested = match spacing {
+        Some(spacing) if spacing.distance > 1 => spacing,
+        _ => return attempt(spacing),
+    };
+    if let Some(points) = attempt(Some(requested)) {
+        return Some(points);
+    }
+    // Closes in on the largest distance that fits, halving the gap between
+    // one that does and one that doesn't each time.
+    let (mut fits, mut fails) = (1, requested.distance);
+    let mut placed = None;
+    while fails - fits > 1 {
+        let distance = (fits + fails) / 2;
+        match attempt(Some(Spacing {
+            distance,
+            ..requested
+        })) {
+            Some(points) => {
+                fits = distance;
+                placed = Some(points);
+            }
+            None => fails = distance,
+        }
+    }
+    placed.or_else(|| {
+        attempt(Some(Spacing {
+            distance: 1,
+            ..requested
+        }))
+    })
+}
+
+// Draws `count` of the candidates at random, skipping the ones `take`
+// turns down. None once every candidate has been taken or turned down, or
+// as soon as `room` says none of them could be taken any more, rather than
+// drawing the rest only to turn them down.
+fn place<T: Clone>(
+    candidates: &[T],
+    count: usize,
+    rand: &mut impl FnMut(usize, usize) -> usize,
+    room: impl Fn() -> bool,
+    mut take: impl FnMut(&T) -> bool,
+) -> Option<Vec<T>> {
+    // Candidates taken or turned down.
+    let mut seen = vec![false; candidates.len()];
+    let mut left = candidates.len();
+    let mut placed = Vec::with_capacity(count);
+    for _ in 0..count {
+        loop {
+            if left == 0 || !room() {
+                return None;
+            }
+            let i = rand(0, candidates.len());
+            if seen[i] {
+                continue;
+            }
+            seen[i] = true;
+            left -= 1;
+            if take(&candidates[i]) {
+                placed.push(candidates[i].clone());
+                break;
+            }
+        }
+    }
+    Some(placed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn near(spacing: &Spacing) -> Vec<(i32, i32)> {
+        (-10..=10)
+            .flat_map(|y| (-10..=10).map(move |x| (x, y)))
+            .filter(|(x, y)| spacing.is_near(*x, *y))
+            .collect()
+    }
+
+    #[test]
+    fn test_near() {
+        let king = Spacing {
+            distance: 2,
+            moves: Adjacency::King,
+        };
+        assert_eq!(near(&king).len(), 9);
+        let knight = Spacing {
+            distance: 2,
+            moves: Adjacency::Knight,
+        };
+        assert_eq!(near(&knight).len(), 9);
+        assert!(near(&knight).contains(&(1, 2)));
+        assert!(!near(&knight).contains(&(1, 1)));
+        let knight = Spacing {
+            distance: 3,
+            ..knight
+        };
+        assert!(near(&knight).contains(&(1, 1)));
+        assert!(!near(&knight).contains(&(5, 0)));
+        let none = Spacing {
+            distance: 0,
+            ..knight
+        };
+        assert_eq!(near(&none), vec![(0, 0)]);
+    }
+
+    #[test]
+    fn test_apart_matches_a_search() {
+        let knight = Spacing {
+            distance: 1,
+            moves: Adjacency::Knight,
+        };
+        let steps = [
+            (-2, -1),
+            (-2, 1),
+            (-1, -2),
+            (-1, 2),
+            (1, -2),
+            (1, 2),
+            (2, -1),
+            (2, 1),
+        ];
+        // Searches a wider area than it checks, so paths can go round.
+        let mut moves = std::collections::HashMap::new();
+        moves.insert((0, 0), 0);
+        let mut edge = vec![(0, 0)];
+        for n in 1.. {
+            edge = edge
+                .iter()
+                .flat_map(|(x, y)| steps.iter().map(move |(dx, dy)| (x + dx, y + dy)))
+                .filter(|(x, y): &(i32, i32)| x.abs() <= 30 && y.abs() <= 30)
+                .filter(|offset| !moves.contains_key(offset))
+                .collect();
+            edge.sort_unstable();
+            edge.dedup();
+            if edge.is_empty() {
+                break;
+            }
+            for offset in &edge {
+                moves.insert(*offset, n);
+            }
+        }
+        for y in -20..=20 {
+            for x in -20..=20 {
+                assert_eq!(knight.apart(x, y), moves[&(x, y)], "{}, {}", x, y);
+            }
+        }
+    }
+    #[test]
+    fn test_too_tight() {
+        let king = Spacing {
+            distance: 2,
+            moves: Adjacency::King,
+        };
+        assert!(!king.too_tight(5, 5, 9));
+        assert!(king.too_tight(5, 5, 10));
+        let knight = Spacing {
+            distance: 5,
+            moves: Adjacency::Knight,
+        };
+        // Any two cells in a 4x4 square are under 5 knight moves apart.
+        assert!(!knight.too_tight(8, 8, 4));
+        assert!(knight.too_tight(8, 8, 5));
+        // Far more than the board is wide leaves room for one mine.
+        let knight = Spacing {
+            distance: 1000,
+            ..knight
+        };
+        assert!(!knight.too_tight(10, 10, 1));
+        assert!(knight.too_tight(10, 10, 2));
+    }
+
+    #[test]
+    fn test_fits() {
+        let knight = Spacing {
+            distance: 11,
+            moves: Adjacency::Knight,
+        };
+        assert!(!knight.fits_board(10, 4));
+        assert!(!knight.fits(10, 4, 1));
+        assert!(knight.fits(11, 4, 1));
+        assert!(!knight.fits(11, 4, 2));
+    }
+}
@@ -0,0 +1,1012 @@
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use std::fmt;
+
+use crate::strategy::{BasicSolver, BoardView, CellView, Strategy};
+use crate::tournament::play;
+use crate::Board;
+use crate::BoardBuilder;
+use crate::BoardState;
+use crate::MapElementCellState;
+use crate::Move;
+use crate::Point;
+
+// Frontiers are searched exhaustively for up to this many steps, and this
+// many of their layouts are kept; past that, layouts are sampled at random.
+const SEARCH_STEPS: usize = 50_000;
+const MAX_LAYOUTS: usize = 10_000;
+const SAMPLES: usize = 200;
+// Odds closer than this are taken as equal.
+const CERTAIN: f64 = 1e-9;
+
+// An open number's closed neighbours, and how many of them are mines once
+// its flags are taken into account.
+struct Constraint {
+    mines: usize,
+    cells: Vec<usize>,
+}
+
+// What the player doesn't know about a position: the closed cells next to
+// an open number (the frontier) and the numbers constraining them, the
+// closed cells away from every number (the interior), and how many mines
+// are left besides the flagged ones.
+struct Unknowns {
+    frontier: Vec<Point>,
+    constraints: Vec<Constraint>,
+    // The constraints each frontier cell is part of.
+    touching: Vec<Vec<usize>>,
+    interior: Vec<Point>,
+    flagged: Vec<Point>,
+    mines: usize,
+}
+
+impl Unknowns {
+    // None if the flags and the numbers contradict each other.
+    fn new(board: &BoardView) -> Option<Unknowns> {
+        let width = board.width();
+        let index = |p: &Point| p.y as usize * width + p.x as usize;
+        let frontier = board.frontier();
+        let mut frontier_index: Vec<Option<usize>> = vec![None; width * board.height()];
+        for (i, p) in frontier.iter().enumerate() {
+            frontier_index[index(p)] = Some(i);
+        }
+        let mut constraints = vec![];
+        let mut flagged = vec![];
+        for p in board.points() {
+            let count = match board.at(&p) {
+                Some(CellView::Open(count)) => count as usize,
+                Some(CellView::Flagged) => {
+                    flagged.push(p);
+                    continue;
+                }
+                _ => continue,
+            };
+            let neighbours = board.neighbours(&p);
+            let flags = neighbours
+                .iter()
+                .filter(|n| board.at(n) == Some(CellView::Flagged))
+                .count();
+            let closed: Vec<Point> = neighbours
+                .into_iter()
+                .filter(|n| board.at(n) == Some(CellView::Closed))
+                .collect();
+            let mines = count.checked_sub(flags)?;
+            if mines > closed.len() {
+                return None;
+            }
+            if closed.is_empty() {
+                continue;
+            }
+            let cells = closed
+                .iter()
+                .filter_map(|n| frontier_index[index(n)])
+                .collect();
+            constraints.push(Constraint { mines, cells });
+        }
+        let mut touching = vec![vec![]; frontier.len()];
+        for (c, constraint) in constraints.iter().enumerate() {
+            for &cell in &constraint.cells {
+                touching[cell].push(c);
+            }
+        }
+        let interior: Vec<Point> = board
+            .points()
+            .filter(|p| board.at(p) == Some(CellView::Closed) && frontier_index[index(p)].is_none())
+            .collect();
+        let mines = board.mines().checked_sub(flagged.len())?;
+        if mines > frontier.len() + interior.len() {
+            return None;
+        }
+        Some(Unknowns {
+            frontier,
+            constraints,
+            touching,
+            interior,
+            flagged,
+            mines,
+        })
+    }
+
+    // How likely a layout with this many frontier mines is, relative to the
+    // others: the number of ways to place the remaining mines in the
+    // interior, as a logarithm since it gets huge.
+    fn ln_weight(&self, placed: usize) -> f64 {
+        ln_choose(self.interior.len(), self.mines - placed)
+    }
+
+    // The frontier split into groups no number links to each other, in
+    // reading order of their first cells. Where one group's mines are says
+    // nothing about another's, beyond how many mines that leaves, so each
+    // is Unknowns of its own with every cell outside it as interior.
+    fn components(&self) -> Vec<Unknowns> {
+        // Union-find, joining the cells of each constraint.
+        let mut parent: Vec<usize> = (0..self.frontier.len()).collect();
+        fn root(parent: &mut [usize], mut i: usize) -> usize {
+            while parent[i] != i {
+                parent[i] = parent[parent[i]];
+                i = parent[i];
+            }
+            i
+        }
+        for constraint in &self.constraints {
+            for pair in constraint.cells.windows(2) {
+                let (a, b) = (root(&mut parent, pair[0]), root(&mut parent, pair[1]));
+                parent[a.max(b)] = a.min(b);
+            }
+        }
+        let mut groups: Vec<Vec<usize>> = vec![];
+        let mut group_of = vec![usize::MAX; self.frontier.len()];
+        for i in 0..self.frontier.len() {
+            let r = root(&mut parent, i);
+            if group_of[r] == usize::MAX {
+                group_of[r] = groups.len();
+                groups.push(vec![]);
+            }
+            group_of[i] = group_of[r];
+            groups[group_of[i]].push(i);
+        }
+        groups
+            .iter()
+            .enumerate()
+            .map(|(g, cells)| {
+                // Where each of the group's cells is in the group.
+                let mut local = vec![usize::MAX; self.frontier.len()];
+                for (j, &i) in cells.iter().enumerate() {
+                    local[i] = j;
+                }
+                let constraints: Vec<Constraint> = self
+                    .constraints
+                    .iter()
+                    .filter(|c| c.cells.first().is_some_and(|&i| group_of[i] == g))
+                    .map(|c| Constraint {
+                        mines: c.mines,
+                        cells: c.cells.iter().map(|&i| local[i]).collect(),
+                    })
+                    .collect();
+                let mut touching = vec![vec![]; cells.len()];
+                for (c, constraint) in constraints.iter().enumerate() {
+                    for &cell in &constraint.cells {
+                        touching[cell].push(c);
+                    }
+                }
+                let outside = (0..self.frontier.len())
+                    .filter(|&i| group_of[i] != g)
+                    .map(|i| self.frontier[i]);
+                Unknowns {
+                    frontier: cells.iter().map(|&i| self.frontier[i]).collect(),
+                    constraints,
+                    touching,
+                    interior: self.interior.iter().copied().chain(outside).collect(),
+                    flagged: self.flagged.clone(),
+                    mines: self.mines,
+                }
+            })
+            .collect()
+    }
+}
+
+fn ln_choose(n: usize, k: usize) -> f64 {
+    let k = k.min(n - k);
+    (0..k)
+        .map(|i| ((n - i) as f64).ln() - ((i + 1) as f64).ln())
+        .sum()
+}
+
+// A depth-first search over the frontier, deciding one cell at a time
+// whether it holds a mine and backing out as soon as a number can't be met.
+struct Search<'a> {
+    unknowns: &'a Unknowns,
+    mine: Vec<bool>,
+    // Per constraint, the mines placed so far and the cells left to decide.
+    placed: Vec<usize>,
+    undecided: Vec<usize>,
+    steps: usize,
+}
+
+impl<'a> Search<'a> {
+    fn new(unknowns: &'a Unknowns, steps: usize) -> Search<'a> {
+        Search {
+            unknowns,
+            mine: vec![false; unknowns.frontier.len()],
+            placed: vec![0; unknowns.constraints.len()],
+            undecided: unknowns.constraints.iter().map(|c| c.cells.len()).collect(),
+            steps,
+        }
+    }
+
+    // Calls `found` with every layout of the frontier that meets all the
+    // numbers, and how many mines it has, until `found` returns false or the
+    // search runs out of steps. `mine_first` picks which way to try first.
+    fn run(
+        &mut self,
+        i: usize,
+        placed: usize,
+        mine_first: &mut dyn FnMut() -> bool,
+        found: &mut dyn FnMut(&[bool], usize) -> bool,
+    ) -> bool {
+        if i == self.unknowns.frontier.len() {
+            return found(&self.mine, placed);
+        }
+        if self.steps == 0 {
+            return false;
+        }
+        self.steps -= 1;
+        let first = mine_first();
+        for &mine in &[first, !first] {
+            if !self.fits(i, mine, placed) {
+                continue;
+            }
+            self.decide(i, mine);
+            let placed = placed + mine as usize;
+            let go_on = self.run(i + 1, placed, mine_first, found);
+            self.undo(i, mine);
+            if !go_on {
+                return false;
+            }
+        }
+        true
+    }
+
+    fn fits(&self, i: usize, mine: bool, placed: usize) -> bool {
+        let placed = placed + mine as usize;
+        let left = self.unknowns.frontier.len() - i - 1 + self.unknowns.interior.len();
+        if placed > self.unknowns.mines || self.unknowns.mines - placed > left {
+            return false;
+        }
+        self.unknowns.touching[i].iter().all(|&c| {
+            let placed = self.placed[c] + mine as usize;
+            let target = self.unknowns.constraints[c].mines;
+            placed <= target && placed + self.undecided[c] > target
+        })
+    }
+
+    fn decide(&mut self, i: usize, mine: bool) {
+        self.mine[i] = mine;
+        for &c in &self.unknowns.touching[i] {
+            self.placed[c] += mine as usize;
+            self.undecided[c] -= 1;
+        }
+    }
+
+    fn undo(&mut self, i: usize, mine: bool) {
+        self.mine[i] = false;
+        for &c in &self.unknowns.touching[i] {
+            self.placed[c] -= mine as usize;
+            self.undecided[c] += 1;
+        }
+    }
+}
+
+// Ways the frontier's mines could be laid out, as the indexes of the
+// frontier cells holding one. All of them if there are few enough;
+// otherwise random samples.
+fn layouts(unknowns: &Unknowns, rng: &mut impl Rng) -> Vec<Vec<usize>> {
+    every_layout(unknowns).unwrap_or_else(|| sampled_layouts(unknowns, rng))
+}
+
+// Random choices make each search land on a different layout. They don't
+// land on every layout equally often, so this is an approximation.
+fn sampled_layouts(unknowns: &Unknowns, rng: &mut impl Rng) -> Vec<Vec<usize>> {
+    let mut layouts = vec![];
+    for _ in 0..SAMPLES {
+        let mut search = Search::new(unknowns, SEARCH_STEPS / SAMPLES);
+        search.run(0, 0, &mut || rng.gen(), &mut |mine, _| {
+            layouts.push(mine_indexes(mine));
+            false
+        });
+    }
+    layouts
+}
+
+// The groups of frontier cells that no number links to each other, in
+// reading order of their first cells, for showing how the solver splits a
+// position up. Empty if the position contradicts itself.
+pub fn frontier_components(board: &BoardView) -> Vec<Vec<Point>> {
+    Unknowns::new(board)
+        .map(|unknowns| {
+            unknowns
+                .components()
+                .into_iter()
+                .map(|c| c.frontier)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+// None if there are more than MAX_LAYOUTS, or they take more than
+// SEARCH_STEPS to find.
+fn every_layout(unknowns: &Unknowns) -> Option<Vec<Vec<usize>>> {
+    let mut layouts = vec![];
+    let mut search = Search::new(unknowns, SEARCH_STEPS);
+    let complete = search.run(0, 0, &mut || false, &mut |mine, _| {
+        layouts.push(mine_indexes(mine));
+        layouts.len() < MAX_LAYOUTS
+    });
+    if complete {
+        Some(layouts)
+    } else {
+        None
+    }
+}
+
+fn mine_indexes(mine: &[bool]) -> Vec<usize> {
+    (0..mine.len()).filter(|&i| mine[i]).collect()
+}
+
+// A way the mines could be laid out that meets every open number: which
+// frontier cells hold one, and how many are left over for the closed cells
+// away from the numbers, which could be any of them.
+#[derive(Debug, PartialEq, Clone)]
+pub struct MineAssignment {
+    pub mines: Vec<Point>,
+    pub interior_mines: usize,
+}
+
+// Every MineAssignment that fits what the player can see, in the order the
+// search finds them. Only for small frontiers: empty if there are more than
+// MAX_LAYOUTS of them or they take too long to find, and also if the
+// position contradicts itself.
+pub fn consistent_configurations(board: &Board) -> Vec<MineAssignment> {
+    let unknowns = match Unknowns::new(&BoardView::new(board)) {
+        Some(unknowns) => unknowns,
+        None => return vec![],
+    };
+    every_layout(&unknowns)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|layout| MineAssignment {
+            interior_mines: unknowns.mines - layout.len(),
+            mines: layout.iter().map(|&i| unknowns.frontier[i]).collect(),
+        })
+        .collect()
+}
+
+// Each layout's share of all the boards consistent with the position.
+fn layout_weights(unknowns: &Unknowns, layouts: &[Vec<usize>]) -> Vec<f64> {
+    let ln_weights: Vec<f64> = layouts
+        .iter()
+        .map(|l| unknowns.ln_weight(l.len()))
+        .collect();
+    let max = ln_weights.iter().cloned().fold(f64::MIN, f64::max);
+    let weights: Vec<f64> = ln_weights.iter().map(|w| (w - max).exp()).collect();
+    let total: f64 = weights.iter().sum();
+    weights.iter().map(|w| w / total).collect()
+}
+
+// The chance of each closed cell holding a mine, given everything the
+// player can see.
+#[derive(Debug, PartialEq, Clone)]
+pub struct MineOdds {
+    // False if a part of the frontier was too big to go through every
+    // layout of, and the odds come from a sample of the whole frontier's.
+    pub exact: bool,
+    odds: Vec<(Point, f64)>,
+}
+
+impl MineOdds {
+    pub fn at(&self, p: &Point) -> Option<f64> {
+        self.odds
+            .iter()
+            .find(|(q, _)| q == p)
+            .map(|(_, odds)| *odds)
+    }
+
+    // The closed cell least likely to be a mine, first in reading order on
+    // ties.
+    pub fn safest(&self) -> Option<(Point, f64)> {
+        self.odds
+            .iter()
+            .fold(None, |best: Option<(Point, f64)>, &(p, odds)| match best {
+                Some((_, b)) if b <= odds => best,
+                _ => Some((p, odds)),
+            })
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &(Point, f64)> {
+        self.odds.iter()
+    }
+}
+
+// How many of a component's layouts have each number of mines, and of
+// those, how many have a mine on each of its cells.
+struct Tally {
+    layouts: Vec<f64>,
+    mined: Vec<Vec<f64>>,
+}
+
+// None if the component has too many layouts to go through.
+fn tally(component: &Unknowns) -> Option<Tally> {
+    let layouts = every_layout(component)?;
+    let most = layouts.iter().map(Vec::len).max().unwrap_or(0);
+    let mut tally = Tally {
+        layouts: vec![0_f64; most + 1],
+        mined: vec![vec![0_f64; component.frontier.len()]; most + 1],
+    };
+    for layout in layouts {
+        let mines = layout.len();
+        tally.layouts[mines] += 1_f64;
+        for i in layout {
+            tally.mined[mines][i] += 1_f64;
+        }
+    }
+    Some(tally)
+}
+
+// The ways to put mines in a and b together, by how many: a[i] ways with i
+// mines and b[j] with j make a[i] * b[j] with i + j.
+fn convolve(a: &[f64], b: &[f64]) -> Vec<f64> {
+    let mut ways = vec![0_f64; a.len() + b.len() - 1];
+    for (i, x) in a.iter().enumerate() {
+        for (j, y) in b.iter().enumerate() {
+            ways[i + j] += x * y;
+        }
+    }
+    ways
+}
+
+// Exact odds for each frontier cell and for the interior, from each
+// component's layouts on their own. A frontier layout is a choice of one
+// layout per component, so counting them only needs each component's by
+// mine count, and each is weighted by the ways to put the mines left over
+// in the interior. None if the components' layouts can't add up to the
+// mines there are.
+fn component_odds(
+    unknowns: &Unknowns,
+    components: &[Unknowns],
+    tallies: &[Tally],
+) -> Option<(Vec<f64>, f64)> {
+    let interior = unknowns.interior.len();
+    // The weight of leaving `rest` mines for the interior, scaled down by
+    // the biggest so it stays in range.
+    let ln_weights: Vec<Option<f64>> = (0..=unknowns.mines)
+        .map(|rest| (rest <= interior).then(|| ln_choose(interior, rest)))
+        .collect();
+    let max = ln_weights
+        .iter()
+        .flatten()
+        .cloned()
+        .fold(f64::MIN, f64::max);
+    let weight = |placed: usize| {
+        ln_weights
+            .get(unknowns.mines.checked_sub(placed)?)
+            .copied()
+            .flatten()
+            .map(|w| (w - max).exp())
+    };
+    let all = tallies
+        .iter()
+        .fold(vec![1_f64], |ways, t| convolve(&ways, &t.layouts));
+    let total: f64 = all
+        .iter()
+        .enumerate()
+        .map(|(placed, ways)| ways * weight(placed).unwrap_or(0_f64))
+        .sum();
+    if total <= 0_f64 || !total.is_finite() {
+        return None;
+    }
+    let mut frontier = vec![0_f64; unknowns.frontier.len()];
+    for (c, (component, t)) in components.iter().zip(tallies).enumerate() {
+        // The ways the other components can go, by how many mines.
+        let others = tallies
+            .iter()
+            .enumerate()
+            .filter(|(o, _)| *o != c)
+            .fold(vec![1_f64], |ways, (_, t)| convolve(&ways, &t.layouts));
+        for (mines, mined) in t.mined.iter().enumerate() {
+            let share: f64 = others
+                .iter()
+                .enumerate()
+                .map(|(placed, ways)| ways * weight(mines + placed).unwrap_or(0_f64))
+                .sum();
+            for (j, p) in component.frontier.iter().enumerate() {
+                let i = unknowns.frontier.iter().position(|q| q == p)?;
+                frontier[i] += mined[j] * share / total;
+            }
+        }
+    }
+    let interior_odds = if interior == 0 {
+        0_f64
+    } else {
+        all.iter()
+            .enumerate()
+            .map(|(placed, ways)| {
+                let rest = unknowns.mines.saturating_sub(placed) as f64;
+                ways * weight(placed).unwrap_or(0_f64) * rest / interior as f64
+            })
+            .sum::<f64>()
+            / total
+    };
+    Some((frontier, interior_odds))
+}
+
+// None if the position contradicts itself, say because of a wrong flag.
+// Each part of the frontier that no number links to the rest is gone
+// through on its own, which keeps the odds exact on big boards with many
+// small parts; only when one part is too big are they sampled.
+pub fn mine_odds(board: &BoardView) -> Option<MineOdds> {
+    let unknowns = Unknowns::new(board)?;
+    let components = unknowns.components();
+    let tallies: Option<Vec<Tally>> = components.iter().map(tally).collect();
+    let (exact, frontier, interior) = match tallies {
+        Some(tallies) => {
+            let (frontier, interior) = component_odds(&unknowns, &components, &tallies)?;
+            (true, frontier, interior)
+        }
+        None => {
+            // A fixed seed, so the same position always gets the same odds.
+            let layouts = sampled_layouts(&unknowns, &mut StdRng::seed_from_u64(0));
+            if layouts.is_empty() {
+                return None;
+            }
+            let weights = layout_weights(&unknowns, &layouts);
+            let mut frontier = vec![0_f64; unknowns.frontier.len()];
+            let mut interior = 0_f64;
+            for (layout, weight) in layouts.iter().zip(&weights) {
+                for &i in layout {
+                    frontier[i] += weight;
+                }
+                if !unknowns.interior.is_empty() {
+                    let rest = unknowns.mines - layout.len();
+                    interior += weight * rest as f64 / unknowns.interior.len() as f64;
+                }
+            }
+            (false, frontier, interior)
+        }
+    };
+    let odds = board
+        .points()
+        .filter_map(|p| {
+            if let Some(i) = unknowns.frontier.iter().position(|q| *q == p) {
+                Some((p, frontier[i]))
+            } else if unknowns.interior.contains(&p) {
+                Some((p, interior))
+            } else {
+                None
+            }
+        })
+        .collect();
+    Some(MineOdds { exact, odds })
+}
+
+// The closed cells that have to be mines, given everything the player can
+// see. Sampled odds don't prove anything, so without exact odds this falls
+// back to the basic solver's rules.
+pub fn certain_mines(board: &BoardView) -> Vec<Point> {
+    match mine_odds(board) {
+        Some(odds) if odds.exact => odds
+            .iter()
+            .filter(|(_, odds)| 1_f64 - *odds < CERTAIN)
+            .map(|(p, _)| *p)
+            .collect(),
+        _ => BasicSolver.forced_mines(board),
+    }
+}
+
+// The closed cells that can't be mines, the same way.
+pub fn certain_safe(board: &BoardView) -> Vec<Point> {
+    match mine_odds(board) {
+        Some(odds) if odds.exact => odds
+            .iter()
+            .filter(|(_, odds)| *odds < CERTAIN)
+            .map(|(p, _)| *p)
+            .collect(),
+        _ => BasicSolver.safe_cells(board),
+    }
+}
+
+// How the move that lost a game compares to the others that could have been
+// played instead.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Loss {
+    // Some cells were certainly safe, so there was no need to guess.
+    Avoidable {
+        safe: Vec<Point>,
+    },
+    // It was a guess, but other cells were less likely to be mines.
+    RiskierGuess {
+        odds: f64,
+        safer: Vec<Point>,
+        safer_odds: f64,
+    },
+    // It was a guess, and as safe as any.
+    Forced {
+        odds: f64,
+    },
+}
+
+impl Loss {
+    // The cells that would have been better to open.
+    pub fn better(&self) -> &[Point] {
+        match self {
+            Loss::Avoidable { safe } => safe,
+            Loss::RiskierGuess { safer, .. } => safer,
+            Loss::Forced { .. } => &[],
+        }
+    }
+}
+
+impl fmt::Display for Loss {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Loss::Avoidable { safe } => write!(
+                f,
+                "That didn't have to be a guess: {} cells were certainly safe.",
+                safe.len()
+            ),
+            Loss::RiskierGuess {
+                odds,
+                safer,
+                safer_odds,
+            } => write!(
+                f,
+                "That cell was a {:.0}% risk, but {} cells were only {:.0}%.",
+                odds * 100_f64,
+                safer.len(),
+                safer_odds * 100_f64
+            ),
+            Loss::Forced { odds } => write!(
+                f,
+                "That was a forced guess, and as safe as any at {:.0}% risk.",
+                odds * 100_f64
+            ),
+        }
+    }
+}
+
+// Looks back at the move opening `fatal` on `board`, the position just
+// before the game was lost. None if the position contradicts itself.
+pub fn analyse_loss(board: &BoardView, fatal: &Point) -> Option<Loss> {
+    let odds = mine_odds(board)?;
+    let risk = odds.at(fatal)?;
+    let safe = certain_safe(board);
+    if !safe.is_empty() {
+        return Some(Loss::Avoidable { safe });
+    }
+    let (_, safer_odds) = odds.safest()?;
+    if risk - safer_odds < CERTAIN {
+        return Some(Loss::Forced { odds: risk });
+    }
+    let safer = odds
+        .iter()
+        .filter(|(_, odds)| *odds - safer_odds < CERTAIN)
+        .map(|(p, _)| *p)
+        .collect();
+    Some(Loss::RiskierGuess {
+        odds: risk,
+        safer,
+        safer_odds,
+    })
+}
+
+// Plays the basic solver's moves while it has any, and otherwise opens the
+// cell least likely to be a mine.
+pub struct SafestGuess;
+
+impl Strategy for SafestGuess {
+    fn name(&self) -> &str {
+        "safest guess"
+    }
+
+    fn next_move(&self, board: &BoardView) -> Option<Move> {
+        BasicSolver.next_move(board).or_else(|| {
+            mine_odds(board)
+                .and_then(|odds| odds.safest())
+                .map(|(p, _)| Move::Open(p))
+        })
+    }
+}
+
+// Plays `games` boards that match everything the player can see through to
+// the end with SafestGuess, and returns the share of them it won. It isn't
+// a perfect player, so the real chance of winning under the best play is at
+// least this. None if the position contradicts itself.
+pub fn win_chance(board: &BoardView, games: usize, rng: &mut impl Rng) -> Option<f64> {
+    match board.state() {
+        BoardState::Won => return Some(1_f64),
+        BoardState::Failed => return Some(0_f64),
+        _ => {}
+    }
+    let unknowns = Unknowns::new(board)?;
+    let layouts = layouts(&unknowns, rng);
+    if layouts.is_empty() || games == 0 {
+        return None;
+    }
+    let weights = layout_weights(&unknowns, &layouts);
+    let mut won = 0;
+    for _ in 0..games {
+        let mut pick = rng.gen::<f64>();
+        let layout = layouts
+            .iter()
+            .zip(&weights)
+            .find(|(_, w)| {
+                pick -= *w;
+                pick < 0_f64
+            })
+            .map_or(&layouts[layouts.len() - 1], |(l, _)| l);
+        let mut mines: Vec<Point> = layout.iter().map(|&i| unknowns.frontier[i]).collect();
+        mines.extend(&unknowns.flagged);
+        mines.extend(
+            unknowns
+                .interior
+                .choose_multiple(rng, unknowns.mines - layout.len())
+                .copied(),
+        );
+        let mut builder = BoardBuilder::new(board.width(), board.height())
+            .mines_at(mines)
+            .adjacency(board.adjacency());
+        for p in board.points() {
+            builder = match board.at(&p) {
+                Some(CellView::Open(_)) => builder.cell_state(p, MapElementCellState::Open),
+                Some(CellView::Flagged) => builder.cell_state(p, MapElementCellState::Flagged),
+                _ => builder,
+            };
+        }
+        let (played, _) = play(&SafestGuess, builder.build().ok()?);
+        if played.state == BoardState::Won {
+            won += 1;
+        }
+    }
+    Some(won as f64 / games as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Adjacency;
+    use pretty_assertions::assert_eq;
+
+    fn king_board(width: usize, height: usize, mines: &[(usize, usize)]) -> Board {
+        BoardBuilder::new(width, height)
+            .mines_at(mines.iter().map(|(x, y)| Point::new(*x, *y)).collect())
+            .adjacency(Adjacency::King)
+            .build()
+            .unwrap()
+    }
+
+    fn open(board: Board, points: &[(usize, usize)]) -> Board {
+        points.iter().fold(board, |b, (x, y)| {
+            b.apply(&Move::Open(Point::new(*x, *y))).unwrap()
+        })
+    }
+
+    #[test]
+    fn test_odds_split_between_cells_sharing_numbers() {
+        // Opening the corner cascades up to the two 1s next to the mine.
+        let board = open(king_board(3, 2, &[(0, 0)]), &[(2, 1)]);
+        let odds = mine_odds(&BoardView::new(&board)).unwrap();
+        assert!(odds.exact);
+        assert_eq!(odds.at(&Point::new(0, 0)), Some(0.5));
+        assert_eq!(odds.at(&Point::new(0, 1)), Some(0.5));
+        assert_eq!(odds.at(&Point::new(2, 1)), None);
+    }
+
+    #[test]
+    fn test_odds_are_certain_when_forced() {
+        let board = open(king_board(2, 2, &[(0, 0)]), &[(1, 0), (0, 1), (1, 1)]);
+        let odds = mine_odds(&BoardView::new(&board)).unwrap();
+        assert_eq!(odds.at(&Point::new(0, 0)), Some(1_f64));
+        assert_eq!(odds.safest(), Some((Point::new(0, 0), 1_f64)));
+    }
+
+    #[test]
+    fn test_interior_odds_use_remaining_mines() {
+        let board = king_board(4, 3, &[(0, 0), (3, 2)]);
+        let odds = mine_odds(&BoardView::new(&board)).unwrap();
+        assert_eq!(odds.iter().count(), 12);
+        for (_, p) in odds.iter() {
+            assert!((p - 2_f64 / 12_f64).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_consistent_configurations() {
+        let board = open(king_board(3, 2, &[(0, 0)]), &[(2, 1)]);
+        assert_eq!(
+            consistent_configurations(&board),
+            vec![
+                MineAssignment {
+                    mines: vec![Point::new(0, 1)],
+                    interior_mines: 0,
+                },
+                MineAssignment {
+                    mines: vec![Point::new(0, 0)],
+                    interior_mines: 0,
+                },
+            ]
+        );
+        // Nothing open, so every mine could be anywhere.
+        let closed = king_board(4, 3, &[(0, 0), (3, 2)]);
+        assert_eq!(
+            consistent_configurations(&closed),
+            vec![MineAssignment {
+                mines: vec![],
+                interior_mines: 2,
+            }]
+        );
+        let wrong = open(king_board(2, 2, &[(0, 0)]), &[(1, 0)])
+            .flag_item(&Point::new(0, 1))
+            .flag_item(&Point::new(1, 1));
+        assert_eq!(consistent_configurations(&wrong), vec![]);
+    }
+
+    #[test]
+    fn test_wrong_flags_have_no_odds() {
+        let board = open(king_board(2, 2, &[(0, 0)]), &[(1, 0)])
+            .flag_item(&Point::new(0, 1))
+            .flag_item(&Point::new(1, 1));
+        assert_eq!(mine_odds(&BoardView::new(&board)), None);
+    }
+
+    #[test]
+    fn test_win_chance_of_finished_boards() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let won = open(king_board(2, 2, &[(0, 0)]), &[(1, 0), (0, 1), (1, 1)]);
+        assert_eq!(won.state, BoardState::Won);
+        assert_eq!(win_chance(&BoardView::new(&won), 10, &mut rng), Some(1_f64));
+        let lost = open(king_board(2, 2, &[(0, 0)]), &[(0, 0)]);
+        assert_eq!(
+            win_chance(&BoardView::new(&lost), 10, &mut rng),
+            Some(0_f64)
+        );
+    }
+
+    #[test]
+    fn test_win_chance_of_a_coin_flip() {
+        // One mine left in two cells that every number touches.
+        let board = open(king_board(2, 2, &[(0, 0)]), &[(0, 1), (1, 1)]);
+        let mut rng = StdRng::seed_from_u64(2);
+        let chance = win_chance(&BoardView::new(&board), 200, &mut rng).unwrap();
+        assert!(chance > 0.35 && chance < 0.65, "{}", chance);
+    }
+
+    // Far apart 2s, each with eight closed knight's move neighbours and no
+    // number linking them.
+    fn separate_twos(sources: &[(usize, usize)]) -> Board {
+        let mines = sources
+            .iter()
+            .flat_map(|&(x, y)| vec![Point::new(x - 2, y - 1), Point::new(x + 2, y + 1)])
+            .collect();
+        let board = BoardBuilder::new(30, 16).mines_at(mines).build().unwrap();
+        open(board, sources)
+    }
+
+    #[test]
+    fn test_separate_parts_stay_exact() {
+        // Four 2s can be laid out in 28^4 ways together, but only 28 each.
+        let board = separate_twos(&[(3, 3), (13, 3), (23, 3), (3, 11)]);
+        let view = BoardView::new(&board);
+        assert_eq!(frontier_components(&view).len(), 4);
+        let odds = mine_odds(&view).unwrap();
+        assert!(odds.exact);
+        assert_eq!(consistent_configurations(&board), vec![]);
+        for (_, p) in odds.iter() {
+            assert!((0_f64..=1_f64).contains(p));
+        }
+        let (p, _) = odds.safest().unwrap();
+        assert_eq!(view.at(&p), Some(CellView::Closed));
+    }
+
+    #[test]
+    fn test_parts_give_the_same_odds_as_the_whole() {
+        let board = separate_twos(&[(3, 3), (13, 3)]);
+        let view = BoardView::new(&board);
+        let unknowns = Unknowns::new(&view).unwrap();
+        assert_eq!(unknowns.components().len(), 2);
+        let layouts = every_layout(&unknowns).unwrap();
+        let weights = layout_weights(&unknowns, &layouts);
+        let odds = mine_odds(&view).unwrap();
+        assert!(odds.exact);
+        for (i, p) in unknowns.frontier.iter().enumerate() {
+            let whole: f64 = layouts
+                .iter()
+                .zip(&weights)
+                .filter(|(layout, _)| layout.contains(&i))
+                .map(|(_, w)| w)
+                .sum();
+            assert!((odds.at(p).unwrap() - whole).abs() < 1e-9, "{:?}", p);
+        }
+        let mined: f64 = odds.iter().map(|(_, p)| p).sum();
+        assert!((mined - 4_f64).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_large_frontiers_are_sampled() {
+        // A row of open cells between two closed ones, so every number links
+        // its neighbours' columns to the next. Each column with one mine in
+        // it could have it in either row, which makes 2^20 layouts.
+        let mines: Vec<(usize, usize)> = (0..30)
+            .filter_map(|x| match x % 3 {
+                0 => Some((x, 0)),
+                1 => Some((x, 2)),
+                _ => None,
+            })
+            .collect();
+        let row: Vec<(usize, usize)> = (0..30).map(|x| (x, 1)).collect();
+        let board = open(king_board(30, 3, &mines), &row);
+        let view = BoardView::new(&board);
+        assert_eq!(frontier_components(&view).len(), 1);
+        let odds = mine_odds(&view).unwrap();
+        assert!(!odds.exact);
+        for (_, p) in odds.iter() {
+            assert!((0_f64..=1_f64).contains(p));
+        }
+    }
+
+    #[test]
+    fn test_frontier_components() {
+        let board = separate_twos(&[(13, 3), (3, 3)]);
+        let components = frontier_components(&BoardView::new(&board));
+        assert_eq!(components.len(), 2);
+        assert!(components.iter().all(|c| c.len() == 8));
+        // In reading order of their first cells.
+        assert_eq!(components[0][0], Point::new(2, 1));
+        assert_eq!(components[1][0], Point::new(12, 1));
+        assert_eq!(
+            frontier_components(&BoardView::new(&king_board(3, 3, &[]))),
+            vec![] as Vec<Vec<Point>>
+        );
+    }
+
+    fn king_position(mines: &[(usize, usize)], open: &[(usize, usize)]) -> Board {
+        let board = BoardBuilder::new(3, 3)
+            .mines_at(mines.iter().map(|(x, y)| Point::new(*x, *y)).collect())
+            .adjacency(Adjacency::King);
+        open.iter()
+            .fold(board, |b, (x, y)| {
+                b.cell_state(Point::new(*x, *y), MapElementCellState::Open)
+            })
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_loss_next_to_an_open_zero_was_avoidable() {
+        let board = king_position(&[(0, 0)], &[(2, 0)]);
+        let loss = analyse_loss(&BoardView::new(&board), &Point::new(0, 0)).unwrap();
+        assert_eq!(
+            loss,
+            Loss::Avoidable {
+                safe: vec![Point::new(1, 0), Point::new(1, 1), Point::new(2, 1)]
+            }
+        );
+    }
+
+    #[test]
+    fn test_loss_on_a_riskier_guess() {
+        // The 1 has five closed neighbours and the other mine is in one of
+        // the three cells away from it.
+        let board = king_position(&[(0, 0), (2, 2)], &[(1, 0)]);
+        let view = BoardView::new(&board);
+        let loss = analyse_loss(&view, &Point::new(2, 2)).unwrap();
+        assert_eq!(loss.better().len(), 5);
+        assert_eq!(
+            loss.to_string(),
+            "That cell was a 33% risk, but 5 cells were only 20%."
+        );
+        assert!(matches!(
+            analyse_loss(&view, &Point::new(0, 0)),
+            Some(Loss::Forced { .. })
+        ));
+    }
+
+    #[test]
+    fn test_certain_mines() {
+        let board = king_position(&[(0, 0)], &[(1, 0), (0, 1), (1, 1)]);
+        assert_eq!(
+            certain_mines(&BoardView::new(&board)),
+            vec![Point::new(0, 0)]
+        );
+        let board = king_position(&[(0, 0), (2, 2)], &[(1, 0)]);
+        assert_eq!(certain_mines(&BoardView::new(&board)), vec![]);
+    }
+
+    #[test]
+    fn test_certain_safe() {
+        let board = king_position(&[(0, 0)], &[(2, 0)]);
+        assert_eq!(
+            certain_safe(&BoardView::new(&board)),
+            vec![Point::new(1, 0), Point::new(1, 1), Point::new(2, 1)]
+        );
+    }
+}
@@ -0,0 +1,144 @@
+#[cfg(feature = "serde")]
+use serde_derive::{Deserialize, Serialize};
+
+use crate::daily::splitmix;
+use crate::Adjacency;
+use crate::Board;
+use crate::BoardBuilder;
+use crate::Replay;
+
+// Race boards have the same shape as the daily board.
+const RACE_WIDTH: usize = 16;
+const RACE_HEIGHT: usize = 16;
+const RACE_MINES: usize = 40;
+pub const MAX_RACE_BOARDS: usize = 20;
+// Keys in codes are written in base 36, this many digits long.
+const KEY_DIGITS: u32 = 6;
+
+// A run of boards a group plays through in order, from a code they pass
+// around. Everything follows from the code, so nobody needs a server to
+// play the same boards.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Race {
+    pub code: String,
+    pub width: usize,
+    pub height: usize,
+    pub mines: usize,
+    pub adjacency: Adjacency,
+    pub seeds: Vec<u64>,
+}
+
+// Codes are `{boards}-{key}`, e.g. "5-k3j9x2": how many boards the race
+// has, then anything to tell races apart. Case and spaces around it don't
+// matter. None if it doesn't parse.
+pub fn race(code: &str) -> Option<Race> {
+    let code = code.trim().to_lowercase();
+    let (boards, key) = code.split_once('-')?;
+    let boards: usize = boards.parse().ok()?;
+    if boards == 0 || boards > MAX_RACE_BOARDS {
+        return None;
+    }
+    if key.is_empty() || !key.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return None;
+    }
+    // FNV-1a, so every key gets its own boards.
+    let hash = key.bytes().fold(0xcbf2_9ce4_8422_2325, |hash: u64, b| {
+        (hash ^ u64::from(b)).wrapping_mul(0x0100_0000_01b3)
+    });
+    Some(Race {
+        seeds: (0..boards as u64)
+            .map(|i| splitmix(hash.wrapping_add(i)))
+            .collect(),
+        code,
+        width: RACE_WIDTH,
+        height: RACE_HEIGHT,
+        mines: RACE_MINES,
+        adjacency: Adjacency::Knight,
+    })
+}
+
+// A code for a new race of `boards` boards, with its key taken from
+// `random`.
+pub fn race_code(boards: usize, random: u64) -> String {
+    let mut key = random % 36_u64.pow(KEY_DIGITS);
+    let mut digits = vec![];
+    for _ in 0..KEY_DIGITS {
+        digits.push(std::char::from_digit((key % 36) as u32, 36).unwrap());
+        key /= 36;
+    }
+    format!("{}-{}", boards, digits.iter().rev().collect::<String>())
+}
+
+impl Race {
+    pub fn len(&self) -> usize {
+        self.seeds.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.seeds.is_empty()
+    }
+
+    pub fn board(&self, index: usize) -> Option<Board> {
+        let seed = *self.seeds.get(index)?;
+        BoardBuilder::new(self.width, self.height)
+            .mine_count(self.mines)
+            .adjacency(self.adjacency)
+            .seed(seed)
+            .build()
+            .ok()
+    }
+
+    // Which of the race's boards `replay` was played on, if any.
+    pub fn index_of(&self, replay: &Replay) -> Option<usize> {
+        let shape = replay.width == self.width
+            && replay.height == self.height
+            && replay.mines == self.mines
+            && replay.adjacency == self.adjacency
+            && replay.symmetry.is_none()
+            && replay.spacing.is_none()
+            && replay.min_opening.is_none();
+        if !shape {
+            return None;
+        }
+        self.seeds.iter().position(|seed| *seed == replay.seed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_codes_pick_the_boards() {
+        let a = race("3-abc").unwrap();
+        assert_eq!(a.len(), 3);
+        assert_eq!(race(" 3-ABC ").unwrap(), a);
+        assert!(race("3-abd").unwrap().seeds != a.seeds);
+        // The first boards of a longer race are the same ones.
+        assert_eq!(race("5-abc").unwrap().seeds[..3].to_vec(), a.seeds);
+        for code in &["abc", "0-abc", "21-abc", "3-", "3-a c", "x-abc"] {
+            assert_eq!(race(code), None, "{}", code);
+        }
+    }
+
+    #[test]
+    fn test_race_code() {
+        assert_eq!(race_code(5, 0), "5-000000");
+        assert_eq!(race_code(5, 35), "5-00000z");
+        let code = race_code(20, u64::MAX);
+        assert_eq!(race(&code).unwrap().code, code);
+    }
+
+    #[test]
+    fn test_index_of() {
+        let race = race("2-abc").unwrap();
+        let replay = |i| Replay::for_board(&race.board(i).unwrap()).unwrap();
+        assert_eq!(race.index_of(&replay(0)), Some(0));
+        assert_eq!(race.index_of(&replay(1)), Some(1));
+        assert_eq!(race.board(2), None);
+        let other = super::race("2-abd").unwrap();
+        assert_eq!(other.index_of(&replay(0)), None);
+    }
+}
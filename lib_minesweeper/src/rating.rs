@@ -0,0 +1,172 @@
+#[cfg(feature = "serde")]
+use serde_derive::{Deserialize, Serialize};
+
+use crate::Board;
+use crate::BoardState;
+use crate::BoardView;
+use crate::Move;
+use crate::Point;
+use crate::{certain_mines, certain_safe, BasicSolver};
+
+// How hard a board is to win without guessing, going by what the solvers
+// need to win it. Doesn't depend on how many mines there are: a dense board
+// can be Casual and a sparse one Brutal.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Rating {
+    // Every move follows from a single number.
+    Casual,
+    // Some moves take weighing several numbers together, but none is a
+    // guess.
+    Tricky,
+    // Can't be won without guessing, or not by the solvers.
+    Brutal,
+}
+
+impl Rating {
+    // Rates the board as played from a zero in its biggest opening.
+    pub fn of(board: &Board) -> Rating {
+        match board.opening_start() {
+            Some(start) => Rating::from_start(board, &start),
+            None => Rating::Brutal,
+        }
+    }
+
+    // Rates the board as played from `start`, opening with the basic
+    // solver's moves while it has any and only then with the exact ones.
+    // Brutal if `start` is a mine.
+    pub fn from_start(board: &Board, start: &Point) -> Rating {
+        match board.cascade_open_item(start) {
+            Some(board) => Rating::from_position(&board),
+            None => Rating::Brutal,
+        }
+    }
+
+    // Rates the rest of a game already under way, the same way.
+    pub fn from_position(board: &Board) -> Rating {
+        deduce(board).1
+    }
+}
+
+// Plays only certain moves from `board`, the way Rating does, and returns
+// the board where that stops with the rating so far: Brutal if it stopped
+// short of winning.
+pub(crate) fn deduce(board: &Board) -> (Board, Rating) {
+    let mut board = board.clone();
+    let mut rating = Rating::Casual;
+    while board.state == BoardState::Playing {
+        let view = BoardView::new(&board);
+        let mut moves = certain_moves(
+            BasicSolver.safe_cells(&view),
+            BasicSolver.forced_mines(&view),
+        );
+        if moves.is_empty() {
+            moves = certain_moves(certain_safe(&view), certain_mines(&view));
+            rating = Rating::Tricky;
+        }
+        let before = board.clone();
+        for m in &moves {
+            if let Some(next) = board.apply(m) {
+                board = next;
+            }
+        }
+        if board == before {
+            return (board, Rating::Brutal);
+        }
+    }
+    match board.state {
+        BoardState::Won => (board, rating),
+        _ => (board, Rating::Brutal),
+    }
+}
+
+impl Board {
+    // Whether the game can be won from here without another guess. A lost
+    // game can't, and a won one trivially can.
+    pub fn is_fully_deducible(&self) -> bool {
+        Rating::from_position(self) != Rating::Brutal
+    }
+}
+
+fn certain_moves(safe: Vec<Point>, mines: Vec<Point>) -> Vec<Move> {
+    safe.into_iter()
+        .map(Move::Open)
+        .chain(mines.into_iter().map(Move::Flag))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Adjacency;
+    use crate::BoardBuilder;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_rating() {
+        let board = |mines| {
+            BoardBuilder::new(4, 1)
+                .mines_at(mines)
+                .adjacency(Adjacency::King)
+                .build()
+                .unwrap()
+        };
+        // Opening the right end opens every safe cell.
+        assert_eq!(Rating::of(&board(vec![Point::new(0, 0)])), Rating::Casual);
+        // No zeros, so even the first click is a guess.
+        let guess = BoardBuilder::new(2, 2)
+            .mines_at(vec![Point::new(0, 0)])
+            .adjacency(Adjacency::King)
+            .build()
+            .unwrap();
+        assert_eq!(Rating::of(&guess), Rating::Brutal);
+        assert_eq!(
+            Rating::from_start(&board(vec![Point::new(0, 0)]), &Point::new(0, 0)),
+            Rating::Brutal
+        );
+    }
+
+    #[test]
+    fn test_is_fully_deducible() {
+        let board = BoardBuilder::new(4, 1)
+            .mines_at(vec![Point::new(0, 0)])
+            .adjacency(Adjacency::King)
+            .build()
+            .unwrap();
+        assert!(!board.is_fully_deducible());
+        let one = board.cascade_open_item(&Point::new(1, 0)).unwrap();
+        assert!(one.is_fully_deducible());
+        let won = board.cascade_open_item(&Point::new(3, 0)).unwrap();
+        assert_eq!(won.state, BoardState::Won);
+        assert!(won.is_fully_deducible());
+        let lost = board.cascade_open_item(&Point::new(0, 0)).unwrap();
+        assert!(!lost.is_fully_deducible());
+        // The 1 could be any of its three closed neighbours.
+        let guess = BoardBuilder::new(2, 2)
+            .mines_at(vec![Point::new(0, 0)])
+            .adjacency(Adjacency::King)
+            .build()
+            .unwrap()
+            .cascade_open_item(&Point::new(1, 1))
+            .unwrap();
+        assert!(!guess.is_fully_deducible());
+    }
+
+    #[test]
+    fn test_tricky_boards_exist() {
+        let ratings: Vec<Rating> = (0..40)
+            .map(|seed| {
+                let board = BoardBuilder::new(9, 9)
+                    .mine_count(10)
+                    .adjacency(Adjacency::King)
+                    .seed(seed)
+                    .build()
+                    .unwrap();
+                Rating::of(&board)
+            })
+            .collect();
+        for rating in &[Rating::Casual, Rating::Tricky, Rating::Brutal] {
+            assert!(ratings.contains(rating), "no {:?} board", rating);
+        }
+    }
+}
@@ -0,0 +1,125 @@
+use crate::Answer;
+use crate::Board;
+use crate::MapElement::Number;
+use crate::MapElementCellState::Open;
+use crate::Pattern;
+use crate::Point;
+
+const CATALOG: &str = include_str!("reference.txt");
+
+// A small hand-made position showing one pattern, with a cell in it the
+// pattern decides, or in the case of traps doesn't.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Reference {
+    pub pattern: Pattern,
+    pub name: String,
+    pub board: Board,
+    pub target: Point,
+    pub answer: Answer,
+    pub note: String,
+}
+
+// The patterns shipped with the game, in catalog order.
+pub fn reference() -> Vec<Reference> {
+    CATALOG.lines().filter_map(parse_line).collect()
+}
+
+// `pattern | name | grid | answer column row | note`. Blank lines, comments
+// and lines that don't parse are skipped.
+fn parse_line(line: &str) -> Option<Reference> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+    let fields: Vec<&str> = line.split('|').map(str::trim).collect();
+    let (pattern, name, grid, answer, note) = match fields[..] {
+        [pattern, name, grid, answer, note] => (pattern, name, grid, answer, note),
+        _ => return None,
+    };
+    let board: Board = grid.replace('/', "\n").parse().ok()?;
+    let answer: Vec<&str> = answer.split_whitespace().collect();
+    let (answer, column, row) = match answer[..] {
+        [answer, column, row] => (
+            match answer {
+                "safe" => Answer::Safe,
+                "mine" => Answer::Mine,
+                "unknown" => Answer::Unknown,
+                _ => return None,
+            },
+            column.parse::<usize>().ok()?.checked_sub(1)?,
+            row.parse::<usize>().ok()?.checked_sub(1)?,
+        ),
+        _ => return None,
+    };
+    let target = Point::new(column, row);
+    board.at(&target)?;
+    Some(Reference {
+        pattern: Pattern::from_key(pattern)?,
+        name: name.to_string(),
+        board,
+        target,
+        answer,
+        note: note.to_string(),
+    })
+}
+
+impl Reference {
+    // The open numbers the pattern reads: the ones a knight's move from the
+    // cell, or for traps, the ones right beside it that aren't.
+    pub fn sources(&self) -> Vec<Point> {
+        let around = match self.pattern {
+            Pattern::Trap => self.board.surrounding_points(&self.target),
+            _ => self.board.neighbours(&self.target),
+        };
+        let knight = self.board.neighbours(&self.target);
+        around
+            .into_iter()
+            .filter(|p| matches!(self.board.at(p), Some(Number { state: Open, .. })))
+            .filter(|p| self.pattern != Pattern::Trap || !knight.contains(p))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_every_line_parses() {
+        let lines = CATALOG
+            .lines()
+            .filter(|l| !l.trim().is_empty() && !l.starts_with('#'))
+            .count();
+        assert_eq!(reference().len(), lines);
+        let parsed = parse_line("filled | A | 2../..*/.*. | mine 3 2 | Note").unwrap();
+        assert_eq!(parsed.pattern, Pattern::Filled);
+        assert_eq!(parsed.target, Point::new(2, 1));
+        assert_eq!(parsed.answer, Answer::Mine);
+        assert_eq!(parsed.sources(), vec![Point::new(0, 0)]);
+        assert_eq!(
+            parse_line("filled | A | 2../..*/.*. | mine 4 2 | Note"),
+            None
+        );
+        assert_eq!(parse_line("rook | A | 2../..*/.*. | mine 3 2 | Note"), None);
+    }
+
+    #[cfg(feature = "solver")]
+    #[test]
+    fn test_answers_are_right() {
+        use crate::{certain_mines, certain_safe, BoardView, CellView};
+        for reference in reference() {
+            let view = BoardView::new(&reference.board);
+            assert_eq!(view.at(&reference.target), Some(CellView::Closed));
+            let answer = if certain_safe(&view).contains(&reference.target) {
+                Answer::Safe
+            } else if certain_mines(&view).contains(&reference.target) {
+                Answer::Mine
+            } else {
+                Answer::Unknown
+            };
+            assert_eq!(answer, reference.answer, "{}", reference.name);
+            assert!(!reference.sources().is_empty(), "{}", reference.name);
+        }
+    }
+}
@@ -0,0 +1,155 @@
+use crate::rating::deduce;
+use crate::Board;
+use crate::BoardBuilder;
+use crate::MapElement::{Mine, Number};
+use crate::MapElementCellState::Open;
+use crate::Point;
+use crate::Rating;
+
+// One change `make_solvable` made to a board's mines.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Fix {
+    Added(Point),
+    Removed(Point),
+}
+
+impl Fix {
+    pub fn point(self) -> Point {
+        match self {
+            Fix::Added(p) | Fix::Removed(p) => p,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Repair {
+    // The board with the fixes made, every cell closed.
+    pub board: Board,
+    pub fixes: Vec<Fix>,
+}
+
+// Adds and takes away mines until `board` can be won from `start` without
+// guessing, for puzzle authors. Each fix is the one, out of the cells where
+// the solver got stuck, that lets it open the most; a win beats any number
+// of cells, and ties go to the first in reading order. A mine on `start`
+// comes off first. None if that takes more than `max_fixes`, or if `start`
+// is off the board.
+pub fn make_solvable(board: &Board, start: &Point, max_fixes: usize) -> Option<Repair> {
+    board.at(start)?;
+    let mut mines: Vec<Point> = board
+        .points()
+        .filter(|p| matches!(board.at(p), Some(Mine { .. })))
+        .collect();
+    let mut fixes = vec![];
+    if let Some(i) = mines.iter().position(|p| p == start) {
+        mines.remove(i);
+        fixes.push(Fix::Removed(*start));
+    }
+    loop {
+        let current = with_mines(board, &mines);
+        let (stuck, rating) = played_from(&current, start);
+        if rating != Rating::Brutal {
+            return (fixes.len() <= max_fixes).then_some(Repair {
+                board: current,
+                fixes,
+            });
+        }
+        if fixes.len() >= max_fixes {
+            return None;
+        }
+        let mut best: Option<((bool, usize), Fix)> = None;
+        for p in stuck_cells(&stuck) {
+            if p == *start || fixes.iter().any(|f| f.point() == p) {
+                continue;
+            }
+            let fix = if mines.contains(&p) {
+                Fix::Removed(p)
+            } else {
+                Fix::Added(p)
+            };
+            let (after, rating) = played_from(&with_mines(board, &toggled(&mines, fix)), start);
+            let score = (rating != Rating::Brutal, after.stats().opened);
+            if best.is_none_or(|(most, _)| score > most) {
+                best = Some((score, fix));
+            }
+        }
+        let (_, fix) = best?;
+        mines = toggled(&mines, fix);
+        fixes.push(fix);
+    }
+}
+
+fn played_from(board: &Board, start: &Point) -> (Board, Rating) {
+    match board.cascade_open_item(start) {
+        Some(opened) => deduce(&opened),
+        None => (board.clone(), Rating::Brutal),
+    }
+}
+
+// The cells that aren't open but are next to an open number: the ones the
+// solver couldn't decide.
+fn stuck_cells(board: &Board) -> Vec<Point> {
+    let is_open = |p: &Point| matches!(board.at(p), Some(Number { state: Open, .. }));
+    board
+        .points()
+        .filter(|p| !is_open(p) && board.neighbours(p).iter().any(is_open))
+        .collect()
+}
+
+fn toggled(mines: &[Point], fix: Fix) -> Vec<Point> {
+    match fix {
+        Fix::Added(p) => mines.iter().copied().chain(Some(p)).collect(),
+        Fix::Removed(p) => mines.iter().copied().filter(|m| *m != p).collect(),
+    }
+}
+
+// `board`'s size and rules with these mines, every cell closed.
+fn with_mines(board: &Board, mines: &[Point]) -> Board {
+    BoardBuilder::new(board.width, board.height)
+        .mines_at(mines.to_vec())
+        .adjacency(board.adjacency)
+        .cascade(board.cascade)
+        .build()
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Adjacency;
+    use pretty_assertions::assert_eq;
+
+    fn king_board(width: usize, height: usize, mines: Vec<Point>) -> Board {
+        BoardBuilder::new(width, height)
+            .mines_at(mines)
+            .adjacency(Adjacency::King)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_make_solvable() {
+        // Opening the far corner leaves the mine a coin flip between the two
+        // cells on the left. Taking it off and adding a second both make it
+        // solvable; taking it off comes first in reading order.
+        let board = king_board(3, 2, vec![Point::new(0, 0)]);
+        let start = Point::new(2, 1);
+        assert_eq!(Rating::from_start(&board, &start), Rating::Brutal);
+        let repair = make_solvable(&board, &start, 3).unwrap();
+        assert_eq!(repair.fixes, vec![Fix::Removed(Point::new(0, 0))]);
+        assert_ne!(Rating::from_start(&repair.board, &start), Rating::Brutal);
+        assert_eq!(make_solvable(&board, &start, 0), None);
+    }
+
+    #[test]
+    fn test_make_solvable_keeps_solvable_boards() {
+        let board = king_board(4, 1, vec![Point::new(0, 0)]);
+        let repair = make_solvable(&board, &Point::new(3, 0), 0).unwrap();
+        assert_eq!(repair.fixes, vec![]);
+        assert_eq!(repair.board, board);
+        // Starting on the mine takes it off.
+        let repair = make_solvable(&board, &Point::new(0, 0), 1).unwrap();
+        assert_eq!(repair.fixes, vec![Fix::Removed(Point::new(0, 0))]);
+        assert_eq!(make_solvable(&board, &Point::new(9, 0), 1), None);
+    }
+}
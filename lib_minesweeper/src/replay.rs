@@ -0,0 +1,743 @@
+#[cfg(feature = "serde")]
+use serde_derive::{Deserialize, Serialize};
+
+use crate::Adjacency;
+use crate::Board;
+use crate::BoardBuilder;
+use crate::Cascade;
+use crate::MapElement::{Mine, Number};
+use crate::MapElementCellState::Open;
+use crate::Point;
+use crate::Spacing;
+use crate::Symmetry;
+use crate::MAX_CELLS;
+
+// The most cells a replay from elsewhere can ask for, well under MAX_CELLS,
+// so checking one is quick however it was made.
+pub const MAX_REPLAY_CELLS: usize = 250_000;
+
+// The most moves a packed replay unpacks to, so a short text can't ask for
+// more than a replay would ever hold.
+pub const MAX_REPLAY_MOVES: usize = 1_000_000;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Move {
+    Open(Point),
+    Flag(Point),
+    OpenAllUnflagged,
+    // Opens around a satisfied number, as `Board::chord_open` does.
+    Chord(Point),
+}
+
+// Everything needed to play a game back: the parameters and seed the board
+// was generated from, and the moves that changed it, in order.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Replay {
+    pub width: usize,
+    pub height: usize,
+    pub mines: usize,
+    pub adjacency: Adjacency,
+    pub seed: u64,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Symmetry::is_none")
+    )]
+    pub symmetry: Symmetry,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub spacing: Option<Spacing>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub min_opening: Option<usize>,
+    // How far opens spread in the game, which the moves need to replay the
+    // same.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Cascade::is_zeros")
+    )]
+    pub cascade: Cascade,
+    pub moves: Vec<Move>,
+    // Indexes of the moves an assist played for the player.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub assisted: Vec<usize>,
+    // Milliseconds from the first move to each move, for games that were
+    // timed. Empty otherwise.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub times: Vec<f64>,
+    // What `checksum` came to when the replay was sealed, so a copy that was
+    // cut short or garbled on the way can be told apart. It's no signature:
+    // anyone can work it out again for a replay they've edited.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub checksum: Option<u64>,
+}
+
+impl Replay {
+    // Boards whose mines weren't placed from a seed can't be replayed.
+    pub fn for_board(board: &Board) -> Option<Replay> {
+        Some(Replay {
+            width: board.width,
+            height: board.height,
+            mines: board.mines,
+            adjacency: board.adjacency,
+            seed: board.seed?,
+            symmetry: board.symmetry,
+            spacing: board.spacing,
+            min_opening: board.min_opening,
+            cascade: board.cascade,
+            moves: vec![],
+            assisted: vec![],
+            times: vec![],
+            checksum: None,
+        })
+    }
+
+    pub fn record(&mut self, m: Move) {
+        self.moves.push(m);
+    }
+
+    pub fn record_assisted(&mut self, m: Move) {
+        self.assisted.push(self.moves.len());
+        self.moves.push(m);
+    }
+
+    pub fn is_assisted(&self, index: usize) -> bool {
+        self.assisted.contains(&index)
+    }
+
+    // Stamps the move just recorded with when it was made. Only games with
+    // every move stamped count as timed.
+    pub fn time_last(&mut self, at_ms: f64) {
+        if self.times.len() + 1 == self.moves.len() {
+            self.times.push(at_ms);
+        }
+    }
+
+    // Whether every move has a time, so the game can be played back in
+    // time with another.
+    pub fn is_timed(&self) -> bool {
+        !self.moves.is_empty() && self.times.len() == self.moves.len()
+    }
+
+    // How many moves had been made `at_ms` into the game.
+    pub fn step_at(&self, at_ms: f64) -> usize {
+        self.times.partition_point(|t| *t <= at_ms)
+    }
+
+    // Whether both replays start from the same board, however they went on.
+    pub fn is_same_board(&self, other: &Replay) -> bool {
+        (
+            self.width,
+            self.height,
+            self.mines,
+            self.adjacency,
+            self.seed,
+            self.symmetry,
+            self.spacing,
+            self.min_opening,
+        ) == (
+            other.width,
+            other.height,
+            other.mines,
+            other.adjacency,
+            other.seed,
+            other.symmetry,
+            other.spacing,
+            other.min_opening,
+        )
+    }
+
+    // A hash chained over the replay: the seed and parameters start it, and
+    // each move, whether an assist played it and when it was made carry it
+    // on from the one before. Pinned, as the scores server checks it too.
+    // There's no key, so it catches accidents, not cheats.
+    pub fn checksum(&self) -> u64 {
+        let mut bytes = vec![];
+        for n in &[self.width, self.height, self.mines] {
+            bytes.extend((*n as u64).to_le_bytes());
+        }
+        bytes.push(adjacency_byte(self.adjacency));
+        bytes.extend(self.seed.to_le_bytes());
+        bytes.push(match self.symmetry {
+            Symmetry::None => 0,
+            Symmetry::Mirror => 1,
+            Symmetry::Rotational => 2,
+        });
+        match self.spacing {
+            Some(spacing) => {
+                bytes.push(adjacency_byte(spacing.moves));
+                bytes.extend((spacing.distance as u64).to_le_bytes());
+            }
+            None => bytes.push(u8::MAX),
+        }
+        bytes.extend(
+            self.min_opening
+                .map_or(u64::MAX, |n| n as u64)
+                .to_le_bytes(),
+        );
+        // Left out when it's the usual, so replays sealed before there was a
+        // choice still check.
+        match self.cascade {
+            Cascade::Zeros => {}
+            Cascade::Chording => bytes.push(1),
+            Cascade::Off => bytes.push(2),
+        }
+        let start = fnv(0xcbf2_9ce4_8422_2325, &bytes);
+        self.moves.iter().enumerate().fold(start, |hash, (i, m)| {
+            let mut bytes = vec![];
+            let (kind, p) = match m {
+                Move::Open(p) => (0, *p),
+                Move::Flag(p) => (1, *p),
+                Move::OpenAllUnflagged => (2, Point::new(0, 0)),
+                Move::Chord(p) => (3, *p),
+            };
+            bytes.push(kind);
+            bytes.extend(p.x.to_le_bytes());
+            bytes.extend(p.y.to_le_bytes());
+            bytes.push(self.is_assisted(i) as u8);
+            if let Some(t) = self.times.get(i) {
+                bytes.extend(t.to_bits().to_le_bytes());
+            }
+            fnv(hash, &bytes)
+        })
+    }
+
+    // Stamps the replay with its checksum, once it's finished.
+    pub fn seal(&mut self) {
+        self.checksum = Some(self.checksum());
+    }
+
+    // Whether the replay was sealed and hasn't been damaged since. A replay
+    // edited on purpose can just be sealed again, so this says nothing
+    // about whether its moves were really played.
+    pub fn is_sealed(&self) -> bool {
+        self.checksum == Some(self.checksum())
+    }
+
+    // Whether the parameters are small enough to build a board from without
+    // it taking long: at most MAX_REPLAY_CELLS cells, no more mines than
+    // that, spacing no wider than the board and an opening no bigger, and
+    // at most MAX_REPLAY_MOVES moves. Says nothing about whether the board
+    // can be built.
+    pub fn is_within_limits(&self) -> bool {
+        let cells = match self.width.checked_mul(self.height) {
+            Some(cells) if cells <= MAX_REPLAY_CELLS => cells,
+            _ => return false,
+        };
+        self.mines <= cells
+            && self
+                .spacing
+                .is_none_or(|s| s.fits_board(self.width, self.height))
+            && self.min_opening.is_none_or(|min| min <= cells)
+            && self.moves.len() <= MAX_REPLAY_MOVES
+    }
+
+    // Whether the replay can be played back: its parameters are within the
+    // limits, they make a board, and every move is on it. Replays from
+    // elsewhere should pass this before they're trusted with anything.
+    pub fn is_playable(&self) -> bool {
+        if !self.is_within_limits() {
+            return false;
+        }
+        self.initial_board()
+            .is_some_and(|board| self.stays_on(&board))
+    }
+
+    // The final board, if the replay is playable. Builds the board once,
+    // where checking with `is_playable` and then playing it builds it twice.
+    pub fn playable_board(&self) -> Option<Board> {
+        if !self.is_within_limits() {
+            return None;
+        }
+        let board = self.initial_board().filter(|board| self.stays_on(board))?;
+        Some(
+            self.moves
+                .iter()
+                .fold(board, |b, m| b.apply(m).unwrap_or(b)),
+        )
+    }
+
+    fn stays_on(&self, board: &Board) -> bool {
+        self.moves.iter().all(|m| match m {
+            Move::Open(p) | Move::Flag(p) | Move::Chord(p) => board.at(p).is_some(),
+            Move::OpenAllUnflagged => true,
+        })
+    }
+
+    // None if the parameters don't make a board, e.g. too many mines.
+    pub fn initial_board(&self) -> Option<Board> {
+        let builder = BoardBuilder::new(self.width, self.height)
+            .mine_count(self.mines)
+            .adjacency(self.adjacency)
+            .symmetry(self.symmetry)
+            .cascade(self.cascade)
+            .seed(self.seed);
+        let builder = match self.spacing {
+            Some(spacing) => builder.spacing(spacing),
+            None => builder,
+        };
+        match self.min_opening {
+            Some(cells) => builder.min_opening(cells),
+            None => builder,
+        }
+        .build()
+        .ok()
+    }
+
+    // The board as it was after the first `step` moves. Moves that can't
+    // be played, like ones off the board, are skipped.
+    pub fn board_at(&self, step: usize) -> Option<Board> {
+        let board = self.initial_board()?;
+        Some(
+            self.moves
+                .iter()
+                .take(step)
+                .fold(board, |b, m| b.apply(m).unwrap_or(b)),
+        )
+    }
+
+    pub fn final_board(&self) -> Option<Board> {
+        self.board_at(self.moves.len())
+    }
+
+    // Which of the first `step` moves opened each cell, by cell in reading
+    // order, for following a game's path. With `times`, that's when each
+    // cell was opened. None for cells still closed.
+    pub fn opened_by(&self, step: usize) -> Option<Vec<Option<usize>>> {
+        let mut board = self.initial_board()?;
+        let mut opened = vec![None; board.width * board.height];
+        for (i, m) in self.moves.iter().take(step).enumerate() {
+            let after = match board.apply(m) {
+                Some(after) => after,
+                None => continue,
+            };
+            for p in board.changed_points(&after) {
+                if matches!(
+                    after.at(&p),
+                    Some(Number { state: Open, .. }) | Some(Mine { state: Open })
+                ) {
+                    opened[p.y as usize * board.width + p.x as usize] = Some(i);
+                }
+            }
+            board = after;
+        }
+        Some(opened)
+    }
+}
+
+impl Board {
+    // None for a move off the board, or one that can't be played, like
+    // opening an open cell.
+    pub fn apply(&self, m: &Move) -> Option<Board> {
+        match m {
+            Move::Open(p) => self.cascade_open_item(p),
+            Move::Flag(p) => {
+                self.at(p)?;
+                Some(self.flag_item(p))
+            }
+            Move::OpenAllUnflagged => self.open_all_unflagged(),
+            Move::Chord(p) => self.chord_open(p),
+        }
+    }
+}
+
+fn adjacency_byte(adjacency: Adjacency) -> u8 {
+    match adjacency {
+        Adjacency::King => 0,
+        Adjacency::Knight => 1,
+    }
+}
+
+// FNV-1a, carried on from `hash`.
+fn fnv(hash: u64, bytes: &[u8]) -> u64 {
+    bytes.iter().fold(hash, |hash, b| {
+        (hash ^ u64::from(*b)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+// A compact text form of a list of moves, for storing lots of replays. Each
+// move is `o{x},{y}` (open), `f{x},{y}` (flag), `c{x},{y}` (chord) or `a`
+// (open all unflagged). Runs of the same kind of move that keep moving by
+// the same step are written once, as `o{x},{y}+{dx},{dy}*{n}` for the first
+// move and `n` more steps.
+pub fn pack_moves(moves: &[Move]) -> String {
+    let mut tokens = vec![];
+    let mut i = 0;
+    while i < moves.len() {
+        let (kind, start) = match moves[i] {
+            Move::Open(p) => ('o', p),
+            Move::Flag(p) => ('f', p),
+            Move::Chord(p) => ('c', p),
+            Move::OpenAllUnflagged => {
+                tokens.push(String::from("a"));
+                i += 1;
+                continue;
+            }
+        };
+        let mut token = format!("{}{},{}", kind, start.x, start.y);
+        if let Some((dx, dy, n)) = run_after(moves, i) {
+            token += &format!("+{},{}*{}", dx, dy, n);
+            i += n;
+        }
+        tokens.push(token);
+        i += 1;
+    }
+    tokens.join(" ")
+}
+
+// The step and length of the run following `moves[i]`, if it's worth
+// writing as one: shorter runs take more space than the moves themselves.
+fn run_after(moves: &[Move], i: usize) -> Option<(i32, i32, usize)> {
+    let point = |m: &Move| match (moves[i], m) {
+        (Move::Open(_), Move::Open(p))
+        | (Move::Flag(_), Move::Flag(p))
+        | (Move::Chord(_), Move::Chord(p)) => Some(*p),
+        _ => None,
+    };
+    let start = point(&moves[i])?;
+    let next = point(moves.get(i + 1)?)?;
+    let (dx, dy) = (next.x - start.x, next.y - start.y);
+    let mut n = 0;
+    let mut last = start;
+    for m in &moves[i + 1..] {
+        match point(m) {
+            Some(p) if p.x - last.x == dx && p.y - last.y == dy => {
+                n += 1;
+                last = p;
+            }
+            _ => break,
+        }
+    }
+    if n >= 2 {
+        Some((dx, dy, n))
+    } else {
+        None
+    }
+}
+
+// The inverse of `pack_moves`. Returns None if `text` isn't in that form,
+// has a run longer than MAX_CELLS or a cell that doesn't fit in an i32, or
+// comes to more than MAX_REPLAY_MOVES moves.
+pub fn unpack_moves(text: &str) -> Option<Vec<Move>> {
+    let mut moves = vec![];
+    for token in text.split_whitespace() {
+        if moves.len() == MAX_REPLAY_MOVES {
+            return None;
+        }
+        if token == "a" {
+            moves.push(Move::OpenAllUnflagged);
+            continue;
+        }
+        let kind = token.chars().next()?;
+        let make: fn(Point) -> Move = match kind {
+            'o' => Move::Open,
+            'f' => Move::Flag,
+            'c' => Move::Chord,
+            _ => return None,
+        };
+        let (start, run) = match token[1..].split_once('+') {
+            Some((start, run)) => (start, Some(run)),
+            None => (&token[1..], None),
+        };
+        let (x, y) = parse_pair(start)?;
+        moves.push(make(Point { x, y }));
+        if let Some(run) = run {
+            let (step, n) = run.split_once('*')?;
+            let (dx, dy) = parse_pair(step)?;
+            let n: usize = n.parse().ok()?;
+            if n > MAX_CELLS || moves.len() + n > MAX_REPLAY_MOVES {
+                return None;
+            }
+            for k in 1..=n as i32 {
+                moves.push(make(Point {
+                    x: dx.checked_mul(k)?.checked_add(x)?,
+                    y: dy.checked_mul(k)?.checked_add(y)?,
+                }));
+            }
+        }
+    }
+    Some(moves)
+}
+
+fn parse_pair(text: &str) -> Option<(i32, i32)> {
+    let (x, y) = text.split_once(',')?;
+    Some((x.parse().ok()?, y.parse().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BoardState;
+    use crate::MapElementCellState::Closed;
+    use pretty_assertions::assert_eq;
+
+    fn played_replay() -> (Replay, Board) {
+        let mut board = BoardBuilder::new(8, 8)
+            .mine_count(10)
+            .seed(7)
+            .build()
+            .unwrap();
+        let mut replay = Replay::for_board(&board).unwrap();
+        let moves = vec![
+            Move::Flag(Point::new(1, 1)),
+            Move::Open(Point::new(3, 4)),
+            Move::Flag(Point::new(1, 1)),
+            Move::Open(Point::new(6, 2)),
+        ];
+        for m in moves {
+            if let Some(b) = board.apply(&m) {
+                board = b;
+                replay.record(m);
+            }
+        }
+        (replay, board)
+    }
+
+    #[test]
+    fn test_replay_rebuilds_final_board() {
+        let (replay, board) = played_replay();
+        assert_eq!(replay.final_board(), Some(board));
+    }
+
+    #[test]
+    fn test_replay_board_at() {
+        let (replay, _board) = played_replay();
+        assert_eq!(replay.board_at(0), replay.initial_board());
+        assert_eq!(replay.board_at(0).unwrap().state, BoardState::Ready);
+        let flagged = replay.board_at(1).unwrap();
+        assert_eq!(flagged.stats().flagged, 1);
+    }
+
+    #[test]
+    fn test_replay_opened_by() {
+        let board = BoardBuilder::new(8, 8)
+            .mine_count(10)
+            .seed(7)
+            .build()
+            .unwrap();
+        let is_open = |b: &Board, p: &Point| matches!(b.at(p), Some(Number { state: Open, .. }));
+        let start = board.opening_start().unwrap();
+        let cascaded = board.apply(&Move::Open(start)).unwrap();
+        let mine = board
+            .points()
+            .find(|p| matches!(board.at(p), Some(Mine { .. })))
+            .unwrap();
+        let later = board
+            .points()
+            .find(|p| matches!(cascaded.at(p), Some(Number { state: Closed, .. })))
+            .unwrap();
+        let mut replay = Replay::for_board(&board).unwrap();
+        replay.record(Move::Open(start));
+        replay.record(Move::Flag(mine));
+        replay.record(Move::Open(later));
+        assert_eq!(replay.opened_by(0), Some(vec![None; 64]));
+        let opened = replay.opened_by(3).unwrap();
+        let finished = replay.final_board().unwrap();
+        assert!(is_open(&finished, &later));
+        for p in board.points() {
+            let expected = if is_open(&cascaded, &p) {
+                Some(0)
+            } else if is_open(&finished, &p) {
+                Some(2)
+            } else {
+                None
+            };
+            assert_eq!(opened[p.y as usize * 8 + p.x as usize], expected, "{:?}", p);
+        }
+        assert!(opened.iter().filter(|o| **o == Some(0)).count() > 1);
+        // Stopping early leaves the later moves' cells closed.
+        assert!(!replay.opened_by(2).unwrap().contains(&Some(2)));
+    }
+
+    #[test]
+    fn test_replay_marks_assisted_moves() {
+        let (mut replay, board) = played_replay();
+        let p = (0..board.width)
+            .map(|x| Point::new(x, 0))
+            .find(|p| matches!(board.at(p), Some(crate::MapElement::Number { .. })))
+            .unwrap();
+        replay.record_assisted(Move::Open(p));
+        let last = replay.moves.len() - 1;
+        assert!(replay.is_assisted(last));
+        assert!(!replay.is_assisted(0));
+    }
+
+    #[test]
+    fn test_replay_times() {
+        let (mut replay, _board) = played_replay();
+        assert!(!replay.is_timed());
+        replay.time_last(40_f64);
+        assert!(!replay.is_timed());
+        replay.times = vec![0_f64, 10_f64, 20_f64, 40_f64];
+        assert!(replay.is_timed());
+        assert_eq!(replay.step_at(-1_f64), 0);
+        assert_eq!(replay.step_at(15_f64), 2);
+        assert_eq!(replay.step_at(40_f64), 4);
+        let (mut other, _board) = played_replay();
+        other.moves.clear();
+        assert!(replay.is_same_board(&other));
+        other.seed += 1;
+        assert!(!replay.is_same_board(&other));
+    }
+
+    #[test]
+    fn test_replay_checksum() {
+        let (mut replay, _board) = played_replay();
+        assert!(!replay.is_sealed());
+        replay.seal();
+        assert!(replay.is_sealed());
+        assert_eq!(replay.checksum, Some(replay.checksum()));
+        let sealed = replay.clone();
+        // Pinned, as the scores server and other clients check it.
+        assert_eq!(sealed.checksum(), 1_378_296_334_784_858_809);
+
+        replay.moves[1] = Move::Open(Point::new(3, 5));
+        assert!(!replay.is_sealed());
+        let mut replay = sealed.clone();
+        replay.moves.swap(0, 1);
+        assert!(!replay.is_sealed());
+        let mut replay = sealed.clone();
+        replay.assisted.push(2);
+        assert!(!replay.is_sealed());
+        let mut replay = sealed.clone();
+        replay.seed += 1;
+        assert!(!replay.is_sealed());
+        let mut replay = sealed.clone();
+        replay.times = vec![0_f64, 10_f64, 20_f64, 40_f64];
+        assert!(!replay.is_sealed());
+        replay.seal();
+        replay.times[3] = 30_f64;
+        assert!(!replay.is_sealed());
+        let mut replay = sealed;
+        replay.cascade = Cascade::Off;
+        assert!(!replay.is_sealed());
+    }
+
+    #[test]
+    fn test_replay_keeps_cascade() {
+        let board = BoardBuilder::new(8, 8)
+            .mine_count(10)
+            .seed(7)
+            .cascade(Cascade::Off)
+            .build()
+            .unwrap();
+        let replay = Replay::for_board(&board).unwrap();
+        assert_eq!(replay.cascade, Cascade::Off);
+        assert_eq!(replay.initial_board(), Some(board));
+    }
+
+    #[test]
+    fn test_replay_is_playable() {
+        let (mut replay, _board) = played_replay();
+        assert!(replay.is_playable());
+        replay.record(Move::Open(Point::new(8, 0)));
+        assert!(!replay.is_playable());
+        let board = replay.initial_board().unwrap();
+        assert_eq!(board.apply(&Move::Open(Point::new(8, 0))), None);
+        assert_eq!(board.apply(&Move::Flag(Point { x: -1, y: 0 })), None);
+        assert_eq!(replay.final_board(), played_replay().0.final_board());
+        assert_eq!(replay.playable_board(), None);
+        let (played, _board) = played_replay();
+        assert_eq!(played.playable_board(), played.final_board());
+        let (mut replay, _board) = played_replay();
+        replay.mines = 65;
+        assert!(!replay.is_playable());
+        assert_eq!(replay.final_board(), None);
+        assert_eq!(replay.opened_by(1), None);
+    }
+
+    #[test]
+    fn test_replay_is_within_limits() {
+        let (replay, _board) = played_replay();
+        assert!(replay.is_within_limits());
+        let too_big = Replay {
+            width: 1000,
+            height: 1000,
+            ..replay.clone()
+        };
+        assert!(!too_big.is_within_limits());
+        assert!(!too_big.is_playable());
+        let overflowing = Replay {
+            width: usize::MAX,
+            height: 2,
+            ..replay.clone()
+        };
+        assert!(!overflowing.is_within_limits());
+        let spread = Replay {
+            spacing: Some(Spacing {
+                distance: 9,
+                moves: Adjacency::Knight,
+            }),
+            ..replay.clone()
+        };
+        assert!(!spread.is_within_limits());
+        let opening = Replay {
+            min_opening: Some(65),
+            ..replay
+        };
+        assert!(!opening.is_within_limits());
+    }
+
+    #[test]
+    fn test_replay_needs_seed() {
+        let board = BoardBuilder::new(2, 2)
+            .mines_at(vec![Point::new(0, 0)])
+            .build()
+            .unwrap();
+        assert_eq!(Replay::for_board(&board), None);
+    }
+
+    #[test]
+    fn test_pack_moves_round_trip() {
+        let (replay, _board) = played_replay();
+        let packed = pack_moves(&replay.moves);
+        assert_eq!(packed, "f1,1 o3,4 f1,1 o6,2");
+        assert_eq!(unpack_moves(&packed), Some(replay.moves));
+    }
+
+    #[test]
+    fn test_pack_moves_runs() {
+        let mut moves: Vec<Move> = (0..5).map(|x| Move::Open(Point::new(x, 2))).collect();
+        moves.push(Move::Open(Point::new(4, 3)));
+        moves.push(Move::OpenAllUnflagged);
+        moves.extend((0..3).map(|y| Move::Flag(Point::new(1, 7 - 2 * y))));
+        moves.push(Move::Chord(Point::new(2, 5)));
+        let packed = pack_moves(&moves);
+        assert_eq!(packed, "o0,2+1,0*4 o4,3 a f1,7+0,-2*2 c2,5");
+        assert_eq!(unpack_moves(&packed), Some(moves));
+        assert_eq!(unpack_moves(""), Some(vec![]));
+    }
+
+    #[test]
+    fn test_unpack_moves_rejects_garbage() {
+        assert_eq!(unpack_moves("x1,1"), None);
+        assert_eq!(unpack_moves("o1"), None);
+        assert_eq!(unpack_moves("o1,1+1,0"), None);
+        assert_eq!(unpack_moves("f1,b"), None);
+        assert_eq!(unpack_moves("o1,1+1,0*-2"), None);
+    }
+
+    #[test]
+    fn test_unpack_moves_rejects_oversized() {
+        assert_eq!(unpack_moves("o0,0+1,0*4000001"), None);
+        assert_eq!(unpack_moves("o0,0+1000,0*3000000"), None);
+        assert_eq!(unpack_moves("o2147483647,0+1,0*2"), None);
+        let run = "o0,0+1,0*999999";
+        assert_eq!(unpack_moves(run).map(|m| m.len()), Some(MAX_REPLAY_MOVES));
+        assert_eq!(unpack_moves(&format!("{} a", run)), None);
+    }
+}
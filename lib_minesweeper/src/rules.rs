@@ -0,0 +1,228 @@
+use crate::Board;
+use crate::BoardState;
+use crate::MapElement::{Mine, Number};
+use crate::MapElementCellState::{Closed, Open};
+use crate::Move;
+use crate::Point;
+
+// Optional rules the engine plays by itself after the player's moves.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub struct Rules {
+    // Flag the last closed cells once they can only be mines.
+    pub auto_flag: bool,
+    // Flag the mine instead when an open sets one off. Turn it off once
+    // it's been used for it to only forgive one mistake.
+    pub second_chance: bool,
+}
+
+// Something a rule did after a move, for a frontend to show.
+#[derive(Debug, PartialEq, Clone)]
+pub enum RuleEvent {
+    // These cells were flagged, in reading order.
+    AutoFlagged(Vec<Point>),
+    // The mine here was flagged instead of losing the game.
+    Forgiven(Point),
+}
+
+impl Board {
+    // Once the closed cells left are as many as the mines left to flag, they
+    // can only be mines, and this flags them all. With a wrong flag on the
+    // board there are always more closed cells than that, so this only ever
+    // finishes off a game the last open already won. None if there's
+    // nothing to flag.
+    pub fn flag_endgame(&self) -> Option<(Board, Vec<Point>)> {
+        if !matches!(self.state, BoardState::Playing | BoardState::Won) {
+            return None;
+        }
+        let stats = self.stats();
+        if stats.closed == 0 || stats.closed as i32 != stats.mines_remaining {
+            return None;
+        }
+        let closed: Vec<Point> = self
+            .points()
+            .filter(|p| {
+                matches!(
+                    self.at(p),
+                    Some(Number { state: Closed, .. }) | Some(Mine { state: Closed })
+                )
+            })
+            .collect();
+        let board = closed.iter().fold(self.clone(), |b, p| b.flag_item(p));
+        Some((board, closed))
+    }
+
+    // Takes back the open at `p` that lost the game: the mine there is
+    // flagged rather than set off, and play goes on. None unless this board
+    // was lost on a mine at `p`.
+    pub fn forgive(&self, p: &Point) -> Option<Board> {
+        if self.state != BoardState::Failed || !matches!(self.at(p), Some(Mine { state: Closed })) {
+            return None;
+        }
+        Some(self.flag_item(p).settled())
+    }
+
+    // The mine a move's cascade ran into on its way to `lost`: one beside
+    // a number it opened with as many flags around it as its count, which
+    // only a wrong flag gets past under `Cascade::Chording`.
+    fn set_off(&self, lost: &Board) -> Option<Point> {
+        self.changed_points(lost)
+            .into_iter()
+            .filter(|q| match lost.at(q) {
+                Some(Number { state: Open, count }) => self.flags_around(&lost.map, q) == *count,
+                _ => false,
+            })
+            .flat_map(|q| self.neighbours(&q))
+            .find(|n| matches!(self.at(n), Some(Mine { state: Closed })))
+    }
+
+    // Plays `m`, then whichever of `rules` it sets off. Rules only follow
+    // opens, so flagging stays entirely up to the player until the end.
+    pub fn apply_with(&self, m: &Move, rules: Rules) -> Option<(Board, Vec<RuleEvent>)> {
+        let board = self.apply(m)?;
+        let lost = board.state == BoardState::Failed && self.state != BoardState::Failed;
+        if rules.second_chance && lost {
+            match m {
+                Move::Open(p) => {
+                    if let Some(forgiven) = board.forgive(p) {
+                        return Some((forgiven, vec![RuleEvent::Forgiven(*p)]));
+                    }
+                    if let Some(mine) = self.set_off(&board) {
+                        let forgiven = self.flag_item(&mine).settled();
+                        return Some((forgiven, vec![RuleEvent::Forgiven(mine)]));
+                    }
+                }
+                // A chord past a wrong flag is taken back, and the mine it
+                // would have opened flagged instead.
+                Move::Chord(p) => {
+                    let mine = self
+                        .neighbours(p)
+                        .into_iter()
+                        .find(|n| matches!(self.at(n), Some(Mine { state: Closed })))
+                        .or_else(|| self.set_off(&board));
+                    if let Some(mine) = mine {
+                        let forgiven = self.flag_item(&mine).settled();
+                        return Some((forgiven, vec![RuleEvent::Forgiven(mine)]));
+                    }
+                }
+                _ => {}
+            }
+        }
+        let opened = matches!(m, Move::Open(_) | Move::OpenAllUnflagged | Move::Chord(_));
+        if rules.auto_flag && opened {
+            if let Some((flagged, points)) = board.flag_endgame() {
+                return Some((flagged, vec![RuleEvent::AutoFlagged(points)]));
+            }
+        }
+        Some((board, vec![]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Adjacency;
+    use crate::BoardBuilder;
+    use crate::Cascade;
+    use crate::MapElementCellState::Flagged;
+    use pretty_assertions::assert_eq;
+
+    fn one_mine() -> Board {
+        BoardBuilder::new(4, 1)
+            .mines_at(vec![Point::new(0, 0)])
+            .adjacency(Adjacency::King)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_flag_endgame() {
+        assert_eq!(one_mine().flag_endgame(), None);
+        let one = one_mine().cascade_open_item(&Point::new(1, 0)).unwrap();
+        assert_eq!(one.flag_endgame(), None);
+        let won = one_mine().cascade_open_item(&Point::new(3, 0)).unwrap();
+        let (flagged, points) = won.flag_endgame().unwrap();
+        assert_eq!(points, vec![Point::new(0, 0)]);
+        assert_eq!(flagged.state, BoardState::Won);
+        assert_eq!(flagged.stats().flagged, 1);
+        assert_eq!(flagged.flag_endgame(), None);
+        let lost = one_mine().cascade_open_item(&Point::new(0, 0)).unwrap();
+        assert_eq!(lost.flag_endgame(), None);
+    }
+
+    #[test]
+    fn test_apply_with() {
+        let rules = Rules {
+            auto_flag: true,
+            ..Rules::default()
+        };
+        let open = Move::Open(Point::new(3, 0));
+        let (board, events) = one_mine().apply_with(&open, rules).unwrap();
+        assert_eq!(events, vec![RuleEvent::AutoFlagged(vec![Point::new(0, 0)])]);
+        assert_eq!(board.stats().flagged, 1);
+        let (board, events) = one_mine().apply_with(&open, Rules::default()).unwrap();
+        assert_eq!(events, vec![]);
+        assert_eq!(board.stats().flagged, 0);
+        let flag = Move::Flag(Point::new(0, 0));
+        let (_, events) = one_mine().apply_with(&flag, rules).unwrap();
+        assert_eq!(events, vec![]);
+    }
+
+    #[test]
+    fn test_forgive() {
+        let mine = Point::new(0, 0);
+        assert_eq!(one_mine().forgive(&mine), None);
+        let lost = one_mine().cascade_open_item(&mine).unwrap();
+        assert_eq!(lost.forgive(&Point::new(1, 0)), None);
+        let forgiven = lost.forgive(&mine).unwrap();
+        assert_eq!(forgiven.state, BoardState::Playing);
+        assert_eq!(forgiven.at(&mine), Some(&Mine { state: Flagged }));
+        assert_eq!(forgiven.forgive(&mine), None);
+    }
+
+    #[test]
+    fn test_apply_with_second_chance() {
+        let rules = Rules {
+            second_chance: true,
+            ..Rules::default()
+        };
+        let mine = Point::new(0, 0);
+        let (board, events) = one_mine().apply_with(&Move::Open(mine), rules).unwrap();
+        assert_eq!(events, vec![RuleEvent::Forgiven(mine)]);
+        assert_eq!(board.state, BoardState::Playing);
+        assert_eq!(board.stats().flagged, 1);
+        let (board, events) = one_mine()
+            .apply_with(&Move::Open(mine), Rules::default())
+            .unwrap();
+        assert_eq!(events, vec![]);
+        assert_eq!(board.state, BoardState::Failed);
+    }
+
+    #[test]
+    fn test_second_chance_under_chording() {
+        // The 1 beside the mine counts the wrong flag past it as its mine,
+        // so opening it chords onto the mine.
+        let board = BoardBuilder::new(4, 1)
+            .mines_at(vec![Point::new(0, 0)])
+            .adjacency(Adjacency::King)
+            .cascade(Cascade::Chording)
+            .build()
+            .unwrap()
+            .flag_item(&Point::new(2, 0));
+        let rules = Rules {
+            second_chance: true,
+            ..Rules::default()
+        };
+        let open = Move::Open(Point::new(1, 0));
+        let (forgiven, events) = board.apply_with(&open, rules).unwrap();
+        assert_eq!(events, vec![RuleEvent::Forgiven(Point::new(0, 0))]);
+        assert_eq!(forgiven.state, BoardState::Playing);
+        assert_eq!(
+            forgiven.at(&Point::new(0, 0)),
+            Some(&Mine { state: Flagged })
+        );
+        assert_eq!(forgiven.stats().opened, 0);
+        let (lost, events) = board.apply_with(&open, Rules::default()).unwrap();
+        assert_eq!(events, vec![]);
+        assert_eq!(lost.state, BoardState::Failed);
+    }
+}
@@ -0,0 +1,343 @@
+use crate::Board;
+use crate::BoardState;
+use crate::Intent;
+use crate::MapElement::{Mine, Number};
+use crate::MapElementCellState::{Flagged, Open};
+use crate::Point;
+
+const CATALOG: &str = include_str!("scenarios.txt");
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ScenarioKind {
+    // Teaches the rules, a step at a time.
+    Tutorial,
+    // One move a reference pattern decides.
+    Drill,
+    // The first levels of the campaign: whole boards, with a word to start.
+    Intro,
+}
+
+impl ScenarioKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            ScenarioKind::Tutorial => "tutorial",
+            ScenarioKind::Drill => "drill",
+            ScenarioKind::Intro => "intro",
+        }
+    }
+}
+
+// What moves a scenario on to its next step.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Trigger {
+    // The player reading on.
+    Next,
+    Open(Point),
+    Flag(Point),
+    Won,
+}
+
+impl Trigger {
+    // Whether `board` shows the trigger's been met. Next never is.
+    pub fn is_met(self, board: &Board) -> bool {
+        match self {
+            Trigger::Next => false,
+            Trigger::Open(p) => matches!(board.at(&p), Some(Number { state: Open, .. })),
+            Trigger::Flag(p) => matches!(
+                board.at(&p),
+                Some(Number { state: Flagged, .. }) | Some(Mine { state: Flagged })
+            ),
+            Trigger::Won => board.state == BoardState::Won,
+        }
+    }
+}
+
+// A message shown, and cells picked out, until the trigger's met.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ScenarioStep {
+    pub trigger: Trigger,
+    pub highlights: Vec<Point>,
+    pub message: String,
+}
+
+// A board to play through step by step, with only some actions allowed,
+// written out in scenarios.txt rather than in code.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Scenario {
+    pub kind: ScenarioKind,
+    pub key: String,
+    pub title: String,
+    pub board: Board,
+    pub allowed: Vec<Intent>,
+    pub steps: Vec<ScenarioStep>,
+}
+
+// The scenarios shipped with the game, in catalog order.
+pub fn scenarios() -> Vec<Scenario> {
+    parse_scenarios(CATALOG)
+}
+
+// See scenarios.txt for the format. Blank lines and comments are skipped,
+// and so are lines that don't parse, along with the steps of a scenario
+// that doesn't.
+pub fn parse_scenarios(text: &str) -> Vec<Scenario> {
+    let mut scenarios: Vec<Scenario> = vec![];
+    // Whether the steps that follow belong to the last scenario parsed.
+    let mut taking_steps = false;
+    for line in text.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let fields: Vec<&str> = line.split('|').map(str::trim).collect();
+        match fields[..] {
+            ["scenario", kind, key, title, grid, allowed] => {
+                let scenario = parse_scenario(kind, key, title, grid, allowed);
+                taking_steps = scenario.is_some();
+                scenarios.extend(scenario);
+            }
+            ["step", trigger, highlights, message] if taking_steps => {
+                let scenario = scenarios.last_mut().unwrap();
+                if let Some(step) = parse_step(&scenario.board, trigger, highlights, message) {
+                    scenario.steps.push(step);
+                }
+            }
+            _ => {}
+        }
+    }
+    scenarios
+}
+
+fn parse_scenario(
+    kind: &str,
+    key: &str,
+    title: &str,
+    grid: &str,
+    allowed: &str,
+) -> Option<Scenario> {
+    let kind = [
+        ScenarioKind::Tutorial,
+        ScenarioKind::Drill,
+        ScenarioKind::Intro,
+    ]
+    .iter()
+    .copied()
+    .find(|k| k.name() == kind)?;
+    let allowed = allowed
+        .split_whitespace()
+        .map(|a| match a {
+            "dig" => Some(Intent::Dig),
+            "flag" => Some(Intent::Flag),
+            "chord" => Some(Intent::Chord),
+            _ => None,
+        })
+        .collect::<Option<Vec<Intent>>>()?;
+    Some(Scenario {
+        kind,
+        key: key.to_string(),
+        title: title.to_string(),
+        board: grid.replace('/', "\n").parse().ok()?,
+        allowed,
+        steps: vec![],
+    })
+}
+
+fn parse_step(
+    board: &Board,
+    trigger: &str,
+    highlights: &str,
+    message: &str,
+) -> Option<ScenarioStep> {
+    let words: Vec<&str> = trigger.split_whitespace().collect();
+    let trigger = match words[..] {
+        ["next"] => Trigger::Next,
+        ["won"] => Trigger::Won,
+        ["open", column, row] => Trigger::Open(parse_point(board, column, row)?),
+        ["flag", column, row] => Trigger::Flag(parse_point(board, column, row)?),
+        _ => return None,
+    };
+    let highlights = match highlights {
+        "-" => vec![],
+        _ => highlights
+            .split(',')
+            .map(|p| match p.split_whitespace().collect::<Vec<_>>()[..] {
+                [column, row] => parse_point(board, column, row),
+                _ => None,
+            })
+            .collect::<Option<Vec<Point>>>()?,
+    };
+    Some(ScenarioStep {
+        trigger,
+        highlights,
+        message: message.to_string(),
+    })
+}
+
+// A column and row counting from 1, on the board.
+fn parse_point(board: &Board, column: &str, row: &str) -> Option<Point> {
+    let p = Point::new(
+        column.parse::<usize>().ok()?.checked_sub(1)?,
+        row.parse::<usize>().ok()?.checked_sub(1)?,
+    );
+    board.at(&p)?;
+    Some(p)
+}
+
+// A scenario being played, and the step it's on.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ScenarioRun {
+    pub scenario: Scenario,
+    step: usize,
+}
+
+impl ScenarioRun {
+    pub fn new(scenario: Scenario) -> ScenarioRun {
+        ScenarioRun { scenario, step: 0 }
+    }
+
+    // None once every step's done.
+    pub fn step(&self) -> Option<&ScenarioStep> {
+        self.scenario.steps.get(self.step)
+    }
+
+    // Which step it's on, counting from 0.
+    pub fn index(&self) -> usize {
+        self.step
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.step >= self.scenario.steps.len()
+    }
+
+    pub fn allows(&self, intent: Intent) -> bool {
+        self.scenario.allowed.contains(&intent)
+    }
+
+    // Moves past every step whose trigger `board` meets, and whether it
+    // moved at all.
+    pub fn advance(&mut self, board: &Board) -> bool {
+        let from = self.step;
+        while self.step().is_some_and(|s| s.trigger.is_met(board)) {
+            self.step += 1;
+        }
+        self.step != from
+    }
+
+    // Reads on from a step that waits for it, then past any the board
+    // already meets.
+    pub fn next(&mut self, board: &Board) -> bool {
+        if self.step().map(|s| s.trigger) != Some(Trigger::Next) {
+            return false;
+        }
+        self.step += 1;
+        self.advance(board);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Move;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_every_line_parses() {
+        let lines = |kind: &str| CATALOG.lines().filter(|l| l.starts_with(kind)).count();
+        let scenarios = scenarios();
+        assert_eq!(scenarios.len(), lines("scenario |"));
+        let steps: usize = scenarios.iter().map(|s| s.steps.len()).sum();
+        assert_eq!(steps, lines("step |"));
+        assert!(scenarios.iter().all(|s| !s.steps.is_empty()));
+        let mut keys: Vec<&str> = scenarios.iter().map(|s| s.key.as_str()).collect();
+        keys.sort_unstable();
+        keys.dedup();
+        assert_eq!(keys.len(), scenarios.len());
+    }
+
+    #[test]
+    fn test_bad_lines_are_skipped() {
+        let text = "scenario | quest | a | A | 1..../..*.. | dig\n\
+                    step | next | - | Skipped with its scenario\n\
+                    scenario | drill | b | B | 1..../..*.. | dig\n\
+                    step | open 3 1 | 1 1 | Kept\n\
+                    step | open 6 1 | - | Off the board\n\
+                    step | jump | - | Not a trigger";
+        let scenarios = parse_scenarios(text);
+        assert_eq!(scenarios.len(), 1);
+        assert_eq!(
+            scenarios[0].steps,
+            vec![ScenarioStep {
+                trigger: Trigger::Open(Point::new(2, 0)),
+                highlights: vec![Point::new(0, 0)],
+                message: String::from("Kept"),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_run() {
+        let text = "scenario | tutorial | a | A | 1..../..*.. | flag\n\
+                    step | next | - | Read this\n\
+                    step | flag 3 2 | 3 2 | Flag it\n\
+                    step | next | - | Done";
+        let scenario = parse_scenarios(text).remove(0);
+        let board = scenario.board.clone();
+        let mut run = ScenarioRun::new(scenario);
+        assert!(run.allows(Intent::Flag));
+        assert!(!run.allows(Intent::Dig));
+        assert!(!run.advance(&board));
+        assert!(run.next(&board));
+        assert_eq!(run.index(), 1);
+        assert!(!run.next(&board));
+        let board = board.apply(&Move::Flag(Point::new(2, 1))).unwrap();
+        assert!(run.advance(&board));
+        assert_eq!(run.step().unwrap().message, "Done");
+        assert!(run.next(&board));
+        assert!(run.is_finished());
+    }
+
+    // Plays each scenario by its triggers, checking every open is safe,
+    // every flag is a mine and a board that has to be won can be without a
+    // guess.
+    #[test]
+    fn test_scenarios_can_be_played_through() {
+        for scenario in scenarios() {
+            let mut board = scenario.board.clone();
+            let mut run = ScenarioRun::new(scenario.clone());
+            while let Some(step) = run.step().cloned() {
+                match step.trigger {
+                    Trigger::Next => {}
+                    Trigger::Open(p) => {
+                        assert!(
+                            matches!(board.at(&p), Some(Number { .. })),
+                            "{}",
+                            scenario.key
+                        );
+                        assert!(run.allows(Intent::Dig), "{}", scenario.key);
+                        board = board.apply(&Move::Open(p)).unwrap();
+                    }
+                    Trigger::Flag(p) => {
+                        assert!(
+                            matches!(board.at(&p), Some(Mine { .. })),
+                            "{}",
+                            scenario.key
+                        );
+                        assert!(run.allows(Intent::Flag), "{}", scenario.key);
+                        board = board.apply(&Move::Flag(p)).unwrap();
+                    }
+                    #[cfg(feature = "solver")]
+                    Trigger::Won => {
+                        assert!(board.is_fully_deducible(), "{}", scenario.key);
+                        break;
+                    }
+                    #[cfg(not(feature = "solver"))]
+                    Trigger::Won => break,
+                }
+                if !run.advance(&board) {
+                    assert!(run.next(&board), "{} is stuck", scenario.key);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,341 @@
+use std::fmt;
+
+use crate::Adjacency;
+use crate::Board;
+use crate::BoardState;
+use crate::MapElement::Mine;
+use crate::MapElement::Number;
+use crate::MapElementCellState::{Closed, Flagged, Open};
+use crate::Move;
+use crate::Point;
+
+// A cell as the player sees it: closed mines look like any other closed cell.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum CellView {
+    Closed,
+    Flagged,
+    Open(i32),
+    Exploded,
+}
+
+// The parts of a board a player is allowed to know about, so strategies
+// can't peek at where the mines are.
+pub struct BoardView<'a> {
+    board: &'a Board,
+}
+
+impl<'a> BoardView<'a> {
+    pub fn new(board: &'a Board) -> BoardView<'a> {
+        BoardView { board }
+    }
+
+    pub fn width(&self) -> usize {
+        self.board.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.board.height
+    }
+
+    pub fn mines(&self) -> usize {
+        self.board.mines
+    }
+
+    pub fn state(&self) -> &BoardState {
+        &self.board.state
+    }
+
+    pub fn adjacency(&self) -> Adjacency {
+        self.board.adjacency
+    }
+
+    pub fn at(&self, p: &Point) -> Option<CellView> {
+        self.board.at(p).map(|el| match el {
+            Number { state: Closed, .. } | Mine { state: Closed } => CellView::Closed,
+            Number { state: Flagged, .. } | Mine { state: Flagged } => CellView::Flagged,
+            Number { state: Open, count } => CellView::Open(*count),
+            Mine { state: Open } => CellView::Exploded,
+        })
+    }
+
+    pub fn neighbours(&self, p: &Point) -> Vec<Point> {
+        self.board.neighbours(p)
+    }
+
+    pub fn frontier(&self) -> Vec<Point> {
+        self.board.frontier()
+    }
+
+    pub fn points(&self) -> impl Iterator<Item = Point> {
+        let (width, height) = (self.width(), self.height());
+        (0..height).flat_map(move |y| (0..width).map(move |x| Point::new(x, y)))
+    }
+}
+
+// Sync so tournaments can play a strategy on several threads at once.
+pub trait Strategy: Sync {
+    fn name(&self) -> &str;
+    fn next_move(&self, board: &BoardView) -> Option<Move>;
+}
+
+// Why a move is certain: the open number at `source` and its closed and
+// flagged neighbours, which it constrains.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Deduction {
+    pub m: Move,
+    pub source: Point,
+    pub count: usize,
+    pub closed: Vec<Point>,
+    pub flagged: Vec<Point>,
+}
+
+impl fmt::Display for Deduction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (x, y) = (self.source.x + 1, self.source.y + 1);
+        match self.m {
+            Move::Flag(_) => write!(
+                f,
+                "The {} at column {}, row {} has {} flagged and {} closed neighbours, \
+                 so the closed ones must all be mines.",
+                self.count,
+                x,
+                y,
+                self.flagged.len(),
+                self.closed.len()
+            ),
+            _ => write!(
+                f,
+                "The {} at column {}, row {} already has {} flagged neighbours, \
+                 so its other closed neighbours are safe.",
+                self.count, x, y, self.count
+            ),
+        }
+    }
+}
+
+// Looks at one open number at a time: if its closed neighbours must all be
+// mines they get flagged, and if its flags already account for every mine
+// the rest get opened. Never guesses, so it can't start a game by itself.
+pub struct BasicSolver;
+
+impl BasicSolver {
+    // The next move, with the reasoning behind it.
+    pub fn explain(&self, board: &BoardView) -> Option<Deduction> {
+        board.points().find_map(|p| self.deduce(board, p, None))
+    }
+
+    // The number that proves `m` right by itself, if there is one. Moves
+    // only the full solver can prove have none.
+    pub fn justify(&self, board: &BoardView, m: &Move) -> Option<Deduction> {
+        let target = match m {
+            Move::Open(p) | Move::Flag(p) => *p,
+            Move::OpenAllUnflagged | Move::Chord(_) => return None,
+        };
+        board
+            .neighbours(&target)
+            .into_iter()
+            .filter_map(|p| self.deduce(board, p, Some(target)))
+            .find(|d| d.m == *m)
+    }
+
+    // What the open number at `p` proves about `target`, or about the first
+    // of its closed neighbours if there's no target.
+    fn deduce(&self, board: &BoardView, p: Point, target: Option<Point>) -> Option<Deduction> {
+        let count = match board.at(&p) {
+            Some(CellView::Open(count)) if count > 0 => count as usize,
+            _ => return None,
+        };
+        let neighbours = board.neighbours(&p);
+        let with_view = |view: CellView| -> Vec<Point> {
+            neighbours
+                .iter()
+                .filter(|n| board.at(n) == Some(view))
+                .copied()
+                .collect()
+        };
+        let closed = with_view(CellView::Closed);
+        let flagged = with_view(CellView::Flagged);
+        let n = match target {
+            Some(target) if closed.contains(&target) => target,
+            Some(_) => return None,
+            None => *closed.first()?,
+        };
+        let m = if count == closed.len() + flagged.len() {
+            Move::Flag(n)
+        } else if count == flagged.len() {
+            Move::Open(n)
+        } else {
+            return None;
+        };
+        Some(Deduction {
+            m,
+            source: p,
+            count,
+            closed,
+            flagged,
+        })
+    }
+
+    // Every closed cell an open number proves safe, because its flags
+    // already account for all of its mines.
+    pub fn safe_cells(&self, board: &BoardView) -> Vec<Point> {
+        self.forced(board, false)
+    }
+
+    // Every closed cell an open number proves to be a mine, because it has
+    // no other closed neighbours to hold its mines.
+    pub fn forced_mines(&self, board: &BoardView) -> Vec<Point> {
+        self.forced(board, true)
+    }
+
+    fn forced(&self, board: &BoardView, mines: bool) -> Vec<Point> {
+        let mut cells = vec![];
+        for p in board.points() {
+            let count = match board.at(&p) {
+                Some(CellView::Open(count)) => count as usize,
+                _ => continue,
+            };
+            let neighbours = board.neighbours(&p);
+            let with_view = |view: CellView| -> Vec<Point> {
+                neighbours
+                    .iter()
+                    .filter(|n| board.at(n) == Some(view))
+                    .copied()
+                    .collect()
+            };
+            let closed = with_view(CellView::Closed);
+            let flagged = with_view(CellView::Flagged).len();
+            let forced = if mines {
+                count == closed.len() + flagged
+            } else {
+                count == flagged
+            };
+            if !forced {
+                continue;
+            }
+            for n in closed {
+                if !cells.contains(&n) {
+                    cells.push(n);
+                }
+            }
+        }
+        cells
+    }
+}
+
+impl Strategy for BasicSolver {
+    fn name(&self) -> &str {
+        "basic"
+    }
+
+    fn next_move(&self, board: &BoardView) -> Option<Move> {
+        self.explain(board).map(|d| d.m)
+    }
+}
+
+// Falls back to opening the first closed cell whenever the wrapped strategy
+// has nothing certain to play.
+pub struct Guessing<S: Strategy>(pub S);
+
+impl<S: Strategy> Strategy for Guessing<S> {
+    fn name(&self) -> &str {
+        "guessing"
+    }
+
+    fn next_move(&self, board: &BoardView) -> Option<Move> {
+        self.0.next_move(board).or_else(|| {
+            board
+                .points()
+                .find(|p| board.at(p) == Some(CellView::Closed))
+                .map(Move::Open)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_view_hides_mines() {
+        let board: Board = "*.0..\n.F...".parse().unwrap();
+        let view = BoardView::new(&board);
+        assert_eq!(view.at(&Point::new(0, 0)), Some(CellView::Closed));
+        assert_eq!(view.at(&Point::new(1, 0)), Some(CellView::Closed));
+        assert_eq!(view.at(&Point::new(2, 0)), Some(CellView::Open(0)));
+        assert_eq!(view.at(&Point::new(1, 1)), Some(CellView::Flagged));
+        assert_eq!(view.points().count(), 10);
+    }
+
+    #[test]
+    fn test_basic_solver_flags_forced_mines() {
+        let board: Board = "*..1.\n.*...".parse().unwrap();
+        assert_eq!(
+            BasicSolver.next_move(&BoardView::new(&board)),
+            Some(Move::Flag(Point::new(1, 1)))
+        );
+    }
+
+    #[test]
+    fn test_basic_solver_opens_safe_cells() {
+        let board: Board = "F....\n.*1..".parse().unwrap();
+        assert_eq!(
+            BasicSolver.next_move(&BoardView::new(&board)),
+            Some(Move::Open(Point::new(4, 0)))
+        );
+    }
+
+    #[test]
+    fn test_basic_solver_lists_forced_cells() {
+        let board: Board = "F....\n.*1..".parse().unwrap();
+        let view = BoardView::new(&board);
+        assert_eq!(BasicSolver.safe_cells(&view), vec![Point::new(4, 0)]);
+        let board: Board = "*..1.\n.*...".parse().unwrap();
+        let view = BoardView::new(&board);
+        assert_eq!(BasicSolver.forced_mines(&view), vec![Point::new(1, 1)]);
+    }
+
+    #[test]
+    fn test_basic_solver_explains_moves() {
+        let board: Board = "*..1.\n.*...".parse().unwrap();
+        let deduction = BasicSolver.explain(&BoardView::new(&board)).unwrap();
+        assert_eq!(deduction.m, Move::Flag(Point::new(1, 1)));
+        assert_eq!(deduction.source, Point::new(3, 0));
+        assert_eq!(deduction.closed, vec![Point::new(1, 1)]);
+        assert_eq!(
+            deduction.to_string(),
+            "The 1 at column 4, row 1 has 0 flagged and 1 closed neighbours, \
+             so the closed ones must all be mines."
+        );
+    }
+
+    #[test]
+    fn test_basic_solver_justifies_moves() {
+        let board: Board = "*..1.\n.*...".parse().unwrap();
+        let view = BoardView::new(&board);
+        let flag = Move::Flag(Point::new(1, 1));
+        assert_eq!(
+            BasicSolver.justify(&view, &flag),
+            BasicSolver.explain(&view)
+        );
+        assert_eq!(
+            BasicSolver.justify(&view, &Move::Flag(Point::new(0, 0))),
+            None
+        );
+        assert_eq!(
+            BasicSolver.justify(&view, &Move::Open(Point::new(1, 1))),
+            None
+        );
+    }
+
+    #[test]
+    fn test_basic_solver_never_guesses() {
+        let board: Board = "*....\n.*...".parse().unwrap();
+        assert_eq!(BasicSolver.next_move(&BoardView::new(&board)), None);
+        assert_eq!(
+            Guessing(BasicSolver).next_move(&BoardView::new(&board)),
+            Some(Move::Open(Point::new(0, 0)))
+        );
+    }
+}
@@ -0,0 +1,144 @@
+#[cfg(all(feature = "parallel", not(target_arch = "wasm32")))]
+use rayon::prelude::*;
+
+use crate::Adjacency;
+use crate::Board;
+use crate::BoardBuilder;
+use crate::BoardState;
+use crate::BoardView;
+use crate::Strategy;
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Standing {
+    pub name: String,
+    pub played: usize,
+    pub won: usize,
+    pub moves: usize,
+    // Average share of safe cells opened, over every board played.
+    pub progress: f64,
+}
+
+// Plays every strategy on the same seeded boards.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Tournament {
+    pub width: usize,
+    pub height: usize,
+    pub mines: usize,
+    pub adjacency: Adjacency,
+    pub seeds: Vec<u64>,
+}
+
+impl Tournament {
+    pub fn run(&self, strategies: &[&dyn Strategy]) -> Vec<Standing> {
+        strategies.iter().map(|s| self.standing(*s)).collect()
+    }
+
+    fn standing(&self, strategy: &dyn Strategy) -> Standing {
+        let mut standing = Standing {
+            name: strategy.name().to_string(),
+            played: 0,
+            won: 0,
+            moves: 0,
+            progress: 0_f64,
+        };
+        // Summed in seed order, so threads don't change the rounding.
+        for (board, moves) in self.games(strategy) {
+            standing.played += 1;
+            standing.moves += moves;
+            standing.progress += board.stats().progress;
+            if board.state == BoardState::Won {
+                standing.won += 1;
+            }
+        }
+        if standing.played > 0 {
+            standing.progress /= standing.played as f64;
+        }
+        standing
+    }
+
+    fn game(&self, strategy: &dyn Strategy, seed: u64) -> (Board, usize) {
+        let board = BoardBuilder::new(self.width, self.height)
+            .mine_count(self.mines)
+            .adjacency(self.adjacency)
+            .seed(seed)
+            .build()
+            .unwrap();
+        play(strategy, board)
+    }
+
+    #[cfg(not(all(feature = "parallel", not(target_arch = "wasm32"))))]
+    fn games(&self, strategy: &dyn Strategy) -> Vec<(Board, usize)> {
+        self.seeds
+            .iter()
+            .map(|seed| self.game(strategy, *seed))
+            .collect()
+    }
+
+    // One board per core at a time; `collect` keeps them in seed order.
+    #[cfg(all(feature = "parallel", not(target_arch = "wasm32")))]
+    fn games(&self, strategy: &dyn Strategy) -> Vec<(Board, usize)> {
+        self.seeds
+            .par_iter()
+            .map(|seed| self.game(strategy, *seed))
+            .collect()
+    }
+}
+
+// Lets the strategy play until the game ends, it runs out of ideas or it
+// plays a move that doesn't change anything. Returns the final board and
+// how many moves it took.
+pub fn play(strategy: &dyn Strategy, mut board: Board) -> (Board, usize) {
+    let mut moves = 0;
+    while matches!(board.state, BoardState::Ready | BoardState::Playing) {
+        let next = strategy
+            .next_move(&BoardView::new(&board))
+            .and_then(|m| board.apply(&m));
+        match next {
+            Some(next) if next != board => board = next,
+            _ => break,
+        }
+        moves += 1;
+    }
+    (board, moves)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BasicSolver, Guessing};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_tournament_plays_every_seed() {
+        let tournament = Tournament {
+            width: 8,
+            height: 8,
+            mines: 10,
+            adjacency: Adjacency::Knight,
+            seeds: vec![1, 2, 3],
+        };
+        let standings = tournament.run(&[&BasicSolver, &Guessing(BasicSolver)]);
+        assert_eq!(standings.len(), 2);
+        assert_eq!(standings[0].name, "basic");
+        assert_eq!(standings[0].played, 3);
+        assert_eq!(standings[0].moves, 0);
+        assert!(standings[1].moves > 0);
+        assert_eq!(
+            standings,
+            tournament.run(&[&BasicSolver, &Guessing(BasicSolver)])
+        );
+    }
+
+    #[test]
+    fn test_play_finishes_large_boards() {
+        for mines in &[10, 500] {
+            let board = BoardBuilder::new(50, 50)
+                .mine_count(*mines)
+                .seed(7)
+                .build()
+                .unwrap();
+            let (board, _) = play(&Guessing(BasicSolver), board);
+            assert!(matches!(board.state, BoardState::Won | BoardState::Failed));
+        }
+    }
+}
@@ -0,0 +1,317 @@
+use crate::Adjacency;
+use crate::Board;
+use crate::Cells;
+use crate::MapElement::{Mine, Number};
+use crate::MapElementCellState::Closed;
+use crate::Point;
+
+// A way to move a board's cells around. Turns and flips keep every number,
+// as both kinds of adjacency look the same from any side.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Transform {
+    // Left to right.
+    MirrorX,
+    // Top to bottom.
+    MirrorY,
+    // Turned clockwise by a quarter, a half or three quarters.
+    Rotate90,
+    Rotate180,
+    Rotate270,
+    // Flipped over the diagonal from the top left corner, or the other one.
+    Transpose,
+    AntiTranspose,
+    // Slid by (dx, dy). Cells pushed past an edge are dropped, and the ones
+    // left behind are closed and safe.
+    Translate { dx: i32, dy: i32 },
+}
+
+impl Transform {
+    // Every turn and flip that doesn't leave the board as it was.
+    pub const SYMMETRIES: [Transform; 7] = [
+        Transform::MirrorX,
+        Transform::MirrorY,
+        Transform::Rotate90,
+        Transform::Rotate180,
+        Transform::Rotate270,
+        Transform::Transpose,
+        Transform::AntiTranspose,
+    ];
+
+    // The width and height a `width` by `height` board has afterwards.
+    pub fn size(self, width: usize, height: usize) -> (usize, usize) {
+        match self {
+            Transform::Rotate90
+            | Transform::Rotate270
+            | Transform::Transpose
+            | Transform::AntiTranspose => (height, width),
+            _ => (width, height),
+        }
+    }
+
+    // Where the cell at `p` of a `width` by `height` board ends up. None if
+    // it's slid off.
+    pub fn point(self, p: Point, width: usize, height: usize) -> Option<Point> {
+        let (w, h) = (width as i32, height as i32);
+        let (x, y) = match self {
+            Transform::MirrorX => (w - 1 - p.x, p.y),
+            Transform::MirrorY => (p.x, h - 1 - p.y),
+            Transform::Rotate90 => (h - 1 - p.y, p.x),
+            Transform::Rotate180 => (w - 1 - p.x, h - 1 - p.y),
+            Transform::Rotate270 => (p.y, w - 1 - p.x),
+            Transform::Transpose => (p.y, p.x),
+            Transform::AntiTranspose => (h - 1 - p.y, w - 1 - p.x),
+            Transform::Translate { dx, dy } => (p.x + dx, p.y + dy),
+        };
+        let (w, h) = self.size(width, height);
+        if x < 0 || y < 0 || x >= w as i32 || y >= h as i32 {
+            None
+        } else {
+            Some(Point { x, y })
+        }
+    }
+}
+
+impl Board {
+    // This board with its cells moved by `transform`, each keeping its state.
+    // The numbers are counted again, which only changes any after a slide.
+    // It's no longer the board its seed makes, so it has no seed.
+    pub fn transformed(&self, transform: Transform) -> Board {
+        let (width, height) = transform.size(self.width, self.height);
+        let mut map = Cells::new(width, height);
+        for p in self.points() {
+            let to = match transform.point(p, self.width, self.height) {
+                Some(to) => to,
+                None => continue,
+            };
+            let el = match self.at(&p) {
+                Some(Mine { state }) => Mine {
+                    state: state.clone(),
+                },
+                Some(Number { state, .. }) => Number {
+                    state: state.clone(),
+                    count: 0,
+                },
+                None => continue,
+            };
+            map.set(to.x as usize, to.y as usize, el);
+        }
+        let board = Board {
+            adjacency: self.adjacency,
+            cascade: self.cascade,
+            ..Board::from_map(map)
+        }
+        .with_numbers()
+        .settled();
+        // A lost game's cells don't show which mine went off.
+        Board {
+            state: self.state.clone(),
+            ..board
+        }
+    }
+
+    // The same for this board and every turn and flip of it, to find boards
+    // that are copies of each other.
+    pub fn canonical_hash(&self) -> u64 {
+        Transform::SYMMETRIES
+            .iter()
+            .map(|t| self.transformed(*t).position_hash())
+            .fold(self.position_hash(), u64::min)
+    }
+
+    // This board's mines and numbers with every cell closed, as it was
+    // before the first move.
+    pub fn layout(&self) -> Board {
+        let mut map = Cells::new(self.width, self.height);
+        for p in self.points() {
+            let el = match self.at(&p) {
+                Some(Mine { .. }) => Mine { state: Closed },
+                Some(Number { count, .. }) => Number {
+                    state: Closed,
+                    count: *count,
+                },
+                None => continue,
+            };
+            map.set(p.x as usize, p.y as usize, el);
+        }
+        Board {
+            adjacency: self.adjacency,
+            cascade: self.cascade,
+            ..Board::from_map(map)
+        }
+        .settled()
+    }
+
+    // Whether both boards have the same shape, adjacency and mines, however
+    // far each has been played.
+    pub fn layout_eq(&self, other: &Board) -> bool {
+        self.layout_key() == other.layout_key()
+    }
+
+    // The layout turned or flipped whichever way puts its mines earliest in
+    // reading order. Boards that are turns or flips of each other have the
+    // same one.
+    pub fn canonical_layout(&self) -> Board {
+        let layout = self.layout();
+        Transform::SYMMETRIES
+            .iter()
+            .map(|t| layout.transformed(*t))
+            .fold(layout.clone(), |best, b| {
+                if b.layout_key() < best.layout_key() {
+                    b
+                } else {
+                    best
+                }
+            })
+    }
+
+    // The shape, then the mines as (y, x) in reading order.
+    fn layout_key(&self) -> (usize, usize, u8, Vec<(i32, i32)>) {
+        let adjacency = match self.adjacency {
+            Adjacency::King => 0,
+            Adjacency::Knight => 1,
+        };
+        let mines = self
+            .points()
+            .filter(|p| matches!(self.at(p), Some(Mine { .. })))
+            .map(|p| (p.y, p.x))
+            .collect();
+        (self.width, self.height, adjacency, mines)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BoardBuilder;
+    use crate::MapElementCellState::{Closed, Flagged, Open};
+    use crate::Move;
+    use pretty_assertions::assert_eq;
+
+    // An L of mines in the top left corner of a 4x3 board, with the cell
+    // below it flagged and the bottom right one open.
+    fn corner() -> Board {
+        BoardBuilder::new(4, 3)
+            .mines_at(vec![Point::new(0, 0), Point::new(1, 0)])
+            .cell_state(Point::new(0, 1), Flagged)
+            .cell_state(Point::new(3, 2), Open)
+            .seed(1)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_turns_and_flips() {
+        let board = corner();
+        let mine = |b: &Board, x, y| matches!(b.at(&Point::new(x, y)), Some(Mine { .. }));
+        let turned = board.transformed(Transform::Rotate90);
+        assert_eq!((turned.width, turned.height), (3, 4));
+        assert!(mine(&turned, 2, 0) && mine(&turned, 2, 1));
+        assert_eq!(turned.at(&Point::new(1, 0)), board.at(&Point::new(0, 1)));
+        assert_eq!(turned.at(&Point::new(0, 3)), board.at(&Point::new(3, 2)));
+        assert_eq!(turned.seed, None);
+        let mirrored = board.transformed(Transform::MirrorX);
+        assert!(mine(&mirrored, 3, 0) && mine(&mirrored, 2, 0));
+        assert_eq!(mirrored.at(&Point::new(0, 2)), board.at(&Point::new(3, 2)));
+        for t in Transform::SYMMETRIES.iter() {
+            let moved = board.transformed(*t);
+            assert_eq!(moved.mines, board.mines);
+            assert_eq!(moved.state, board.state);
+            assert_eq!(moved.stats(), board.stats());
+        }
+    }
+
+    #[test]
+    fn test_turning_back() {
+        let board = Board {
+            seed: None,
+            ..corner()
+        };
+        let back = |t: Transform, u: Transform| board.transformed(t).transformed(u);
+        assert_eq!(back(Transform::Rotate90, Transform::Rotate270), board);
+        assert_eq!(back(Transform::Rotate180, Transform::Rotate180), board);
+        assert_eq!(back(Transform::MirrorX, Transform::MirrorX), board);
+        assert_eq!(back(Transform::Transpose, Transform::Transpose), board);
+        assert_eq!(
+            back(Transform::AntiTranspose, Transform::AntiTranspose),
+            board
+        );
+    }
+
+    #[test]
+    fn test_numbers_follow_the_mines() {
+        for adjacency in &[Adjacency::King, Adjacency::Knight] {
+            let board = BoardBuilder::new(7, 5)
+                .mine_count(8)
+                .adjacency(*adjacency)
+                .seed(4)
+                .build()
+                .unwrap();
+            for t in Transform::SYMMETRIES.iter() {
+                let moved = board.transformed(*t);
+                for p in board.points() {
+                    let to = t.point(p, board.width, board.height).unwrap();
+                    assert_eq!(moved.at(&to), board.at(&p));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_translate() {
+        let slid = corner().transformed(Transform::Translate { dx: 1, dy: 1 });
+        assert_eq!((slid.width, slid.height), (4, 3));
+        assert_eq!(slid.mines, 2);
+        assert_eq!(slid.at(&Point::new(1, 1)), Some(&Mine { state: Closed }));
+        // Counted again: a knight's move from the mine now at (2, 1).
+        assert_eq!(
+            slid.at(&Point::new(0, 0)),
+            Some(&Number {
+                state: Closed,
+                count: 1
+            })
+        );
+        let gone = corner().transformed(Transform::Translate { dx: 0, dy: 3 });
+        assert_eq!(gone.mines, 0);
+    }
+
+    #[test]
+    fn test_canonical_hash() {
+        let board = corner();
+        for t in Transform::SYMMETRIES.iter() {
+            assert_eq!(
+                board.transformed(*t).canonical_hash(),
+                board.canonical_hash()
+            );
+        }
+        let other = board.transformed(Transform::Translate { dx: 1, dy: 0 });
+        assert_ne!(other.canonical_hash(), board.canonical_hash());
+    }
+
+    #[test]
+    fn test_layout_eq() {
+        let board = corner();
+        let played = board.apply(&Move::Open(Point::new(2, 2))).unwrap();
+        assert!(played.layout_eq(&board));
+        assert_eq!(played.layout(), board.layout());
+        assert_eq!(board.layout().stats().closed, 12);
+        assert!(!board.layout_eq(&board.transformed(Transform::MirrorX)));
+        let king = Board {
+            adjacency: Adjacency::King,
+            ..board.clone()
+        };
+        assert!(!board.layout_eq(&king));
+    }
+
+    #[test]
+    fn test_canonical_layout() {
+        let board = corner();
+        let canonical = board.canonical_layout();
+        assert_eq!(canonical.stats().closed, 12);
+        assert_eq!(canonical.mines, 2);
+        for t in Transform::SYMMETRIES.iter() {
+            assert_eq!(board.transformed(*t).canonical_layout(), canonical);
+        }
+        let slid = board.transformed(Transform::Translate { dx: 1, dy: 0 });
+        assert!(!slid.canonical_layout().layout_eq(&canonical));
+    }
+}
@@ -0,0 +1,439 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use axum::extract::{Query, State};
+use axum::http::StatusCode;
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde_derive::{Deserialize, Serialize};
+use tower_http::cors::CorsLayer;
+
+pub use lib_minesweeper::{daily, Daily};
+use lib_minesweeper::{BoardState, Replay, Timing};
+
+const MAX_NAME_LEN: usize = 24;
+const DEFAULT_LIMIT: usize = 10;
+// Scores kept for each board and timing. Slower ones than these drop off.
+const MAX_SCORES: usize = 1_000;
+// How far a time can fall short of the replay's last stamp, for the
+// rounding and the moment between the winning move and the clock stopping.
+const TIME_SLACK_MS: f64 = 1_000_f64;
+
+pub fn today() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() / 86_400)
+        .unwrap_or(0)
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Submission {
+    pub name: String,
+    pub day: u64,
+    pub duration_ms: f64,
+    // Scores from before there was a choice are real time.
+    #[serde(default)]
+    pub timing: Timing,
+    pub replay: Replay,
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Score {
+    pub name: String,
+    pub duration_ms: f64,
+    pub moves: usize,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum SubmissionError {
+    FutureDay,
+    Corrupted,
+    WrongBoard,
+    Unplayable,
+    NotWon,
+    BadName,
+    BadDuration,
+    WrongDuration,
+}
+
+impl fmt::Display for SubmissionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SubmissionError::FutureDay => write!(f, "that day's board isn't out yet"),
+            SubmissionError::Corrupted => {
+                write!(
+                    f,
+                    "the replay doesn't match its checksum, so it was damaged"
+                )
+            }
+            SubmissionError::WrongBoard => write!(f, "the replay isn't of that day's board"),
+            SubmissionError::Unplayable => {
+                write!(f, "the replay has moves off the board or no board at all")
+            }
+            SubmissionError::NotWon => write!(f, "the replay doesn't end in a win"),
+            SubmissionError::BadName => {
+                write!(f, "names need between 1 and {} characters", MAX_NAME_LEN)
+            }
+            SubmissionError::BadDuration => write!(f, "the time isn't a positive number"),
+            SubmissionError::WrongDuration => {
+                write!(f, "the time is shorter than the replay's own timings")
+            }
+        }
+    }
+}
+
+impl std::error::Error for SubmissionError {}
+
+// Best times per daily board and timing, fastest first.
+#[derive(Debug, Default)]
+pub struct Leaderboards {
+    days: HashMap<(u64, Timing), Vec<Score>>,
+}
+
+impl Leaderboards {
+    // Checks the replay arrived whole, then plays it back on the day's board
+    // and only accepts it if it wins, in no less time than its own stamps
+    // allow. The checksum only guards against damage on the way, as anyone
+    // can recompute it: the playback is what's trusted. Returns the score's
+    // rank, starting from 1.
+    pub fn submit(&mut self, submission: Submission) -> Result<usize, SubmissionError> {
+        let Submission {
+            name,
+            day,
+            duration_ms,
+            timing,
+            replay,
+        } = submission;
+        let name = name.trim().to_string();
+        if name.is_empty() || name.chars().count() > MAX_NAME_LEN {
+            return Err(SubmissionError::BadName);
+        }
+        if !(duration_ms.is_finite() && duration_ms > 0_f64) {
+            return Err(SubmissionError::BadDuration);
+        }
+        if !replay.is_sealed() {
+            return Err(SubmissionError::Corrupted);
+        }
+        if !daily(day).is_board_of(&replay) {
+            return Err(SubmissionError::WrongBoard);
+        }
+        if !replay.is_playable() {
+            return Err(SubmissionError::Unplayable);
+        }
+        if replay
+            .final_board()
+            .is_none_or(|board| board.state != BoardState::Won)
+        {
+            return Err(SubmissionError::NotWon);
+        }
+        // Every move is stamped with the game time it was made at, which
+        // no time on either clock can be shorter than.
+        let stamped = replay.times.windows(2).all(|t| t[0] <= t[1])
+            && replay.times.iter().all(|t| t.is_finite() && *t >= 0_f64);
+        match replay.times.last() {
+            Some(last) if replay.is_timed() && stamped && duration_ms + TIME_SLACK_MS >= *last => {}
+            _ => return Err(SubmissionError::WrongDuration),
+        }
+
+        let scores = self.days.entry((day, timing)).or_default();
+        let rank = scores
+            .iter()
+            .take_while(|s| s.duration_ms <= duration_ms)
+            .count();
+        scores.insert(
+            rank,
+            Score {
+                name,
+                duration_ms,
+                moves: replay.moves.len(),
+            },
+        );
+        scores.truncate(MAX_SCORES);
+        Ok(rank + 1)
+    }
+
+    pub fn top(&self, day: u64, timing: Timing, limit: usize) -> Vec<Score> {
+        self.days
+            .get(&(day, timing))
+            .map(|scores| scores.iter().take(limit).cloned().collect())
+            .unwrap_or_default()
+    }
+}
+
+type Shared = Arc<Mutex<Leaderboards>>;
+
+// Nothing's left half changed by a panic while the lock is held, as
+// scores are only added once they've been checked, so a poisoned lock is
+// still safe to use.
+fn lock(leaderboards: &Shared) -> MutexGuard<'_, Leaderboards> {
+    leaderboards.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+pub fn app() -> Router {
+    Router::new()
+        .route("/daily", get(get_daily))
+        .route("/scores", post(post_score))
+        .route("/leaderboard", get(get_leaderboard))
+        .layer(CorsLayer::permissive())
+        .with_state(Shared::default())
+}
+
+#[derive(Debug, Deserialize)]
+struct DayQuery {
+    day: Option<u64>,
+    timing: Option<Timing>,
+    limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+struct Rank {
+    rank: usize,
+}
+
+async fn get_daily(Query(query): Query<DayQuery>) -> Json<Daily> {
+    Json(daily(query.day.unwrap_or_else(today)))
+}
+
+async fn post_score(
+    State(leaderboards): State<Shared>,
+    Json(submission): Json<Submission>,
+) -> Result<Json<Rank>, (StatusCode, String)> {
+    if submission.day > today() {
+        let e = SubmissionError::FutureDay;
+        return Err((StatusCode::UNPROCESSABLE_ENTITY, e.to_string()));
+    }
+    match lock(&leaderboards).submit(submission) {
+        Ok(rank) => Ok(Json(Rank { rank })),
+        Err(e) => Err((StatusCode::UNPROCESSABLE_ENTITY, e.to_string())),
+    }
+}
+
+async fn get_leaderboard(
+    State(leaderboards): State<Shared>,
+    Query(query): Query<DayQuery>,
+) -> Json<Vec<Score>> {
+    let day = query.day.unwrap_or_else(today);
+    let timing = query.timing.unwrap_or_default();
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT);
+    Json(lock(&leaderboards).top(day, timing, limit))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lib_minesweeper::MapElement::Number;
+    use lib_minesweeper::MapElementCellState::Closed;
+    use lib_minesweeper::{Move, Point};
+    use pretty_assertions::assert_eq;
+
+    fn daily_replay(day: u64) -> Replay {
+        let mut replay = Replay::for_board(&daily(day).board()).unwrap();
+        replay.seal();
+        replay
+    }
+
+    fn won_replay(day: u64) -> Replay {
+        let mut replay = daily_replay(day);
+        let mut board = replay.initial_board().unwrap();
+        for y in 0..board.height {
+            for x in 0..board.width {
+                let p = Point::new(x, y);
+                if let Some(Number { state: Closed, .. }) = board.at(&p) {
+                    board = board.apply(&Move::Open(p)).unwrap();
+                    replay.record(Move::Open(p));
+                    replay.time_last(replay.moves.len() as f64 * 100_f64);
+                }
+            }
+        }
+        replay.seal();
+        replay
+    }
+
+    fn submission(name: &str, duration_ms: f64, replay: Replay) -> Submission {
+        Submission {
+            name: name.to_string(),
+            day: 3,
+            duration_ms,
+            timing: Timing::RealTime,
+            replay,
+        }
+    }
+
+    #[test]
+    fn test_daily_is_stable_and_changes_daily() {
+        assert_eq!(daily(3), daily(3));
+        assert!(daily(3).seed != daily(4).seed);
+    }
+
+    #[test]
+    fn test_submit_ranks_wins() {
+        let mut leaderboards = Leaderboards::default();
+        assert_eq!(
+            leaderboards.submit(submission("ana", 50_000_f64, won_replay(3))),
+            Ok(1)
+        );
+        assert_eq!(
+            leaderboards.submit(submission("bo", 40_000_f64, won_replay(3))),
+            Ok(1)
+        );
+        assert_eq!(
+            leaderboards.submit(submission("cy", 60_000_f64, won_replay(3))),
+            Ok(3)
+        );
+        let names: Vec<String> = leaderboards
+            .top(3, Timing::RealTime, 2)
+            .into_iter()
+            .map(|s| s.name)
+            .collect();
+        assert_eq!(names, vec!["bo", "ana"]);
+        assert_eq!(leaderboards.top(4, Timing::RealTime, 10), vec![]);
+    }
+
+    #[test]
+    fn test_timings_rank_separately() {
+        let mut leaderboards = Leaderboards::default();
+        assert_eq!(
+            leaderboards.submit(submission("ana", 50_000_f64, won_replay(3))),
+            Ok(1)
+        );
+        let game_time = Submission {
+            timing: Timing::GameTime,
+            ..submission("bo", 60_000_f64, won_replay(3))
+        };
+        assert_eq!(leaderboards.submit(game_time), Ok(1));
+        let names = |timing| -> Vec<String> {
+            leaderboards
+                .top(3, timing, 10)
+                .into_iter()
+                .map(|s| s.name)
+                .collect()
+        };
+        assert_eq!(names(Timing::RealTime), vec!["ana"]);
+        assert_eq!(names(Timing::GameTime), vec!["bo"]);
+    }
+
+    #[test]
+    fn test_submit_rejects_bad_submissions() {
+        let mut leaderboards = Leaderboards::default();
+        assert_eq!(
+            leaderboards.submit(submission("ana", 1_f64, daily_replay(3))),
+            Err(SubmissionError::NotWon)
+        );
+        assert_eq!(
+            leaderboards.submit(submission("ana", 1_f64, won_replay(4))),
+            Err(SubmissionError::WrongBoard)
+        );
+        assert_eq!(
+            leaderboards.submit(submission(" ", 1_f64, won_replay(3))),
+            Err(SubmissionError::BadName)
+        );
+        assert_eq!(
+            leaderboards.submit(submission("ana", -1_f64, won_replay(3))),
+            Err(SubmissionError::BadDuration)
+        );
+        let mut edited = won_replay(3);
+        edited.moves.pop();
+        assert_eq!(
+            leaderboards.submit(submission("ana", 1_f64, edited)),
+            Err(SubmissionError::Corrupted)
+        );
+        let mut unsealed = won_replay(3);
+        unsealed.checksum = None;
+        assert_eq!(
+            leaderboards.submit(submission("ana", 1_f64, unsealed)),
+            Err(SubmissionError::Corrupted)
+        );
+        assert_eq!(leaderboards.top(3, Timing::RealTime, 10), vec![]);
+    }
+
+    #[test]
+    fn test_resealing_an_edit_is_caught_by_playback() {
+        // Anyone can seal an edited replay again, so it gets as far as the
+        // playback, which is what turns it down.
+        let mut leaderboards = Leaderboards::default();
+        let mut edited = won_replay(3);
+        edited.moves.pop();
+        edited.times.pop();
+        edited.seal();
+        assert_eq!(
+            leaderboards.submit(submission("ana", 50_000_f64, edited)),
+            Err(SubmissionError::NotWon)
+        );
+    }
+
+    #[test]
+    fn test_submit_rejects_unplayable_replays() {
+        let mut leaderboards = Leaderboards::default();
+        let mut off_board = won_replay(3);
+        off_board.record(Move::Open(Point { x: -1, y: 500 }));
+        off_board.time_last(100_000_f64);
+        off_board.seal();
+        assert_eq!(
+            leaderboards.submit(submission("ana", 200_000_f64, off_board)),
+            Err(SubmissionError::Unplayable)
+        );
+        let mut too_many_mines = won_replay(3);
+        too_many_mines.mines = usize::MAX;
+        too_many_mines.seal();
+        assert_eq!(
+            leaderboards.submit(submission("ana", 200_000_f64, too_many_mines)),
+            Err(SubmissionError::WrongBoard)
+        );
+    }
+
+    #[test]
+    fn test_submit_checks_duration_against_times() {
+        let mut leaderboards = Leaderboards::default();
+        let replay = won_replay(3);
+        let last = *replay.times.last().unwrap();
+        assert_eq!(
+            leaderboards.submit(submission(
+                "ana",
+                last - TIME_SLACK_MS - 1_f64,
+                replay.clone()
+            )),
+            Err(SubmissionError::WrongDuration)
+        );
+        let mut untimed = replay.clone();
+        untimed.times.clear();
+        untimed.seal();
+        assert_eq!(
+            leaderboards.submit(submission("ana", last, untimed)),
+            Err(SubmissionError::WrongDuration)
+        );
+        assert_eq!(leaderboards.submit(submission("ana", last, replay)), Ok(1));
+    }
+
+    #[test]
+    fn test_scores_are_capped() {
+        let mut leaderboards = Leaderboards::default();
+        let replay = won_replay(3);
+        for i in 0..MAX_SCORES + 5 {
+            let duration_ms = 100_000_f64 + i as f64;
+            leaderboards
+                .submit(submission("ana", duration_ms, replay.clone()))
+                .unwrap();
+        }
+        let scores = leaderboards.top(3, Timing::RealTime, usize::MAX);
+        assert_eq!(scores.len(), MAX_SCORES);
+        assert_eq!(scores[0].duration_ms, 100_000_f64);
+    }
+
+    #[test]
+    fn test_poisoned_lock_still_serves() {
+        let shared = Shared::default();
+        let poisoner = shared.clone();
+        let _ = std::thread::spawn(move || {
+            let _guard = poisoner.lock().unwrap();
+            panic!("poison");
+        })
+        .join();
+        assert!(shared.is_poisoned());
+        assert_eq!(
+            lock(&shared).submit(submission("ana", 50_000_f64, won_replay(3))),
+            Ok(1)
+        );
+    }
+}
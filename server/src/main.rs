@@ -0,0 +1,13 @@
+use std::env;
+
+// Listens on MINESWEEPER_SERVER_ADDR, or localhost:3000 if it isn't set.
+#[tokio::main]
+async fn main() {
+    let addr =
+        env::var("MINESWEEPER_SERVER_ADDR").unwrap_or_else(|_| String::from("127.0.0.1:3000"));
+    let listener = tokio::net::TcpListener::bind(&addr).await.unwrap();
+    println!("Serving daily boards on {}", addr);
+    axum::serve(listener, minesweeper_server::app())
+        .await
+        .unwrap();
+}
@@ -0,0 +1,41 @@
+// Adaptive difficulty keeps new boards' mine density where the player wins
+// about half the time, between these bounds.
+const MIN_DENSITY: f64 = 0.08;
+const MAX_DENSITY: f64 = 0.25;
+// How far one game moves the density.
+const STEP: f64 = 0.01;
+// The games the win rate is taken over, and the rate it aims for.
+pub const RECENT: usize = 10;
+const TARGET: f64 = 0.5;
+
+pub fn win_rate(recent: &[bool]) -> Option<f64> {
+    if recent.is_empty() {
+        return None;
+    }
+    Some(recent.iter().filter(|won| **won).count() as f64 / recent.len() as f64)
+}
+
+// Up a step after a win while the player's been winning more than the
+// target, down a step after a loss while they've been winning less. A lucky
+// win in a bad run, or a slip in a good one, leaves it be. `recent` includes
+// the game just played.
+pub fn nudge(density: f64, won: bool, recent: &[bool]) -> f64 {
+    let rate = win_rate(recent).unwrap_or(TARGET);
+    let density = match won {
+        true if rate > TARGET => density + STEP,
+        false if rate < TARGET => density - STEP,
+        _ => density,
+    };
+    density.clamp(MIN_DENSITY, MAX_DENSITY)
+}
+
+// Where the density starts from on a board of this size with this many
+// mines, within bounds.
+pub fn density(width: usize, height: usize, mines: usize) -> f64 {
+    (mines as f64 / (width * height) as f64).clamp(MIN_DENSITY, MAX_DENSITY)
+}
+
+// Always at least one mine.
+pub fn mines(width: usize, height: usize, density: f64) -> usize {
+    ((width * height) as f64 * density).round().max(1_f64) as usize
+}
@@ -0,0 +1,141 @@
+use lib_minesweeper::Board;
+use lib_minesweeper::CellSpec;
+use lib_minesweeper::MapElement::{Mine, Number};
+use lib_minesweeper::MapElementCellState::{Closed, Flagged, Open};
+use lib_minesweeper::Point;
+use yew::prelude::*;
+
+// What a click on the analysis board does to a cell.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Tool {
+    Open,
+    Flag,
+    Mine,
+}
+
+impl Tool {
+    pub fn next(self) -> Tool {
+        match self {
+            Tool::Open => Tool::Flag,
+            Tool::Flag => Tool::Mine,
+            Tool::Mine => Tool::Open,
+        }
+    }
+
+    pub fn emoji(self) -> &'static str {
+        match self {
+            Tool::Open => "👆",
+            Tool::Flag => "🚩",
+            Tool::Mine => "💣",
+        }
+    }
+
+    pub fn describe(self) -> &'static str {
+        match self {
+            Tool::Open => "Clicks open and close cells",
+            Tool::Flag => "Clicks flag and unflag cells",
+            Tool::Mine => "Clicks put down and take away mines",
+        }
+    }
+}
+
+// A position to take apart, like a chess analysis board: every mine shows,
+// cells can be opened, closed, flagged and mined at will, and 🤖 can be
+// asked for its next move or for each closed cell's odds. Nothing done here
+// is a game.
+pub struct Analysis {
+    pub board: Board,
+    pub tool: Tool,
+    // Each closed cell's odds of being a mine once asked for, and whether
+    // they're exact.
+    pub odds: Option<(bool, Vec<(Point, f64)>)>,
+    // What 🤖 last said about the position.
+    pub note: Option<String>,
+}
+
+impl Analysis {
+    pub fn new(board: Board) -> Analysis {
+        Analysis {
+            board,
+            tool: Tool::Open,
+            odds: None,
+            note: None,
+        }
+    }
+
+    // Changes the cell at `p` the way the tool does, and returns whether
+    // that changed anything. Open cells can't be flagged.
+    pub fn edit(&mut self, p: &Point) -> bool {
+        let cell = match (self.tool, self.board.at(p)) {
+            (_, None) => return false,
+            (Tool::Open, Some(Number { state: Open, .. })) => CellSpec::Safe(Closed),
+            (Tool::Open, Some(Number { .. })) => CellSpec::Safe(Open),
+            (Tool::Open, Some(Mine { state: Open })) => CellSpec::Mine(Closed),
+            (Tool::Open, Some(Mine { .. })) => CellSpec::Mine(Open),
+            (Tool::Flag, Some(Number { state: Closed, .. })) => CellSpec::Safe(Flagged),
+            (Tool::Flag, Some(Number { state: Flagged, .. })) => CellSpec::Safe(Closed),
+            (Tool::Flag, Some(Mine { state: Closed })) => CellSpec::Mine(Flagged),
+            (Tool::Flag, Some(Mine { state: Flagged })) => CellSpec::Mine(Closed),
+            (Tool::Flag, Some(_)) => return false,
+            (Tool::Mine, Some(Mine { state })) => CellSpec::Safe(state.clone()),
+            (Tool::Mine, Some(Number { state, .. })) => CellSpec::Mine(state.clone()),
+        };
+        match self.board.edit(p, cell) {
+            Some(board) => {
+                self.set_board(board);
+                true
+            }
+            None => false,
+        }
+    }
+
+    // Whatever 🤖 said no longer holds for the new position.
+    pub fn set_board(&mut self, board: Board) {
+        self.board = board;
+        self.odds = None;
+        self.note = None;
+    }
+}
+
+// A dot on every mine that isn't open, and once they've been asked for, the
+// odds of each closed cell as a percentage, drawn over the board the way
+// the notes are.
+pub fn render_overlay(analysis: &Analysis) -> Html {
+    let board = &analysis.board;
+    let mines: Vec<Point> = (0..board.height)
+        .flat_map(|y| (0..board.width).map(move |x| Point::new(x, y)))
+        .filter(|p| {
+            matches!(
+                board.at(p),
+                Some(Mine {
+                    state: Closed | Flagged
+                })
+            )
+        })
+        .collect();
+    let odds = analysis.odds.as_ref().map_or(&[][..], |(_, odds)| odds);
+    html! {
+        <svg
+         id="analysis-overlay"
+         viewBox=format!("0 0 {} {}", board.width, board.height)
+         preserveAspectRatio="none">
+            {
+                for mines.iter().map(|p| html! {
+                    <circle
+                     cx=(p.x as f64 + 0.5).to_string()
+                     cy=(p.y as f64 + 0.5).to_string()
+                     r="0.15" />
+                })
+            }
+            {
+                for odds.iter().map(|(p, odds)| html! {
+                    <text
+                     x=(p.x as f64 + 0.5).to_string()
+                     y=(p.y as f64 + 0.9).to_string()>
+                        { format!("{:.0}", odds * 100_f64) }
+                    </text>
+                })
+            }
+        </svg>
+    }
+}
@@ -0,0 +1,56 @@
+use serde_json::{Map, Value};
+
+use crate::history::{self, History};
+use crate::profiles::{self, Profiles};
+use crate::storage::{InMemory, StorageBackend};
+
+// The profiles, their stats and the game history, as one file to carry to
+// another browser. Each is saved under its storage key just as it is in
+// storage, version and all, so older backups go through the same migrations
+// as older saves.
+pub fn export(profiles: &Profiles, history: &History) -> String {
+    let mut storage = InMemory::default();
+    profiles.store(&mut storage);
+    history.store(&mut storage);
+    let mut backup = Map::new();
+    for key in &[profiles::KEY, history::KEY] {
+        let saved = storage
+            .restore(key)
+            .and_then(|v| serde_json::from_str(&v).ok());
+        backup.insert(key.to_string(), saved.unwrap_or(Value::Null));
+    }
+    Value::Object(backup).to_string()
+}
+
+pub struct Backup {
+    pub profiles: Profiles,
+    pub history: History,
+}
+
+// None unless `text` is a file `export` wrote. An error if its history
+// doesn't load, e.g. a replay with more than MAX_REPLAY_MOVES moves, rather
+// than merging the profiles without it.
+pub fn parse(text: &str) -> Option<Result<Backup, String>> {
+    let backup: Map<String, Value> = serde_json::from_str(text).ok()?;
+    if !backup.contains_key(profiles::KEY) {
+        return None;
+    }
+    let has_history = backup.get(history::KEY).is_some_and(|h| !h.is_null());
+    let mut storage = InMemory::default();
+    for (key, value) in backup {
+        storage.store(&key, value.to_string());
+    }
+    let history = match History::try_restore(&storage) {
+        Some(history) => history,
+        None if has_history => {
+            return Some(Err(String::from(
+                "That backup's game history is damaged or too big to load",
+            )))
+        }
+        None => History::default(),
+    };
+    Some(Ok(Backup {
+        profiles: Profiles::restore(&storage),
+        history,
+    }))
+}
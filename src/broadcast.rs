@@ -0,0 +1,72 @@
+use yew::callback::Callback;
+use yew::format::Text;
+use yew::services::websocket::{WebSocketService, WebSocketStatus, WebSocketTask};
+
+use crate::protocol::PeerMessage;
+
+// How often a broadcaster resends the whole game for late joiners.
+pub const CHECKPOINT_EVERY: usize = 10;
+
+pub enum BroadcastEvent {
+    Opened,
+    Received { message: PeerMessage },
+    // Something on the topic that isn't one of our messages.
+    Unreadable,
+    Closed,
+}
+
+// A connection to a topic on the broadcast relay. The relay is any WebSocket
+// server that passes every text frame sent to `{endpoint}/{topic}` on to
+// everyone else connected to the same topic; self-hosters point the app at
+// one with <meta name="minesweeper-broadcast-endpoint" content="wss://...">.
+pub struct Broadcast {
+    pub topic: String,
+    pub watching: bool,
+    task: WebSocketTask,
+}
+
+impl Broadcast {
+    pub fn start(events: Callback<BroadcastEvent>) -> Result<Broadcast, String> {
+        let topic = format!("{:08x}", rand::random::<u32>());
+        Broadcast::connect(topic, false, events)
+    }
+
+    pub fn watch(topic: String, events: Callback<BroadcastEvent>) -> Result<Broadcast, String> {
+        Broadcast::connect(topic, true, events)
+    }
+
+    fn connect(
+        topic: String,
+        watching: bool,
+        events: Callback<BroadcastEvent>,
+    ) -> Result<Broadcast, String> {
+        let endpoint = crate::page_setting("minesweeper-broadcast-endpoint")
+            .ok_or_else(|| String::from("Broadcasting isn't set up on this site"))?;
+        let url = format!("{}/{}", endpoint.trim_end_matches('/'), topic);
+        let received =
+            events.reform(
+                |text: Text| match text.ok().and_then(PeerMessage::from_text) {
+                    Some(message) => BroadcastEvent::Received { message },
+                    None => BroadcastEvent::Unreadable,
+                },
+            );
+        let status = events.reform(|status| match status {
+            WebSocketStatus::Opened => BroadcastEvent::Opened,
+            WebSocketStatus::Closed | WebSocketStatus::Error => BroadcastEvent::Closed,
+        });
+        let task = WebSocketService::new()
+            .connect_text(&url, received, status)
+            .map_err(String::from)?;
+        Ok(Broadcast {
+            topic,
+            watching,
+            task,
+        })
+    }
+
+    pub fn send(&mut self, message: &PeerMessage) {
+        if let Some(text) = message.to_text() {
+            self.task.send(Ok(text));
+        }
+    }
+}
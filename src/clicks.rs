@@ -0,0 +1,39 @@
+use serde_derive::{Deserialize, Serialize};
+
+// What the player's clicks on the board did over a game. Moves played by
+// the robot or an assist aren't clicks.
+#[derive(Serialize, Deserialize, Clone, Copy, Default)]
+pub struct Clicks {
+    pub clicks: u32,
+    // Clicks that didn't change the board, like digging an open cell.
+    pub wasted: u32,
+    pub chords: u32,
+}
+
+impl Clicks {
+    pub fn record(&mut self, chord: bool, changed: bool) {
+        self.clicks += 1;
+        if chord {
+            self.chords += 1;
+        }
+        if !changed {
+            self.wasted += 1;
+        }
+    }
+
+    pub fn add(&mut self, other: &Clicks) {
+        self.clicks += other.clicks;
+        self.wasted += other.wasted;
+        self.chords += other.chords;
+    }
+
+    // The board's 3BV over the clicks made. Clearing it with exactly the
+    // clicks it needs scores 1; chording can beat that.
+    pub fn efficiency(&self, three_bv: u64) -> Option<f64> {
+        if self.clicks == 0 {
+            None
+        } else {
+            Some(three_bv as f64 / self.clicks as f64)
+        }
+    }
+}
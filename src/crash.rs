@@ -0,0 +1,90 @@
+use std::cell::RefCell;
+use std::panic::{self, PanicHookInfo};
+
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys::{Document, HtmlElement, HtmlTextAreaElement};
+use yew::format::{Json, Text};
+
+use crate::debug_log;
+use crate::State;
+
+thread_local! {
+    static LAST_STATE: RefCell<Option<State>> = const { RefCell::new(None) };
+}
+
+// Keeps a copy of the state to put in the report if the app panics later on.
+pub fn remember(state: &State) {
+    LAST_STATE.with(|s| *s.borrow_mut() = Some(state.clone()));
+}
+
+// Panics abort the wasm module, which would otherwise leave a frozen page
+// behind. Instead, cover it with an error screen holding everything needed to
+// reproduce the problem.
+pub fn install_hook() {
+    panic::set_hook(Box::new(|info| {
+        #[cfg(feature = "console_error_panic_hook")]
+        console_error_panic_hook::hook(info);
+        let _ = show_error_screen(&report(info));
+    }));
+}
+
+fn report(info: &PanicHookInfo) -> String {
+    let state = LAST_STATE
+        .with(|s| {
+            let state = s.try_borrow().ok()?;
+            let text: Text = Json(state.as_ref()?).into();
+            text.ok()
+        })
+        .unwrap_or_else(|| String::from("null"));
+    format!(
+        "{}\n\nState:\n{}\n\nLog:\n{}\n",
+        info,
+        state,
+        debug_log::dump()
+    )
+}
+
+fn show_error_screen(report: &str) -> Result<(), JsValue> {
+    let document = web_sys::window()
+        .and_then(|w| w.document())
+        .ok_or_else(|| JsValue::from_str("no document"))?;
+    let body = document
+        .body()
+        .ok_or_else(|| JsValue::from_str("no body"))?;
+    // Only the first panic gets a screen; later ones come from the same mess.
+    if document.get_element_by_id("crash_placeholder").is_some() {
+        return Ok(());
+    }
+    let screen = element(&document, "div")?;
+    screen.set_id("crash_placeholder");
+    let title = element(&document, "p")?;
+    title.set_inner_text("💥 Something went wrong. Please reload the page.");
+    let hint = element(&document, "p")?;
+    hint.set_inner_text("Sending us the report below with a bug report helps us fix it.");
+    let text: HtmlTextAreaElement = document.create_element("textarea")?.unchecked_into();
+    text.set_read_only(true);
+    text.set_value(report);
+    let copy = element(&document, "div")?;
+    copy.set_class_name("clickable item");
+    copy.set_inner_text("📋");
+    copy.set_title("Copy report");
+    let report = report.to_string();
+    let on_copy = Closure::wrap(Box::new(move || {
+        if let Some(window) = web_sys::window() {
+            let _ = window.navigator().clipboard().write_text(&report);
+        }
+    }) as Box<dyn FnMut()>);
+    copy.set_onclick(Some(on_copy.as_ref().unchecked_ref()));
+    // The screen stays up until the page is reloaded.
+    on_copy.forget();
+    for child in &[&title, &hint, text.unchecked_ref(), &copy] {
+        screen.append_child(child)?;
+    }
+    body.append_child(&screen)?;
+    Ok(())
+}
+
+fn element(document: &Document, tag: &str) -> Result<HtmlElement, JsValue> {
+    Ok(document.create_element(tag)?.unchecked_into())
+}
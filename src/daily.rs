@@ -0,0 +1,50 @@
+use std::time::Duration;
+
+use js_sys::Date;
+use wasm_bindgen_futures::{spawn_local, JsFuture};
+use web_sys::{Notification, NotificationOptions, NotificationPermission};
+use yew::callback::Callback;
+
+const DAY_MS: f64 = 86_400_000_f64;
+
+// The number of days since the epoch, in UTC, as the scores server counts
+// them, so a new daily board comes out at the same moment for everyone.
+pub fn today() -> u64 {
+    (Date::now() / DAY_MS) as u64
+}
+
+pub fn until_tomorrow() -> Duration {
+    let now = Date::now();
+    let tomorrow = (today() + 1) as f64 * DAY_MS;
+    Duration::from_millis((tomorrow - now).max(0_f64) as u64)
+}
+
+pub fn reminders_allowed() -> bool {
+    Notification::permission() == NotificationPermission::Granted
+}
+
+// Asks the browser for permission to show notifications, then calls
+// `answered` with whether it was given.
+pub fn ask_permission(answered: Callback<bool>) {
+    if reminders_allowed() {
+        return answered.emit(true);
+    }
+    let promise = match Notification::request_permission() {
+        Ok(promise) => promise,
+        Err(_) => return answered.emit(false),
+    };
+    spawn_local(async move {
+        let permission = JsFuture::from(promise).await.ok();
+        answered.emit(permission.and_then(|p| p.as_string()).as_deref() == Some("granted"));
+    });
+}
+
+pub fn remind(streak: u32) {
+    let options = NotificationOptions::new();
+    options.set_body(&format!(
+        "Play today's board to keep your 🔥 {} streak going",
+        streak
+    ));
+    options.set_tag("daily");
+    let _ = Notification::new_with_options("A new daily board is out", &options);
+}
@@ -0,0 +1,60 @@
+use std::cell::RefCell;
+use std::collections::VecDeque;
+
+use js_sys::Date;
+use serde_derive::Serialize;
+use yew::format::{Json, Text};
+use yew::services::ConsoleService;
+
+// Enough for a few games on the biggest boards.
+const CAPACITY: usize = 2000;
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub enum Kind {
+    Move,
+    State,
+    Solver,
+}
+
+#[derive(Serialize)]
+struct Entry {
+    at: f64,
+    kind: Kind,
+    message: String,
+}
+
+thread_local! {
+    static ENTRIES: RefCell<VecDeque<Entry>> = const { RefCell::new(VecDeque::new()) };
+}
+
+// Logs to the console and keeps the latest entries around, so they can be
+// attached to bug reports.
+pub fn record(kind: Kind, message: String) {
+    ConsoleService::new().log(&format!("[{:?}] {}", kind, message));
+    ENTRIES.with(|entries| {
+        let mut entries = entries.borrow_mut();
+        if entries.len() == CAPACITY {
+            entries.pop_front();
+        }
+        entries.push_back(Entry {
+            at: Date::now(),
+            kind,
+            message,
+        });
+    });
+}
+
+// One JSON object per line, oldest first.
+pub fn dump() -> String {
+    ENTRIES.with(|entries| {
+        entries
+            .borrow()
+            .iter()
+            .filter_map(|entry| {
+                let text: Text = Json(entry).into();
+                text.ok()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    })
+}
@@ -0,0 +1,237 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use js_sys::{Array, Object, Reflect};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::{spawn_local, JsFuture};
+use web_sys::{
+    MessageEvent, RtcConfiguration, RtcDataChannel, RtcDataChannelEvent, RtcPeerConnection,
+    RtcPeerConnectionIceEvent, RtcSdpType, RtcSessionDescriptionInit,
+};
+use yew::callback::Callback;
+
+use crate::protocol::PeerMessage;
+
+const STUN_SERVER: &str = "stun:stun.l.google.com:19302";
+
+pub enum DuelEvent {
+    // Our half of the handshake is ready to be handed to the other player.
+    Signal { signal: String },
+    Connected,
+    Received { message: PeerMessage },
+    Closed,
+    Failed { reason: String },
+}
+
+type Handler = Closure<dyn FnMut(JsValue)>;
+
+// A WebRTC data channel to one other browser. Signalling is done by hand:
+// the host gets an invite to send to the guest, the guest answers with a
+// reply to send back, and once the host pastes the reply the channel opens.
+// ICE candidates are gathered up front so each side only copies once.
+pub struct Duel {
+    host: bool,
+    peer: RtcPeerConnection,
+    channel: Rc<RefCell<Option<RtcDataChannel>>>,
+    handlers: Rc<RefCell<Vec<Handler>>>,
+}
+
+impl Duel {
+    pub fn host(events: Callback<DuelEvent>) -> Result<Duel, JsValue> {
+        let duel = Duel::new(true, events.clone())?;
+        let channel = duel.peer.create_data_channel("duel");
+        attach(&channel, &duel.channel, &duel.handlers, &events);
+
+        let peer = duel.peer.clone();
+        spawn_local(async move {
+            let offer = async {
+                let offer = JsFuture::from(peer.create_offer()).await?;
+                set_local(&peer, RtcSdpType::Offer, &offer).await
+            };
+            if let Err(e) = offer.await {
+                events.emit(failed(e));
+            }
+        });
+        Ok(duel)
+    }
+
+    pub fn join(invite: &str, events: Callback<DuelEvent>) -> Result<Duel, JsValue> {
+        let duel = Duel::new(false, events.clone())?;
+        let sdp = decode(invite)?;
+
+        let peer = duel.peer.clone();
+        spawn_local(async move {
+            let answer = async {
+                let offer = RtcSessionDescriptionInit::new(RtcSdpType::Offer);
+                offer.set_sdp(&sdp);
+                JsFuture::from(peer.set_remote_description(&offer)).await?;
+                let answer = JsFuture::from(peer.create_answer()).await?;
+                set_local(&peer, RtcSdpType::Answer, &answer).await
+            };
+            if let Err(e) = answer.await {
+                events.emit(failed(e));
+            }
+        });
+        Ok(duel)
+    }
+
+    fn new(host: bool, events: Callback<DuelEvent>) -> Result<Duel, JsValue> {
+        let server = Object::new();
+        Reflect::set(&server, &"urls".into(), &STUN_SERVER.into())?;
+        let config = RtcConfiguration::new();
+        config.set_ice_servers(&Array::of1(&server));
+        let peer = RtcPeerConnection::new_with_configuration(&config)?;
+
+        let duel = Duel {
+            host,
+            peer,
+            channel: Rc::new(RefCell::new(None)),
+            handlers: Rc::new(RefCell::new(vec![])),
+        };
+
+        // A null candidate means gathering is done, so the local description
+        // now lists every candidate and can be sent in one go.
+        let peer = duel.peer.clone();
+        let signal_events = events.clone();
+        duel.listen(
+            move |e| {
+                let e: RtcPeerConnectionIceEvent = e.unchecked_into();
+                if e.candidate().is_some() {
+                    return;
+                }
+                let sdp = peer.local_description().map(|d| d.sdp());
+                match sdp.map(|sdp| encode(&sdp)) {
+                    Some(Ok(signal)) => signal_events.emit(DuelEvent::Signal { signal }),
+                    Some(Err(e)) => signal_events.emit(failed(e)),
+                    None => (),
+                }
+            },
+            |f| duel.peer.set_onicecandidate(f),
+        );
+
+        if !host {
+            let (channel, handlers) = (duel.channel.clone(), duel.handlers.clone());
+            duel.listen(
+                move |e| {
+                    let e: RtcDataChannelEvent = e.unchecked_into();
+                    attach(&e.channel(), &channel, &handlers, &events);
+                },
+                |f| duel.peer.set_ondatachannel(f),
+            );
+        }
+        Ok(duel)
+    }
+
+    fn listen(
+        &self,
+        f: impl FnMut(JsValue) + 'static,
+        set: impl FnOnce(Option<&js_sys::Function>),
+    ) {
+        let handler = Closure::wrap(Box::new(f) as Box<dyn FnMut(JsValue)>);
+        set(Some(handler.as_ref().unchecked_ref()));
+        self.handlers.borrow_mut().push(handler);
+    }
+
+    pub fn is_host(&self) -> bool {
+        self.host
+    }
+
+    // Finishes the host's side of the handshake with the guest's reply.
+    pub fn accept(&self, reply: &str, events: Callback<DuelEvent>) {
+        let sdp = match decode(reply) {
+            Ok(sdp) => sdp,
+            Err(e) => return events.emit(failed(e)),
+        };
+        let peer = self.peer.clone();
+        spawn_local(async move {
+            let answer = RtcSessionDescriptionInit::new(RtcSdpType::Answer);
+            answer.set_sdp(&sdp);
+            if let Err(e) = JsFuture::from(peer.set_remote_description(&answer)).await {
+                events.emit(failed(e));
+            }
+        });
+    }
+
+    pub fn send(&self, message: &PeerMessage) {
+        if let (Some(channel), Some(text)) = (&*self.channel.borrow(), message.to_text()) {
+            let _ = channel.send_with_str(&text);
+        }
+    }
+}
+
+impl Drop for Duel {
+    fn drop(&mut self) {
+        if let Some(channel) = &*self.channel.borrow() {
+            channel.set_onclose(None);
+            channel.close();
+        }
+        self.peer.close();
+    }
+}
+
+fn attach(
+    channel: &RtcDataChannel,
+    slot: &Rc<RefCell<Option<RtcDataChannel>>>,
+    handlers: &Rc<RefCell<Vec<Handler>>>,
+    events: &Callback<DuelEvent>,
+) {
+    let mut handlers = handlers.borrow_mut();
+
+    let open_events = events.clone();
+    let on_open = Closure::wrap(
+        Box::new(move |_| open_events.emit(DuelEvent::Connected)) as Box<dyn FnMut(JsValue)>
+    );
+    channel.set_onopen(Some(on_open.as_ref().unchecked_ref()));
+    handlers.push(on_open);
+
+    let message_events = events.clone();
+    let on_message = Closure::wrap(Box::new(move |e: JsValue| {
+        let e: MessageEvent = e.unchecked_into();
+        let message = e.data().as_string().and_then(PeerMessage::from_text);
+        if let Some(message) = message {
+            message_events.emit(DuelEvent::Received { message });
+        }
+    }) as Box<dyn FnMut(JsValue)>);
+    channel.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+    handlers.push(on_message);
+
+    let close_events = events.clone();
+    let on_close = Closure::wrap(
+        Box::new(move |_| close_events.emit(DuelEvent::Closed)) as Box<dyn FnMut(JsValue)>
+    );
+    channel.set_onclose(Some(on_close.as_ref().unchecked_ref()));
+    handlers.push(on_close);
+
+    slot.replace(Some(channel.clone()));
+}
+
+async fn set_local(
+    peer: &RtcPeerConnection,
+    kind: RtcSdpType,
+    description: &JsValue,
+) -> Result<(), JsValue> {
+    let sdp = Reflect::get(description, &"sdp".into())?
+        .as_string()
+        .unwrap_or_default();
+    let init = RtcSessionDescriptionInit::new(kind);
+    init.set_sdp(&sdp);
+    JsFuture::from(peer.set_local_description(&init)).await?;
+    Ok(())
+}
+
+// Session descriptions span many lines; base64 keeps them in one piece when
+// pasted through chat apps.
+fn encode(sdp: &str) -> Result<String, JsValue> {
+    web_sys::window().ok_or(JsValue::NULL)?.btoa(sdp)
+}
+
+fn decode(signal: &str) -> Result<String, JsValue> {
+    web_sys::window().ok_or(JsValue::NULL)?.atob(signal.trim())
+}
+
+fn failed(e: JsValue) -> DuelEvent {
+    DuelEvent::Failed {
+        reason: e.as_string().unwrap_or_else(|| format!("{:?}", e)),
+    }
+}
@@ -0,0 +1,115 @@
+use std::time::Duration;
+
+use yew::prelude::*;
+use yew::services::timeout::{TimeoutService, TimeoutTask};
+
+use crate::random;
+
+// Long enough for the last piece to fall past the bottom.
+const CONFETTI_FOR: Duration = Duration::from_millis(3500);
+const CONFETTI_PIECES: usize = 60;
+// The number colours, see style.css.
+const CONFETTI_COLOURS: [&str; 6] = [
+    "#5296a5", "#50723c", "#d81159", "#ffbc42", "#218380", "#8f2d56",
+];
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Effect {
+    Confetti,
+}
+
+#[derive(Clone, Properties, PartialEq)]
+pub struct EffectsProps {
+    pub effect: Effect,
+    // Plays the effect each time this goes up. Nothing plays at 0.
+    pub play: u64,
+}
+
+// A layer over the page that plays an effect for a moment, then clears
+// itself. It never takes clicks, and style.css leaves it out for players
+// who ask their browser for reduced motion.
+pub struct Effects {
+    link: ComponentLink<Self>,
+    props: EffectsProps,
+    // Each piece's left edge in %, delay in seconds, colour and spin.
+    pieces: Vec<(f64, f64, &'static str, u32)>,
+    _clear: Option<TimeoutTask>,
+}
+
+pub enum EffectsMsg {
+    Clear,
+}
+
+impl Component for Effects {
+    type Message = EffectsMsg;
+    type Properties = EffectsProps;
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        let mut effects = Effects {
+            link,
+            props,
+            pieces: vec![],
+            _clear: None,
+        };
+        if effects.props.play > 0 {
+            effects.start();
+        }
+        effects
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        let replay = props.play > self.props.play;
+        self.props = props;
+        if replay {
+            self.start();
+        }
+        replay
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            EffectsMsg::Clear => {
+                self.pieces = vec![];
+                self._clear = None;
+            }
+        }
+        true
+    }
+
+    fn view(&self) -> Html {
+        if self.pieces.is_empty() {
+            return html! {};
+        }
+        html! {
+            <div id="effects">
+                { for self.pieces.iter().map(|(left, delay, colour, spin)| html! {
+                    <span
+                     class="confetti"
+                     style=format!(
+                         "left: {:.1}%; animation-delay: {:.2}s; background-color: {}; --spin: {}deg",
+                         left, delay, colour, spin
+                     )>
+                    </span>
+                }) }
+            </div>
+        }
+    }
+}
+
+impl Effects {
+    fn start(&mut self) {
+        self.pieces = match self.props.effect {
+            Effect::Confetti => (0..CONFETTI_PIECES)
+                .map(|_| {
+                    (
+                        (random() % 1000) as f64 / 10_f64,
+                        (random() % 150) as f64 / 100_f64,
+                        CONFETTI_COLOURS[random() as usize % CONFETTI_COLOURS.len()],
+                        (random() % 720) as u32,
+                    )
+                })
+                .collect(),
+        };
+        let clear = self.link.callback(|_| EffectsMsg::Clear);
+        self._clear = Some(TimeoutService::new().spawn(CONFETTI_FOR, clear));
+    }
+}
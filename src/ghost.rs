@@ -0,0 +1,73 @@
+use lib_minesweeper::Board;
+use lib_minesweeper::MapElement::{Mine, Number};
+use lib_minesweeper::MapElementCellState::{Closed, Open};
+use lib_minesweeper::Point;
+use lib_minesweeper::Replay;
+
+// A past win on the same board, played back in time with the current game
+// so the player can race it. It shows as the cells it had open by then that
+// the player hasn't opened yet.
+pub struct Ghost {
+    replay: Replay,
+    step: usize,
+    board: Board,
+}
+
+impl Ghost {
+    // None unless every move of the replay was timed.
+    pub fn new(replay: &Replay) -> Option<Ghost> {
+        if !replay.is_timed() {
+            return None;
+        }
+        Some(Ghost {
+            board: replay.initial_board()?,
+            replay: replay.clone(),
+            step: 0,
+        })
+    }
+
+    // How long the past game took.
+    pub fn finish_ms(&self) -> f64 {
+        self.replay.times[self.replay.times.len() - 1]
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.step == self.replay.moves.len()
+    }
+
+    // Plays the past game on to `at_ms` into it. Returns the cells that
+    // changed.
+    pub fn advance(&mut self, at_ms: f64) -> Vec<Point> {
+        let step = self.replay.step_at(at_ms).max(self.step);
+        let before = self.board.clone();
+        for m in &self.replay.moves[self.step..step] {
+            if let Some(b) = self.board.apply(m) {
+                self.board = b;
+            }
+        }
+        self.step = step;
+        before.changed_points(&self.board)
+    }
+
+    // The cells the ghost has open that are still closed on `board`.
+    pub fn marks(&self, board: &Board) -> Vec<(Point, &'static str)> {
+        let mut marks = vec![];
+        for y in 0..board.height {
+            for x in 0..board.width {
+                let p = Point::new(x, y);
+                let ghost_open = matches!(
+                    self.board.at(&p),
+                    Some(Number { state: Open, .. }) | Some(Mine { state: Open, .. })
+                );
+                let closed = matches!(
+                    board.at(&p),
+                    Some(Number { state: Closed, .. }) | Some(Mine { state: Closed, .. })
+                );
+                if ghost_open && closed {
+                    marks.push((p, "ghost"));
+                }
+            }
+        }
+        marks
+    }
+}
@@ -0,0 +1,73 @@
+use lib_minesweeper::Board;
+use lib_minesweeper::MapElement::Mine;
+use lib_minesweeper::MapElementCellState::Open;
+use lib_minesweeper::Point;
+use serde_derive::{Deserialize, Serialize};
+
+// Cells on each side of the heatmap.
+pub const SIZE: usize = 8;
+
+// Where on the board the player clicks and loses, added up over games.
+// Boards of every size are scaled to the same SIZE × SIZE grid, so e.g. the
+// top left corner is the same square on all of them.
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct Heatmap {
+    // Row by row. Empty until the first game is recorded.
+    pub clicks: Vec<u32>,
+    pub losses: Vec<u32>,
+}
+
+impl Heatmap {
+    fn square(p: &Point, board: &Board) -> usize {
+        let x = p.x as usize * SIZE / board.width;
+        let y = p.y as usize * SIZE / board.height;
+        y * SIZE + x
+    }
+
+    fn fill(&mut self) {
+        self.clicks.resize(SIZE * SIZE, 0);
+        self.losses.resize(SIZE * SIZE, 0);
+    }
+
+    // Adds a finished game's clicks and, if it was lost, the mine that lost
+    // it.
+    pub fn record(&mut self, clicks: &[Point], board: &Board) {
+        self.fill();
+        for p in clicks {
+            self.clicks[Heatmap::square(p, board)] += 1;
+        }
+        if let Some(p) = fatal_mine(board) {
+            self.losses[Heatmap::square(&p, board)] += 1;
+        }
+    }
+
+    pub fn add(&mut self, other: &Heatmap) {
+        self.fill();
+        for (mine, theirs) in self.clicks.iter_mut().zip(&other.clicks) {
+            *mine += theirs;
+        }
+        for (mine, theirs) in self.losses.iter_mut().zip(&other.losses) {
+            *mine += theirs;
+        }
+    }
+
+    // (clicks, losses) in the square at column `x` and row `y`.
+    pub fn at(&self, x: usize, y: usize) -> (u32, u32) {
+        let i = y * SIZE + x;
+        (
+            self.clicks.get(i).copied().unwrap_or(0),
+            self.losses.get(i).copied().unwrap_or(0),
+        )
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.clicks.iter().all(|c| *c == 0)
+    }
+}
+
+// The mine that was opened, on a lost board.
+pub fn fatal_mine(board: &Board) -> Option<Point> {
+    (0..board.height)
+        .flat_map(|y| (0..board.width).map(move |x| Point::new(x, y)))
+        .find(|p| matches!(board.at(p), Some(Mine { state: Open })))
+}
@@ -0,0 +1,317 @@
+use std::collections::VecDeque;
+
+use lib_minesweeper::Annotation;
+use lib_minesweeper::BoardState;
+use lib_minesweeper::Cascade;
+use lib_minesweeper::Move;
+use lib_minesweeper::Replay;
+use lib_minesweeper::Spacing;
+use lib_minesweeper::Symmetry;
+use lib_minesweeper::Timing;
+use lib_minesweeper::{pack_moves, unpack_moves};
+
+use serde_derive::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::storage::{restore_json, store_json, Migration, StorageBackend};
+use crate::Difficulty;
+
+pub const KEY: &str = "jgpaiva.minesweeper.history";
+const MAX_GAMES: usize = 50;
+
+// One per change to the saved shape of GameRecord, including the engine
+// types its replay uses.
+const MIGRATIONS: &[Migration] = &[pack_saved_moves, add_game_time];
+
+// Version 1 packs each replay's moves into a string.
+fn pack_saved_moves(mut data: Value) -> Value {
+    let games = data.get_mut("games").and_then(Value::as_array_mut);
+    for game in games.into_iter().flatten() {
+        let saved = game.get_mut("replay").and_then(|r| r.get_mut("moves"));
+        if let Some(saved) = saved {
+            if let Ok(moves) = serde_json::from_value::<Vec<Move>>(saved.clone()) {
+                *saved = Value::from(pack_moves(&moves));
+            }
+        }
+    }
+    data
+}
+
+// Version 2 adds game time, which games from before didn't keep.
+fn add_game_time(mut data: Value) -> Value {
+    let games = data.get_mut("games").and_then(Value::as_array_mut);
+    for game in games.into_iter().flatten().filter_map(Value::as_object_mut) {
+        game.insert(String::from("game_ms"), Value::Null);
+    }
+    data
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct GameRecord {
+    pub difficulty: Difficulty,
+    pub result: BoardState,
+    // Real time, and game time when it was kept.
+    pub duration_ms: f64,
+    pub game_ms: Option<f64>,
+    pub finished_at: f64,
+    #[serde(with = "packed")]
+    pub replay: Replay,
+    // The notes the player left on the board.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub annotations: Vec<Annotation>,
+    // Whether it was a board already played, started again from its seed.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub retry: bool,
+}
+
+impl GameRecord {
+    pub fn time_ms(&self, timing: Timing) -> Option<f64> {
+        match timing {
+            Timing::RealTime => Some(self.duration_ms),
+            Timing::GameTime => self.game_ms,
+        }
+    }
+}
+
+fn is_false(b: &bool) -> bool {
+    !b
+}
+
+// Replays are saved with their moves packed, as cascades and the robot make
+// long runs of similar moves.
+mod packed {
+    use serde::de::Error;
+    use serde::{Deserializer, Serializer};
+
+    use super::*;
+
+    #[derive(Serialize, Deserialize)]
+    struct PackedReplay {
+        width: usize,
+        height: usize,
+        mines: usize,
+        adjacency: lib_minesweeper::Adjacency,
+        seed: u64,
+        #[serde(default, skip_serializing_if = "Symmetry::is_none")]
+        symmetry: Symmetry,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        spacing: Option<Spacing>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        min_opening: Option<usize>,
+        #[serde(default, skip_serializing_if = "Cascade::is_zeros")]
+        cascade: Cascade,
+        moves: String,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        assisted: Vec<usize>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        times: Vec<f64>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        checksum: Option<u64>,
+    }
+
+    pub fn serialize<S: Serializer>(replay: &Replay, serializer: S) -> Result<S::Ok, S::Error> {
+        serde::Serialize::serialize(
+            &PackedReplay {
+                width: replay.width,
+                height: replay.height,
+                mines: replay.mines,
+                adjacency: replay.adjacency,
+                seed: replay.seed,
+                symmetry: replay.symmetry,
+                spacing: replay.spacing,
+                min_opening: replay.min_opening,
+                cascade: replay.cascade,
+                moves: pack_moves(&replay.moves),
+                assisted: replay.assisted.clone(),
+                times: replay.times.clone(),
+                checksum: replay.checksum,
+            },
+            serializer,
+        )
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Replay, D::Error> {
+        let packed: PackedReplay = serde::Deserialize::deserialize(deserializer)?;
+        Ok(Replay {
+            width: packed.width,
+            height: packed.height,
+            mines: packed.mines,
+            adjacency: packed.adjacency,
+            seed: packed.seed,
+            symmetry: packed.symmetry,
+            spacing: packed.spacing,
+            min_opening: packed.min_opening,
+            cascade: packed.cascade,
+            moves: unpack_moves(&packed.moves)
+                .ok_or_else(|| D::Error::custom("malformed moves"))?,
+            assisted: packed.assisted,
+            times: packed.times,
+            checksum: packed.checksum,
+        })
+    }
+}
+
+// Finished games, oldest first. Only the last MAX_GAMES are kept.
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct History {
+    games: VecDeque<GameRecord>,
+}
+
+impl History {
+    pub fn restore(storage: &dyn StorageBackend) -> History {
+        History::try_restore(storage).unwrap_or_default()
+    }
+
+    // None if there's no history saved or it doesn't load.
+    pub fn try_restore(storage: &dyn StorageBackend) -> Option<History> {
+        restore_json(storage, KEY, MIGRATIONS)
+    }
+
+    pub fn store(&self, storage: &mut dyn StorageBackend) {
+        store_json(storage, KEY, MIGRATIONS, self)
+    }
+
+    pub fn push(&mut self, game: GameRecord) {
+        if self.games.len() == MAX_GAMES {
+            self.games.pop_front();
+        }
+        self.games.push_back(game);
+    }
+
+    // Adds the games that aren't here yet, keeping the newest MAX_GAMES of
+    // both. Returns how many were added.
+    pub fn merge(&mut self, other: History) -> usize {
+        let before = self.games.len();
+        // Only the newest MAX_GAMES of the backup could be kept, so the rest
+        // aren't checked at all.
+        let mut others = Vec::from(other.games);
+        others.sort_by(|a, b| b.finished_at.total_cmp(&a.finished_at));
+        others.truncate(MAX_GAMES);
+        // A backup could come from anywhere, so games too big to check or
+        // that can't be played back are left out. The size is checked
+        // before any board is built.
+        let games = others
+            .into_iter()
+            .filter(|g| g.replay.is_within_limits() && g.replay.is_playable());
+        for game in games {
+            let known = self
+                .games
+                .iter()
+                .any(|g| g.finished_at == game.finished_at && g.replay == game.replay);
+            if !known {
+                self.games.push_back(game);
+            }
+        }
+        let added = self.games.len() - before;
+        self.games
+            .make_contiguous()
+            .sort_by(|a, b| a.finished_at.total_cmp(&b.finished_at));
+        while self.games.len() > MAX_GAMES {
+            self.games.pop_front();
+        }
+        added
+    }
+
+    pub fn get(&self, index: usize) -> Option<&GameRecord> {
+        self.games.get(index)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.games.is_empty()
+    }
+
+    // The quickest timed win on the board `replay` starts from, to race.
+    pub fn fastest_win(&self, replay: &Replay) -> Option<&Replay> {
+        self.games
+            .iter()
+            .filter(|g| g.result == BoardState::Won)
+            .map(|g| &g.replay)
+            .filter(|r| r.is_timed() && r.is_same_board(replay))
+            .min_by(|a, b| a.times[a.times.len() - 1].total_cmp(&b.times[b.times.len() - 1]))
+    }
+
+    // Newest first, with the index each game can be fetched back with.
+    pub fn newest_first(&self) -> impl Iterator<Item = (usize, &GameRecord)> {
+        self.games.iter().enumerate().rev()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::InMemory;
+    use lib_minesweeper::Point;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    // A game as version 0 saved it: moves as a list and no game time.
+    fn old_game() -> Value {
+        json!({
+            "difficulty": "Easy",
+            "result": "Won",
+            "duration_ms": 1000.0,
+            "finished_at": 5.0,
+            "replay": {
+                "width": 8,
+                "height": 8,
+                "mines": 10,
+                "adjacency": "Knight",
+                "seed": 7,
+                "moves": [
+                    {"Open": {"x": 0, "y": 2}},
+                    {"Open": {"x": 1, "y": 2}},
+                    {"Open": {"x": 2, "y": 2}},
+                    "OpenAllUnflagged"
+                ]
+            }
+        })
+    }
+
+    #[test]
+    fn test_pack_saved_moves() {
+        let data = pack_saved_moves(json!({ "games": [old_game()] }));
+        assert_eq!(data["games"][0]["replay"]["moves"], json!("o0,2+1,0*2 a"));
+        // Moves that are already packed, or aren't moves, are left alone.
+        assert_eq!(pack_saved_moves(data.clone()), data);
+        assert_eq!(pack_saved_moves(json!({})), json!({}));
+    }
+
+    #[test]
+    fn test_add_game_time() {
+        let data = add_game_time(json!({ "games": [old_game()] }));
+        assert_eq!(data["games"][0]["game_ms"], Value::Null);
+        assert_eq!(data["games"][0]["duration_ms"], json!(1000.0));
+        assert_eq!(add_game_time(json!({})), json!({}));
+    }
+
+    #[test]
+    fn test_restore_version_0() {
+        let mut storage = InMemory::default();
+        let saved = json!({ "games": [old_game()] });
+        storage.store(KEY, saved.to_string());
+        let history = History::try_restore(&storage).unwrap();
+        let (_, game) = history.newest_first().next().unwrap();
+        assert_eq!(game.game_ms, None);
+        assert_eq!(game.replay.moves.len(), 4);
+        assert_eq!(game.replay.moves[2], Move::Open(Point::new(2, 2)));
+
+        // And it's saved back at the latest version.
+        history.store(&mut storage);
+        let stored: Value = serde_json::from_str(&storage.restore(KEY).unwrap()).unwrap();
+        assert_eq!(stored["version"], json!(MIGRATIONS.len()));
+        let restored = History::try_restore(&storage).unwrap();
+        assert_eq!(restored.newest_first().count(), 1);
+    }
+
+    #[test]
+    fn test_restore_bad_history() {
+        let mut storage = InMemory::default();
+        assert!(History::try_restore(&storage).is_none());
+        let mut game = old_game();
+        game["replay"]["moves"] = json!("o0,0+1,0*-1");
+        let saved = json!({ "version": 2, "data": { "games": [game] } });
+        storage.store(KEY, saved.to_string());
+        assert!(History::try_restore(&storage).is_none());
+        assert_eq!(History::restore(&storage).newest_first().count(), 0);
+    }
+}
@@ -0,0 +1,61 @@
+use serde_derive::{Deserialize, Serialize};
+
+pub const PLAYERS: [&str; 2] = ["🔵", "🔴"];
+
+// Two players taking turns on one device. Each turn is one dig or chord,
+// and everything it opens counts for the player whose turn it was. Flags
+// don't end a turn. Whoever opens a mine is out, and the other wins;
+// otherwise the most cells opened wins once the board is cleared.
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct HotSeat {
+    pub turn: usize,
+    pub opened: [usize; 2],
+    pub out: Option<usize>,
+    // Who played each of the replay's moves.
+    pub moves: Vec<usize>,
+}
+
+impl HotSeat {
+    // Puts a move down to the player whose turn it is.
+    pub fn record(&mut self, opened: usize, hit_mine: bool) {
+        self.moves.push(self.turn);
+        self.opened[self.turn] += opened;
+        if hit_mine {
+            self.out = Some(self.turn);
+        }
+    }
+
+    pub fn pass(&mut self) {
+        self.turn = (self.turn + 1) % PLAYERS.len();
+    }
+
+    // "🔵 12 – 8 🔴", in cells opened.
+    pub fn score(&self) -> String {
+        format!(
+            "{} {} – {} {}",
+            PLAYERS[0], self.opened[0], self.opened[1], PLAYERS[1]
+        )
+    }
+
+    // "🔵 to play · 🔵 12 – 8 🔴"
+    pub fn status(&self) -> String {
+        match self.out {
+            Some(out) => format!("{} is out · {}", PLAYERS[out], self.score()),
+            None => format!("{} to play · {}", PLAYERS[self.turn], self.score()),
+        }
+    }
+
+    // Who won, once the game is over.
+    pub fn result(&self) -> String {
+        let winner = match self.out {
+            Some(out) => Some(1 - out),
+            None if self.opened[0] == self.opened[1] => None,
+            None if self.opened[0] > self.opened[1] => Some(0),
+            None => Some(1),
+        };
+        match winner {
+            Some(winner) => format!("👥 {} wins! {}", PLAYERS[winner], self.score()),
+            None => format!("👥 It's a draw! {}", self.score()),
+        }
+    }
+}
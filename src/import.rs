@@ -0,0 +1,115 @@
+use lib_minesweeper::Board;
+use lib_minesweeper::BoardState::{Playing, Ready};
+use lib_minesweeper::Point;
+use lib_minesweeper::Replay;
+use wasm_bindgen_futures::{spawn_local, JsFuture};
+use web_sys::{DragEvent, File};
+use yew::callback::Callback;
+
+use crate::backup::{self, Backup};
+
+pub enum Imported {
+    Board(Board),
+    Replay(Replay),
+    Backup(Backup),
+}
+
+// Reads a board pasted into the import screen. It can be the grid Board's
+// Display writes, the CSV from the board export, or a board's JSON; the
+// format is picked from the text itself.
+pub fn parse_board(text: &str) -> Result<Board, String> {
+    let text = text.trim();
+    let board = if text.starts_with('{') {
+        let board: Board =
+            serde_json::from_str(text).map_err(|e| format!("Not a board's JSON: {}", e))?;
+        // Going through the grid recounts the numbers, which hand-edited
+        // JSON may have got wrong.
+        board
+            .to_string()
+            .parse()
+            .map_err(|e| format!("Invalid board: {}", e))?
+    } else if text.lines().next().is_some_and(|l| l.contains(',')) {
+        Board::from_csv(text).map_err(|e| format!("Invalid CSV: {}", e))?
+    } else {
+        text.parse().map_err(|e| format!("Invalid board: {}", e))?
+    };
+    match board.state {
+        Ready | Playing => Ok(board),
+        _ => Err(String::from("That board is already finished")),
+    }
+}
+
+// The start cell for making a board solvable, as "column,row" counting
+// from 1. Blank picks the zero with the biggest opening, or the middle cell
+// if there's none.
+pub fn parse_start(text: &str, board: &Board) -> Result<Point, String> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Ok(board
+            .opening_start()
+            .unwrap_or_else(|| Point::new(board.width / 2, board.height / 2)));
+    }
+    let numbers: Vec<usize> = text
+        .split(',')
+        .map(|n| n.trim().parse::<usize>())
+        .collect::<Result<_, _>>()
+        .map_err(|_| String::from("The start is a column and row, like 3,4"))?;
+    match numbers[..] {
+        [column, row]
+            if (1..=board.width).contains(&column) && (1..=board.height).contains(&row) =>
+        {
+            Ok(Point::new(column - 1, row - 1))
+        }
+        _ => Err(String::from(
+            "The start has to be a cell on the board, like 3,4",
+        )),
+    }
+}
+
+// Files dropped on the page can also hold a replay, as the scores server
+// takes them, or a backup of the stats. A replay has to match its checksum,
+// which catches damaged files, and make a board it stays on, as it could
+// come from anywhere. Its size is checked before any board is built.
+pub fn parse_file(text: &str) -> Result<Imported, String> {
+    if let Some(backup) = backup::parse(text) {
+        return backup.map(Imported::Backup);
+    }
+    if let Ok(replay) = serde_json::from_str::<Replay>(text) {
+        if !replay.is_sealed() {
+            return Err(String::from(
+                "That replay has no checksum, or was damaged since it was saved",
+            ));
+        }
+        if !replay.is_within_limits() {
+            return Err(String::from("That replay's board is too big to load"));
+        }
+        if !replay.is_playable() {
+            return Err(String::from("That replay's board can't be built"));
+        }
+        return Ok(Imported::Replay(replay));
+    }
+    parse_board(text).map(Imported::Board)
+}
+
+// Reads the first file dropped with `event`, then calls `loaded` with its
+// text, or None if it couldn't be read.
+pub fn read_dropped(event: &DragEvent, loaded: Callback<Option<String>>) {
+    let file = event
+        .data_transfer()
+        .and_then(|d| d.files())
+        .and_then(|f| f.get(0));
+    read_file(file, loaded);
+}
+
+// Reads `file`, then calls `loaded` with its text, or None if it couldn't
+// be read.
+pub fn read_file(file: Option<File>, loaded: Callback<Option<String>>) {
+    let file = match file {
+        Some(file) => file,
+        None => return loaded.emit(None),
+    };
+    spawn_local(async move {
+        let text = JsFuture::from(file.text()).await.ok();
+        loaded.emit(text.and_then(|t| t.as_string()));
+    });
+}
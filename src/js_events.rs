@@ -0,0 +1,108 @@
+use std::cell::RefCell;
+
+use js_sys::{Function, Object, Reflect};
+use lib_minesweeper::Board;
+use lib_minesweeper::MapElement::{Mine, Number};
+use lib_minesweeper::MapElementCellState::Open;
+use wasm_bindgen::prelude::*;
+
+use crate::stopwatch::Times;
+use crate::tab;
+
+// Callbacks registered by the page embedding the game, e.g.
+//
+//     import init, { onGameEnd } from './pkg/minesweeper.js';
+//     await init();
+//     onGameEnd(e => console.log(e.result, e.durationMs));
+#[derive(Default)]
+struct Listeners {
+    cell_opened: Vec<Function>,
+    game_end: Vec<Function>,
+    board_changed: Vec<Function>,
+}
+
+thread_local! {
+    static LISTENERS: RefCell<Listeners> = RefCell::new(Listeners::default());
+}
+
+// Called with {x, y, count} for every cell a move opens; `count` is null for
+// a mine.
+#[wasm_bindgen(js_name = onCellOpened)]
+pub fn on_cell_opened(callback: Function) {
+    LISTENERS.with(|l| l.borrow_mut().cell_opened.push(callback));
+}
+
+// Called with {result, durationMs, seed} when a game is won or lost. The seed
+// is a string, as it doesn't fit in a JS number.
+#[wasm_bindgen(js_name = onGameEnd)]
+pub fn on_game_end(callback: Function) {
+    LISTENERS.with(|l| l.borrow_mut().game_end.push(callback));
+}
+
+// Called with {board, state, width, height} whenever the board changes;
+// `board` uses the same text format as Board's Display.
+#[wasm_bindgen(js_name = onBoardChanged)]
+pub fn on_board_changed(callback: Function) {
+    LISTENERS.with(|l| l.borrow_mut().board_changed.push(callback));
+}
+
+pub fn board_changed(before: Option<&Board>, after: &Board) {
+    // The tab's title and icon follow the same events as the page.
+    tab::board_changed(after);
+    let changed = match before {
+        Some(before) => before.changed_points(after),
+        None => vec![],
+    };
+    LISTENERS.with(|l| {
+        let l = l.borrow();
+        for p in changed {
+            let count = match after.at(&p) {
+                Some(Number { state: Open, count }) => JsValue::from(*count),
+                Some(Mine { state: Open }) => JsValue::NULL,
+                _ => continue,
+            };
+            emit(
+                &l.cell_opened,
+                &[("x", p.x.into()), ("y", p.y.into()), ("count", count)],
+            );
+        }
+        emit(
+            &l.board_changed,
+            &[
+                ("board", after.to_string().into()),
+                ("state", format!("{:?}", after.state).into()),
+                ("width", (after.width as u32).into()),
+                ("height", (after.height as u32).into()),
+            ],
+        );
+    });
+}
+
+pub fn game_ended(board: &Board, duration: Times) {
+    let seed = board.seed.map_or(JsValue::NULL, |s| s.to_string().into());
+    LISTENERS.with(|l| {
+        emit(
+            &l.borrow().game_end,
+            &[
+                ("result", format!("{:?}", board.state).into()),
+                ("durationMs", duration.real_ms.into()),
+                ("gameTimeMs", duration.game_ms.into()),
+                ("seed", seed),
+            ],
+        )
+    });
+}
+
+fn emit(callbacks: &[Function], fields: &[(&str, JsValue)]) {
+    if callbacks.is_empty() {
+        return;
+    }
+    let event = Object::new();
+    for (key, value) in fields {
+        let _ = Reflect::set(&event, &JsValue::from_str(key), value);
+    }
+    for callback in callbacks {
+        // A broken listener on the page shouldn't break the game.
+        let _ = callback.call1(&JsValue::NULL, &event);
+    }
+}
@@ -0,0 +1,266 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use serde_derive::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys::{Element, KeyboardEvent};
+use yew::Callback;
+
+// Everything the keyboard can do, in the order the keys screen lists them.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Serialize, Deserialize)]
+pub enum KeyAction {
+    Up,
+    Down,
+    Left,
+    Right,
+    Dig,
+    Flag,
+    Chord,
+    Hint,
+    Robot,
+    NewGame,
+}
+
+pub const ACTIONS: [KeyAction; 10] = [
+    KeyAction::Up,
+    KeyAction::Down,
+    KeyAction::Left,
+    KeyAction::Right,
+    KeyAction::Dig,
+    KeyAction::Flag,
+    KeyAction::Chord,
+    KeyAction::Hint,
+    KeyAction::Robot,
+    KeyAction::NewGame,
+];
+
+impl KeyAction {
+    pub fn describe(self) -> &'static str {
+        match self {
+            KeyAction::Up => "⬆️ Cursor up",
+            KeyAction::Down => "⬇️ Cursor down",
+            KeyAction::Left => "⬅️ Cursor left",
+            KeyAction::Right => "➡️ Cursor right",
+            KeyAction::Dig => "⛏️ Dig under the cursor",
+            KeyAction::Flag => "🚩 Flag under the cursor",
+            KeyAction::Chord => "🎹 Chord under the cursor",
+            KeyAction::Hint => "💡 Show 🤖's next sure move",
+            KeyAction::Robot => "🤖 Let 🤖 play a move",
+            KeyAction::NewGame => "🔁 New board",
+        }
+    }
+
+    // As KeyboardEvent.key names them, after `normalize`.
+    fn default_key(self) -> &'static str {
+        match self {
+            KeyAction::Up => "ArrowUp",
+            KeyAction::Down => "ArrowDown",
+            KeyAction::Left => "ArrowLeft",
+            KeyAction::Right => "ArrowRight",
+            KeyAction::Dig => " ",
+            KeyAction::Flag => "f",
+            KeyAction::Chord => "c",
+            KeyAction::Hint => "h",
+            KeyAction::Robot => "r",
+            KeyAction::NewGame => "n",
+        }
+    }
+}
+
+// The key for each action the player has moved off its default. No two
+// actions share a key.
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct Keybindings {
+    #[serde(default)]
+    keys: HashMap<KeyAction, String>,
+}
+
+impl Keybindings {
+    pub fn key(&self, action: KeyAction) -> &str {
+        self.keys
+            .get(&action)
+            .map_or(action.default_key(), |key| key.as_str())
+    }
+
+    pub fn action(&self, key: &str) -> Option<KeyAction> {
+        ACTIONS.iter().copied().find(|a| self.key(*a) == key)
+    }
+
+    // Puts `action` on `key`. Whatever action had `key` takes `action`'s
+    // old one, and is returned.
+    pub fn bind(&mut self, action: KeyAction, key: &str) -> Option<KeyAction> {
+        let old = self.key(action).to_string();
+        let swapped = self.action(key).filter(|a| *a != action);
+        if let Some(other) = swapped {
+            self.set(other, &old);
+        }
+        self.set(action, key);
+        swapped
+    }
+
+    pub fn is_default(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    // Only keys off their default are kept, so new actions get theirs.
+    fn set(&mut self, action: KeyAction, key: &str) {
+        if key == action.default_key() {
+            self.keys.remove(&action);
+        } else {
+            self.keys.insert(action, key.to_string());
+        }
+    }
+}
+
+// "Space" for " ", and arrows for the arrow keys.
+pub fn label(key: &str) -> String {
+    match key {
+        " " => String::from("Space"),
+        "ArrowUp" => String::from("↑"),
+        "ArrowDown" => String::from("↓"),
+        "ArrowLeft" => String::from("←"),
+        "ArrowRight" => String::from("→"),
+        key if key.chars().count() == 1 => key.to_uppercase(),
+        key => key.to_string(),
+    }
+}
+
+// Keys that only change what another key does, so they're never bound by
+// themselves.
+pub fn is_modifier(key: &str) -> bool {
+    matches!(
+        key,
+        "Shift" | "Control" | "Alt" | "AltGraph" | "Meta" | "OS" | "Fn" | "CapsLock" | "NumLock"
+    )
+}
+
+// Letters the same with or without shift or caps lock.
+fn normalize(key: String) -> String {
+    if key.chars().count() == 1 {
+        key.to_lowercase()
+    } else {
+        key
+    }
+}
+
+// A key pressed anywhere on the page outside a text box, with the action it
+// does if any.
+pub struct Press {
+    pub key: String,
+    pub action: Option<KeyAction>,
+}
+
+thread_local! {
+    static BINDINGS: RefCell<Keybindings> = RefCell::new(Keybindings::default());
+}
+
+// The keys the listener goes by, e.g. once a profile's been switched to.
+pub fn set(bindings: &Keybindings) {
+    BINDINGS.with(|b| *b.borrow_mut() = bindings.clone());
+}
+
+// Tells `callback` about every key pressed. Keys with an action don't
+// also scroll the page or type into it. Added alongside any other keydown
+// listeners on the page rather than in place of them.
+pub fn on_key_down(callback: Callback<Press>) {
+    let document = match web_sys::window().and_then(|w| w.document()) {
+        Some(document) => document,
+        None => return,
+    };
+    let on_key = Closure::wrap(Box::new(move |e: KeyboardEvent| {
+        if e.ctrl_key() || e.meta_key() || e.alt_key() {
+            return;
+        }
+        let typing = e
+            .target()
+            .and_then(|t| t.dyn_into::<Element>().ok())
+            .is_some_and(|t| matches!(t.tag_name().as_str(), "INPUT" | "TEXTAREA" | "SELECT"));
+        if typing {
+            return;
+        }
+        let key = normalize(e.key());
+        let action = BINDINGS.with(|b| b.borrow().action(&key));
+        if action.is_some() {
+            e.prevent_default();
+        }
+        callback.emit(Press { key, action });
+    }) as Box<dyn FnMut(KeyboardEvent)>);
+    if document
+        .add_event_listener_with_callback("keydown", on_key.as_ref().unchecked_ref())
+        .is_err()
+    {
+        return;
+    }
+    // Listens for as long as the page is open.
+    on_key.forget();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_defaults() {
+        let keys = Keybindings::default();
+        assert!(keys.is_default());
+        assert_eq!(keys.key(KeyAction::Dig), " ");
+        assert_eq!(keys.action("ArrowUp"), Some(KeyAction::Up));
+        assert_eq!(keys.action("x"), None);
+        // Every action has a key of its own.
+        for action in &ACTIONS {
+            assert_eq!(keys.action(keys.key(*action)), Some(*action));
+        }
+    }
+
+    #[test]
+    fn test_bind() {
+        let mut keys = Keybindings::default();
+        assert_eq!(keys.bind(KeyAction::Flag, "g"), None);
+        assert_eq!(keys.key(KeyAction::Flag), "g");
+        assert_eq!(keys.action("g"), Some(KeyAction::Flag));
+        assert_eq!(keys.action("f"), None);
+        assert!(!keys.is_default());
+        // Binding back to the default forgets the change.
+        assert_eq!(keys.bind(KeyAction::Flag, "f"), None);
+        assert!(keys.is_default());
+    }
+
+    #[test]
+    fn test_bind_swaps_conflicts() {
+        let mut keys = Keybindings::default();
+        assert_eq!(keys.bind(KeyAction::Dig, "f"), Some(KeyAction::Flag));
+        assert_eq!(keys.key(KeyAction::Dig), "f");
+        assert_eq!(keys.key(KeyAction::Flag), " ");
+        // The action's own key isn't a conflict.
+        assert_eq!(keys.bind(KeyAction::Dig, "f"), None);
+        // Swapping back leaves both on their defaults.
+        assert_eq!(keys.bind(KeyAction::Flag, "f"), Some(KeyAction::Dig));
+        assert!(keys.is_default());
+    }
+
+    #[test]
+    fn test_saved_bindings() {
+        let mut keys = Keybindings::default();
+        keys.bind(KeyAction::Hint, "?");
+        let saved = serde_json::to_string(&keys).unwrap();
+        assert_eq!(saved, r#"{"keys":{"Hint":"?"}}"#);
+        let restored: Keybindings = serde_json::from_str(&saved).unwrap();
+        assert_eq!(restored.key(KeyAction::Hint), "?");
+        let empty: Keybindings = serde_json::from_str("{}").unwrap();
+        assert!(empty.is_default());
+    }
+
+    #[test]
+    fn test_keys() {
+        assert_eq!(normalize(String::from("F")), "f");
+        assert_eq!(normalize(String::from("Enter")), "Enter");
+        assert_eq!(label(" "), "Space");
+        assert_eq!(label("ArrowLeft"), "←");
+        assert_eq!(label("f"), "F");
+        assert_eq!(label("Enter"), "Enter");
+        assert!(is_modifier("Shift"));
+        assert!(!is_modifier("s"));
+    }
+}
@@ -0,0 +1,109 @@
+use std::time::Duration;
+
+use lib_minesweeper::{Board, KnightPath, PathError, Point};
+use yew::prelude::*;
+
+// How long the round lasts, from the first cell picked.
+pub const BONUS_FOR: Duration = Duration::from_secs(30);
+
+// The bonus round on a won board: hop from open cell to open cell like a
+// knight, landing on as many as possible before time runs out.
+#[derive(Default)]
+pub struct Bonus {
+    pub path: KnightPath,
+    // When the first cell was picked, from Date::now(). The clock waits for
+    // it.
+    started_ms: Option<f64>,
+    pub finished: bool,
+}
+
+impl Bonus {
+    pub fn is_started(&self) -> bool {
+        self.started_ms.is_some()
+    }
+
+    pub fn remaining_ms(&self, now: f64) -> f64 {
+        let elapsed = self.started_ms.map_or(0_f64, |started| now - started);
+        (BONUS_FOR.as_millis() as f64 - elapsed).max(0_f64)
+    }
+
+    // Adds `p` to the path, starting the clock on the first cell.
+    pub fn hop(&mut self, board: &Board, p: Point, now: f64) -> Result<(), PathError> {
+        self.path.extend(board, p)?;
+        self.started_ms.get_or_insert(now);
+        Ok(())
+    }
+
+    // Out of time, or out of cells to hop to.
+    pub fn is_over(&self, board: &Board, now: f64) -> bool {
+        self.path.is_stuck(board) || self.is_started() && self.remaining_ms(now) == 0_f64
+    }
+
+    // The path so far, where it is now and where it can go next.
+    pub fn marks(&self, board: &Board) -> Vec<(Point, &'static str)> {
+        let mut marks: Vec<(Point, &'static str)> = self
+            .path
+            .cells
+            .iter()
+            .map(|p| (*p, "knight-path"))
+            .collect();
+        if let Some(last) = marks.last_mut() {
+            last.1 = "knight-head";
+        }
+        if self.is_started() && !self.finished {
+            marks.extend(
+                self.path
+                    .next_cells(board)
+                    .into_iter()
+                    .map(|p| (p, "knight-next")),
+            );
+        }
+        marks
+    }
+
+    // "♞ 12 cells for 120 points", and whether it was a full tour.
+    pub fn summary(&self, board: &Board) -> String {
+        let tour = if self.path.is_tour(board) {
+            ", a full knight's tour!"
+        } else {
+            ""
+        };
+        format!(
+            "♞ {} cells for {} points{}",
+            self.path.cells.len(),
+            self.path.score(board),
+            tour
+        )
+    }
+}
+
+pub fn describe(error: &PathError) -> &'static str {
+    match error {
+        PathError::NotOpen => "♞ Only open cells count",
+        PathError::Visited => "♞ The path's been there already",
+        PathError::NotAKnightMove => "♞ That's not a knight's move away",
+    }
+}
+
+// A line through the middle of each cell of the path, drawn over a
+// `width` by `height` board. The cells are square and evenly spaced, see
+// style.css, so a cell's middle is at (x + 0.5, y + 0.5) in board units.
+pub fn render_overlay(path: &KnightPath, width: usize, height: usize) -> Html {
+    if path.cells.len() < 2 {
+        return html! {};
+    }
+    let points = path
+        .cells
+        .iter()
+        .map(|p| format!("{}.5,{}.5", p.x, p.y))
+        .collect::<Vec<_>>()
+        .join(" ");
+    html! {
+        <svg
+         id="knight-overlay"
+         viewBox=format!("0 0 {} {}", width, height)
+         preserveAspectRatio="none">
+            <polyline points=points />
+        </svg>
+    }
+}
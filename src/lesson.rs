@@ -0,0 +1,51 @@
+use lib_minesweeper::Board;
+use lib_minesweeper::BoardState::Playing;
+
+use crate::robot::{Robot, Step};
+
+// Plenty for a lesson, and keeps the first step quick to show on big boards.
+const MAX_STEPS: usize = 200;
+
+// The robot's certain moves from a position, one at a time, with the board
+// as it was before each of them.
+pub struct Lesson {
+    boards: Vec<Board>,
+    steps: Vec<Step>,
+}
+
+impl Lesson {
+    pub fn new(robot: &Robot, board: &Board) -> Lesson {
+        let mut boards = vec![board.clone()];
+        let mut steps = vec![];
+        while steps.len() < MAX_STEPS {
+            let board = &boards[boards.len() - 1];
+            if board.state != Playing {
+                break;
+            }
+            let step = match robot.explain(board) {
+                Some(step) => step,
+                None => break,
+            };
+            let next = match board.apply(&step.m) {
+                Some(next) => next,
+                None => break,
+            };
+            steps.push(step);
+            boards.push(next);
+        }
+        Lesson { boards, steps }
+    }
+
+    pub fn step_count(&self) -> usize {
+        self.steps.len()
+    }
+
+    // The board before step `i`, or after the last step.
+    pub fn board(&self, i: usize) -> &Board {
+        &self.boards[i.min(self.steps.len())]
+    }
+
+    pub fn step(&self, i: usize) -> Option<&Step> {
+        self.steps.get(i)
+    }
+}
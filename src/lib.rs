@@ -1,7 +1,10 @@
-#![recursion_limit = "512"]
+#![recursion_limit = "1024"]
 
-use lib_minesweeper::create_board;
-use lib_minesweeper::numbers_on_board;
+use lib_minesweeper::opening_book;
+use lib_minesweeper::Action;
+use lib_minesweeper::Adjacency;
+use lib_minesweeper::Annotation;
+use lib_minesweeper::Answer;
 use lib_minesweeper::Board;
 use lib_minesweeper::BoardState;
 use lib_minesweeper::BoardState::Failed;
@@ -9,375 +12,5758 @@ use lib_minesweeper::BoardState::NotReady;
 use lib_minesweeper::BoardState::Playing;
 use lib_minesweeper::BoardState::Ready;
 use lib_minesweeper::BoardState::Won;
+use lib_minesweeper::BuildBoardError;
+use lib_minesweeper::Cascade;
+use lib_minesweeper::Drill;
+use lib_minesweeper::GameController;
+use lib_minesweeper::Intent;
 use lib_minesweeper::MapElement;
 use lib_minesweeper::MapElement::Mine;
 use lib_minesweeper::MapElement::Number;
 use lib_minesweeper::MapElementCellState::Closed;
 use lib_minesweeper::MapElementCellState::Flagged;
 use lib_minesweeper::MapElementCellState::Open;
+use lib_minesweeper::Mode;
+use lib_minesweeper::Move;
+use lib_minesweeper::Opening;
+use lib_minesweeper::Pattern;
+use lib_minesweeper::Played;
 use lib_minesweeper::Point;
+use lib_minesweeper::Reference;
+use lib_minesweeper::Replay;
+use lib_minesweeper::Rules;
+use lib_minesweeper::ScenarioRun;
+use lib_minesweeper::Spacing;
+use lib_minesweeper::Symmetry;
+use lib_minesweeper::Timing;
+use lib_minesweeper::Trigger;
+use lib_minesweeper::SECOND_CHANCE_PENALTY;
 
+use std::mem;
+use std::rc::Rc;
 use std::time::Duration;
 
 use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::{spawn_local, JsFuture};
+use web_sys::DragEvent;
 
 use serde_derive::{Deserialize, Serialize};
 //use yew::format::Json;
 use yew::prelude::*;
+use yew::services::timeout::{TimeoutService, TimeoutTask};
 use yew::services::{ConsoleService, IntervalService};
 
 use js_sys::Date;
 
-//use yew::services::storage::{Area, StorageService};
+mod adaptive;
+mod analysis;
+mod backup;
+mod broadcast;
+mod clicks;
+mod crash;
+mod daily;
+mod debug_log;
+mod download;
+mod duel;
+mod effects;
+mod ghost;
+mod heatmap;
+mod history;
+mod hot_seat;
+mod import;
+mod js_events;
+mod keys;
+mod knight_bonus;
+mod lesson;
+mod notes;
+mod power_ups;
+mod presets;
+mod profiles;
+mod protocol;
+mod race;
+mod robot;
+mod robot_log;
+mod route;
+mod session;
+mod sound;
+mod speed_drill;
+mod stopwatch;
+mod storage;
+mod stress;
+mod tab;
+mod toast;
+mod trail;
+mod trainer;
+mod turn_timer;
+mod zen;
 
-fn small_board() -> Board {
-    use rand::Rng;
-    let width = 10;
-    let height = 10;
-    let mines = 10;
+use analysis::Analysis;
+use backup::Backup;
+use broadcast::{Broadcast, BroadcastEvent};
+use clicks::Clicks;
+use debug_log::Kind;
+use duel::{Duel, DuelEvent};
+use effects::{Effect, Effects};
+use ghost::Ghost;
+use history::{GameRecord, History};
+use hot_seat::HotSeat;
+use import::Imported;
+use keys::{KeyAction, Keybindings, Press};
+use knight_bonus::Bonus;
+use lesson::Lesson;
+use notes::Draft;
+use power_ups::{PowerUp, PowerUps};
+use presets::{Difficulty, DifficultyPreset, Presets};
+use profiles::{Layout, Profile, Profiles, Settings};
+use protocol::PeerMessage;
+use race::{Finish, Run};
+use robot::{AutoOpen, Challenge, Robot, Step};
+use robot_log::{Entry, RobotLog};
+use route::Route;
+use session::{Session, SessionEvent, RECAP_EVERY};
+use sound::{Audio, Sound, Speaker};
+use speed_drill::{SpeedDrill, SpeedRecords};
+use stopwatch::{Stopwatch, Times, SPLITS};
+use storage::StorageBackend;
+use stress::Stress;
+use toast::Toasts;
+use trainer::Trainer;
+use turn_timer::{Expiry, TurnTimer};
+use zen::Zen;
 
-    let board = create_board(width, height, mines, |x, y| {
-        rand::thread_rng().gen_range(x, y)
-    });
+// SAFETY: the app never spawns threads, so nothing allocates concurrently.
+#[cfg(all(feature = "small-allocator", target_arch = "wasm32"))]
+#[global_allocator]
+static ALLOCATOR: lol_alloc::AssumeSingleThreaded<lol_alloc::FreeListAllocator> =
+    unsafe { lol_alloc::AssumeSingleThreaded::new(lol_alloc::FreeListAllocator::new()) };
 
-    numbers_on_board(board)
+// Long enough for the highlight's pulse to play twice.
+const HIGHLIGHT_FOR: Duration = Duration::from_millis(1600);
+// How long a finished zen board takes to fade out before the next one.
+const ZEN_NEXT_AFTER: Duration = Duration::from_millis(1200);
+// Just long enough to see how a drill board ended before the next one.
+const SPEED_DRILL_NEXT_AFTER: Duration = Duration::from_millis(400);
+// The seconds the auto-restart setting goes through. 0 is off.
+const AUTO_RESTART_AFTER: [u32; 4] = [0, 3, 5, 10];
+// Long enough for a new board to be drawn before 🤖 looks it over.
+const CHECK_GUESSING_AFTER: Duration = Duration::from_millis(50);
+// How long after a move the certain moves are counted, so the move shows
+// first.
+const COUNT_DEDUCTIONS_AFTER: Duration = Duration::from_millis(50);
+// The opening book's boards: enough for the ranking to settle, few enough to
+// build between frames on Expert.
+const OPENING_BOOK_SEEDS: u64 = 200;
+const OPENINGS_SHOWN: usize = 5;
+
+// Reads <meta name="{name}" content="..."> from the page, which is how
+// self-hosters point the app at their own servers.
+fn page_setting(name: &str) -> Option<String> {
+    let document = web_sys::window()?.document()?;
+    let value = document
+        .query_selector(&format!("meta[name={}]", name))
+        .ok()??
+        .get_attribute("content")?;
+    if value.is_empty() {
+        None
+    } else {
+        Some(value)
+    }
+}
+
+// A random number for picking things that don't have to be reproducible.
+fn random() -> u64 {
+    (js_sys::Math::random() * u32::MAX as f64) as u64
+}
+
+// What the print legend shows `dx`, `dy` away from its centre.
+fn print_legend_cell(adjacency: Adjacency, dx: i32, dy: i32) -> &'static str {
+    let (dx, dy) = (dx.abs(), dy.abs());
+    let counted = match adjacency {
+        Adjacency::King => dx.max(dy) == 1,
+        Adjacency::Knight => dx.min(dy) == 1 && dx.max(dy) == 2,
+    };
+    match (dx, dy, adjacency) {
+        (0, 0, Adjacency::King) => "♚",
+        (0, 0, Adjacency::Knight) => "♞",
+        _ if counted => "●",
+        _ => "",
+    }
+}
+
+// The reference's pattern for one of 🤖's moves: what a single number
+// proves, or for moves none proves alone, numbers weighed together.
+fn pattern_for(m: &Move, step: Option<&Step>) -> Pattern {
+    match (m, step) {
+        (Move::Flag(_), Some(_)) => Pattern::Filled,
+        (_, Some(_)) => Pattern::Satisfied,
+        (_, None) => Pattern::Subset,
+    }
+}
+
+fn render_pattern_link(pattern: Pattern) -> Html {
+    html! {
+        <a class="pattern-link" href=Route::Patterns(Some(pattern)).to_hash()>
+            { format!("📖 {}", pattern.name()) }
+        </a>
+    }
+}
+
+// A mini board with the flashing cell, and the numbers that decide it picked
+// out on hover.
+fn render_reference(reference: &Reference, active: bool) -> Html {
+    let board = &reference.board;
+    let sources = reference.sources();
+    let cell = |p: Point| {
+        let (class, text) = match board.at(&p) {
+            Some(Number {
+                state: Open,
+                count: 0,
+            }) => ("open", String::new()),
+            Some(Number { state: Open, count }) => ("open", count.to_string()),
+            Some(Number { state: Flagged, .. }) | Some(Mine { state: Flagged }) => {
+                ("closed", String::from("🚩"))
+            }
+            _ => ("closed", String::new()),
+        };
+        let mark = if p == reference.target {
+            " pattern-target"
+        } else if sources.contains(&p) {
+            " pattern-source"
+        } else {
+            ""
+        };
+        html! { <td class=format!("{}{}", class, mark)>{ text }</td> }
+    };
+    html! {
+        <div class=if active { "pattern-card active" } else { "pattern-card" }>
+            <p class="pattern-name">
+                { &reference.name }
+                <span class="pattern-kind">{ reference.pattern.name() }</span>
+            </p>
+            <table class="pattern-board">
+                { for (0..board.height).map(|y| html! {
+                    <tr>{ for (0..board.width).map(|x| cell(Point::new(x, y))) }</tr>
+                }) }
+            </table>
+            <p class="pattern-why">
+                { format!("It's {}. {}", trainer::answer_name(reference.answer), reference.note) }
+            </p>
+        </div>
+    }
+}
+
+enum Screen {
+    Game,
+    History,
+    Profiles,
+    Duel,
+    Broadcast,
+    Import,
+    Print,
+    Featured,
+    Learn,
+    Race,
+    Replay {
+        index: usize,
+        step: usize,
+        board: Board,
+    },
+    Lesson {
+        lesson: Lesson,
+        step: usize,
+    },
+    // A drill, and the answer the player picked once they have.
+    Trainer {
+        drill: Drill,
+        picked: Option<Answer>,
+    },
+    // The pattern reference, with one pattern's examples picked out.
+    Patterns {
+        pattern: Option<Pattern>,
+    },
+    Analysis {
+        analysis: Analysis,
+    },
+    Stress,
+    // The keybindings, with the action whose new key is being waited for.
+    Keys {
+        capturing: Option<KeyAction>,
+    },
+}
+
+struct Model {
+    link: ComponentLink<Self>,
+    storage: Box<dyn StorageBackend>,
+    state: State,
+    seed_input: String,
+    stopwatch: Stopwatch,
+    // Whether the page is in the background, which pauses game time.
+    hidden: bool,
+    // The fastest past win on this board, raced while the game is on.
+    ghost: Option<Ghost>,
+    _ghost_tick: Option<yew::services::interval::IntervalTask>,
+    history: History,
+    profiles: Profiles,
+    presets: Presets,
+    duel: Option<Duel>,
+    lobby: Lobby,
+    broadcast: Option<Broadcast>,
+    spectator: Spectator,
+    importer: Importer,
+    stress: Stress,
+    _stress_rendered: Option<TimeoutTask>,
+    dragging: bool,
+    // The seeded race the player is in, if any.
+    race: Option<Run>,
+    race_input: String,
+    trainer: Trainer,
+    speaker: Speaker,
+    // Goes up with each win, to play the confetti again.
+    celebrations: u64,
+    // The guided scenario being played, and where it is in the list.
+    scenario: Option<(usize, ScenarioRun)>,
+    // The knight's path round played on a won board, and its clock.
+    bonus: Option<Bonus>,
+    // The note being written, while clicks pick cells for one rather than
+    // play.
+    draft: Option<Draft>,
+    _bonus_tick: Option<yew::services::interval::IntervalTask>,
+    robot: Robot,
+    // Cells that would have been better to open than the one that lost the
+    // game, shown until the next one starts.
+    better_than_loss: Vec<Point>,
+    // Whether 🤖 found the board can't be won without guessing, once it's
+    // looked. Boards picked for a challenge are already known.
+    needs_guess: Option<bool>,
+    _check_guessing: Option<TimeoutTask>,
+    // How many moves 🤖 can prove from here, for the deductions counter,
+    // once it's counted them since the last move.
+    deductions: Option<usize>,
+    _count_deductions: Option<TimeoutTask>,
+    // The best first clicks for a difficulty, over OPENING_BOOK_SEEDS of its
+    // boards, shown while a board of it hasn't been started.
+    openings: Option<(Difficulty, Vec<Opening>)>,
+    // Cells a robot or assist move just changed, and the numbers that
+    // proved it, pulsed for a moment so the move can be followed.
+    highlight: Vec<(Point, &'static str)>,
+    _clear_highlight: Option<TimeoutTask>,
+    // A duel's power-ups: those earned, a decoy flag the opponent put on
+    // the board and, while the opponent has it frozen, when it thaws.
+    power_ups: PowerUps,
+    decoy: Option<Point>,
+    _clear_decoy: Option<TimeoutTask>,
+    _thaw: Option<TimeoutTask>,
+    // Zen mode's session, and the next board it's about to bring in.
+    zen: Option<Zen>,
+    _zen_next: Option<TimeoutTask>,
+    // The speed drill under way and the next board it's about to bring
+    // in, and the best drills so far.
+    speed_drill: Option<SpeedDrill>,
+    _speed_drill_next: Option<TimeoutTask>,
+    speed_records: SpeedRecords,
+    // The seconds left before a lost board makes way for a new one, and
+    // the clock counting them down.
+    restart_in: Option<u32>,
+    _restart_tick: Option<yew::services::interval::IntervalTask>,
+    // The clock on each move in a match with a time limit, from the first
+    // move on.
+    turn_timer: Option<TurnTimer>,
+    _turn_tick: Option<yew::services::interval::IntervalTask>,
+    // Whether this board was already played, and the game goes in the
+    // retries rather than the stats.
+    retrying: bool,
+    // What's happened since the page was opened, and the recap showing.
+    session: Session,
+    recap: Option<Recap>,
+    robot_log: RobotLog,
+    // The player's clicks in the current game, and where they were.
+    clicks: Clicks,
+    clicked: Vec<Point>,
+    pad: Pad,
+    toasts: Toasts,
+    board_changes: BoardChanges,
+    screen: Screen,
+    // Fires when the next daily board comes out.
+    _new_day: TimeoutTask,
+}
+
+// Which session the recap is of: this one, or the one the page was left
+// with last time.
+enum Recap {
+    Current,
+    Last(Session),
+}
+
+// The on-screen d-pad: a cursor over the board that its buttons move, dig
+// under and flag, so small cells don't have to be hit with a finger.
+struct Pad {
+    shown: bool,
+    cursor: Point,
+    // Whether the keyboard's been used, which shows the cursor without the
+    // pad.
+    keyboard: bool,
+}
+
+impl Default for Pad {
+    // Shown by default on touch screens only.
+    fn default() -> Pad {
+        let touch = web_sys::window().is_some_and(|w| w.navigator().max_touch_points() > 0);
+        Pad {
+            shown: touch,
+            cursor: Point::new(0, 0),
+            keyboard: false,
+        }
+    }
+}
+
+// What the duel screen shows while a match is set up and played.
+#[derive(Default)]
+struct Lobby {
+    signal: String,
+    input: String,
+    connected: bool,
+    opponent: Option<(f64, BoardState)>,
+    error: Option<String>,
+    // The seconds for each move, and what running out does, as the host
+    // set them.
+    turn_rules: (u32, Expiry),
+}
+
+// The game being watched, as rebuilt from a broadcast.
+#[derive(Default)]
+struct Spectator {
+    input: String,
+    game: Option<(Replay, Board)>,
+    in_sync: bool,
+    error: Option<String>,
+}
+
+// The text pasted into the import screen, and why it didn't load. The
+// start cell and report are for making the board solvable.
+#[derive(Default)]
+struct Importer {
+    input: String,
+    error: Option<String>,
+    start: String,
+    report: Option<String>,
+}
+
+enum Msg {
+    ToggleDifficulty,
+    ToggleMode,
+    UpdateBoard { point: Point, action: Action },
+    RunRobot,
+    RunRobotSteps { count: usize },
+    ClearHighlight,
+    HoverLogEntry { index: Option<usize> },
+    SelectStrategy { index: usize },
+    ToggleLesson,
+    ToggleTrainer,
+    AnswerDrill { answer: Answer },
+    NextDrill,
+    EstimateWinChance,
+    Analyse,
+    CycleAnalysisTool,
+    AnalysisStep,
+    AnalysisOdds,
+    ToggleMistakeWarnings,
+    SetLayout { layout: Layout },
+    SetAudio { audio: Audio },
+    ToggleFullscreen,
+    ToggleCompact,
+    ToggleSplits,
+    CycleTiming,
+    PageHidden { hidden: bool },
+    GhostTick,
+    CycleAutoOpen,
+    ToggleAutoFlag,
+    ToggleSecondChance,
+    ToggleCasual,
+    CycleCascade,
+    ToggleAdaptive,
+    CycleChallenge,
+    StepLesson { step: usize },
+    OpenAllUnflagged,
+    UpdateSeedInput { value: String },
+    NewGameFromSeed,
+    DailyGame,
+    ToggleDailyReminder,
+    ReminderPermission { granted: bool },
+    NewDay,
+    ToggleHistory,
+    DownloadLog,
+    OpenReplay { index: usize },
+    StepReplay { step: usize },
+    RestartGame { index: usize },
+    ToggleProfiles,
+    SwitchProfile { index: usize },
+    AddProfile,
+    AddPreset,
+    ExportBoard,
+    ExportStats,
+    ExportBackup,
+    PickFile { file: Option<web_sys::File> },
+    CopyPosition,
+    TogglePrint,
+    ToggleFeatured,
+    TogglePatterns,
+    PlayFeatured { index: usize },
+    ToggleLearn,
+    ToggleOpenings,
+    ToggleRecap,
+    CycleRecapEvery,
+    CopyRecap,
+    RecapCopied { ok: bool },
+    PlayScenario { index: usize },
+    ScenarioNext,
+    ToggleRace,
+    UpdateRaceInput { value: String },
+    JoinRace,
+    NewRace,
+    PlayRace,
+    ExportRace,
+    LeaveRace,
+    Navigate { hash: String },
+    Print,
+    PositionCopied { ok: bool },
+    ToggleImport,
+    UpdateImportInput { value: String },
+    ImportBoard,
+    UpdateRepairStart { value: String },
+    RepairBoard,
+    UpdateStressWidth { value: String },
+    UpdateStressHeight { value: String },
+    UpdateStressMines { value: String },
+    RunStress,
+    StressRendered,
+    DragFile { over: bool },
+    DropFile { event: DragEvent },
+    FileLoaded { text: Option<String> },
+    ToggleHotSeat,
+    CycleTurnLimit,
+    CycleTurnExpiry,
+    TurnTick,
+    ToggleZen,
+    ZenNext,
+    ToggleSpeedDrill,
+    CycleSpeedDrill,
+    SpeedDrillNext,
+    CycleAutoRestart,
+    ToggleAutoRetry,
+    ToggleTrail,
+    RestartTick,
+    CancelRestart,
+    RetryBoard,
+    StartBonus,
+    ToggleNotes,
+    CycleNoteTag,
+    UpdateNote { value: String },
+    AddNote,
+    SuggestNotes,
+    RemoveNote { index: usize },
+    BonusTick,
+    CheckGuessing,
+    CountDeductions,
+    ToggleDeductions,
+    Reroll,
+    UsePowerUp { index: usize },
+    ClearDecoy,
+    Thaw,
+    ToggleDuel,
+    HostDuel,
+    UpdateDuelInput { value: String },
+    ConnectDuel,
+    LeaveDuel,
+    Duel { event: DuelEvent },
+    ToggleBroadcastScreen,
+    StartBroadcast,
+    UpdateWatchInput { value: String },
+    Watch,
+    StopBroadcast,
+    Broadcast { event: BroadcastEvent },
+    TogglePad,
+    MoveCursor { dx: i32, dy: i32 },
+    DigAtCursor,
+    FlagAtCursor,
+    KeyPressed { press: Press },
+    ToggleKeys,
+    CaptureKey { action: KeyAction },
+    ResetKeys,
+    DismissToast { id: u64 },
+    StoragePulled { key: String, value: String },
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct State {
+    difficulty: Difficulty,
+    #[serde(flatten)]
+    game: GameController,
+    // Set while two players take turns on this device.
+    #[serde(default)]
+    hot_seat: Option<HotSeat>,
+}
+
+//const KEY: &'static str = "jgpaiva.minesweeper.self";
+
+impl Component for Model {
+    type Message = Msg;
+    type Properties = ();
+    fn create(_: Self::Properties, link: ComponentLink<Self>) -> Self {
+        let mut storage = storage::default_backend();
+        for key in &[
+            history::KEY,
+            profiles::KEY,
+            presets::KEY,
+            race::KEY,
+            trainer::KEY,
+            speed_drill::KEY,
+        ] {
+            storage.pull(
+                key,
+                link.callback(|(key, value)| Msg::StoragePulled { key, value }),
+            );
+        }
+        //        let difficulty = {
+        //            if let Json(Ok(restored_model)) = storage.restore(KEY) {
+        //                restored_model
+        //            } else {
+        //
+        //            }
+        //        };
+        let profiles = Profiles::restore(storage.as_ref());
+        let presets = Presets::restore(storage.as_ref());
+        let Settings {
+            difficulty,
+            mode,
+            cascade,
+            ..
+        } = profiles.active().settings.clone();
+        let difficulty = presets.get(&difficulty).difficulty();
+        // The saved difficulty may not build, and then the game starts on
+        // the default one, which always does.
+        let built = presets
+            .board(&difficulty, None)
+            .map_err(|error| (difficulty.clone(), error));
+        let (difficulty, mut board) = match &built {
+            Ok(board) => (difficulty, board.clone()),
+            Err(_) => {
+                let default = Difficulty::default();
+                let board = presets.board(&default, None);
+                (default, board.expect("built-in difficulties build"))
+            }
+        };
+        board.cascade = cascade;
+        let state = State {
+            difficulty,
+            game: GameController::new(board, mode),
+            hot_seat: None,
+        };
+        let history = History::restore(storage.as_ref());
+        let race = Run::restore(storage.as_ref());
+        let trainer = Trainer::restore(storage.as_ref());
+        let speed_records = SpeedRecords::restore(storage.as_ref());
+        let recap = Session::take_last(storage.as_mut()).map(Recap::Last);
+        let new_day = Model::schedule_new_day(&link);
+        stopwatch::on_hidden_change(link.callback(|hidden| Msg::PageHidden { hidden }));
+        route::on_change(link.callback(|hash| Msg::Navigate { hash }));
+        keys::on_key_down(link.callback(|press| Msg::KeyPressed { press }));
+        let mut model = Self {
+            link,
+            storage,
+            state,
+            seed_input: String::new(),
+            stopwatch: Stopwatch::default(),
+            hidden: false,
+            ghost: None,
+            _ghost_tick: None,
+            history,
+            profiles,
+            presets,
+            duel: None,
+            lobby: Lobby::default(),
+            broadcast: None,
+            spectator: Spectator::default(),
+            importer: Importer::default(),
+            stress: Stress::default(),
+            _stress_rendered: None,
+            dragging: false,
+            race,
+            trainer,
+            speaker: Speaker::default(),
+            celebrations: 0,
+            scenario: None,
+            bonus: None,
+            draft: None,
+            _bonus_tick: None,
+            race_input: String::new(),
+            robot: Robot::new(),
+            better_than_loss: vec![],
+            needs_guess: None,
+            _check_guessing: None,
+            deductions: None,
+            _count_deductions: None,
+            openings: None,
+            highlight: vec![],
+            _clear_highlight: None,
+            power_ups: PowerUps::default(),
+            decoy: None,
+            _clear_decoy: None,
+            _thaw: None,
+            zen: None,
+            _zen_next: None,
+            speed_drill: None,
+            _speed_drill_next: None,
+            speed_records,
+            restart_in: None,
+            _restart_tick: None,
+            turn_timer: None,
+            _turn_tick: None,
+            retrying: false,
+            session: Session::default(),
+            recap,
+            robot_log: RobotLog::default(),
+            clicks: Clicks::default(),
+            clicked: vec![],
+            pad: Pad::default(),
+            toasts: Toasts::default(),
+            board_changes: BoardChanges::default(),
+            screen: Screen::Game,
+            _new_day: new_day,
+        };
+        model.apply_controls();
+        if let Err((difficulty, error)) = built {
+            model.cant_build(&difficulty, &error);
+        }
+        model.navigate(&route::current());
+        route::replace(&model.route());
+        model
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        let was_ready = self.state.game.board.state == Ready;
+        let was_over = matches!(self.state.game.board.state, Won | Failed);
+        let board_state = self.state.game.board.state.clone();
+        let screen = mem::discriminant(&self.screen);
+        let navigating = matches!(msg, Msg::Navigate { .. });
+        match msg {
+            Msg::ToggleDifficulty => self.toggle_difficulty(),
+            Msg::ToggleMode => self.toggle_mode(),
+            Msg::UpdateBoard { point, action } => self.update_board(point, action),
+            Msg::RunRobot => self.run_robot(),
+            Msg::RunRobotSteps { count } => self.run_robot_steps(count),
+            Msg::ClearHighlight => self.clear_highlight(),
+            Msg::HoverLogEntry { index } => self.hover_log_entry(index),
+            Msg::SelectStrategy { index } => self.select_strategy(index),
+            Msg::ToggleLesson => self.toggle_lesson(),
+            Msg::ToggleTrainer => self.toggle_trainer(),
+            Msg::AnswerDrill { answer } => self.answer_drill(answer),
+            Msg::NextDrill => self.next_drill(),
+            Msg::EstimateWinChance => self.estimate_win_chance(),
+            Msg::Analyse => self.analyse(),
+            Msg::CycleAnalysisTool => {
+                if let Screen::Analysis { analysis } = &mut self.screen {
+                    analysis.tool = analysis.tool.next();
+                }
+            }
+            Msg::AnalysisStep => self.analysis_step(),
+            Msg::AnalysisOdds => self.analysis_odds(),
+            Msg::ToggleMistakeWarnings => self.toggle_mistake_warnings(),
+            Msg::SetLayout { layout } => self.set_layout(layout),
+            Msg::SetAudio { audio } => self.set_audio(audio),
+            Msg::ToggleFullscreen => self.toggle_fullscreen(),
+            Msg::ToggleCompact => self.toggle_compact(),
+            Msg::ToggleSplits => self.toggle_splits(),
+            Msg::CycleTiming => self.cycle_timing(),
+            Msg::PageHidden { hidden } => {
+                self.hidden = hidden;
+                // The page may be on its way out, so keep the session for
+                // a recap next time.
+                if hidden {
+                    self.session.store(self.storage.as_mut());
+                }
+            }
+            Msg::GhostTick => return self.advance_ghost(),
+            Msg::CycleAutoOpen => self.cycle_auto_open(),
+            Msg::ToggleAutoFlag => self.toggle_auto_flag(),
+            Msg::ToggleSecondChance => self.toggle_second_chance(),
+            Msg::ToggleCasual => self.toggle_casual(),
+            Msg::CycleCascade => self.cycle_cascade(),
+            Msg::ToggleAdaptive => self.toggle_adaptive(),
+            Msg::CycleChallenge => self.cycle_challenge(),
+            Msg::StepLesson { step } => self.step_lesson(step),
+            Msg::OpenAllUnflagged => self.open_all_unflagged(),
+            Msg::UpdateSeedInput { value } => self.seed_input = value,
+            Msg::NewGameFromSeed => self.new_game_from_seed(),
+            Msg::DailyGame => self.daily_game(),
+            Msg::ToggleDailyReminder => self.toggle_daily_reminder(),
+            Msg::ReminderPermission { granted } => self.set_daily_reminder(granted),
+            Msg::NewDay => self.new_day(),
+            Msg::ToggleHistory => self.toggle_history(),
+            Msg::DownloadLog => self.download_log(),
+            Msg::OpenReplay { index } => self.open_replay(index, 0),
+            Msg::StepReplay { step } => self.step_replay(step),
+            Msg::RestartGame { index } => self.restart_game(index),
+            Msg::ToggleProfiles => self.toggle_profiles(),
+            Msg::SwitchProfile { index } => self.switch_profile(index),
+            Msg::AddProfile => self.add_profile(),
+            Msg::AddPreset => self.add_preset(),
+            Msg::ExportBoard => self.export_board(),
+            Msg::ExportStats => self.export_stats(),
+            Msg::ExportBackup => self.export_backup(),
+            Msg::PickFile { file } => self.pick_file(file),
+            Msg::CopyPosition => self.copy_position(),
+            Msg::TogglePrint => self.toggle_print(),
+            Msg::ToggleFeatured => self.toggle_featured(),
+            Msg::TogglePatterns => self.toggle_patterns(),
+            Msg::PlayFeatured { index } => self.play_featured(index),
+            Msg::ToggleLearn => self.toggle_learn(),
+            Msg::ToggleOpenings => self.toggle_openings(),
+            Msg::ToggleRecap => self.toggle_recap(),
+            Msg::CycleRecapEvery => self.cycle_recap_every(),
+            Msg::CopyRecap => self.copy_recap(),
+            Msg::RecapCopied { ok } => self.toast(if ok {
+                "📋 Recap copied"
+            } else {
+                "Couldn't copy the recap"
+            }),
+            Msg::PlayScenario { index } => self.play_scenario(index),
+            Msg::ScenarioNext => self.scenario_next(),
+            Msg::ToggleRace => self.toggle_race(),
+            Msg::UpdateRaceInput { value } => self.race_input = value,
+            Msg::JoinRace => self.join_race(),
+            Msg::NewRace => self.new_race(),
+            Msg::PlayRace => self.play_race(),
+            Msg::ExportRace => self.export_race(),
+            Msg::LeaveRace => self.leave_race(),
+            Msg::Navigate { hash } => self.navigate(&hash),
+            Msg::Print => {
+                if let Some(window) = web_sys::window() {
+                    let _ = window.print();
+                }
+            }
+            Msg::PositionCopied { ok } => self.toast(if ok {
+                "📋 Position copied"
+            } else {
+                "Couldn't copy the position"
+            }),
+            Msg::ToggleImport => self.toggle_import(),
+            Msg::UpdateImportInput { value } => self.importer.input = value,
+            Msg::ImportBoard => self.import_board(),
+            Msg::UpdateRepairStart { value } => self.importer.start = value,
+            Msg::RepairBoard => self.repair_board(),
+            Msg::UpdateStressWidth { value } => self.stress.width = value,
+            Msg::UpdateStressHeight { value } => self.stress.height = value,
+            Msg::UpdateStressMines { value } => self.stress.mines = value,
+            Msg::RunStress => self.run_stress(),
+            Msg::StressRendered => {
+                self._stress_rendered = None;
+                self.stress.rendered();
+            }
+            Msg::DragFile { over } => self.dragging = over,
+            Msg::DropFile { event } => self.drop_file(event),
+            Msg::FileLoaded { text } => self.file_loaded(text),
+            Msg::ToggleHotSeat => self.toggle_hot_seat(),
+            Msg::CycleTurnLimit => self.cycle_turn_limit(),
+            Msg::CycleTurnExpiry => self.cycle_turn_expiry(),
+            Msg::TurnTick => self.turn_tick(),
+            Msg::ToggleZen => self.toggle_zen(),
+            Msg::ZenNext => self.new_game(self.state.difficulty.clone(), None),
+            Msg::ToggleSpeedDrill => self.toggle_speed_drill(),
+            Msg::CycleSpeedDrill => self.cycle_speed_drill(),
+            Msg::SpeedDrillNext => self.next_speed_drill_board(),
+            Msg::CycleAutoRestart => self.cycle_auto_restart(),
+            Msg::ToggleAutoRetry => self.toggle_auto_retry(),
+            Msg::ToggleTrail => self.toggle_trail(),
+            Msg::ToggleDeductions => self.toggle_deductions(),
+            Msg::RetryBoard => self.retry_board(),
+            Msg::RestartTick => self.restart_tick(),
+            Msg::CancelRestart => {
+                self.restart_in = None;
+                self._restart_tick = None;
+            }
+            Msg::StartBonus => self.start_bonus(),
+            Msg::ToggleNotes => self.toggle_notes(),
+            Msg::CycleNoteTag => {
+                if let Some(draft) = &mut self.draft {
+                    draft.tag = draft.tag.next();
+                }
+            }
+            Msg::UpdateNote { value } => {
+                if let Some(draft) = &mut self.draft {
+                    draft.note = value;
+                }
+            }
+            Msg::AddNote => self.add_note(),
+            Msg::SuggestNotes => self.suggest_notes(),
+            Msg::RemoveNote { index } => self.remove_note(index),
+            Msg::BonusTick => self.bonus_tick(),
+            Msg::CheckGuessing => self.check_guessing(),
+            Msg::CountDeductions => {
+                self._count_deductions = None;
+                self.deductions = self.robot.count_certain_moves(&self.state.game.board);
+            }
+            Msg::Reroll => self.new_game(self.state.difficulty.clone(), None),
+            Msg::UsePowerUp { index } => self.use_power_up(index),
+            Msg::ClearDecoy => self.clear_decoy(),
+            Msg::Thaw => self._thaw = None,
+            Msg::ToggleDuel => self.toggle_duel(),
+            Msg::HostDuel => self.host_duel(),
+            Msg::UpdateDuelInput { value } => self.lobby.input = value,
+            Msg::ConnectDuel => self.connect_duel(),
+            Msg::LeaveDuel => self.leave_duel(),
+            Msg::Duel { event } => self.duel_event(event),
+            Msg::ToggleBroadcastScreen => self.toggle_broadcast_screen(),
+            Msg::StartBroadcast => self.start_broadcast(),
+            Msg::UpdateWatchInput { value } => self.spectator.input = value,
+            Msg::Watch => self.watch(),
+            Msg::StopBroadcast => self.stop_broadcast(),
+            Msg::Broadcast { event } => self.broadcast_event(event),
+            Msg::TogglePad => self.toggle_pad(),
+            Msg::MoveCursor { dx, dy } => self.move_cursor(dx, dy),
+            Msg::DigAtCursor => self.update_board(self.pad.cursor, Action::Dig),
+            Msg::FlagAtCursor => self.update_board(self.pad.cursor, Action::Flag),
+            Msg::KeyPressed { press } => self.key_pressed(press),
+            Msg::ToggleKeys => self.toggle_keys(),
+            Msg::CaptureKey { action } => self.capture_key(action),
+            Msg::ResetKeys => {
+                self.save_keys(Keybindings::default());
+                self.toast("⌨️ Every key is back on its default");
+            }
+            Msg::DismissToast { id } => self.toasts.dismiss(id),
+            Msg::StoragePulled { key, value } => self.storage_pulled(key, value),
+        }
+        if was_ready && self.state.game.board.state != Ready {
+            self.stopwatch.start();
+            if self.ghost.is_some() {
+                let tick = self.link.callback(|_| Msg::GhostTick);
+                self._ghost_tick =
+                    Some(IntervalService::new().spawn(Duration::from_millis(100), tick));
+            }
+        }
+        // Game time only counts while the board is on screen.
+        self.stopwatch
+            .pause(self.hidden || !matches!(self.screen, Screen::Game));
+        if matches!(self.state.game.board.state, Playing | Won) {
+            self.stopwatch.reach(self.state.game.board.stats().progress);
+        }
+        if !was_over && matches!(self.state.game.board.state, Won | Failed) {
+            self.stopwatch.stop();
+            self._ghost_tick = None;
+            self.record_game();
+        }
+        // Only moves on the game screen know exactly which cells they
+        // changed. Anything else redraws the whole board.
+        if !matches!(self.screen, Screen::Game)
+            || mem::discriminant(&self.screen) != screen
+            || self.state.game.board.state != board_state
+        {
+            self.board_changes = self.board_changes.next(None);
+        }
+        // Going back or forward only follows the history, rather than
+        // adding to it.
+        if navigating {
+            route::replace(&self.route());
+        } else {
+            route::push(&self.route());
+        }
+        crash::remember(&self.state);
+        true
+    }
+
+    fn view(&self) -> Html {
+        html! {
+            <body
+             class={self.render_body_class()}
+             style={self.render_body_style()}
+             ondragenter=self.link.callback(|_| Msg::DragFile { over: true })>
+                <div id="difficulty_button_placeholder" class="flex-container">
+                    <div
+                     id="difficulty-button"
+                     class="clickable item"
+                     onclick=self.link.callback(|_| Msg::ToggleDifficulty) >
+                        { self.render_difficulty() }
+                    </div>
+                    <div
+                     id="adaptive-button"
+                     class=self.render_adaptive_class()
+                     title="Adaptive: more mines as you win, fewer as you lose"
+                     onclick=self.link.callback(|_| Msg::ToggleAdaptive) >
+                        { "🎯" }
+                    </div>
+                    <div
+                     id="mode-button"
+                     class={self.render_mode_class()}
+                     onclick=self.link.callback(|_| Msg::ToggleMode) >
+                        { self.render_mode() }
+                    </div>
+                    <div
+                     id="robot-button"
+                     class={self.render_mode_class()}
+                     onclick=self.link.callback(|_| Msg::RunRobot) >
+                        { self.render_robot()}
+                    </div>
+                    <div
+                     id="open-all-button"
+                     class={self.render_open_all_class()}
+                     onclick=self.link.callback(|_| Msg::OpenAllUnflagged) >
+                        { self.render_open_all() }
+                    </div>
+                    <div
+                     id="pad-button"
+                     class="clickable item"
+                     onclick=self.link.callback(|_| Msg::TogglePad) >
+                        { "🕹️" }
+                    </div>
+                    <div
+                     id="history-button"
+                     class="clickable item"
+                     onclick=self.link.callback(|_| Msg::ToggleHistory) >
+                        { self.render_history_button() }
+                    </div>
+                    <div
+                     id="profile-button"
+                     class="clickable item"
+                     onclick=self.link.callback(|_| Msg::ToggleProfiles) >
+                        { &self.profiles.active().avatar }
+                    </div>
+                    <div
+                     id="duel-button"
+                     class="clickable item"
+                     onclick=self.link.callback(|_| Msg::ToggleDuel) >
+                        { "⚔️" }
+                    </div>
+                    { self.render_hot_seat() }
+                    <div
+                     id="broadcast-button"
+                     class="clickable item"
+                     onclick=self.link.callback(|_| Msg::ToggleBroadcastScreen) >
+                        { "📡" }
+                    </div>
+                    <div
+                     id="fullscreen-button"
+                     class="clickable item"
+                     title="Fullscreen"
+                     onclick=self.link.callback(|_| Msg::ToggleFullscreen) >
+                        { "⛶" }
+                    </div>
+                    <div
+                     id="compact-button"
+                     class="clickable item"
+                     title="Compact mode"
+                     onclick=self.link.callback(|_| Msg::ToggleCompact) >
+                        { "🗜️" }
+                    </div>
+                    { self.render_clock() }
+                </div>
+                { self.render_screen() }
+                { self.render_toasts() }
+                { self.render_drop_target() }
+            </body>
+        }
+    }
 }
 
-fn medium_board() -> Board {
-    use rand::Rng;
-    let width = 16;
-    let height = 16;
-    let mines = 40;
+impl Model {
+    // Difficulties that can't build a board are stepped past, so one bad
+    // preset doesn't stop the toggle there.
+    fn toggle_difficulty(&mut self) {
+        let mut new_difficulty = if self.state.game.is_fresh() {
+            self.presets.next(&self.state.difficulty)
+        } else {
+            self.state.difficulty.clone()
+        };
+        for _ in 0..self.presets.iter().count() {
+            match self.try_new_game(new_difficulty.clone(), None) {
+                Ok(()) => break,
+                Err(error) => {
+                    self.cant_build(&new_difficulty, &error);
+                    new_difficulty = self.presets.next(&new_difficulty);
+                }
+            }
+        }
+        self.save_settings();
+    }
+
+    fn new_game(&mut self, difficulty: Difficulty, seed: Option<u64>) {
+        if let Err(error) = self.try_new_game(difficulty.clone(), seed) {
+            self.cant_build(&difficulty, &error);
+        }
+    }
+
+    // Boards without a seed of their own are picked for the challenge, and
+    // get adaptive difficulty's mines. The game goes on as it was if the
+    // board can't be built.
+    fn try_new_game(
+        &mut self,
+        difficulty: Difficulty,
+        seed: Option<u64>,
+    ) -> Result<(), BuildBoardError> {
+        let difficulty = self.presets.get(&difficulty).difficulty();
+        let difficulty = match seed {
+            None if self.profiles.active().settings.adaptive => {
+                self.adaptive_difficulty(difficulty)
+            }
+            _ => difficulty,
+        };
+        let mut known = None;
+        let built = match seed {
+            Some(seed) => self.presets.board(&difficulty, Some(seed)),
+            None => {
+                let challenge = self.profiles.active().settings.challenge;
+                let presets = &self.presets;
+                let (board, rated) = self
+                    .robot
+                    .rated_board(challenge, |seed| presets.board(&difficulty, seed))?;
+                if !rated {
+                    self.toast(&format!(
+                        "🎚️ No {} {} board turned up, so this one isn't",
+                        challenge.name(),
+                        difficulty.0
+                    ));
+                } else if challenge != Challenge::Any {
+                    known = Some(challenge == Challenge::Brutal);
+                }
+                Ok(board)
+            }
+        };
+        let mut board = built?;
+        board.cascade = self.profiles.active().settings.cascade;
+        debug_log::record(
+            Kind::State,
+            format!("New {} game, seed {}", difficulty.0, board.seed.unwrap()),
+        );
+        self.state.difficulty = difficulty;
+        self.load_board(board);
+        if known.is_some() {
+            self.needs_guess = known;
+            self._check_guessing = None;
+        }
+        Ok(())
+    }
+
+    // Runs the solver over the board's layout a moment after it's shown.
+    // Only the start of the game counts, so moves since don't matter.
+    fn check_guessing(&mut self) {
+        self._check_guessing = None;
+        self.needs_guess = self.robot.needs_guessing(&self.state.game.board);
+    }
+
+    fn load_board(&mut self, board: Board) {
+        self.state.game.load(board);
+        self.state.hot_seat = self.state.hot_seat.as_ref().map(|_| HotSeat::default());
+        self.stopwatch = Stopwatch::default();
+        self._zen_next = None;
+        self._speed_drill_next = None;
+        self.restart_in = None;
+        self._restart_tick = None;
+        self.turn_timer = None;
+        self._turn_tick = None;
+        self.retrying = false;
+        // There's no racing in zen mode.
+        self.ghost = self
+            .state
+            .game
+            .replay
+            .as_ref()
+            .filter(|_| self.zen.is_none())
+            .and_then(|replay| self.history.fastest_win(replay))
+            .and_then(Ghost::new);
+        self._ghost_tick = None;
+        if let Some(ghost) = &self.ghost {
+            let text = format!(
+                "👻 Racing your best on this board: {}s",
+                stopwatch::seconds(ghost.finish_ms())
+            );
+            self.toast(&text);
+        }
+        self.screen = Screen::Game;
+        self.better_than_loss = vec![];
+        self.scenario = None;
+        self.bonus = None;
+        self.draft = None;
+        self._bonus_tick = None;
+        self.needs_guess = None;
+        self.deductions = None;
+        self._count_deductions = None;
+        self._check_guessing = if self.robot.is_available() {
+            let check = self.link.callback(|_| Msg::CheckGuessing);
+            Some(TimeoutService::new().spawn(CHECK_GUESSING_AFTER, check))
+        } else {
+            None
+        };
+        self.power_ups = PowerUps::default();
+        self.decoy = None;
+        self._thaw = None;
+        self.highlight = vec![];
+        self.robot_log = RobotLog::default();
+        self.clicks = Clicks::default();
+        self.clicked = vec![];
+        self.pad.cursor = Point::new(
+            self.state.game.board.width / 2,
+            self.state.game.board.height / 2,
+        );
+        self.board_changes = self.board_changes.next(None);
+        self.send_checkpoint();
+        js_events::board_changed(None, &self.state.game.board);
+    }
+
+    // `difficulty`'s board with the mines adaptive difficulty is at, added
+    // as a preset of its own like an imported replay's.
+    fn adaptive_difficulty(&mut self, difficulty: Difficulty) -> Difficulty {
+        let base = self.presets.get(&difficulty).clone();
+        let density = self
+            .profiles
+            .active()
+            .stats
+            .density
+            .unwrap_or_else(|| adaptive::density(base.width, base.height, base.mines));
+        let mines = adaptive::mines(base.width, base.height, density);
+        if mines == base.mines {
+            return difficulty;
+        }
+        let preset = DifficultyPreset {
+            adjacency: base.adjacency,
+            accent: base.accent.clone(),
+            ..DifficultyPreset::custom(base.width, base.height, mines, base.symmetry, base.spacing)
+        };
+        let adapted = preset.difficulty();
+        if self.presets.add(preset).is_err() {
+            return difficulty;
+        }
+        self.presets.store(self.storage.as_mut());
+        adapted
+    }
+
+    fn new_game_from_seed(&mut self) {
+        match self.seed_input.trim().parse::<u64>() {
+            Ok(seed) => {
+                self.new_game(self.state.difficulty.clone(), Some(seed));
+                self.seed_input = String::new();
+            }
+            Err(_) => self.toast("Seeds are whole numbers"),
+        }
+    }
+
+    fn toggle_import(&mut self) {
+        self.screen = match self.screen {
+            Screen::Import => Screen::Game,
+            _ => Screen::Import,
+        }
+    }
+
+    fn import_board(&mut self) {
+        match import::parse_board(&self.importer.input) {
+            Ok(board) => {
+                self.importer = Importer::default();
+                self.load_imported(board);
+            }
+            Err(error) => self.importer.error = Some(error),
+        }
+    }
+
+    // Changes the pasted board's mines so it can be won from the start cell
+    // without guessing, and puts the result back in the box to load or
+    // copy.
+    fn repair_board(&mut self) {
+        self.importer.report = None;
+        let parsed = import::parse_board(&self.importer.input).and_then(|board| {
+            let start = import::parse_start(&self.importer.start, &board)?;
+            Ok((board, start))
+        });
+        let (board, start) = match parsed {
+            Ok(parsed) => parsed,
+            Err(error) => return self.importer.error = Some(error),
+        };
+        let (repaired, fixes) = match self.robot.repair(&board, &start) {
+            Some(repair) => repair,
+            None => {
+                return self.importer.error = Some(String::from(
+                    "🛠️ Couldn't make it solvable from there with a few changes",
+                ))
+            }
+        };
+        let cells = |added: bool| {
+            fixes
+                .iter()
+                .filter(|(_, a)| *a == added)
+                .map(|(p, _)| format!("{},{}", p.x + 1, p.y + 1))
+                .collect::<Vec<_>>()
+        };
+        let mut changes = vec![];
+        if !cells(true).is_empty() {
+            changes.push(format!("added mines at {}", cells(true).join(" ")));
+        }
+        if !cells(false).is_empty() {
+            changes.push(format!("took mines off {}", cells(false).join(" ")));
+        }
+        self.importer.report = Some(if changes.is_empty() {
+            format!("🛠️ Already solvable from {},{}", start.x + 1, start.y + 1)
+        } else {
+            format!(
+                "🛠️ Solvable from {},{}: {}",
+                start.x + 1,
+                start.y + 1,
+                changes.join(", ")
+            )
+        });
+        self.importer.input = repaired.to_string();
+        self.importer.error = None;
+    }
+
+    fn load_imported(&mut self, board: Board) {
+        debug_log::record(Kind::State, format!("Imported board\n{}", board));
+        self.load_board(board);
+    }
+
+    fn drop_file(&mut self, event: DragEvent) {
+        self.dragging = false;
+        let loaded = self.link.callback(|text| Msg::FileLoaded { text });
+        import::read_dropped(&event, loaded);
+    }
+
+    fn pick_file(&mut self, file: Option<web_sys::File>) {
+        let loaded = self.link.callback(|text| Msg::FileLoaded { text });
+        import::read_file(file, loaded);
+    }
+
+    fn file_loaded(&mut self, text: Option<String>) {
+        let text = match text {
+            Some(text) => text,
+            None => return self.toast("Couldn't read that file"),
+        };
+        match import::parse_file(&text) {
+            Ok(Imported::Board(board)) => self.load_imported(board),
+            Ok(Imported::Replay(replay)) => self.load_replay(replay),
+            Ok(Imported::Backup(backup)) => self.merge_backup(backup),
+            Err(error) => self.toast(&error),
+        }
+    }
+
+    // Starts a game on a replay's board, adding a difficulty for it if none of
+    // the presets builds boards like it.
+    fn load_replay(&mut self, replay: Replay) {
+        let difficulty = match self.presets.for_replay(&replay) {
+            Some(difficulty) => difficulty,
+            None => {
+                let preset = DifficultyPreset {
+                    adjacency: replay.adjacency,
+                    min_opening: replay.min_opening,
+                    ..DifficultyPreset::custom(
+                        replay.width,
+                        replay.height,
+                        replay.mines,
+                        replay.symmetry,
+                        replay.spacing,
+                    )
+                };
+                let difficulty = preset.difficulty();
+                if let Err(error) = self.presets.add(preset) {
+                    return self.toast(&format!("That replay's board can't be built: {}", error));
+                }
+                self.presets.store(self.storage.as_mut());
+                difficulty
+            }
+        };
+        self.new_game(difficulty, Some(replay.seed));
+    }
+
+    // The host's board, if this side's difficulty of the same name builds
+    // the same one from its seed.
+    fn start_duel_game(&mut self, difficulty: Difficulty, seed: u64, hash: u64) {
+        let error = match self.presets.find(&difficulty) {
+            None => format!("You don't have the {} difficulty", difficulty.0),
+            Some(_) => match self.presets.board(&difficulty, Some(seed)) {
+                Ok(board) if board.position_hash() == hash => {
+                    return self.new_game(difficulty, Some(seed))
+                }
+                Ok(_) => format!("Your {} difficulty isn't your opponent's", difficulty.0),
+                Err(error) => format!(
+                    "Your {} difficulty can't build this board: {}",
+                    difficulty.0, error
+                ),
+            },
+        };
+        self.lobby.error = Some(error);
+    }
+
+    // The render's timed on the next turn of the event loop, by when the
+    // board's been drawn.
+    fn run_stress(&mut self) {
+        self.stress.run(&self.robot);
+        self._stress_rendered = self.stress.board.as_ref().map(|_| {
+            let rendered = self.link.callback(|_| Msg::StressRendered);
+            TimeoutService::new().spawn(Duration::from_millis(0), rendered)
+        });
+    }
+
+    fn toggle_print(&mut self) {
+        self.screen = match self.screen {
+            Screen::Print => Screen::Game,
+            _ => Screen::Print,
+        }
+    }
+
+    fn toggle_featured(&mut self) {
+        self.screen = match self.screen {
+            Screen::Featured => Screen::Game,
+            _ => Screen::Featured,
+        }
+    }
+
+    fn toggle_learn(&mut self) {
+        self.screen = match self.screen {
+            Screen::Learn => Screen::Game,
+            _ => Screen::Learn,
+        }
+    }
+
+    // Builds the book for the current difficulty, or puts it away. It's
+    // only shown before the first click, as it's no use after.
+    fn toggle_openings(&mut self) {
+        let difficulty = self.state.difficulty.clone();
+        if self.openings.as_ref().map(|(d, _)| d) == Some(&difficulty) {
+            self.openings = None;
+        } else {
+            let presets = &self.presets;
+            let boards = (0..OPENING_BOOK_SEEDS)
+                .map(|seed| presets.board(&difficulty, Some(seed)))
+                .collect::<Result<Vec<_>, _>>();
+            let book = match boards {
+                Ok(boards) => opening_book(boards),
+                Err(error) => return self.cant_build(&difficulty, &error),
+            };
+            self.openings = Some((difficulty, book));
+            if self.state.game.board.state != Ready {
+                self.toast("🧭 Start a new board to see its best first clicks");
+            }
+        }
+        self.screen = Screen::Game;
+        self.board_changes = self.board_changes.next(None);
+    }
+
+    fn toggle_patterns(&mut self) {
+        self.screen = match self.screen {
+            Screen::Patterns { .. } => Screen::Game,
+            _ => Screen::Patterns { pattern: None },
+        }
+    }
+
+    // Where the player is, for the URL.
+    fn route(&self) -> Route {
+        match &self.screen {
+            Screen::Game | Screen::Lesson { .. } if self.is_daily() => Route::Daily,
+            Screen::Game | Screen::Lesson { .. } => Route::Play(self.state.difficulty.0.clone()),
+            Screen::History => Route::History,
+            Screen::Replay { index, .. } => Route::Replay(*index),
+            Screen::Profiles => Route::Profiles,
+            Screen::Featured => Route::Featured,
+            Screen::Learn => Route::Learn,
+            Screen::Race => Route::Race(self.race.as_ref().map(|run| run.code.clone())),
+            Screen::Duel => Route::Duel,
+            Screen::Broadcast => Route::Broadcast,
+            Screen::Import => Route::Import,
+            Screen::Print => Route::Print,
+            Screen::Trainer { .. } => Route::Trainer,
+            Screen::Patterns { pattern } => Route::Patterns(*pattern),
+            Screen::Analysis { .. } => Route::Analysis,
+            Screen::Stress => Route::Stress(self.stress.ran),
+            Screen::Keys { .. } => Route::Keys,
+        }
+    }
+
+    // Goes where the URL says, from a link or the back and forward buttons.
+    // Games are only started anew to change the board the URL asks for.
+    fn navigate(&mut self, hash: &str) {
+        let route = match Route::parse(hash) {
+            Some(route) => route,
+            None => return,
+        };
+        if route == self.route() {
+            return;
+        }
+        match route {
+            Route::Play(name) => {
+                let difficulty = self
+                    .presets
+                    .iter()
+                    .find(|p| route::slug(&p.name) == route::slug(&name))
+                    .map(|p| p.difficulty());
+                match difficulty {
+                    Some(d) if d == self.state.difficulty && !self.is_daily() => {
+                        self.screen = Screen::Game
+                    }
+                    Some(d) => {
+                        self.new_game(d, None);
+                        self.save_settings();
+                    }
+                    None => self.toast(&format!("There's no {} difficulty", name)),
+                }
+            }
+            Route::Daily if self.is_daily() => self.screen = Screen::Game,
+            Route::Daily => self.daily_game(),
+            Route::History => self.screen = Screen::History,
+            Route::Replay(index) => self.open_replay(index, 0),
+            Route::Profiles => self.screen = Screen::Profiles,
+            Route::Featured => self.screen = Screen::Featured,
+            Route::Race(code) => {
+                let joined = self.race.as_ref().map(|run| run.code.clone());
+                if code.is_some() && code != joined {
+                    self.race_input = code.unwrap_or_default();
+                }
+                self.screen = Screen::Race;
+            }
+            Route::Duel => self.screen = Screen::Duel,
+            Route::Broadcast => self.screen = Screen::Broadcast,
+            Route::Import => self.screen = Screen::Import,
+            Route::Print => self.screen = Screen::Print,
+            Route::Trainer => self.next_drill(),
+            Route::Learn => self.screen = Screen::Learn,
+            Route::Patterns(pattern) => self.screen = Screen::Patterns { pattern },
+            Route::Analysis => self.analyse(),
+            Route::Stress(size) => {
+                if let Some((width, height, mines)) = size {
+                    self.stress.set_size(width, height, mines);
+                }
+                self.screen = Screen::Stress;
+                self.run_stress();
+            }
+            Route::Keys => self.screen = Screen::Keys { capturing: None },
+        }
+    }
+
+    fn is_daily(&self) -> bool {
+        let daily = lib_minesweeper::daily(daily::today());
+        self.state
+            .game
+            .replay
+            .as_ref()
+            .is_some_and(|replay| daily.is_board_of(replay))
+    }
+
+    fn toggle_race(&mut self) {
+        self.screen = match self.screen {
+            Screen::Race => Screen::Game,
+            _ => Screen::Race,
+        }
+    }
+
+    fn join_race(&mut self) {
+        match Run::new(&self.race_input) {
+            Some(run) => {
+                self.race = Some(run);
+                self.race_input = String::new();
+                Run::store(&self.race, self.storage.as_mut());
+            }
+            None => self.toast(&format!(
+                "Race codes look like 5-k3j9x2, with up to {} boards",
+                lib_minesweeper::MAX_RACE_BOARDS
+            )),
+        }
+    }
+
+    fn new_race(&mut self) {
+        let code = lib_minesweeper::race_code(race::NEW_RACE_BOARDS, random());
+        self.race = Run::new(&code);
+        Run::store(&self.race, self.storage.as_mut());
+    }
+
+    fn play_race(&mut self) {
+        let run = match &self.race {
+            Some(run) => run,
+            None => return,
+        };
+        let board = run.next().and_then(|next| run.race().board(next));
+        if let Some(replay) = board.as_ref().and_then(Replay::for_board) {
+            self.load_replay(replay);
+        }
+    }
+
+    fn export_race(&mut self) {
+        if let Some(run) = &self.race {
+            let name = format!("minesweeper-race-{}.txt", run.code);
+            let summary = run.summary();
+            self.download(&name, "text/plain", &summary);
+        }
+    }
+
+    fn leave_race(&mut self) {
+        let confirmed = web_sys::window()
+            .and_then(|w| {
+                w.confirm_with_message("Leave the race? Its results are lost.")
+                    .ok()
+            })
+            .unwrap_or(false);
+        if confirmed {
+            self.race = None;
+            Run::store(&self.race, self.storage.as_mut());
+        }
+    }
+
+    // Plays the board through the difficulty that builds it, or as a
+    // replay if none does, and shows where its first click goes.
+    fn play_featured(&mut self, index: usize) {
+        let featured = match lib_minesweeper::featured().into_iter().nth(index) {
+            Some(featured) => featured,
+            None => return,
+        };
+        let board = featured.board();
+        match self.presets.for_board(&board) {
+            Some(difficulty) => self.new_game(difficulty, Some(featured.seed)),
+            None => match Replay::for_board(&board) {
+                Some(replay) => self.load_replay(replay),
+                None => return,
+            },
+        }
+        if let Some(start) = featured.start() {
+            self.highlight(vec![(start, "robot-move")]);
+        }
+        self.toast(&format!("⭐ {}: start at the flashing cell", featured.name));
+    }
+
+    // Starts the scenario on its board, with its first step showing.
+    fn play_scenario(&mut self, index: usize) {
+        let scenario = match lib_minesweeper::scenarios().into_iter().nth(index) {
+            Some(scenario) => scenario,
+            None => return,
+        };
+        self.load_board(scenario.board.clone());
+        // It's known how hard it is, it's there to learn from.
+        self._check_guessing = None;
+        let mut run = ScenarioRun::new(scenario);
+        run.advance(&self.state.game.board);
+        self.toast(&format!("🎓 {}", run.scenario.title));
+        self.scenario = Some((index, run));
+    }
+
+    fn scenario_next(&mut self) {
+        let before = self.scenario_marks();
+        let board = &self.state.game.board;
+        if let Some((_, run)) = &mut self.scenario {
+            run.next(board);
+        }
+        self.scenario_moved(before);
+    }
+
+    // Moves the scenario past the steps the last move met. A lost board
+    // starts it over.
+    fn advance_scenario(&mut self) {
+        let before = self.scenario_marks();
+        let board = &self.state.game.board;
+        let (index, run) = match &mut self.scenario {
+            Some(scenario) => scenario,
+            None => return,
+        };
+        if board.state == Failed {
+            let index = *index;
+            self.toast("💥 That was a mine. Here it is again");
+            return self.play_scenario(index);
+        }
+        if run.advance(board) {
+            self.scenario_moved(before);
+        }
+    }
+
+    // Redraws the cells the last step picked out and the next one does, and
+    // says so when it's the end.
+    fn scenario_moved(&mut self, before: Vec<(Point, &'static str)>) {
+        let changed = before.into_iter().chain(self.scenario_marks());
+        self.board_changes = self.board_changes.plus(changed.map(|(p, _)| p).collect());
+        if let Some((_, run)) = &self.scenario {
+            if run.is_finished() {
+                let text = format!("🎓 {} done", run.scenario.title);
+                self.toast(&text);
+            }
+        }
+    }
+
+    fn scenario_marks(&self) -> Vec<(Point, &'static str)> {
+        match self.scenario.as_ref().and_then(|(_, run)| run.step()) {
+            Some(step) => step.highlights.iter().map(|p| (*p, "robot-move")).collect(),
+            None => vec![],
+        }
+    }
+
+    // Walks through the robot's certain moves from the current position,
+    // without playing them.
+    fn toggle_lesson(&mut self) {
+        if let Screen::Lesson { .. } = self.screen {
+            self.screen = Screen::Game;
+            return;
+        }
+        let lesson = Lesson::new(&self.robot, &self.state.game.board);
+        if lesson.step_count() == 0 {
+            return self.toast("🤖 has no certain moves to show");
+        }
+        self.screen = Screen::Lesson { lesson, step: 0 };
+    }
+
+    fn step_lesson(&mut self, to: usize) {
+        if let Screen::Lesson { lesson, step } = &mut self.screen {
+            *step = to.min(lesson.step_count());
+            // Every step moves the highlights around.
+            self.board_changes = self.board_changes.next(None);
+        }
+    }
+
+    fn toggle_trainer(&mut self) {
+        match self.screen {
+            Screen::Trainer { .. } => self.screen = Screen::Game,
+            _ => self.next_drill(),
+        }
+    }
+
+    // A new position on whichever pattern the trainer says is due.
+    fn next_drill(&mut self) {
+        let pattern = self.trainer.next();
+        match self.robot.drill(pattern, random()) {
+            Some(drill) => {
+                self.screen = Screen::Trainer {
+                    drill,
+                    picked: None,
+                };
+                self.board_changes = self.board_changes.next(None);
+            }
+            None => self.toast("🏋️ The trainer needs 🤖 to set its drills"),
+        }
+    }
+
+    // Only the first answer to a drill counts.
+    fn answer_drill(&mut self, answer: Answer) {
+        let (drill, picked) = match &mut self.screen {
+            Screen::Trainer { drill, picked } if picked.is_none() => (drill, picked),
+            _ => return,
+        };
+        *picked = Some(answer);
+        self.trainer.answer(drill.pattern, answer == drill.answer);
+        self.trainer.store(self.storage.as_mut());
+        // Shows the numbers that decide it.
+        self.board_changes = self.board_changes.next(None);
+    }
+
+    fn toggle_history(&mut self) {
+        self.screen = match self.screen {
+            Screen::Game => Screen::History,
+            _ => Screen::Game,
+        }
+    }
+
+    fn toggle_profiles(&mut self) {
+        self.screen = match self.screen {
+            Screen::Profiles => Screen::Game,
+            _ => Screen::Profiles,
+        }
+    }
+
+    fn switch_profile(&mut self, index: usize) {
+        if !self.profiles.switch_to(index) {
+            return;
+        }
+        self.profiles.store(self.storage.as_mut());
+        let Settings {
+            difficulty, mode, ..
+        } = self.profiles.active().settings.clone();
+        self.state.game.mode = mode;
+        self.apply_controls();
+        self.new_game(difficulty, None);
+    }
+
+    // Shows the d-pad and picks 🤖's strategy and the keys as the active
+    // profile had them.
+    fn apply_controls(&mut self) {
+        let settings = &self.profiles.active().settings;
+        self.pad.shown = settings.pad.unwrap_or_else(|| Pad::default().shown);
+        self.robot.select_named(&settings.strategy);
+        keys::set(&settings.keys);
+    }
+
+    fn select_strategy(&mut self, index: usize) {
+        self.robot.select(index);
+        let strategy = self
+            .robot
+            .names()
+            .get(self.robot.selected())
+            .map_or(String::new(), |name| name.to_string());
+        self.profiles.save_settings(Settings {
+            strategy,
+            ..self.profiles.active().settings.clone()
+        });
+        self.profiles.store(self.storage.as_mut());
+    }
+
+    fn add_profile(&mut self) {
+        let name = web_sys::window()
+            .and_then(|w| w.prompt_with_message("Name for the new profile?").ok())
+            .flatten()
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty());
+        if let Some(name) = name {
+            self.profiles.add(name);
+            self.save_settings();
+            self.screen = Screen::Game;
+        }
+    }
+
+    fn add_preset(&mut self) {
+        let window = match web_sys::window() {
+            Some(window) => window,
+            None => return,
+        };
+        let answer = window
+            .prompt_with_message(
+                "Width, height and mines for the new difficulty, e.g. 20 20 60. \
+                 Add \"mirror\" or \"rotate\" to place the mines symmetrically, \
+                 and a fourth number to keep them that many knight moves apart.",
+            )
+            .ok()
+            .flatten();
+        let answer = match answer {
+            Some(answer) => answer.to_lowercase(),
+            None => return,
+        };
+        let numbers: Vec<usize> = answer
+            .split(|c: char| !c.is_ascii_digit())
+            .filter_map(|n| n.parse().ok())
+            .collect();
+        let symmetry = if answer.contains("mirror") {
+            Symmetry::Mirror
+        } else if answer.contains("rotat") {
+            Symmetry::Rotational
+        } else {
+            Symmetry::None
+        };
+        let preset = match numbers[..] {
+            [width, height, mines] => {
+                DifficultyPreset::custom(width, height, mines, symmetry, None)
+            }
+            [width, height, _, distance] if distance > width.max(height) => {
+                let _ = window.alert_with_message(&format!(
+                    "Mines can't be more than {} moves apart on a {}×{} board",
+                    width.max(height),
+                    width,
+                    height
+                ));
+                return;
+            }
+            [width, height, mines, distance] => {
+                let spacing = Spacing {
+                    distance,
+                    moves: Adjacency::Knight,
+                };
+                DifficultyPreset::custom(width, height, mines, symmetry, Some(spacing))
+            }
+            _ => return,
+        };
+        let difficulty = preset.difficulty();
+        // Checked before building, so a spacing the mines can't fit is
+        // turned down without trying.
+        if let Err(error) = preset.check().and_then(|_| {
+            preset
+                .board(None)
+                .map(|_| ())
+                .map_err(|error| error.to_string())
+        }) {
+            let _ = window.alert_with_message(&format!("That board can't be built: {}", error));
+            return;
+        }
+        if let Err(error) = self.presets.add(preset) {
+            let _ = window.alert_with_message(&format!("That board can't be built: {}", error));
+            return;
+        }
+        self.presets.store(self.storage.as_mut());
+        self.new_game(difficulty, None);
+        self.save_settings();
+    }
+
+    fn toggle_zen(&mut self) {
+        match self.zen.take() {
+            Some(zen) => {
+                let status = zen.status(&self.state.game.board);
+                self._zen_next = None;
+                self.toast(&format!("{} cleared, back to the clock", status));
+            }
+            None => {
+                self.zen = Some(Zen::default());
+                self.speed_drill = None;
+                self.new_game(self.state.difficulty.clone(), None);
+                self.toast("🧘 No clock, and a fresh board whenever one's done");
+            }
+        }
+    }
+
+    // The drill's boards aren't any of the difficulties, which stay as they
+    // were for when it's over.
+    fn toggle_speed_drill(&mut self) {
+        match self.speed_drill.take() {
+            Some(drill) => {
+                let status = drill.status();
+                self.new_game(self.state.difficulty.clone(), None);
+                self.toast(&format!("{}, drill stopped", status));
+            }
+            None => {
+                let length = self.speed_records.length();
+                self.start_speed_drill(length);
+            }
+        }
+    }
+
+    // Starts over with the next length.
+    fn cycle_speed_drill(&mut self) {
+        let length = self.speed_records.next_length();
+        self.speed_records.store(self.storage.as_mut());
+        self.start_speed_drill(length);
+    }
+
+    fn start_speed_drill(&mut self, length: usize) {
+        self.zen = None;
+        self.speed_drill = Some(SpeedDrill::new(length));
+        self.next_speed_drill_board();
+        self.toast(&format!(
+            "🏃 {} small boards, as fast as you can: only the time on the boards counts",
+            length
+        ));
+    }
+
+    // Hot seat or a duel, where 🤖 would be playing for one side and a new
+    // board would end the match.
+    fn in_match(&self) -> bool {
+        self.state.hot_seat.is_some() || self.lobby.connected
+    }
+
+    // The seconds for each move and what running out does, in hot seat or
+    // a duel, when there's a limit.
+    fn turn_rules(&self) -> Option<(u32, Expiry)> {
+        let settings = &self.profiles.active().settings;
+        let rules = if self.state.hot_seat.is_some() {
+            (settings.turn_limit, settings.turn_expiry)
+        } else if self.lobby.connected {
+            self.lobby.turn_rules
+        } else {
+            return None;
+        };
+        Some(rules).filter(|(limit, _)| *limit > 0)
+    }
+
+    // Only changes a duel from the next one hosted.
+    fn cycle_turn_limit(&mut self) {
+        let settings = self.profiles.active().settings.clone();
+        let index = turn_timer::LIMITS
+            .iter()
+            .position(|l| *l == settings.turn_limit);
+        let turn_limit =
+            turn_timer::LIMITS[index.map_or(0, |i| (i + 1) % turn_timer::LIMITS.len())];
+        self.profiles.save_settings(Settings {
+            turn_limit,
+            ..settings
+        });
+        self.profiles.store(self.storage.as_mut());
+        self.toast(&match turn_limit {
+            0 => String::from("⏳ No time limit on moves"),
+            limit => format!("⏳ {}s for each move in hot seat and duels", limit),
+        });
+    }
+
+    fn cycle_turn_expiry(&mut self) {
+        let settings = self.profiles.active().settings.clone();
+        let turn_expiry = settings.turn_expiry.next();
+        self.profiles.save_settings(Settings {
+            turn_expiry,
+            ..settings
+        });
+        self.profiles.store(self.storage.as_mut());
+        self.toast(&format!(
+            "{} In a match, {}",
+            turn_expiry.emoji(),
+            turn_expiry.describe()
+        ));
+    }
+
+    // Puts the clock on the next move, whoever's it is, starting it with
+    // the first move of a match with a limit. Stops it once the game's
+    // over.
+    fn turn_taken(&mut self) {
+        let rules = self
+            .turn_rules()
+            .filter(|_| self.state.game.board.state == Playing);
+        let (limit, expiry) = match rules {
+            Some(rules) => rules,
+            None => {
+                self.turn_timer = None;
+                self._turn_tick = None;
+                return;
+            }
+        };
+        if self.turn_timer.is_none() {
+            self.turn_timer = TurnTimer::new(limit, expiry);
+            let tick = self.link.callback(|_| Msg::TurnTick);
+            self._turn_tick = Some(IntervalService::new().spawn(Duration::from_secs(1), tick));
+        }
+        let player = self.state.hot_seat.as_ref().map_or(0, |seat| seat.turn);
+        if let Some(timer) = &mut self.turn_timer {
+            timer.start(player);
+        }
+    }
+
+    fn turn_tick(&mut self) {
+        let expired = match &mut self.turn_timer {
+            Some(timer) if self.state.game.board.state == Playing => timer.tick(),
+            _ => {
+                self.turn_timer = None;
+                self._turn_tick = None;
+                return;
+            }
+        };
+        if expired {
+            self.out_of_time();
+        }
+    }
+
+    // Plays the move running out of time costs, as if the player had.
+    fn out_of_time(&mut self) {
+        let (player, expiry, timeouts) = match &self.turn_timer {
+            Some(timer) => (timer.player, timer.expiry, timer.timeouts[timer.player]),
+            None => return,
+        };
+        let p = match expiry.cell(&self.state.game.board, random()) {
+            Some(p) => p,
+            None => return,
+        };
+        let who = match self.state.hot_seat {
+            Some(_) => hot_seat::PLAYERS[player],
+            None => "You",
+        };
+        let times = if timeouts > 1 {
+            format!(" ({} times now)", timeouts)
+        } else {
+            String::new()
+        };
+        self.toast(&format!(
+            "⏳ {} ran out of time{}: {}",
+            who,
+            times,
+            match expiry {
+                Expiry::Forfeit => "that's a forfeit",
+                Expiry::RandomMove => "🎲 a random cell gets dug",
+            }
+        ));
+        let before = self.state.game.board.clone();
+        self.apply_move(Move::Open(p));
+        self.pass_turn(&before);
+        self.turn_taken();
+        self.play_sounds(&before);
+    }
+
+    // Starts a new game either way, so nobody carries over a half-played
+    // board.
+    fn toggle_hot_seat(&mut self) {
+        let on = self.state.hot_seat.is_none();
+        self.state.hot_seat = if on { Some(HotSeat::default()) } else { None };
+        self.new_game(self.state.difficulty.clone(), None);
+        self.toast(if on {
+            "👥 Take turns digging: open a mine and you're out, else most cells opened wins"
+        } else {
+            "👥 Back to playing alone"
+        });
+    }
+
+    fn toggle_duel(&mut self) {
+        self.screen = match self.screen {
+            Screen::Duel => Screen::Game,
+            _ => Screen::Duel,
+        }
+    }
+
+    fn host_duel(&mut self) {
+        self.lobby = Lobby::default();
+        let events = self.link.callback(|event| Msg::Duel { event });
+        match Duel::host(events) {
+            Ok(duel) => self.duel = Some(duel),
+            Err(e) => self.lobby.error = Some(format!("{:?}", e)),
+        }
+    }
+
+    fn connect_duel(&mut self) {
+        let input = self.lobby.input.trim().to_string();
+        if input.is_empty() {
+            return;
+        }
+        let events = self.link.callback(|event| Msg::Duel { event });
+        self.lobby.error = None;
+        match &self.duel {
+            Some(duel) if duel.is_host() => duel.accept(&input, events),
+            Some(_) => (),
+            None => match Duel::join(&input, events) {
+                Ok(duel) => self.duel = Some(duel),
+                Err(_) => self.lobby.error = Some(String::from("That invite doesn't look right")),
+            },
+        }
+    }
+
+    fn leave_duel(&mut self) {
+        self.duel = None;
+        self.lobby = Lobby::default();
+    }
+
+    fn duel_event(&mut self, event: DuelEvent) {
+        match event {
+            DuelEvent::Signal { signal } => self.lobby.signal = signal,
+            DuelEvent::Connected => {
+                self.lobby.connected = true;
+                self.lobby.opponent = Some((0_f64, Ready));
+                if matches!(&self.duel, Some(duel) if duel.is_host()) {
+                    let settings = &self.profiles.active().settings;
+                    let (turn_limit, expiry) = (settings.turn_limit, settings.turn_expiry);
+                    self.lobby.turn_rules = (turn_limit, expiry);
+                    self.new_game(self.state.difficulty.clone(), None);
+                    if let Some(seed) = self.state.game.board.seed {
+                        self.send_to_opponent(PeerMessage::Start {
+                            difficulty: self.state.difficulty.clone(),
+                            seed,
+                            hash: self.state.game.board.position_hash(),
+                            turn_limit,
+                            expiry,
+                        });
+                    }
+                }
+            }
+            DuelEvent::Received {
+                message:
+                    PeerMessage::Start {
+                        difficulty,
+                        seed,
+                        hash,
+                        turn_limit,
+                        expiry,
+                    },
+            } => {
+                self.lobby.turn_rules = (turn_limit, expiry);
+                self.start_duel_game(difficulty, seed, hash);
+            }
+            DuelEvent::Received {
+                message: PeerMessage::Progress { progress, state },
+            } => self.lobby.opponent = Some((progress, state)),
+            DuelEvent::Received {
+                message: PeerMessage::PowerUp { power_up },
+            } => self.power_up_played_on_us(power_up),
+            // Broadcast messages have no meaning in a duel.
+            DuelEvent::Received { .. } => (),
+            DuelEvent::Closed => {
+                self.leave_duel();
+                self.lobby.error = Some(String::from("Your opponent left"));
+            }
+            DuelEvent::Failed { reason } => self.lobby.error = Some(reason),
+        }
+    }
+
+    fn use_power_up(&mut self, index: usize) {
+        if self.state.game.board.state != Playing || !self.lobby.connected {
+            return;
+        }
+        let power_up = match self.power_ups.take(index) {
+            Some(power_up) => power_up,
+            None => return,
+        };
+        if power_up.is_attack() {
+            self.send_to_opponent(PeerMessage::PowerUp { power_up });
+        } else if let Some(p) = self.state.game.board.pick_safe_cell(random()) {
+            self.play_move(Move::Open(p), true);
+            self.highlight(vec![(p, "robot-move")]);
+        }
+        self.toast(&format!(
+            "{} Played: {}",
+            power_up.emoji(),
+            power_up.describe()
+        ));
+    }
+
+    fn power_up_played_on_us(&mut self, power_up: PowerUp) {
+        match power_up {
+            PowerUp::Reveal => return,
+            PowerUp::Decoy => {
+                self.clear_decoy();
+                self.decoy = self.state.game.board.pick_closed_cell(random());
+                self.board_changes = self.board_changes.plus(self.decoy.into_iter().collect());
+                let clear = self.link.callback(|_| Msg::ClearDecoy);
+                self._clear_decoy = Some(TimeoutService::new().spawn(power_ups::DECOY_FOR, clear));
+            }
+            PowerUp::Freeze => {
+                let thaw = self.link.callback(|_| Msg::Thaw);
+                self._thaw = Some(TimeoutService::new().spawn(power_ups::FREEZE_FOR, thaw));
+            }
+        }
+        self.toast(&format!(
+            "{} Your opponent played a power-up",
+            power_up.emoji()
+        ));
+    }
+
+    fn clear_decoy(&mut self) {
+        if let Some(p) = self.decoy.take() {
+            self.board_changes = self.board_changes.next(Some(vec![p]));
+        }
+    }
+
+    fn send_to_opponent(&self, message: PeerMessage) {
+        if let (Some(duel), true) = (&self.duel, self.lobby.connected) {
+            duel.send(&message);
+        }
+    }
+
+    fn toggle_broadcast_screen(&mut self) {
+        self.screen = match self.screen {
+            Screen::Broadcast => Screen::Game,
+            _ => Screen::Broadcast,
+        }
+    }
+
+    fn start_broadcast(&mut self) {
+        let events = self.link.callback(|event| Msg::Broadcast { event });
+        self.spectator = Spectator::default();
+        match Broadcast::start(events) {
+            Ok(broadcast) => self.broadcast = Some(broadcast),
+            Err(error) => self.spectator.error = Some(error),
+        }
+    }
+
+    fn watch(&mut self) {
+        let topic = self.spectator.input.trim().to_string();
+        if topic.is_empty() {
+            return;
+        }
+        let events = self.link.callback(|event| Msg::Broadcast { event });
+        self.spectator = Spectator::default();
+        match Broadcast::watch(topic, events) {
+            Ok(broadcast) => self.broadcast = Some(broadcast),
+            Err(error) => self.spectator.error = Some(error),
+        }
+    }
+
+    fn stop_broadcast(&mut self) {
+        self.broadcast = None;
+        self.spectator = Spectator::default();
+    }
+
+    fn broadcast_event(&mut self, event: BroadcastEvent) {
+        let watching = match &self.broadcast {
+            Some(broadcast) => broadcast.watching,
+            None => return,
+        };
+        match event {
+            BroadcastEvent::Opened if !watching => self.send_checkpoint(),
+            BroadcastEvent::Received {
+                message: PeerMessage::Checkpoint { replay, hash, .. },
+            } if watching => {
+                // Anyone on the topic can send one, so its size is checked
+                // before anything is built, and it's only played back once
+                // it's known to make a board and stay on it.
+                if !replay.is_within_limits() {
+                    self.spectator.error = Some(String::from("A game too big to follow was sent"));
+                    return;
+                }
+                let board = match replay.playable_board() {
+                    Some(board) => board,
+                    None => {
+                        self.spectator.error = Some(String::from("A broken game was sent"));
+                        return;
+                    }
+                };
+                self.spectator.error = None;
+                self.spectator.in_sync = board.position_hash() == hash;
+                self.spectator.game = Some((replay, board));
+            }
+            BroadcastEvent::Received {
+                message: PeerMessage::Move { m },
+            } if watching => {
+                // Moves that can't be played on the board, like ones off
+                // it, are left out rather than recorded.
+                if let Some((replay, board)) = &mut self.spectator.game {
+                    if let Some(b) = board.apply(&m) {
+                        *board = b;
+                        replay.record(m);
+                    }
+                }
+            }
+            BroadcastEvent::Closed => {
+                self.stop_broadcast();
+                self.spectator.error = Some(String::from("The broadcast connection closed"));
+            }
+            _ => (),
+        }
+    }
+
+    fn send_to_spectators(&mut self, message: PeerMessage) {
+        if let Some(broadcast) = &mut self.broadcast {
+            if !broadcast.watching {
+                broadcast.send(&message);
+            }
+        }
+    }
+
+    // Spectators can only follow boards that can be replayed.
+    fn send_checkpoint(&mut self) {
+        if let Some(replay) = self.state.game.replay.clone() {
+            self.send_to_spectators(PeerMessage::Checkpoint {
+                difficulty: self.state.difficulty.clone(),
+                replay,
+                hash: self.state.game.board.position_hash(),
+            });
+        }
+    }
+
+    fn save_settings(&mut self) {
+        self.profiles.save_settings(Settings {
+            difficulty: self.state.difficulty.clone(),
+            mode: self.state.game.mode,
+            ..self.profiles.active().settings.clone()
+        });
+        self.profiles.store(self.storage.as_mut());
+    }
+
+    fn open_replay(&mut self, index: usize, step: usize) {
+        let game = self.history.get(index);
+        if let Some(board) = game.and_then(|game| game.replay.board_at(step)) {
+            self.screen = Screen::Replay { index, step, board };
+        }
+    }
+
+    // Opens the analysis board on the replay's position if one's showing,
+    // else on the game's.
+    fn analyse(&mut self) {
+        let board = match &self.screen {
+            Screen::Analysis { .. } => return,
+            Screen::Replay { board, .. } => board.clone(),
+            _ => self.state.game.board.clone(),
+        };
+        self.screen = Screen::Analysis {
+            analysis: Analysis::new(board),
+        };
+        self.board_changes = self.board_changes.next(None);
+    }
+
+    // Plays 🤖's next move on the analysis board, saying why when a single
+    // number proves it.
+    fn analysis_step(&mut self) {
+        let analysis = match &mut self.screen {
+            Screen::Analysis { analysis } => analysis,
+            _ => return,
+        };
+        let board = analysis.board.clone();
+        let m = match self.robot.next_move(&board) {
+            Some(m) => m,
+            None => {
+                analysis.note = Some(String::from("🤖 has no move here"));
+                return;
+            }
+        };
+        let reason = match self.robot.justify(&board, &m) {
+            Some(step) => step.reason,
+            None => format!("🤖 plays {:?}", m),
+        };
+        if let Some(played) = board.apply(&m) {
+            let changed = board.changed_points(&played);
+            analysis.set_board(played);
+            analysis.note = Some(reason);
+            self.board_changes = self.board_changes.next(Some(changed));
+        }
+    }
+
+    fn analysis_odds(&mut self) {
+        let analysis = match &mut self.screen {
+            Screen::Analysis { analysis } => analysis,
+            _ => return,
+        };
+        match self.robot.mine_odds(&analysis.board) {
+            Some((exact, odds)) => {
+                analysis.note = Some(String::from(if exact {
+                    "% chance of a mine under each closed cell"
+                } else {
+                    "% chance of a mine under each closed cell, from a sample: too many layouts to go through"
+                }));
+                analysis.odds = Some((exact, odds));
+            }
+            None => analysis.note = Some(String::from("No layout of mines fits these numbers")),
+        }
+    }
+
+    fn step_replay(&mut self, step: usize) {
+        if let Screen::Replay { index, .. } = self.screen {
+            self.open_replay(index, step);
+        }
+    }
+
+    fn restart_game(&mut self, index: usize) {
+        if let Some(game) = self.history.get(index) {
+            let difficulty = game.difficulty.clone();
+            let seed = game.replay.seed;
+            self.new_game(difficulty, Some(seed));
+            self.retrying = true;
+        }
+    }
+
+    // The board just played again from the start, from its seed.
+    fn retry_board(&mut self) {
+        let seed = match &self.state.game.replay {
+            Some(replay) => replay.seed,
+            None => return,
+        };
+        self.new_game(self.state.difficulty.clone(), Some(seed));
+        self.retrying = true;
+    }
+
+    // A retry only makes sense for a lost board of one of the difficulties,
+    // played on its own.
+    fn can_retry(&self) -> bool {
+        self.state.game.board.state == Failed
+            && self.state.game.replay.is_some()
+            && self.zen.is_none()
+            && self.speed_drill.is_none()
+            && self.scenario.is_none()
+            && self.state.hot_seat.is_none()
+            && self.duel.is_none()
+    }
+
+    fn record_game(&mut self) {
+        let now = Date::now();
+        let duration = self.stopwatch.elapsed();
+        js_events::game_ended(&self.state.game.board, duration);
+        // Zen boards aren't timed, so there's nothing to keep but the count.
+        if let Some(zen) = &mut self.zen {
+            zen.finish(&self.state.game.board);
+            let next = self.link.callback(|_| Msg::ZenNext);
+            self._zen_next = Some(TimeoutService::new().spawn(ZEN_NEXT_AFTER, next));
+            return;
+        }
+        // Drill boards only count towards the drill, and its own records.
+        if let Some(drill) = &mut self.speed_drill {
+            drill.finish(Finish {
+                won: self.state.game.board.state == Won,
+                ms: duration.real_ms,
+            });
+            let beaten = self.speed_records.record(drill);
+            self.speed_records.store(self.storage.as_mut());
+            return self.speed_drill_finished(beaten);
+        }
+        // Scenarios are lessons, not games: their steps say how it went.
+        if self.scenario.is_some() {
+            return;
+        }
+        // Two players' game isn't either one's to keep.
+        if let Some(seat) = &self.state.hot_seat {
+            let result = seat.result();
+            return self.toast(&result);
+        }
+        let won = self.state.game.board.state == Won;
+        let three_bv = self.state.game.board.three_bv();
+        self.report_clicks(won, three_bv);
+        // Imported boards aren't one of the difficulties, so they don't count.
+        let mut replay = match self.state.game.replay.clone() {
+            Some(replay) => replay,
+            None => return self.toast("Imported boards aren't saved to 📜"),
+        };
+        replay.seal();
+        if won && self.profiles.active().settings.splits {
+            let summary = self.splits_summary(duration);
+            self.toast(&summary);
+        }
+        self.history.push(GameRecord {
+            difficulty: self.state.difficulty.clone(),
+            result: self.state.game.board.state.clone(),
+            duration_ms: duration.real_ms,
+            game_ms: Some(duration.game_ms),
+            finished_at: now,
+            replay,
+            annotations: self.state.game.annotations.clone(),
+            retry: self.retrying,
+        });
+        self.history.store(self.storage.as_mut());
+        self.session.push(SessionEvent::Finished {
+            emoji: self.presets.emoji(&self.state.difficulty).to_string(),
+            won,
+            duration_ms: duration.real_ms,
+        });
+        if won && self.is_daily() {
+            self.session.push(SessionEvent::DailyWon);
+        }
+        if !won {
+            self.schedule_restart();
+        }
+        let every = self.profiles.active().settings.recap_every as usize;
+        if every > 0 && self.session.games().is_multiple_of(every) {
+            self.recap = Some(Recap::Current);
+        }
+        // Mines don't end casual games, so they keep out of the stats and
+        // best times.
+        if self.profiles.active().settings.casual {
+            let text = format!(
+                "🐣 Game saved to 📜, {} mines dug for +{}s",
+                self.state.game.forgiven.len(),
+                self.state.game.penalty.as_secs()
+            );
+            return self.toast(&text);
+        }
+        // A board played before is easier, so it keeps out of the stats and
+        // best times too, and has its own count.
+        if self.retrying {
+            self.profiles.record_retry(won);
+            self.profiles.store(self.storage.as_mut());
+            return self.toast("🔂 Retry saved to 📜, apart from first tries");
+        }
+        let best = self.profiles.record_game(
+            &self.state.difficulty,
+            won,
+            duration,
+            &self.stopwatch.splits,
+            self.profiles.active().settings.timing,
+        );
+        if best {
+            self.session.push(SessionEvent::BestTime {
+                emoji: self.presets.emoji(&self.state.difficulty).to_string(),
+            });
+        }
+        if won {
+            self.profiles.record_clicks(&self.clicks, three_bv);
+        }
+        if self.profiles.active().settings.adaptive {
+            self.nudge_density(won);
+        }
+        self.profiles
+            .record_heatmap(&self.clicked, &self.state.game.board);
+        self.profiles.store(self.storage.as_mut());
+        self.toast(if best {
+            "🏁 New best time!"
+        } else {
+            "Game saved to 📜"
+        });
+        self.record_race(won, duration.real_ms);
+    }
+
+    fn next_speed_drill_board(&mut self) {
+        let mut board = speed_drill::board();
+        board.cascade = self.profiles.active().settings.cascade;
+        self.load_board(board);
+    }
+
+    // Brings in the next board, or sums the drill up after its last.
+    fn speed_drill_finished(&mut self, beaten: bool) {
+        let drill = match &self.speed_drill {
+            Some(drill) => drill,
+            None => return,
+        };
+        if !drill.is_over() {
+            if beaten {
+                self.toast("🏁 New best rolling average!");
+            }
+            let next = self.link.callback(|_| Msg::SpeedDrillNext);
+            self._speed_drill_next =
+                Some(TimeoutService::new().spawn(SPEED_DRILL_NEXT_AFTER, next));
+            return;
+        }
+        let text = format!(
+            "{}, {} won{}",
+            drill.status(),
+            drill.won(),
+            if beaten { " 🏁 New record!" } else { "" }
+        );
+        self.speed_drill = None;
+        self.toast(&text);
+    }
+
+    // Says so when the next board of this size gets a different number of
+    // mines.
+    fn nudge_density(&mut self, won: bool) {
+        let Board {
+            width,
+            height,
+            mines,
+            ..
+        } = self.state.game.board;
+        let density = self
+            .profiles
+            .nudge_density(won, adaptive::density(width, height, mines));
+        let next = adaptive::mines(width, height, density);
+        if next != mines {
+            self.toast(&format!("🎯 Next board: {} mines", next));
+        }
+    }
+
+    // Starts the countdown to a new board after a loss, if the player wants
+    // one. Not in a duel, where the boards are the host's to pick.
+    fn schedule_restart(&mut self) {
+        let after = self.profiles.active().settings.auto_restart;
+        if after == 0 || self.duel.is_some() {
+            return;
+        }
+        self.restart_in = Some(after);
+        let tick = self.link.callback(|_| Msg::RestartTick);
+        self._restart_tick = Some(IntervalService::new().spawn(Duration::from_secs(1), tick));
+    }
+
+    // Leaving the board for another screen calls the restart off, rather
+    // than pulling the player back to a new one.
+    fn restart_tick(&mut self) {
+        match self.restart_in {
+            Some(_) if !matches!(self.screen, Screen::Game) => {
+                self.restart_in = None;
+                self._restart_tick = None;
+            }
+            Some(left) if left > 1 => self.restart_in = Some(left - 1),
+            Some(_) if self.profiles.active().settings.auto_retry && self.can_retry() => {
+                self.retry_board()
+            }
+            Some(_) => self.new_game(self.state.difficulty.clone(), None),
+            None => self._restart_tick = None,
+        }
+    }
+
+    // Counts the game towards the race if it was on the race's next board.
+    fn record_race(&mut self, won: bool, ms: f64) {
+        let run = match &mut self.race {
+            Some(run) => run,
+            None => return,
+        };
+        let race = run.race();
+        let index = self
+            .state
+            .game
+            .replay
+            .as_ref()
+            .and_then(|replay| race.index_of(replay));
+        if index.is_none() || index != run.next() {
+            return;
+        }
+        run.finish(Finish { won, ms });
+        let text = match run.next() {
+            Some(next) => format!(
+                "🏎️ Board {} of {} done, 🏎️ has the next one",
+                next,
+                race.len()
+            ),
+            None => format!("🏎️ Race over: {}", run.score()),
+        };
+        Run::store(&self.race, self.storage.as_mut());
+        self.toast(&text);
+    }
+
+    // "⏱️ 25% 3.214 (-0.512) · … · 🏁 12.345 (+0.100)", against the best
+    // game's times where there's one to compare with, on the player's clock.
+    fn splits_summary(&self, duration: Times) -> String {
+        let Profile {
+            settings, stats, ..
+        } = self.profiles.active();
+        let timing = settings.timing;
+        let difficulty = &self.state.difficulty;
+        let best_splits = stats.best_splits(timing).get(difficulty);
+        let time = |label: String, ms: f64, best: Option<f64>| match best {
+            Some(best) => format!(
+                "{} {} ({})",
+                label,
+                stopwatch::seconds(ms),
+                stopwatch::delta(ms, best)
+            ),
+            None => format!("{} {}", label, stopwatch::seconds(ms)),
+        };
+        let mut times: Vec<String> = SPLITS
+            .iter()
+            .zip(&self.stopwatch.splits)
+            .enumerate()
+            .map(|(i, (at, split))| {
+                let best = best_splits.and_then(|b| b.get(i).copied());
+                time(format!("{:.0}%", at * 100_f64), split.on(timing), best)
+            })
+            .collect();
+        times.push(time(
+            String::from("🏁"),
+            duration.on(timing),
+            stats.best_ms(timing).get(difficulty).copied(),
+        ));
+        format!("⏱️ {}", times.join(" · "))
+    }
+
+    fn report_clicks(&mut self, won: bool, three_bv: usize) {
+        let Clicks {
+            clicks,
+            wasted,
+            chords,
+        } = self.clicks;
+        let efficiency = self.clicks.efficiency(three_bv as u64).filter(|_| won);
+        self.toast(&match efficiency {
+            Some(efficiency) => format!(
+                "🖱️ {} clicks ({} wasted, {} chords) for a 3BV of {}: {:.0}% efficient",
+                clicks,
+                wasted,
+                chords,
+                three_bv,
+                efficiency * 100_f64
+            ),
+            None => format!(
+                "🖱️ {} clicks ({} wasted, {} chords)",
+                clicks, wasted, chords
+            ),
+        });
+    }
+
+    fn storage_pulled(&mut self, key: String, value: String) {
+        self.storage.accept_pulled(&key, value);
+        if key == history::KEY {
+            self.history = History::restore(self.storage.as_ref());
+        }
+        if key == profiles::KEY {
+            self.profiles = Profiles::restore(self.storage.as_ref());
+            self.apply_controls();
+        }
+        if key == presets::KEY {
+            self.presets = Presets::restore(self.storage.as_ref());
+        }
+        if key == race::KEY {
+            self.race = Run::restore(self.storage.as_ref());
+        }
+        if key == trainer::KEY {
+            self.trainer = Trainer::restore(self.storage.as_ref());
+        }
+        if key == speed_drill::KEY {
+            self.speed_records = SpeedRecords::restore(self.storage.as_ref());
+        }
+    }
+
+    fn toggle_mode(&mut self) {
+        if self.state.game.toggle_mode() {
+            self.save_settings();
+        }
+    }
+
+    // The difficulty's accent, which style.css picks up.
+    fn render_body_style(&self) -> String {
+        match self.presets.get(&self.state.difficulty).accent() {
+            Some(accent) => format!("--accent: {}", accent),
+            None => String::new(),
+        }
+    }
+
+    fn render_body_class(&self) -> String {
+        let state = match self.state.game.board.state {
+            Ready | Playing => "ongoing",
+            Won => "won",
+            Failed => "failed",
+            NotReady => unreachable!(),
+        };
+        let layout = self.profiles.active().settings.layout;
+        let mut classes = vec![state];
+        if layout.mirrored {
+            classes.push("mirrored");
+        }
+        if layout.pad_left {
+            classes.push("pad-left");
+        }
+        if self.profiles.active().settings.compact {
+            classes.push("compact");
+        }
+        if self.zen.is_some() {
+            classes.push("zen");
+        }
+        if self._zen_next.is_some() {
+            classes.push("zen-leaving");
+        }
+        classes.join(" ")
+    }
+
+    fn render_difficulty(&self) -> Html {
+        html! {
+            self.presets.emoji(&self.state.difficulty)
+        }
+    }
+
+    // The timer, and the buttons for how it times.
+    fn render_clock(&self) -> Html {
+        let zen = html! {
+            <div
+             id="zen-button"
+             class=if self.zen.is_some() { "clickable item active" } else { "clickable item" }
+             title="Zen: no clock, one board after another"
+             onclick=self.link.callback(|_| Msg::ToggleZen) >
+                { "🧘" }
+            </div>
+        };
+        let speed_drill = html! {
+            <div
+             id="speed-drill-button"
+             class=if self.speed_drill.is_some() { "clickable item active" } else { "clickable item" }
+             title="Speed drill: small boards back to back, timed together"
+             onclick=self.link.callback(|_| Msg::ToggleSpeedDrill) >
+                { "🏃" }
+            </div>
+        };
+        if let Some(session) = &self.zen {
+            return html! {
+                <>
+                    { zen }
+                    { speed_drill }
+                    <span id="zen-status">{ session.status(&self.state.game.board) }</span>
+                </>
+            };
+        }
+        if let Some(drill) = &self.speed_drill {
+            return html! {
+                <>
+                    { zen }
+                    { speed_drill }
+                    <div
+                     id="speed-drill-length"
+                     class="clickable item"
+                     title="Boards in a drill: picking another starts over"
+                     onclick=self.link.callback(|_| Msg::CycleSpeedDrill) >
+                        { format!("🔢 {}", drill.length) }
+                    </div>
+                    <TimeKeeper
+                     stopwatch=self.stopwatch.clone()
+                     timing=Timing::RealTime />
+                    <span id="speed-drill-status">{ drill.status() }</span>
+                    <span id="speed-drill-records">{ self.speed_records.describe(drill.length) }</span>
+                </>
+            };
+        }
+        html! {
+            <>
+                { zen }
+                { speed_drill }
+                <div
+                 id="splits-button"
+                 class=self.render_splits_class()
+                 title="Splits at 25, 50 and 75%"
+                 onclick=self.link.callback(|_| Msg::ToggleSplits) >
+                    { "⏱️" }
+                </div>
+                <div
+                 id="timing-button"
+                 class=self.render_timing_class()
+                 title="Real time, or game time without pauses and menus"
+                 onclick=self.link.callback(|_| Msg::CycleTiming) >
+                    { "🕰️" }
+                </div>
+                <TimeKeeper
+                 stopwatch=self.stopwatch.clone()
+                 timing=self.profiles.active().settings.timing />
+                { self.render_penalty() }
+                { self.render_guess_badge() }
+                { self.render_deductions() }
+                { self.render_retry() }
+                { self.render_restart_countdown() }
+            </>
+        }
+    }
+
+    fn render_screen(&self) -> Html {
+        match &self.screen {
+            Screen::Game => self.render_game(),
+            Screen::History => self.render_history(),
+            Screen::Profiles => self.render_profiles(),
+            Screen::Duel => self.render_duel(),
+            Screen::Broadcast => self.render_broadcast(),
+            Screen::Import => self.render_import(),
+            Screen::Print => self.render_print(),
+            Screen::Featured => self.render_featured(),
+            Screen::Learn => self.render_learn(),
+            Screen::Race => self.render_race(),
+            Screen::Replay { index, step, board } => self.render_replay(*index, *step, board),
+            Screen::Lesson { lesson, step } => self.render_lesson(lesson, *step),
+            Screen::Trainer { drill, picked } => self.render_trainer(drill, *picked),
+            Screen::Patterns { pattern } => self.render_patterns(*pattern),
+            Screen::Analysis { analysis } => self.render_analysis(analysis),
+            Screen::Stress => self.render_stress(),
+            Screen::Keys { capturing } => self.render_keys(*capturing),
+        }
+    }
+
+    fn render_game(&self) -> Html {
+        html! {
+            <>
+                { self.render_opponent() }
+                { self.render_seed_controls() }
+                { self.render_scenario() }
+                { self.render_recap() }
+                { self.render_board_with(&self.state.game.board, &self.game_marks(), self.render_overlays()) }
+                <Effects effect=Effect::Confetti play=self.celebrations />
+                { self.render_bonus() }
+                { self.render_notes() }
+                { self.render_strategy_picker() }
+                { self.render_robot_log() }
+                { self.render_openings() }
+                { self.render_pad() }
+            </>
+        }
+    }
+
+    // Cells size themselves from --board-width, see style.css. `marks` adds a
+    // class to some of the cells, e.g. the d-pad's cursor.
+    fn render_board(&self, board: &Board, marks: &[(Point, &'static str)]) -> Html {
+        self.render_board_with(board, marks, html! {})
+    }
+
+    // With `overlay` drawn over the cells, lined up with them.
+    fn render_board_with(
+        &self,
+        board: &Board,
+        marks: &[(Point, &'static str)],
+        overlay: Html,
+    ) -> Html {
+        html! {
+            <div id="board_game_placeholder">
+                <div
+                 id="board_game"
+                 class={if board.width > 16 { "flex-container wide" } else { "flex-container" }}
+                 style={format!("--board-width: {}", board.width)}>
+                    {
+                        (0..board.height)
+                            .map(|y| html! {
+                                <BoardRow
+                                    y={y}
+                                    board_state={board.state.clone()}
+                                    elements={board.row(y).unwrap()}
+                                    marks={marks.iter().filter(|(p, _)| p.y == y as i32).map(|(p, mark)| (p.x as usize, *mark)).collect::<Vec<_>>()}
+                                    changes={self.board_changes.clone()}
+                                    update_signal={self.link.callback(|msg: Msg| msg)}/>
+                            })
+                            .collect::<Html>()
+                    }
+                    { overlay }
+                </div>
+            </div>
+        }
+    }
+
+    fn render_history_button(&self) -> &str {
+        match self.screen {
+            Screen::Game => "📜",
+            _ => "🎮",
+        }
+    }
+
+    fn render_history(&self) -> Html {
+        if self.history.is_empty() {
+            return html! {
+                <div id="history_placeholder" class="flex-container">
+                    <p>{ "No finished games yet" }</p>
+                    { self.render_download_log() }
+                </div>
+            };
+        }
+        html! {
+            <div id="history_placeholder" class="flex-container">
+                <table id="history">
+                    { self.history.newest_first().map(|(index, game)| self.render_history_row(index, game)).collect::<Html>() }
+                </table>
+                { self.render_download_log() }
+            </div>
+        }
+    }
+
+    // For bug reports: the moves, state changes and robot decisions so far.
+    fn render_download_log(&self) -> Html {
+        html! {
+            <div
+             id="download-log-button"
+             class="clickable item"
+             title="Download debug log"
+             onclick=self.link.callback(|_| Msg::DownloadLog)>
+                { "🐞" }
+            </div>
+        }
+    }
+
+    fn render_history_row(&self, index: usize, game: &GameRecord) -> Html {
+        let time = game
+            .time_ms(self.profiles.active().settings.timing)
+            .map_or(String::from("–"), |ms| format!("{:.1}s", ms / 1000_f64));
+        let finished_at = Date::new(&JsValue::from_f64(game.finished_at));
+        html! {
+            <tr>
+                <td>
+                    { if game.result == Won { "🏆" } else { "☠️" } }
+                    { if game.retry { "🔂" } else { "" } }
+                </td>
+                <td>{ self.presets.emoji(&game.difficulty) }</td>
+                <td>{ time }</td>
+                <td>{ String::from(finished_at.to_locale_string("default", &JsValue::UNDEFINED)) }</td>
+                <td>
+                    <div
+                     class="clickable item"
+                     onclick=self.link.callback(move |_| Msg::OpenReplay { index })>
+                        { "▶️" }
+                    </div>
+                </td>
+                <td>
+                    <div
+                     class="clickable item"
+                     onclick=self.link.callback(move |_| Msg::RestartGame { index })>
+                        { "🔁" }
+                    </div>
+                </td>
+            </tr>
+        }
+    }
+
+    fn render_profiles(&self) -> Html {
+        html! {
+            <>
+            <div id="profiles_placeholder" class="flex-container">
+                <table id="profiles">
+                    { self.profiles.iter().map(|(index, profile)| self.render_profile_row(index, profile)).collect::<Html>() }
+                </table>
+                <div
+                 id="add-profile-button"
+                 class="clickable item"
+                 onclick=self.link.callback(|_| Msg::AddProfile) >
+                    { "➕" }
+                </div>
+                <div
+                 id="export-stats-button"
+                 class="clickable item"
+                 title="Export stats as CSV"
+                 onclick=self.link.callback(|_| Msg::ExportStats) >
+                    { "💾" }
+                </div>
+                <div
+                 id="export-backup-button"
+                 class="clickable item"
+                 title="Back up profiles, stats and history, to carry to another browser"
+                 onclick=self.link.callback(|_| Msg::ExportBackup) >
+                    { "🗄️" }
+                </div>
+                <div
+                 id="keys-button"
+                 class="clickable item"
+                 title="Keyboard keys"
+                 onclick=self.link.callback(|_| Msg::ToggleKeys) >
+                    { "⌨️" }
+                </div>
+                <label
+                 id="import-backup-button"
+                 class="clickable item"
+                 title="Merge a backup from another browser">
+                    { "📥" }
+                    <input
+                     type="file"
+                     accept=".json,application/json"
+                     onchange=self.link.callback(|e: ChangeData| match e {
+                         ChangeData::Files(files) => Msg::PickFile { file: files.get(0) },
+                         _ => Msg::PickFile { file: None },
+                     })/>
+                </label>
+            </div>
+            { self.render_heatmap() }
+            { self.render_layout_options() }
+            { self.render_audio_options() }
+            </>
+        }
+    }
+
+    // The active profile's clicks in blue and losses in red, darker where
+    // there were more.
+    fn render_heatmap(&self) -> Html {
+        let heatmap = &self.profiles.active().stats.heatmap;
+        if heatmap.is_empty() {
+            return html! {};
+        }
+        let most = |f: fn((u32, u32)) -> u32| {
+            (0..heatmap::SIZE * heatmap::SIZE)
+                .map(|i| f(heatmap.at(i % heatmap::SIZE, i / heatmap::SIZE)))
+                .max()
+                .unwrap_or(0)
+                .max(1) as f64
+        };
+        let (most_clicks, most_losses) = (most(|(c, _)| c), most(|(_, l)| l));
+        let square = |x: usize, y: usize| {
+            let (clicks, losses) = heatmap.at(x, y);
+            let style = format!(
+                "background-color: rgba(82, 150, 165, {:.2}); box-shadow: inset 0 0 0 4px rgba(244, 121, 107, {:.2})",
+                clicks as f64 / most_clicks,
+                losses as f64 / most_losses
+            );
+            html! {
+                <td style=style title=format!("{} clicks, {} losses", clicks, losses)></td>
+            }
+        };
+        html! {
+            <div id="heatmap_placeholder" class="flex-container">
+                <p>{ "Where you click (blue) and lose (red), with every board scaled to the same square" }</p>
+                <table id="heatmap">
+                    {
+                        for (0..heatmap::SIZE).map(|y| html! {
+                            <tr>{ for (0..heatmap::SIZE).map(|x| square(x, y)) }</tr>
+                        })
+                    }
+                </table>
+            </div>
+        }
+    }
+
+    fn render_layout_options(&self) -> Html {
+        let layout = self.profiles.active().settings.layout;
+        let option = |label: &str, title: &str, on: bool, changed: Layout| {
+            html! {
+                <div
+                 class=if on { "clickable item active" } else { "clickable item" }
+                 title=title
+                 onclick=self.link.callback(move |_| Msg::SetLayout { layout: changed })>
+                    { label }
+                </div>
+            }
+        };
+        html! {
+            <div id="layout_placeholder" class="flex-container">
+                <span>{ "🫲 Left-handed" }</span>
+                { option("↔️", "Mirror the header", layout.mirrored, Layout {
+                    mirrored: !layout.mirrored,
+                    ..layout
+                }) }
+                { option("🕹️", "D-pad on the left", layout.pad_left, Layout {
+                    pad_left: !layout.pad_left,
+                    ..layout
+                }) }
+                { option("🖱️", "Swap the mouse buttons", layout.swap_buttons, Layout {
+                    swap_buttons: !layout.swap_buttons,
+                    ..layout
+                }) }
+            </div>
+        }
+    }
+
+    fn render_audio_options(&self) -> Html {
+        let audio = self.profiles.active().settings.audio;
+        let option = |label: &str, title: &str, on: bool, changed: Audio| {
+            html! {
+                <div
+                 class=if on { "clickable item active" } else { "clickable item" }
+                 title=title
+                 onclick=self.link.callback(move |_| Msg::SetAudio { audio: changed })>
+                    { label }
+                </div>
+            }
+        };
+        html! {
+            <div id="audio_placeholder" class="flex-container">
+                <span>{ if audio.is_silent() { "🔇 Sound" } else { "🔊 Sound" } }</span>
+                <input
+                 id="volume-slider"
+                 type="range"
+                 min="0"
+                 max="100"
+                 title="Volume"
+                 value=audio.volume.to_string()
+                 oninput=self.link.callback(move |e: InputData| Msg::SetAudio {
+                     audio: Audio {
+                         volume: e.value.parse().unwrap_or(audio.volume),
+                         ..audio
+                     },
+                 }) />
+                { option("💥", "Sounds for opening, flagging and mines", audio.effects, Audio {
+                    effects: !audio.effects,
+                    ..audio
+                }) }
+                { option("🎺", "A tune when a game is won or lost", audio.jingles, Audio {
+                    jingles: !audio.jingles,
+                    ..audio
+                }) }
+            </div>
+        }
+    }
+
+    fn render_profile_row(&self, index: usize, profile: &Profile) -> Html {
+        let stats = &profile.stats;
+        let best_ms = stats.best_ms(profile.settings.timing);
+        let best = |preset: &DifficultyPreset| match best_ms.get(&preset.difficulty()) {
+            Some(ms) => format!("{} {}s", preset.emoji, stopwatch::seconds(*ms)),
+            None => format!("{} –", preset.emoji),
+        };
+        let efficiency = match stats.clicks.efficiency(stats.three_bv) {
+            Some(efficiency) => format!("🖱️ {:.0}%", efficiency * 100_f64),
+            None => String::from("🖱️ –"),
+        };
+        let class = if index == self.profiles.active_index() {
+            "clickable active"
+        } else {
+            "clickable"
+        };
+        html! {
+            <tr
+             class=class
+             onclick=self.link.callback(move |_| Msg::SwitchProfile { index })>
+                <td>{ &profile.avatar }</td>
+                <td>{ &profile.name }</td>
+                <td>{ format!("🏆 {}/{}", stats.won, stats.played) }</td>
+                <td>{ format!("🔂 {}/{}", stats.retries_won, stats.retries) }</td>
+                <td>{ format!("🔥 {} (best {})", stats.streak, stats.best_streak) }</td>
+                <td>{ efficiency }</td>
+                { for self.presets.iter().map(|preset| html! { <td>{ best(preset) }</td> }) }
+            </tr>
+        }
+    }
+
+    fn render_replay(&self, index: usize, step: usize, board: &Board) -> Html {
+        let game = self.history.get(index);
+        let replay = game.map(|game| &game.replay);
+        let moves = replay.map_or(0, |r| r.moves.len());
+        let overlay = match game {
+            Some(game) => html! {
+                <>
+                    { self.render_trail(Some(&game.replay), step) }
+                    { notes::render_overlay(&game.annotations, board.width, board.height) }
+                </>
+            },
+            None => html! {},
+        };
+        // Marks the step if an assist played its move.
+        let assisted = match replay {
+            Some(r) if step > 0 && r.is_assisted(step - 1) => " 🪄",
+            _ => "",
+        };
+        let previous = step.saturating_sub(1);
+        let next = (step + 1).min(moves);
+        html! {
+            <>
+                <div id="replay_placeholder" class="flex-container">
+                    <div
+                     class="clickable item"
+                     onclick=self.link.callback(move |_| Msg::StepReplay { step: previous })>
+                        { "◀️" }
+                    </div>
+                    <span>{ format!("{} / {}{}", step, moves, assisted) }</span>
+                    <div
+                     class="clickable item"
+                     onclick=self.link.callback(move |_| Msg::StepReplay { step: next })>
+                        { "▶️" }
+                    </div>
+                    <div
+                     class="clickable item"
+                     title="Take this position to the analysis board"
+                     onclick=self.link.callback(|_| Msg::Analyse)>
+                        { "🔬" }
+                    </div>
+                </div>
+                { self.render_board_with(board, &[], overlay) }
+            </>
+        }
+    }
+
+    fn render_analysis(&self, analysis: &Analysis) -> Html {
+        let robot = if self.robot.is_available() {
+            html! {
+                <>
+                    <div
+                     id="analysis-step-button"
+                     class="clickable item"
+                     title="Play 🤖's next move"
+                     onclick=self.link.callback(|_| Msg::AnalysisStep) >
+                        { "🤖" }
+                    </div>
+                    <div
+                     id="analysis-odds-button"
+                     class="clickable item"
+                     title="Each closed cell's chance of being a mine"
+                     onclick=self.link.callback(|_| Msg::AnalysisOdds) >
+                        { "%" }
+                    </div>
+                </>
+            }
+        } else {
+            html! {}
+        };
+        html! {
+            <>
+                <div id="analysis_placeholder" class="flex-container">
+                    <div
+                     id="analysis-tool-button"
+                     class="clickable item"
+                     title=analysis.tool.describe()
+                     onclick=self.link.callback(|_| Msg::CycleAnalysisTool) >
+                        { analysis.tool.emoji() }
+                    </div>
+                    { robot }
+                    <span>{ analysis.note.as_deref().unwrap_or("🔬 Every mine shows, and nothing here is a game") }</span>
+                </div>
+                { self.render_board_with(&analysis.board, &[], analysis::render_overlay(analysis)) }
+            </>
+        }
+    }
+
+    fn render_lesson(&self, lesson: &Lesson, step: usize) -> Html {
+        let steps = lesson.step_count();
+        let previous = step.saturating_sub(1);
+        let next = (step + 1).min(steps);
+        let (reason, marks, link) = match lesson.step(step) {
+            Some(s) => {
+                let target = match s.m {
+                    Move::Open(p) | Move::Flag(p) | Move::Chord(p) => Some(p),
+                    Move::OpenAllUnflagged => None,
+                };
+                let mut marks: Vec<(Point, &'static str)> = s
+                    .constrained
+                    .iter()
+                    .map(|p| (*p, "lesson-constrained"))
+                    .collect();
+                marks.push((s.source, "lesson-source"));
+                marks.extend(target.map(|p| (p, "lesson-move")));
+                (
+                    s.reason.clone(),
+                    marks,
+                    render_pattern_link(pattern_for(&s.m, Some(s))),
+                )
+            }
+            None => (
+                String::from("That's every move 🤖 is sure of from here."),
+                vec![],
+                html! {},
+            ),
+        };
+        html! {
+            <>
+                <div id="lesson_placeholder" class="flex-container">
+                    <div
+                     class="clickable item"
+                     onclick=self.link.callback(move |_| Msg::StepLesson { step: previous })>
+                        { "◀️" }
+                    </div>
+                    <span>{ format!("{} / {}", step, steps) }</span>
+                    <div
+                     class="clickable item"
+                     onclick=self.link.callback(move |_| Msg::StepLesson { step: next })>
+                        { "▶️" }
+                    </div>
+                </div>
+                <p id="lesson-reason">{ reason } { link }</p>
+                { self.render_board(lesson.board(step), &marks) }
+            </>
+        }
+    }
+
+    // The pass-and-play button, and whose turn it is while it's on.
+    fn render_hot_seat(&self) -> Html {
+        let (class, status) = match &self.state.hot_seat {
+            Some(seat) => ("clickable item active", seat.status()),
+            None => ("clickable item", String::new()),
+        };
+        html! {
+            <>
+                <div
+                 id="hot-seat-button"
+                 class=class
+                 title="Two players taking turns on this device"
+                 onclick=self.link.callback(|_| Msg::ToggleHotSeat) >
+                    { "👥" }
+                </div>
+                <span id="hot-seat-status">{ status }</span>
+                {
+                    if self.state.hot_seat.is_some() {
+                        self.render_turn_options()
+                    } else {
+                        html! {}
+                    }
+                }
+                { self.render_turn_timer() }
+            </>
+        }
+    }
+
+    // The time limit on each move in a match, and what running out does.
+    fn render_turn_options(&self) -> Html {
+        let settings = &self.profiles.active().settings;
+        let limit = match settings.turn_limit {
+            0 => String::from("⏳ off"),
+            limit => format!("⏳ {}s", limit),
+        };
+        html! {
+            <>
+                <div
+                 id="turn-limit-button"
+                 class=if settings.turn_limit != 0 { "clickable item active" } else { "clickable item" }
+                 title="Seconds for each move"
+                 onclick=self.link.callback(|_| Msg::CycleTurnLimit) >
+                    { limit }
+                </div>
+                <div
+                 id="turn-expiry-button"
+                 class="clickable item"
+                 title=settings.turn_expiry.describe()
+                 onclick=self.link.callback(|_| Msg::CycleTurnExpiry) >
+                    { settings.turn_expiry.emoji() }
+                </div>
+            </>
+        }
+    }
+
+    // The seconds left for the move being played, going red near the end.
+    fn render_turn_timer(&self) -> Html {
+        let timer = match &self.turn_timer {
+            Some(timer) => timer,
+            None => return html! {},
+        };
+        let class = if timer.left <= 3 { "running-out" } else { "" };
+        html! {
+            <span id="turn-timer" class=class>{ timer.status() }</span>
+        }
+    }
+
+    fn render_opponent(&self) -> Html {
+        let (progress, state) = match &self.lobby.opponent {
+            Some(opponent) => opponent,
+            None => return html! {},
+        };
+        let result = match state {
+            Won => "🏆",
+            Failed => "☠️",
+            _ => "",
+        };
+        html! {
+            <div id="opponent_placeholder" class="flex-container">
+                { format!("⚔️ opponent {:.0}% {}", progress * 100_f64, result) }
+                { for self.power_ups.held.iter().enumerate().map(|(index, power_up)| html! {
+                    <div
+                     class="clickable item"
+                     title=power_up.describe()
+                     onclick=self.link.callback(move |_| Msg::UsePowerUp { index })>
+                        { power_up.emoji() }
+                    </div>
+                }) }
+            </div>
+        }
+    }
+
+    fn render_duel(&self) -> Html {
+        html! {
+            <div id="duel_placeholder" class="flex-container">
+                { self.render_duel_steps() }
+                {
+                    match &self.lobby.error {
+                        Some(error) => html! { <p class="duel-error">{ error }</p> },
+                        None => html! {},
+                    }
+                }
+            </div>
+        }
+    }
+
+    fn render_duel_steps(&self) -> Html {
+        let waiting = self.lobby.signal.is_empty();
+        match &self.duel {
+            _ if self.lobby.connected => html! {
+                <>
+                    <p>{ "Connected! You're both playing the same board." }</p>
+                    { self.render_duel_button("🏳️ leave", || Msg::LeaveDuel) }
+                </>
+            },
+            None => html! {
+                <>
+                    { self.render_turn_options() }
+                    { self.render_duel_button("📨 host a duel", || Msg::HostDuel) }
+                    <p>{ "or paste an invite to join one:" }</p>
+                    { self.render_duel_input() }
+                    { self.render_duel_button("🤝 join", || Msg::ConnectDuel) }
+                </>
+            },
+            Some(_) if waiting => html! { <p>{ "Preparing…" }</p> },
+            Some(duel) if duel.is_host() => html! {
+                <>
+                    <p>{ "Send this invite to your opponent:" }</p>
+                    <textarea class="duel-signal" readonly=true value=&self.lobby.signal />
+                    <p>{ "then paste their reply here:" }</p>
+                    { self.render_duel_input() }
+                    { self.render_duel_button("🤝 connect", || Msg::ConnectDuel) }
+                </>
+            },
+            Some(_) => html! {
+                <>
+                    <p>{ "Send this reply back to the host:" }</p>
+                    <textarea class="duel-signal" readonly=true value=&self.lobby.signal />
+                </>
+            },
+        }
+    }
+
+    fn render_duel_input(&self) -> Html {
+        html! {
+            <textarea
+             class="duel-signal"
+             value=&self.lobby.input
+             oninput=self.link.callback(|e: InputData| Msg::UpdateDuelInput { value: e.value }) />
+        }
+    }
+
+    fn render_duel_button(&self, label: &str, msg: fn() -> Msg) -> Html {
+        html! {
+            <div
+             class="clickable item"
+             onclick=self.link.callback(move |_| msg())>
+                { label }
+            </div>
+        }
+    }
+
+    // For puzzle authors: the cell to start from, and the button that
+    // changes the board's mines as little as it can to win from there
+    // without guessing. Needs the solver.
+    fn render_repair(&self) -> Html {
+        if !self.robot.is_available() {
+            return html! {};
+        }
+        html! {
+            <>
+                <input
+                 id="repair-start"
+                 type="text"
+                 placeholder="Start: column,row"
+                 title="The cell to solve from, counting from 1. Blank for the biggest opening"
+                 value=&self.importer.start
+                 oninput=self.link.callback(|e: InputData| Msg::UpdateRepairStart { value: e.value }) />
+                { self.render_duel_button("🛠️ make solvable", || Msg::RepairBoard) }
+                {
+                    match &self.importer.report {
+                        Some(report) => html! { <p>{ report }</p> },
+                        None => html! {},
+                    }
+                }
+            </>
+        }
+    }
+
+    fn render_stress(&self) -> Html {
+        let input = |id: &str, value: &str, msg: fn(String) -> Msg| {
+            html! {
+                <input
+                 id=id
+                 type="number"
+                 value=value
+                 oninput=self.link.callback(move |e: InputData| msg(e.value)) />
+            }
+        };
+        html! {
+            <div id="stress_placeholder" class="flex-container">
+                <p>{ "Builds, opens and draws a giant board, timing each step" }</p>
+                <div>
+                    { input("stress-width", &self.stress.width, |value| Msg::UpdateStressWidth { value }) }
+                    { "×" }
+                    { input("stress-height", &self.stress.height, |value| Msg::UpdateStressHeight { value }) }
+                    { ", mines: " }
+                    { input("stress-mines", &self.stress.mines, |value| Msg::UpdateStressMines { value }) }
+                </div>
+                { self.render_duel_button("⏱️ run", || Msg::RunStress) }
+                {
+                    match &self.stress.error {
+                        Some(error) => html! { <p class="duel-error">{ error }</p> },
+                        None => html! {},
+                    }
+                }
+                <ul id="stress-report">
+                    { for self.stress.report().into_iter().map(|line| html! { <li>{ line }</li> }) }
+                </ul>
+                {
+                    match &self.stress.board {
+                        Some(board) => self.render_board(board, &[]),
+                        None => html! {},
+                    }
+                }
+            </div>
+        }
+    }
+
+    // Each action with its key. Clicking a key waits for the one to put in
+    // its place.
+    fn render_keys(&self, capturing: Option<KeyAction>) -> Html {
+        let bindings = &self.profiles.active().settings.keys;
+        let row = |action: KeyAction| {
+            let (class, label) = if capturing == Some(action) {
+                ("clickable item active", String::from("…"))
+            } else {
+                ("clickable item", keys::label(bindings.key(action)))
+            };
+            html! {
+                <tr>
+                    <td>{ action.describe() }</td>
+                    <td>
+                        <div
+                         class=class
+                         onclick=self.link.callback(move |_| Msg::CaptureKey { action })>
+                            { label }
+                        </div>
+                    </td>
+                </tr>
+            }
+        };
+        html! {
+            <div id="keys_placeholder" class="flex-container">
+                <p>{ "Click a key, then press the one to use instead. Esc keeps it." }</p>
+                <table id="keys">
+                    { for keys::ACTIONS.iter().map(|action| row(*action)) }
+                </table>
+                {
+                    if bindings.is_default() {
+                        html! {}
+                    } else {
+                        self.render_duel_button("↩️ defaults", || Msg::ResetKeys)
+                    }
+                }
+            </div>
+        }
+    }
+
+    fn render_import(&self) -> Html {
+        html! {
+            <div id="import_placeholder" class="flex-container">
+                <p>{ "Paste a board: its grid, its CSV export or its JSON" }</p>
+                <textarea
+                 class="duel-signal"
+                 value=&self.importer.input
+                 oninput=self.link.callback(|e: InputData| Msg::UpdateImportInput { value: e.value }) />
+                { self.render_duel_button("📥 load", || Msg::ImportBoard) }
+                { self.render_repair() }
+                {
+                    match &self.importer.error {
+                        Some(error) => html! { <p class="duel-error">{ error }</p> },
+                        None => html! {},
+                    }
+                }
+            </div>
+        }
+    }
+
+    fn render_trainer(&self, drill: &Drill, picked: Option<Answer>) -> Html {
+        let button = |label: &str, answer: Answer| {
+            html! {
+                <div
+                 class="clickable item"
+                 onclick=self.link.callback(move |_| Msg::AnswerDrill { answer })>
+                    { label }
+                </div>
+            }
+        };
+        let mut marks = vec![(drill.target, "lesson-move")];
+        let feedback = match picked {
+            None => html! {
+                <div id="drill_answers" class="flex-container">
+                    { button("⛏️ safe", Answer::Safe) }
+                    { button("🚩 mine", Answer::Mine) }
+                    { button("🤷 can't tell", Answer::Unknown) }
+                </div>
+            },
+            Some(answer) => {
+                marks.extend(drill.sources.iter().map(|p| (*p, "lesson-source")));
+                let verdict = if answer == drill.answer {
+                    String::from("✅ Right!")
+                } else {
+                    format!("❌ It's {}.", trainer::answer_name(drill.answer))
+                };
+                html! {
+                    <>
+                        <p id="drill-why">
+                            { format!("{} {} ", verdict, drill.why) }
+                            { render_pattern_link(drill.pattern) }
+                        </p>
+                        <div
+                         id="next-drill-button"
+                         class="clickable item"
+                         onclick=self.link.callback(|_| Msg::NextDrill)>
+                            { "▶️ next" }
+                        </div>
+                    </>
+                }
+            }
+        };
+        html! {
+            <>
+                <div id="trainer_placeholder" class="flex-container">
+                    <p>{ "Is the flashing cell safe, a mine, or impossible to tell?" }</p>
+                    { feedback }
+                    <p id="trainer-summary">{ self.trainer.summary() }</p>
+                </div>
+                { self.render_board(&drill.board, &marks) }
+            </>
+        }
+    }
+
+    // Hovering over an example shows what decides its flashing cell.
+    fn render_patterns(&self, pattern: Option<Pattern>) -> Html {
+        html! {
+            <div id="patterns_placeholder" class="flex-container">
+                { for lib_minesweeper::reference().iter().map(|r| render_reference(r, Some(r.pattern) == pattern)) }
+            </div>
+        }
+    }
+
+    fn render_featured(&self) -> Html {
+        html! {
+            <div id="featured_placeholder" class="flex-container">
+                <table id="featured">
+                    { for lib_minesweeper::featured().iter().enumerate().map(|(index, featured)| html! {
+                        <tr>
+                            <td>{ featured.kind.name() }</td>
+                            <td>{ &featured.name }</td>
+                            <td class="featured-note">{ &featured.note }</td>
+                            <td>
+                                <div
+                                 class="clickable item"
+                                 onclick=self.link.callback(move |_| Msg::PlayFeatured { index })>
+                                    { "▶️" }
+                                </div>
+                            </td>
+                        </tr>
+                    }) }
+                </table>
+            </div>
+        }
+    }
+
+    fn render_learn(&self) -> Html {
+        html! {
+            <div id="learn_placeholder" class="flex-container">
+                <table id="learn">
+                    { for lib_minesweeper::scenarios().iter().enumerate().map(|(index, scenario)| html! {
+                        <tr>
+                            <td>{ scenario.kind.name() }</td>
+                            <td>{ &scenario.title }</td>
+                            <td>
+                                <div
+                                 class="clickable item"
+                                 onclick=self.link.callback(move |_| Msg::PlayScenario { index })>
+                                    { "▶️" }
+                                </div>
+                            </td>
+                        </tr>
+                    }) }
+                </table>
+            </div>
+        }
+    }
+
+    // The scenario's step: what to do, and a button to read on when that's
+    // all it asks.
+    fn render_scenario(&self) -> Html {
+        let run = match &self.scenario {
+            Some((_, run)) => run,
+            None => return html! {},
+        };
+        let steps = run.scenario.steps.len();
+        let (message, next) = match run.step() {
+            Some(step) => (step.message.as_str(), step.trigger == Trigger::Next),
+            None => ("Done! Pick another from 🎓.", false),
+        };
+        html! {
+            <div id="scenario-step">
+                <span class="scenario-count">
+                    { format!("🎓 {}/{}", (run.index() + 1).min(steps), steps) }
+                </span>
+                <span>{ message }</span>
+                { if next {
+                    html! {
+                        <div
+                         class="clickable item"
+                         title="Next"
+                         onclick=self.link.callback(|_| Msg::ScenarioNext)>
+                            { "▶️" }
+                        </div>
+                    }
+                } else {
+                    html! {}
+                } }
+            </div>
+        }
+    }
+
+    fn render_race(&self) -> Html {
+        let button = |label: &str, msg: fn() -> Msg| {
+            html! {
+                <div
+                 class="clickable item"
+                 onclick=self.link.callback(move |_| msg())>
+                    { label }
+                </div>
+            }
+        };
+        let run = match &self.race {
+            Some(run) => run,
+            None => {
+                return html! {
+                    <div id="race_placeholder" class="flex-container">
+                        <p>{ "Everyone with the same code plays the same boards, in order." }</p>
+                        { button("🆕 new race", || Msg::NewRace) }
+                        <p>{ "or enter a code to join one:" }</p>
+                        <input
+                         id="race-input"
+                         type="text"
+                         placeholder="5-k3j9x2"
+                         value=&self.race_input
+                         oninput=self.link.callback(|e: InputData| Msg::UpdateRaceInput { value: e.value }) />
+                        { button("🤝 join", || Msg::JoinRace) }
+                    </div>
+                };
+            }
+        };
+        let race = run.race();
+        html! {
+            <div id="race_placeholder" class="flex-container">
+                <p>{ format!("Race {}: send the code to the others.", run.code) }</p>
+                <table id="race">
+                    { for (0..race.len()).map(|i| html! {
+                        <tr>
+                            <td>{ i + 1 }</td>
+                            {
+                                match run.finishes.get(i) {
+                                    Some(finish) => html! {
+                                        <>
+                                            <td>{ if finish.won { "🏆" } else { "☠️" } }</td>
+                                            <td>{ format!("{}s", stopwatch::seconds(finish.ms)) }</td>
+                                        </>
+                                    },
+                                    None => html! { <><td></td><td></td></> },
+                                }
+                            }
+                        </tr>
+                    }) }
+                </table>
+                <p>{ run.score() }</p>
+                {
+                    match run.next() {
+                        Some(next) => button(&format!("▶️ board {}", next + 1), || Msg::PlayRace),
+                        None => html! {},
+                    }
+                }
+                { button("📤 export results", || Msg::ExportRace) }
+                { button("🏳️ leave", || Msg::LeaveRace) }
+            </div>
+        }
+    }
+
+    // The board as a paper puzzle: the open numbers on a plain grid with
+    // coordinates, and a legend showing which cells the numbers count.
+    fn render_print(&self) -> Html {
+        let board = &self.state.game.board;
+        html! {
+            <div id="print_placeholder" class="flex-container">
+                <table id="print_board">
+                    <tr>
+                        <th></th>
+                        { for (1..=board.width).map(|x| html! { <th>{ x }</th> }) }
+                    </tr>
+                    { for (0..board.height).map(|y| self.render_print_row(y)) }
+                </table>
+                <p>{ "Each number counts the mines in the cells marked ● around it:" }</p>
+                <table id="print_legend">
+                    { for (-2..=2).map(|dy| html! {
+                        <tr>
+                            { for (-2..=2).map(|dx| html! {
+                                <td>{ print_legend_cell(board.adjacency, dx, dy) }</td>
+                            }) }
+                        </tr>
+                    }) }
+                </table>
+                <div
+                 id="print-now-button"
+                 class="clickable item"
+                 onclick=self.link.callback(|_| Msg::Print) >
+                    { "🖨️" }
+                </div>
+            </div>
+        }
+    }
+
+    fn render_print_row(&self, y: usize) -> Html {
+        let board = &self.state.game.board;
+        let cell = |x: usize| match board.at(&Point::new(x, y)) {
+            Some(Number { state: Open, count }) => count.to_string(),
+            Some(Number { state: Flagged, .. }) | Some(Mine { state: Flagged }) => {
+                String::from("⚑")
+            }
+            _ => String::new(),
+        };
+        html! {
+            <tr>
+                <th>{ y + 1 }</th>
+                { for (0..board.width).map(|x| html! { <td>{ cell(x) }</td> }) }
+            </tr>
+        }
+    }
+
+    fn render_broadcast(&self) -> Html {
+        html! {
+            <div id="broadcast_placeholder" class="flex-container">
+                { self.render_broadcast_steps() }
+                {
+                    match &self.spectator.error {
+                        Some(error) => html! { <p class="duel-error">{ error }</p> },
+                        None => html! {},
+                    }
+                }
+            </div>
+        }
+    }
+
+    fn render_broadcast_steps(&self) -> Html {
+        match &self.broadcast {
+            None => html! {
+                <>
+                    { self.render_duel_button("📡 broadcast my games", || Msg::StartBroadcast) }
+                    <p>{ "or enter a code to watch someone else:" }</p>
+                    <input
+                     id="watch-input"
+                     type="text"
+                     value=&self.spectator.input
+                     oninput=self.link.callback(|e: InputData| Msg::UpdateWatchInput { value: e.value }) />
+                    { self.render_duel_button("👀 watch", || Msg::Watch) }
+                </>
+            },
+            Some(broadcast) if !broadcast.watching => html! {
+                <>
+                    <p>{ format!("Live! Others can watch with the code {}", broadcast.topic) }</p>
+                    { self.render_duel_button("⏹️ stop", || Msg::StopBroadcast) }
+                </>
+            },
+            Some(broadcast) => html! {
+                <>
+                    <p>{ format!("👀 watching {}", broadcast.topic) }</p>
+                    { self.render_spectated_board() }
+                    { self.render_duel_button("⏹️ stop watching", || Msg::StopBroadcast) }
+                </>
+            },
+        }
+    }
+
+    fn render_spectated_board(&self) -> Html {
+        match &self.spectator.game {
+            Some((_, board)) => html! {
+                <>
+                    {
+                        if self.spectator.in_sync {
+                            html! {}
+                        } else {
+                            html! { <p class="duel-error">{ "⚠️ out of sync, waiting for the next checkpoint" }</p> }
+                        }
+                    }
+                    { self.render_board(board, &[]) }
+                </>
+            },
+            None => html! { <p>{ "Waiting for the next checkpoint…" }</p> },
+        }
+    }
+
+    fn render_seed_controls(&self) -> Html {
+        html! {
+            <div id="seed_placeholder" class="flex-container">
+                <span id="seed-label">{ self.render_seed() }</span>
+                <input
+                 id="seed-input"
+                 type="text"
+                 inputmode="numeric"
+                 placeholder="new game from seed…"
+                 value=&self.seed_input
+                 oninput=self.link.callback(|e: InputData| Msg::UpdateSeedInput { value: e.value }) />
+                <div
+                 id="seed-button"
+                 class="clickable item"
+                 onclick=self.link.callback(|_| Msg::NewGameFromSeed) >
+                    { "🌱" }
+                </div>
+                <div
+                 id="daily-button"
+                 class="clickable item"
+                 title="Today's board"
+                 onclick=self.link.callback(|_| Msg::DailyGame) >
+                    { "📅" }
+                </div>
+                <div
+                 id="daily-reminder-button"
+                 class=self.render_daily_reminder_class()
+                 title="Remind me of new daily boards while on a streak"
+                 onclick=self.link.callback(|_| Msg::ToggleDailyReminder) >
+                    { "🔔" }
+                </div>
+                <div
+                 id="add-preset-button"
+                 class="clickable item"
+                 title="New difficulty"
+                 onclick=self.link.callback(|_| Msg::AddPreset) >
+                    { "🛠️" }
+                </div>
+                <div
+                 id="export-board-button"
+                 class="clickable item"
+                 title="Export board as CSV"
+                 onclick=self.link.callback(|_| Msg::ExportBoard) >
+                    { "💾" }
+                </div>
+                <div
+                 id="print-button"
+                 class="clickable item"
+                 title="Print as a puzzle"
+                 onclick=self.link.callback(|_| Msg::TogglePrint) >
+                    { "🖨️" }
+                </div>
+                <div
+                 id="copy-position-button"
+                 class="clickable item"
+                 title="Copy position"
+                 onclick=self.link.callback(|_| Msg::CopyPosition) >
+                    { "📋" }
+                </div>
+                <div
+                 id="import-board-button"
+                 class="clickable item"
+                 title="Import a board"
+                 onclick=self.link.callback(|_| Msg::ToggleImport) >
+                    { "📥" }
+                </div>
+                <div
+                 id="featured-button"
+                 class="clickable item"
+                 title="Featured boards"
+                 onclick=self.link.callback(|_| Msg::ToggleFeatured) >
+                    { "⭐" }
+                </div>
+                <div
+                 id="notes-button"
+                 class=if self.draft.is_some() { "clickable item active" } else { "clickable item" }
+                 title="Notes on cells"
+                 onclick=self.link.callback(|_| Msg::ToggleNotes) >
+                    { "🏷️" }
+                </div>
+                <div
+                 id="learn-button"
+                 class="clickable item"
+                 title="Tutorial, drills and first levels"
+                 onclick=self.link.callback(|_| Msg::ToggleLearn) >
+                    { "🎓" }
+                </div>
+                <div
+                 id="openings-button"
+                 class="clickable item"
+                 title="Best first clicks for this difficulty"
+                 onclick=self.link.callback(|_| Msg::ToggleOpenings) >
+                    { "🧭" }
+                </div>
+                <div
+                 id="recap-button"
+                 class=if self.recap.is_some() { "clickable item active" } else { "clickable item" }
+                 title="How this session's gone"
+                 onclick=self.link.callback(|_| Msg::ToggleRecap) >
+                    { "👋" }
+                </div>
+                <div
+                 id="patterns-button"
+                 class="clickable item"
+                 title="Knight-move patterns"
+                 onclick=self.link.callback(|_| Msg::TogglePatterns) >
+                    { "📖" }
+                </div>
+                <div
+                 id="race-button"
+                 class="clickable item"
+                 title="Race others through the same boards"
+                 onclick=self.link.callback(|_| Msg::ToggleRace) >
+                    { "🏎️" }
+                </div>
+            </div>
+        }
+    }
+
+    // Covers the page while a file is dragged over it, and takes the drop.
+    fn render_drop_target(&self) -> Html {
+        if !self.dragging {
+            return html! {};
+        }
+        let allow_drop = |e: DragEvent| e.prevent_default();
+        html! {
+            <div
+             id="drop_target"
+             ondragover=Callback::from(allow_drop)
+             ondragleave=self.link.callback(|_| Msg::DragFile { over: false })
+             ondrop=self.link.callback(|e: DragEvent| {
+                 e.prevent_default();
+                 Msg::DropFile { event: e }
+             })>
+                <p>{ "📥 Drop a board or replay to load it" }</p>
+            </div>
+        }
+    }
+
+    fn render_toasts(&self) -> Html {
+        html! {
+            <div id="toasts">
+                {
+                    for self.toasts.iter().map(|toast| {
+                        let id = toast.id;
+                        html! {
+                            <div
+                             class="toast"
+                             onclick=self.link.callback(move |_| Msg::DismissToast { id })>
+                                { &toast.text }
+                            </div>
+                        }
+                    })
+                }
+            </div>
+        }
+    }
+
+    fn render_pad(&self) -> Html {
+        if !self.pad.shown {
+            return html! {};
+        }
+        let button = |label: &str, msg: fn() -> Msg| {
+            html! {
+                <div class="clickable item" onclick=self.link.callback(move |_| msg())>
+                    { label }
+                </div>
+            }
+        };
+        html! {
+            <div id="pad_placeholder">
+                <div></div>
+                { button("⬆️", || Msg::MoveCursor { dx: 0, dy: -1 }) }
+                <div></div>
+                { button("⬅️", || Msg::MoveCursor { dx: -1, dy: 0 }) }
+                { button("⛏️", || Msg::DigAtCursor) }
+                { button("➡️", || Msg::MoveCursor { dx: 1, dy: 0 }) }
+                { button("🚩", || Msg::FlagAtCursor) }
+                { button("⬇️", || Msg::MoveCursor { dx: 0, dy: 1 }) }
+                <div></div>
+            </div>
+        }
+    }
+
+    // Newest first, so the latest moves don't need scrolling to.
+    fn render_robot_log(&self) -> Html {
+        if self.robot_log.is_empty() {
+            return html! {};
+        }
+        html! {
+            <ul id="robot_log_placeholder">
+                {
+                    for self.robot_log.iter().enumerate().rev().map(|(index, entry)| html! {
+                        <li
+                         onmouseenter=self.link.callback(move |_| Msg::HoverLogEntry { index: Some(index) })
+                         onmouseleave=self.link.callback(|_| Msg::HoverLogEntry { index: None })>
+                            { entry.text() }
+                            { " " }
+                            { render_pattern_link(pattern_for(&entry.m, entry.step.as_ref())) }
+                        </li>
+                    })
+                }
+            </ul>
+        }
+    }
+
+    fn render_strategy_picker(&self) -> Html {
+        if self.robot.names().len() < 2 {
+            return html! {};
+        }
+        html! {
+            <div id="strategy_placeholder" class="flex-container">
+                <span>{ "🤖 plays" }</span>
+                <select
+                 id="strategy-select"
+                 onchange=self.link.callback(|e: ChangeData| match e {
+                     ChangeData::Select(select) => Msg::SelectStrategy {
+                         index: select.selected_index().max(0) as usize,
+                     },
+                     _ => Msg::SelectStrategy { index: 0 },
+                 })>
+                    {
+                        for self.robot.names().into_iter().enumerate().map(|(index, name)| html! {
+                            <option selected=index == self.robot.selected()>{ name }</option>
+                        })
+                    }
+                </select>
+                <div
+                 id="run-5-button"
+                 class="clickable item"
+                 title="Play 5 of 🤖's certain moves"
+                 onclick=self.link.callback(|_| Msg::RunRobotSteps { count: 5 }) >
+                    { "⏩5" }
+                </div>
+                <div
+                 id="run-25-button"
+                 class="clickable item"
+                 title="Play 25 of 🤖's certain moves"
+                 onclick=self.link.callback(|_| Msg::RunRobotSteps { count: 25 }) >
+                    { "⏩25" }
+                </div>
+                <div
+                 id="lesson-button"
+                 class="clickable item"
+                 title="Show how 🤖 thinks"
+                 onclick=self.link.callback(|_| Msg::ToggleLesson) >
+                    { "🎓" }
+                </div>
+                <div
+                 id="trainer-button"
+                 class="clickable item"
+                 title="Drill the patterns 🤖 reasons with"
+                 onclick=self.link.callback(|_| Msg::ToggleTrainer) >
+                    { "🏋️" }
+                </div>
+                <div
+                 id="win-chance-button"
+                 class="clickable item"
+                 title="How likely is 🤖 to win from here?"
+                 onclick=self.link.callback(|_| Msg::EstimateWinChance) >
+                    { "🎲" }
+                </div>
+                <div
+                 id="analysis-button"
+                 class="clickable item"
+                 title="Take this position to the analysis board"
+                 onclick=self.link.callback(|_| Msg::Analyse) >
+                    { "🔬" }
+                </div>
+                <div
+                 id="warn-mistakes-button"
+                 class=self.render_warn_mistakes_class()
+                 title="Warn before digging a cell 🤖 can prove is a mine"
+                 onclick=self.link.callback(|_| Msg::ToggleMistakeWarnings) >
+                    { "🛟" }
+                </div>
+                <div
+                 id="auto-open-button"
+                 class=self.render_auto_open_class()
+                 title=format!("Open cells 🤖 proves safe: {}", self.profiles.active().settings.auto_open.name())
+                 onclick=self.link.callback(|_| Msg::CycleAutoOpen) >
+                    { "🪄" }
+                </div>
+                <div
+                 id="auto-flag-button"
+                 class=self.render_auto_flag_class()
+                 title="Flag the last mines once only they are left"
+                 onclick=self.link.callback(|_| Msg::ToggleAutoFlag) >
+                    { "🏁" }
+                </div>
+                <div
+                 id="second-chance-button"
+                 class=self.render_second_chance_class()
+                 title=format!(
+                     "Forgive the first mine you dig each game, for +{}s on the clock",
+                     SECOND_CHANCE_PENALTY.as_secs()
+                 )
+                 onclick=self.link.callback(|_| Msg::ToggleSecondChance) >
+                    { "🍀" }
+                </div>
+                <div
+                 id="casual-button"
+                 class=self.render_casual_class()
+                 title="Casual: mines you dig get flagged, for more time on the clock each"
+                 onclick=self.link.callback(|_| Msg::ToggleCasual) >
+                    { "🐣" }
+                </div>
+                <div
+                 id="cascade-button"
+                 class=self.render_cascade_class()
+                 title=format!("Cascade: {}", self.profiles.active().settings.cascade.name())
+                 onclick=self.link.callback(|_| Msg::CycleCascade) >
+                    { "🌊" }
+                </div>
+                <div
+                 id="auto-restart-button"
+                 class=self.render_auto_restart_class()
+                 title=match self.profiles.active().settings.auto_restart {
+                     0 => String::from("Auto-restart after a loss: off"),
+                     after => format!("Auto-restart after a loss: {}s", after),
+                 }
+                 onclick=self.link.callback(|_| Msg::CycleAutoRestart) >
+                    { "🔁" }
+                </div>
+                <div
+                 id="deductions-button"
+                 class=if self.profiles.active().settings.deductions { "clickable item active" } else { "clickable item" }
+                 title="Count the moves 🤖 can prove, without saying which"
+                 onclick=self.link.callback(|_| Msg::ToggleDeductions) >
+                    { "🧮" }
+                </div>
+                <div
+                 id="trail-button"
+                 class=if self.profiles.active().settings.trail { "clickable item active" } else { "clickable item" }
+                 title="Trail: tint opened cells by how recently they were opened"
+                 onclick=self.link.callback(|_| Msg::ToggleTrail) >
+                    { "👣" }
+                </div>
+                <div
+                 id="auto-retry-button"
+                 class=if self.profiles.active().settings.auto_retry { "clickable item active" } else { "clickable item" }
+                 title="Auto-restart retries the lost board rather than a new one"
+                 onclick=self.link.callback(|_| Msg::ToggleAutoRetry) >
+                    { "🔂" }
+                </div>
+                <div
+                 id="challenge-button"
+                 class=self.render_challenge_class()
+                 title=format!("New boards: {}", self.profiles.active().settings.challenge.describe())
+                 onclick=self.link.callback(|_| Msg::CycleChallenge) >
+                    { "🎚️" }
+                </div>
+            </div>
+        }
+    }
+
+    fn render_seed(&self) -> String {
+        match self.state.game.board.seed {
+            Some(seed) if seed == lib_minesweeper::daily(daily::today()).seed => {
+                String::from("daily board")
+            }
+            Some(seed) => format!("seed {}", seed),
+            None => String::new(),
+        }
+    }
+
+    fn render_mode_class(&self) -> &str {
+        match &self.state.game.board.state {
+            // The 🏆 bounces in, see style.css.
+            Won => "item celebrate",
+            Failed => "item",
+            _ => "clickable item",
+        }
+    }
+
+    fn render_mode(&self) -> &str {
+        match (&self.state.game.board.state, self.state.game.mode) {
+            (Ready, Mode::Flagging) | (Playing, Mode::Flagging) => "🚩",
+            (Ready, Mode::Digging) | (Playing, Mode::Digging) => "⛏️",
+            (Won, _) => "🏆",
+            (Failed, _) => "☠️",
+            _ => unreachable!(),
+        }
+    }
+
+    fn render_robot(&self) -> &str {
+        let playing = matches!(&self.state.game.board.state, Ready | Playing);
+        if self.robot.is_available() && playing && !self.in_match() {
+            "🤖"
+        } else {
+            ""
+        }
+    }
+
+    fn render_daily_reminder_class(&self) -> &str {
+        if self.profiles.active().settings.daily_reminder {
+            "clickable item active"
+        } else {
+            "clickable item"
+        }
+    }
+
+    fn render_warn_mistakes_class(&self) -> &str {
+        if self.profiles.active().settings.warn_mistakes {
+            "clickable item active"
+        } else {
+            "clickable item"
+        }
+    }
+
+    fn render_splits_class(&self) -> &str {
+        if self.profiles.active().settings.splits {
+            "clickable item active"
+        } else {
+            "clickable item"
+        }
+    }
+
+    fn render_timing_class(&self) -> &str {
+        match self.profiles.active().settings.timing {
+            Timing::RealTime => "clickable item",
+            Timing::GameTime => "clickable item active",
+        }
+    }
+
+    fn render_auto_flag_class(&self) -> &str {
+        if self.profiles.active().settings.auto_flag {
+            "clickable item active"
+        } else {
+            "clickable item"
+        }
+    }
+
+    fn render_second_chance_class(&self) -> &str {
+        if self.profiles.active().settings.second_chance {
+            "clickable item active"
+        } else {
+            "clickable item"
+        }
+    }
+
+    // What the mines dug this game have added to the clock, which is
+    // already counted in its time.
+    fn render_penalty(&self) -> Html {
+        if self.state.game.forgiven.is_empty() {
+            return html! {};
+        }
+        html! {
+            <span
+             id="penalty"
+             title=format!("{} mines dug", self.state.game.forgiven.len())>
+                { format!("+{}s", self.state.game.penalty.as_secs()) }
+            </span>
+        }
+    }
+
+    // The bonus round's button once the board's won, then how it's going.
+    fn render_bonus(&self) -> Html {
+        if self.state.game.board.state != Won || self.zen.is_some() {
+            return html! {};
+        }
+        let board = &self.state.game.board;
+        let status = match &self.bonus {
+            None => {
+                return html! {
+                    <div
+                     id="bonus-button"
+                     class="clickable item"
+                     title="Bonus round: a knight's path over the open cells"
+                     onclick=self.link.callback(|_| Msg::StartBonus)>
+                        { "♞" }
+                    </div>
+                }
+            }
+            Some(bonus) if bonus.finished => bonus.summary(board),
+            Some(bonus) if !bonus.is_started() => String::from("♞ Pick a cell to start from"),
+            Some(bonus) => format!(
+                "♞ {} cells · {}s left",
+                bonus.path.cells.len(),
+                (bonus.remaining_ms(Date::now()) / 1000_f64).ceil()
+            ),
+        };
+        html! { <span id="bonus-status">{ status }</span> }
+    }
+
+    fn render_overlays(&self) -> Html {
+        let board = &self.state.game.board;
+        html! {
+            <>
+                { self.render_trail(self.state.game.replay.as_ref(), usize::MAX) }
+                { notes::render_overlay(&self.state.game.annotations, board.width, board.height) }
+                { self.render_bonus_overlay() }
+            </>
+        }
+    }
+
+    // The notes on the board, and while writing one, what it'll say.
+    fn render_notes(&self) -> Html {
+        let annotations = &self.state.game.annotations;
+        let draft = match &self.draft {
+            Some(draft) => html! {
+                <div id="note-draft" class="flex-container">
+                    <div
+                     class=format!("clickable item tag-{}", draft.tag.name())
+                     title="Colour"
+                     onclick=self.link.callback(|_| Msg::CycleNoteTag)>
+                        { "●" }
+                    </div>
+                    <input
+                     id="note-input"
+                     type="text"
+                     placeholder="One of these two is a mine"
+                     value=&draft.note
+                     oninput=self.link.callback(|e: InputData| Msg::UpdateNote { value: e.value }) />
+                    <div
+                     class="clickable item"
+                     title="Add a note on the picked cells"
+                     onclick=self.link.callback(|_| Msg::AddNote)>
+                        { format!("➕ {}", draft.cells.len()) }
+                    </div>
+                    <div
+                     class="clickable item"
+                     title="Note every pair of cells a number has one mine left between"
+                     onclick=self.link.callback(|_| Msg::SuggestNotes)>
+                        { "🤖" }
+                    </div>
+                </div>
+            },
+            None if annotations.is_empty() => return html! {},
+            None => html! {},
+        };
+        html! {
+            <div id="notes">
+                { draft }
+                <ul>
+                    {
+                        for annotations.iter().enumerate().map(|(index, a)| html! {
+                            <li>
+                                <span class=format!("tag-{}", a.tag.name())>{ "● " }</span>
+                                { format!("{} ({})", a.note, notes::describe_cells(&a.cells)) }
+                                <span
+                                 class="clickable"
+                                 title="Remove"
+                                 onclick=self.link.callback(move |_| Msg::RemoveNote { index })>
+                                    { " ✖" }
+                                </span>
+                            </li>
+                        })
+                    }
+                </ul>
+            </div>
+        }
+    }
+
+    // The trail of the first `step` moves, if the player has it on. Imported
+    // boards have no moves to follow.
+    fn render_trail(&self, replay: Option<&Replay>, step: usize) -> Html {
+        match replay {
+            Some(replay) if self.profiles.active().settings.trail => {
+                trail::render_overlay(replay, step)
+            }
+            _ => html! {},
+        }
+    }
+
+    fn render_bonus_overlay(&self) -> Html {
+        match &self.bonus {
+            Some(bonus) => knight_bonus::render_overlay(
+                &bonus.path,
+                self.state.game.board.width,
+                self.state.game.board.height,
+            ),
+            None => html! {},
+        }
+    }
+
+    // The cells to show from the opening book: its best few, while the
+    // board it's for is yet to be started.
+    fn shown_openings(&self) -> &[Opening] {
+        match &self.openings {
+            Some((difficulty, openings))
+                if *difficulty == self.state.difficulty && self.state.game.board.state == Ready =>
+            {
+                &openings[..openings.len().min(OPENINGS_SHOWN)]
+            }
+            _ => &[],
+        }
+    }
+
+    fn render_openings(&self) -> Html {
+        let openings = self.shown_openings();
+        if openings.is_empty() {
+            return html! {};
+        }
+        html! {
+            <table id="openings">
+                <caption>
+                    { format!("🧭 Best first clicks over {} boards", OPENING_BOOK_SEEDS) }
+                </caption>
+                <tr>
+                    <th>{ "Cell" }</th>
+                    <th>{ "Safe" }</th>
+                    <th>{ "Opens" }</th>
+                </tr>
+                {
+                    for openings.iter().map(|o| html! {
+                        <tr>
+                            <td>{ format!("{}, {}", o.point.x + 1, o.point.y + 1) }</td>
+                            <td>{ format!("{:.0}%", o.survival * 100_f64) }</td>
+                            <td>{ format!("{:.1}", o.expected_cascade) }</td>
+                        </tr>
+                    })
+                }
+            </table>
+        }
+    }
+
+    fn render_recap(&self) -> Html {
+        let (title, session) = match &self.recap {
+            Some(Recap::Current) => ("👋 This session", &self.session),
+            Some(Recap::Last(session)) => ("👋 Last time", session),
+            None => return html! {},
+        };
+        let every = match self.profiles.active().settings.recap_every {
+            0 => String::from("Recap: only when asked"),
+            every => format!("Recap every {} games", every),
+        };
+        html! {
+            <div id="recap">
+                <strong>{ title }</strong>
+                <pre>{ session.share_text() }</pre>
+                <span
+                 class="clickable"
+                 title="Copy the recap to share"
+                 onclick=self.link.callback(|_| Msg::CopyRecap)>
+                    { "📋" }
+                </span>
+                <span
+                 class="clickable"
+                 title="How many games this session before the recap comes up"
+                 onclick=self.link.callback(|_| Msg::CycleRecapEvery)>
+                    { every }
+                </span>
+                <span class="clickable" onclick=self.link.callback(|_| Msg::ToggleRecap)>
+                    { "✖️" }
+                </span>
+            </div>
+        }
+    }
+
+    // Offers a new board when 🤖 can't win this one without guessing.
+    fn render_guess_badge(&self) -> Html {
+        if self.needs_guess != Some(true) || self.state.game.is_over() {
+            return html! {};
+        }
+        html! {
+            <span
+             id="guess-badge"
+             class="clickable"
+             title="🤖 can't win this board without a guess. Click for another"
+             onclick=self.link.callback(|_| Msg::Reroll)>
+                { "🎲 May need guessing" }
+            </span>
+        }
+    }
+
+    fn render_casual_class(&self) -> &str {
+        if self.profiles.active().settings.casual {
+            "clickable item active"
+        } else {
+            "clickable item"
+        }
+    }
+
+    fn render_adaptive_class(&self) -> &str {
+        if self.profiles.active().settings.adaptive {
+            "clickable item active"
+        } else {
+            "clickable item"
+        }
+    }
+
+    fn render_auto_open_class(&self) -> &str {
+        match self.profiles.active().settings.auto_open {
+            AutoOpen::Off => "clickable item",
+            _ => "clickable item active",
+        }
+    }
+
+    // How many certain moves there are, without saying which, so a learner
+    // can tell a missed deduction from a real guess.
+    fn render_deductions(&self) -> Html {
+        if !self.profiles.active().settings.deductions || self.state.game.board.state != Playing {
+            return html! {};
+        }
+        let text = match self.deductions {
+            Some(0) => String::from("🧮 No sure moves: time to guess"),
+            Some(1) => String::from("🧮 1 sure move"),
+            Some(count) => format!("🧮 {} sure moves", count),
+            None => String::from("🧮 …"),
+        };
+        html! {
+            <span id="deductions" title="How many moves 🤖 can prove from here">{ text }</span>
+        }
+    }
+
+    fn render_retry(&self) -> Html {
+        if !self.can_retry() {
+            return html! {};
+        }
+        html! {
+            <span
+             id="retry-button"
+             class="clickable"
+             title="Play this board again from the start. Retries don't count towards best times"
+             onclick=self.link.callback(|_| Msg::RetryBoard)>
+                { "🔂 Retry this board" }
+            </span>
+        }
+    }
+
+    fn render_restart_countdown(&self) -> Html {
+        let left = match self.restart_in {
+            Some(left) => left,
+            None => return html! {},
+        };
+        html! {
+            <span
+             id="restart-countdown"
+             class="clickable"
+             title="Click to stay on this board"
+             onclick=self.link.callback(|_| Msg::CancelRestart)>
+                { format!("🔁 New board in {}s ✖️", left) }
+            </span>
+        }
+    }
+
+    fn render_auto_restart_class(&self) -> &str {
+        match self.profiles.active().settings.auto_restart {
+            0 => "clickable item",
+            _ => "clickable item active",
+        }
+    }
+
+    fn render_cascade_class(&self) -> &str {
+        match self.profiles.active().settings.cascade {
+            Cascade::Zeros => "clickable item",
+            _ => "clickable item active",
+        }
+    }
+
+    fn render_challenge_class(&self) -> &str {
+        match self.profiles.active().settings.challenge {
+            Challenge::Any => "clickable item",
+            _ => "clickable item active",
+        }
+    }
+
+    fn render_open_all_class(&self) -> &str {
+        if self.state.game.board.can_open_all_unflagged() {
+            "clickable item"
+        } else {
+            "item"
+        }
+    }
+
+    fn render_open_all(&self) -> &str {
+        if self.state.game.board.can_open_all_unflagged() {
+            "🧹"
+        } else {
+            ""
+        }
+    }
+
+    fn apply_move(&mut self, m: Move) {
+        self.play_move(m, false);
+    }
+
+    // Assisted moves are the ones an assist played for the player, which
+    // the replay keeps track of.
+    fn play_move(&mut self, m: Move, assisted: bool) {
+        debug_log::record(Kind::Move, format!("{:?}", m));
+        let settings = &self.profiles.active().settings;
+        self.state.game.rules = Rules {
+            auto_flag: settings.auto_flag,
+            second_chance: settings.second_chance,
+        };
+        self.state.game.casual = settings.casual;
+        let Played {
+            before, m, penalty, ..
+        } = match self.state.game.play(m, assisted) {
+            Some(played) => played,
+            None => return,
+        };
+        let board = self.state.game.board.clone();
+        if let Some(seat) = &mut self.state.hot_seat {
+            let hit_mine = board.state == Failed && before.state != Failed;
+            let opened = board.stats().opened - before.stats().opened;
+            seat.record(opened, hit_mine);
+        }
+        if board.state != before.state {
+            debug_log::record(
+                Kind::State,
+                format!("{:?} -> {:?}", before.state, board.state),
+            );
+        }
+        if board.state == Failed && before.state != Failed {
+            self.review_loss(&before, &board);
+        }
+        js_events::board_changed(Some(&before), &board);
+        let changed = before.changed_points(&board);
+        self.board_changes = self.board_changes.next(Some(changed));
+        if let Some(penalty) = penalty {
+            self.forgive(penalty);
+        }
+        let at_ms = self.stopwatch.elapsed().game_ms.round();
+        if let Some(replay) = &mut self.state.game.replay {
+            replay.time_last(at_ms);
+        }
+        let progress = self.state.game.board.stats().progress;
+        self.send_to_opponent(PeerMessage::Progress {
+            progress,
+            state: self.state.game.board.state.clone(),
+        });
+        if self.lobby.connected {
+            for power_up in self.power_ups.reach(progress) {
+                self.toast(&format!(
+                    "{} Power-up earned: {}",
+                    power_up.emoji(),
+                    power_up.describe()
+                ));
+            }
+        }
+        self.send_to_spectators(PeerMessage::Move { m });
+        let moves = self.state.game.replay.as_ref().map_or(0, |r| r.moves.len());
+        if moves.is_multiple_of(broadcast::CHECKPOINT_EVERY) {
+            self.send_checkpoint();
+        }
+        self.schedule_deductions();
+    }
+
+    // Counts the certain moves again a moment after the board changes,
+    // when the counter's on.
+    fn schedule_deductions(&mut self) {
+        self.deductions = None;
+        self._count_deductions = None;
+        if self.profiles.active().settings.deductions
+            && self.state.game.board.state == Playing
+            && self.robot.is_available()
+        {
+            let count = self.link.callback(|_| Msg::CountDeductions);
+            self._count_deductions =
+                Some(TimeoutService::new().spawn(COUNT_DEDUCTIONS_AFTER, count));
+        }
+    }
+
+    fn update_board(&mut self, p: Point, action: Action) {
+        if let Screen::Analysis { analysis } = &mut self.screen {
+            if analysis.edit(&p) {
+                self.board_changes = self.board_changes.next(None);
+            }
+            return;
+        }
+        if !matches!(self.screen, Screen::Game) {
+            return;
+        }
+        if self._thaw.is_some() {
+            return self.toast("❄️ Frozen for a moment");
+        }
+        if self.bonus.as_ref().is_some_and(|b| !b.finished) {
+            return self.hop(p);
+        }
+        if let Some(draft) = &mut self.draft {
+            draft.toggle(p);
+            self.board_changes = self.board_changes.next(Some(vec![p]));
+            return;
+        }
+        let swapped = self.profiles.active().settings.layout.swap_buttons;
+        let intent = self.state.game.intent(action, swapped);
+        if self
+            .scenario
+            .as_ref()
+            .is_some_and(|(_, run)| !run.allows(intent))
+        {
+            return self.toast("🎓 Not in this one: follow the steps above the board");
+        }
+        let in_game = matches!(self.state.game.board.state, Ready | Playing);
+        let chord = intent == Intent::Chord;
+        let before = self.state.game.board.clone();
+        match self.state.game.move_for(p, intent) {
+            Move::Open(p) => self.dig(p),
+            Move::Chord(p) => self.chord(p),
+            m => self.apply_move(m),
+        }
+        if in_game {
+            self.clicks.record(chord, before != self.state.game.board);
+            self.clicked.push(p);
+        }
+        self.auto_open();
+        // Whatever the dig and the assists opened was this player's turn.
+        let passed = in_game && self.pass_turn(&before);
+        // A duel's clock is on every move, hot seat's on every turn.
+        let moved = in_game && self.duel.is_some() && before != self.state.game.board;
+        if passed || moved {
+            self.turn_taken();
+        }
+        if in_game {
+            self.play_sounds(&before);
+            if self.state.game.board.state == Won {
+                self.celebrations += 1;
+            }
+            self.advance_scenario();
+        }
+    }
+
+    // Hands hot seat over to the other player if the move opened anything,
+    // and returns whether it did.
+    fn pass_turn(&mut self, before: &Board) -> bool {
+        match &mut self.state.hot_seat {
+            Some(seat) if self.state.game.board.stats().opened > before.stats().opened => {
+                seat.pass();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    // Clicks pick cells for a note while it's on, so it's put away to play.
+    fn toggle_notes(&mut self) {
+        let picked = match self.draft.take() {
+            Some(draft) => draft.cells,
+            None => {
+                self.draft = Some(Draft::default());
+                self.screen = Screen::Game;
+                self.toast("🏷️ Pick cells, then write what you know about them");
+                vec![]
+            }
+        };
+        self.board_changes = self.board_changes.next(Some(picked));
+    }
+
+    fn add_note(&mut self) {
+        let draft = match &mut self.draft {
+            Some(draft) => draft,
+            None => return,
+        };
+        if draft.cells.is_empty() {
+            return self.toast("🏷️ Pick the cells it's about first");
+        }
+        let annotation = draft.take();
+        let cells = annotation.cells.clone();
+        self.state.game.annotate(annotation);
+        self.board_changes = self.board_changes.next(Some(cells));
+    }
+
+    // Adds 🤖's notes to the player's, leaving any on the same cells.
+    fn suggest_notes(&mut self) {
+        let suggested: Vec<Annotation> = Annotation::suggest(&self.state.game.board)
+            .into_iter()
+            .filter(|s| {
+                !self
+                    .state
+                    .game
+                    .annotations
+                    .iter()
+                    .any(|a| a.has_cells(&s.cells))
+            })
+            .collect();
+        if suggested.is_empty() {
+            return self.toast("🤖 Nothing to add");
+        }
+        for annotation in suggested {
+            self.state.game.annotate(annotation);
+        }
+    }
+
+    fn remove_note(&mut self, index: usize) {
+        if index < self.state.game.annotations.len() {
+            self.state.game.annotations.remove(index);
+        }
+    }
+
+    fn start_bonus(&mut self) {
+        if self.state.game.board.state != Won || self.bonus.is_some() {
+            return;
+        }
+        self.bonus = Some(Bonus::default());
+        self.toast(&format!(
+            "♞ Hop like a knight over as many open cells as you can in {}s",
+            knight_bonus::BONUS_FOR.as_secs()
+        ));
+    }
+
+    // Extends the bonus round's path to `p`, starting its clock on the
+    // first cell.
+    fn hop(&mut self, p: Point) {
+        let now = Date::now();
+        let board = &self.state.game.board;
+        let bonus = match &mut self.bonus {
+            Some(bonus) => bonus,
+            None => return,
+        };
+        let before = bonus.marks(board);
+        let started = bonus.is_started();
+        if let Err(e) = bonus.hop(board, p, now) {
+            return self.toast(knight_bonus::describe(&e));
+        }
+        let changed = before.into_iter().chain(bonus.marks(board));
+        self.board_changes = self.board_changes.plus(changed.map(|(p, _)| p).collect());
+        if !started {
+            let tick = self.link.callback(|_| Msg::BonusTick);
+            self._bonus_tick = Some(IntervalService::new().spawn(Duration::from_millis(250), tick));
+        }
+        self.bonus_tick();
+    }
+
+    fn bonus_tick(&mut self) {
+        let board = &self.state.game.board;
+        let summary = match &mut self.bonus {
+            Some(bonus) if !bonus.finished && bonus.is_over(board, Date::now()) => {
+                bonus.finished = true;
+                bonus.summary(board)
+            }
+            _ => return,
+        };
+        self._bonus_tick = None;
+        self.board_changes = self.board_changes.next(None);
+        self.toast(&summary);
+    }
+
+    // For what the player's move did, from the board before it.
+    fn play_sounds(&mut self, before: &Board) {
+        let audio = self.profiles.active().settings.audio;
+        let (was, now) = (before.stats(), self.state.game.board.stats());
+        let sounds: &[Sound] = match self.state.game.board.state {
+            Failed => &[Sound::Explode, Sound::Lost],
+            Won => &[Sound::Open, Sound::Won],
+            _ if now.opened > was.opened => &[Sound::Open],
+            _ if now.flagged != was.flagged => &[Sound::Flag],
+            _ => &[],
+        };
+        for sound in sounds {
+            self.speaker.play(*sound, audio);
+        }
+    }
+
+    fn auto_open(&mut self) {
+        let level = self.profiles.active().settings.auto_open;
+        let before = self.state.game.board.clone();
+        let mut marks = vec![];
+        // Each round can uncover numbers that prove more cells safe.
+        loop {
+            let safe = self.robot.auto_open(&self.state.game.board, level);
+            if safe.is_empty() {
+                break;
+            }
+            for p in safe {
+                if self.state.game.board.state != Playing {
+                    break;
+                }
+                let closed = matches!(
+                    self.state.game.board.at(&p),
+                    Some(Number { state: Closed, .. }) | Some(Mine { state: Closed })
+                );
+                if closed {
+                    marks.extend(self.log_move("🪄", Move::Open(p)));
+                    self.play_move(Move::Open(p), true);
+                }
+            }
+            if self.state.game.board.state != Playing {
+                break;
+            }
+        }
+        if before != self.state.game.board {
+            let changed = before.changed_points(&self.state.game.board);
+            self.board_changes = self.board_changes.plus(changed);
+            self.highlight(marks);
+        }
+    }
+
+    // Logs a move about to be played for the player, and returns what
+    // highlights it: the cell it changes, and the number that proves it if
+    // there's one.
+    fn log_move(&mut self, by: &'static str, m: Move) -> Vec<(Point, &'static str)> {
+        let step = self.robot.justify(&self.state.game.board, &m);
+        let mut marks = vec![];
+        if let Some(step) = &step {
+            marks.push((step.source, "robot-reason"));
+        }
+        if let Move::Open(p) | Move::Flag(p) = m {
+            marks.push((p, "robot-move"));
+        }
+        self.robot_log.push(Entry { by, m, step });
+        marks
+    }
+
+    fn hover_log_entry(&mut self, index: Option<usize>) {
+        let mut points: Vec<Point> = self.robot_log.marks().iter().map(|(p, _)| *p).collect();
+        self.robot_log.hover(index);
+        points.extend(self.robot_log.marks().iter().map(|(p, _)| *p));
+        self.board_changes = self.board_changes.next(Some(points));
+    }
+
+    fn highlight(&mut self, marks: Vec<(Point, &'static str)>) {
+        let points = self.highlight.iter().chain(&marks).map(|(p, _)| *p);
+        self.board_changes = self.board_changes.plus(points.collect());
+        self.highlight = marks;
+        let clear = self.link.callback(|_| Msg::ClearHighlight);
+        self._clear_highlight = Some(TimeoutService::new().spawn(HIGHLIGHT_FOR, clear));
+    }
+
+    fn clear_highlight(&mut self) {
+        let points = self.highlight.drain(..).map(|(p, _)| p).collect();
+        self.board_changes = self.board_changes.next(Some(points));
+    }
+
+    fn dig(&mut self, p: Point) {
+        let warn = self.profiles.active().settings.warn_mistakes
+            && self.state.game.board.state == Playing
+            && self.robot.is_certain_mine(&self.state.game.board, &p);
+        if warn {
+            let confirmed = web_sys::window()
+                .and_then(|w| {
+                    w.confirm_with_message("🤖 can prove that's a mine. Dig it anyway?")
+                        .ok()
+                })
+                .unwrap_or(false);
+            if !confirmed {
+                return;
+            }
+        }
+        self.apply_move(Move::Open(p));
+    }
+
+    // The whole chord is one move in the replay. A number with closed cells
+    // left around it but the wrong number of flags says so.
+    fn chord(&mut self, p: Point) {
+        if self.state.game.needs_flags(&p) {
+            return self.toast("Flag all of this number's mines first");
+        }
+        self.apply_move(Move::Chord(p));
+    }
+
+    fn game_marks(&self) -> Vec<(Point, &'static str)> {
+        let mut marks: Vec<(Point, &'static str)> = self
+            .better_than_loss
+            .iter()
+            .map(|p| (*p, "better-than-loss"))
+            .collect();
+        marks.extend(
+            self.state
+                .game
+                .auto_flagged
+                .iter()
+                .map(|p| (*p, "auto-flagged")),
+        );
+        marks.extend(self.state.game.forgiven.iter().map(|p| (*p, "forgiven")));
+        marks.extend(self.decoy.iter().map(|p| (*p, "decoy")));
+        marks.extend(self.robot_log.marks());
+        marks.extend(self.highlight.iter().copied());
+        if self.pad.shown || self.pad.keyboard {
+            marks.push((self.pad.cursor, "cursor"));
+        }
+        if let Some(ghost) = &self.ghost {
+            marks.extend(ghost.marks(&self.state.game.board));
+        }
+        if let Some(bonus) = &self.bonus {
+            marks.extend(bonus.marks(&self.state.game.board));
+        }
+        marks.extend(self.scenario_marks());
+        if let Some(draft) = &self.draft {
+            marks.extend(draft.marks());
+        }
+        marks.extend(self.shown_openings().iter().map(|o| (o.point, "opening")));
+        marks
+    }
+
+    // Only redraws the cells the ghost opened.
+    fn advance_ghost(&mut self) -> ShouldRender {
+        let at_ms = self.stopwatch.elapsed().game_ms;
+        let ghost = match &mut self.ghost {
+            Some(ghost) => ghost,
+            None => return false,
+        };
+        let was_finished = ghost.is_finished();
+        let changed = ghost.advance(at_ms);
+        if ghost.is_finished() && !was_finished {
+            let text = format!(
+                "👻 Your best finished here, in {}s",
+                stopwatch::seconds(ghost.finish_ms())
+            );
+            self.toast(&text);
+        }
+        if changed.is_empty() {
+            return false;
+        }
+        self.board_changes = self.board_changes.next(Some(changed));
+        true
+    }
+
+    fn toggle_pad(&mut self) {
+        self.pad.shown = !self.pad.shown;
+        self.profiles.save_settings(Settings {
+            pad: Some(self.pad.shown),
+            ..self.profiles.active().settings.clone()
+        });
+        self.profiles.store(self.storage.as_mut());
+        self.board_changes = self.board_changes.next(Some(vec![self.pad.cursor]));
+    }
+
+    // Goes through the keybindings, so every key means whatever the active
+    // profile set it to. On the keys screen, the key pressed is the new one
+    // for the action being picked.
+    fn key_pressed(&mut self, press: Press) {
+        if let Screen::Keys {
+            capturing: Some(action),
+        } = self.screen
+        {
+            return self.bind_key(action, &press.key);
+        }
+        let action = match press.action {
+            Some(action) if matches!(self.screen, Screen::Game) => action,
+            _ => return,
+        };
+        let cursor = self.pad.cursor;
+        match action {
+            KeyAction::Up => self.move_cursor(0, -1),
+            KeyAction::Down => self.move_cursor(0, 1),
+            KeyAction::Left => self.move_cursor(-1, 0),
+            KeyAction::Right => self.move_cursor(1, 0),
+            KeyAction::Dig => self.update_board(cursor, Action::Dig),
+            KeyAction::Flag => self.update_board(cursor, Action::Flag),
+            KeyAction::Chord => self.update_board(cursor, Action::Chord),
+            KeyAction::Hint => self.hint(),
+            KeyAction::Robot | KeyAction::NewGame if self.in_match() => {
+                return self.toast("⌨️ Not during a match")
+            }
+            KeyAction::Robot => self.run_robot(),
+            KeyAction::NewGame => self.new_game(self.state.difficulty.clone(), None),
+        }
+        if !self.pad.keyboard {
+            self.pad.keyboard = true;
+            self.board_changes = self.board_changes.plus(vec![self.pad.cursor]);
+        }
+    }
+
+    fn toggle_keys(&mut self) {
+        self.screen = match self.screen {
+            Screen::Keys { .. } => Screen::Game,
+            _ => Screen::Keys { capturing: None },
+        }
+    }
+
+    // A second click on the same key stops waiting for it.
+    fn capture_key(&mut self, action: KeyAction) {
+        if let Screen::Keys { capturing } = &mut self.screen {
+            *capturing = Some(action).filter(|a| *capturing != Some(*a));
+        }
+    }
+
+    // Escape leaves the key as it was. An action already on `key` swaps
+    // keys with `action`, so every action keeps one.
+    fn bind_key(&mut self, action: KeyAction, key: &str) {
+        match key {
+            // Still waiting for the key it goes with.
+            key if keys::is_modifier(key) => return,
+            "Tab" => return self.toast("⌨️ Tab moves around the page, so pick another key"),
+            "Escape" => {}
+            _ => {
+                let mut bindings = self.profiles.active().settings.keys.clone();
+                let swapped = bindings.bind(action, key);
+                if let Some(other) = swapped {
+                    self.toast(&format!(
+                        "⌨️ {} moved to {}",
+                        other.describe(),
+                        keys::label(bindings.key(other))
+                    ));
+                }
+                self.save_keys(bindings);
+            }
+        }
+        self.screen = Screen::Keys { capturing: None };
+    }
+
+    fn save_keys(&mut self, keys: Keybindings) {
+        keys::set(&keys);
+        self.profiles.save_settings(Settings {
+            keys,
+            ..self.profiles.active().settings.clone()
+        });
+        self.profiles.store(self.storage.as_mut());
+    }
+
+    // Flashes the move 🤖 would make next, and the number that proves it,
+    // without making it.
+    fn hint(&mut self) {
+        if self.state.game.is_over() {
+            return;
+        }
+        match self.robot.explain(&self.state.game.board) {
+            Some(step) => {
+                let mut marks = vec![(step.source, "robot-reason")];
+                if let Move::Open(p) | Move::Flag(p) = step.m {
+                    marks.push((p, "robot-move"));
+                }
+                self.highlight(marks);
+            }
+            None => self.toast("🤖 has no certain moves"),
+        }
+    }
+
+    fn move_cursor(&mut self, dx: i32, dy: i32) {
+        let board = &self.state.game.board;
+        let old = self.pad.cursor;
+        let x = (old.x + dx).max(0).min(board.width as i32 - 1);
+        let y = (old.y + dy).max(0).min(board.height as i32 - 1);
+        self.pad.cursor = Point { x, y };
+        self.board_changes = self.board_changes.next(Some(vec![old, self.pad.cursor]));
+    }
+
+    fn open_all_unflagged(&mut self) {
+        if !self.state.game.board.can_open_all_unflagged() {
+            if self.state.game.board.state == Playing {
+                self.toast("Flag as many cells as there are mines first");
+            }
+            return;
+        }
+        let confirmed = web_sys::window()
+            .and_then(|w| {
+                w.confirm_with_message("Open every cell that isn't flagged?")
+                    .ok()
+            })
+            .unwrap_or(false);
+        if !confirmed {
+            return;
+        }
+        self.apply_move(Move::OpenAllUnflagged);
+    }
+
+    // The scores server's board of the day.
+    fn daily_game(&mut self) {
+        let board = lib_minesweeper::daily(daily::today()).board();
+        if let Some(replay) = Replay::for_board(&board) {
+            self.load_replay(replay);
+        }
+    }
 
-    let board = create_board(width, height, mines, |x, y| {
-        rand::thread_rng().gen_range(x, y)
-    });
+    fn schedule_new_day(link: &ComponentLink<Self>) -> TimeoutTask {
+        TimeoutService::new().spawn(daily::until_tomorrow(), link.callback(|_| Msg::NewDay))
+    }
 
-    numbers_on_board(board)
-}
+    // Turning the reminder on needs the browser's permission first.
+    fn toggle_daily_reminder(&mut self) {
+        if self.profiles.active().settings.daily_reminder {
+            return self.set_daily_reminder(false);
+        }
+        daily::ask_permission(
+            self.link.callback(|granted| Msg::ReminderPermission { granted }),
+        );
+    }
 
-fn large_board() -> Board {
-    use rand::Rng;
-    let width = 16;
-    let height = 30;
-    let mines = 99;
+    fn set_daily_reminder(&mut self, on: bool) {
+        let wanted = !self.profiles.active().settings.daily_reminder;
+        if wanted && !on {
+            self.toast("Reminders need permission to show notifications");
+        }
+        self.profiles.save_settings(Settings {
+            daily_reminder: on,
+            ..self.profiles.active().settings.clone()
+        });
+        self.profiles.store(self.storage.as_mut());
+    }
 
-    let board = create_board(width, height, mines, |x, y| {
-        rand::thread_rng().gen_range(x, y)
-    });
+    fn new_day(&mut self) {
+        let active = self.profiles.active();
+        let on_streak = active.stats.streak > 0;
+        if active.settings.daily_reminder && on_streak && daily::reminders_allowed() {
+            daily::remind(active.stats.streak);
+        }
+        self._new_day = Model::schedule_new_day(&self.link);
+    }
 
-    numbers_on_board(board)
-}
+    // Browsers only go fullscreen from a click, so unlike compact mode this
+    // isn't remembered.
+    fn toggle_fullscreen(&mut self) {
+        let document = match web_sys::window().and_then(|w| w.document()) {
+            Some(document) => document,
+            None => return,
+        };
+        if document.fullscreen_element().is_some() {
+            return document.exit_fullscreen();
+        }
+        let entered = document
+            .document_element()
+            .is_some_and(|e| e.request_fullscreen().is_ok());
+        if !entered {
+            self.toast("This browser can't go fullscreen");
+        }
+    }
 
-#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
-enum Mode {
-    Flagging,
-    Digging,
-}
+    fn toggle_compact(&mut self) {
+        self.profiles.save_settings(Settings {
+            compact: !self.profiles.active().settings.compact,
+            ..self.profiles.active().settings.clone()
+        });
+        self.profiles.store(self.storage.as_mut());
+    }
 
-#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
-enum Difficulty {
-    Easy,
-    Medium,
-    Hard,
-}
-struct Model {
-    link: ComponentLink<Self>,
-    //storage: StorageService,
-    state: State,
-}
+    fn toggle_splits(&mut self) {
+        let splits = !self.profiles.active().settings.splits;
+        self.profiles.save_settings(Settings {
+            splits,
+            ..self.profiles.active().settings.clone()
+        });
+        self.profiles.store(self.storage.as_mut());
+        self.toast(if splits {
+            "⏱️ Splits at 25, 50 and 75% of the board, against your best"
+        } else {
+            "⏱️ Splits off"
+        });
+    }
 
-enum Msg {
-    ToggleDifficulty,
-    ToggleMode,
-    UpdateBoard { point: Point },
-    RunRobot,
-}
+    fn cycle_timing(&mut self) {
+        let timing = self.profiles.active().settings.timing.next();
+        self.profiles.save_settings(Settings {
+            timing,
+            ..self.profiles.active().settings.clone()
+        });
+        self.profiles.store(self.storage.as_mut());
+        self.toast(match timing {
+            Timing::RealTime => "🕰️ Real time: pauses and menus count",
+            Timing::GameTime => "🕰️ Game time: pauses and menus don't count",
+        });
+    }
 
-#[derive(Serialize, Deserialize, Clone)]
-pub struct State {
-    difficulty: Difficulty,
-    mode: Mode,
-    board: Board,
-}
+    fn set_layout(&mut self, layout: Layout) {
+        self.profiles.save_settings(Settings {
+            layout,
+            ..self.profiles.active().settings.clone()
+        });
+        self.profiles.store(self.storage.as_mut());
+    }
 
-//const KEY: &'static str = "jgpaiva.minesweeper.self";
+    // Plays a sound at the new settings, so the volume can be set by ear.
+    fn set_audio(&mut self, audio: Audio) {
+        self.profiles.save_settings(Settings {
+            audio,
+            ..self.profiles.active().settings.clone()
+        });
+        self.profiles.store(self.storage.as_mut());
+        let sample = if audio.effects {
+            Sound::Flag
+        } else {
+            Sound::Won
+        };
+        self.speaker.play(sample, audio);
+    }
 
-impl Component for Model {
-    type Message = Msg;
-    type Properties = ();
-    fn create(_: Self::Properties, link: ComponentLink<Self>) -> Self {
-        //let storage = StorageService::new(Area::Local).expect("storage was disabled by the user");
-        //        let difficulty = {
-        //            if let Json(Ok(restored_model)) = storage.restore(KEY) {
-        //                restored_model
-        //            } else {
-        //
-        //            }
-        //        };
-        let state = State {
-            difficulty: Difficulty::Easy,
-            mode: Mode::Digging,
-            board: small_board(),
+    fn toggle_mistake_warnings(&mut self) {
+        let warn = !self.profiles.active().settings.warn_mistakes;
+        self.profiles.save_settings(Settings {
+            warn_mistakes: warn,
+            ..self.profiles.active().settings.clone()
+        });
+        self.profiles.store(self.storage.as_mut());
+        self.toast(if warn {
+            "🛟 Digging a certain mine now asks first"
+        } else {
+            "🛟 Mistake warnings off"
+        });
+    }
+
+    fn cycle_auto_open(&mut self) {
+        let level = self.profiles.active().settings.auto_open.next();
+        self.profiles.save_settings(Settings {
+            auto_open: level,
+            ..self.profiles.active().settings.clone()
+        });
+        self.profiles.store(self.storage.as_mut());
+        self.toast(&format!("🪄 Auto-open: {}", level.name()));
+    }
+
+    fn toggle_auto_flag(&mut self) {
+        let auto_flag = !self.profiles.active().settings.auto_flag;
+        self.profiles.save_settings(Settings {
+            auto_flag,
+            ..self.profiles.active().settings.clone()
+        });
+        self.profiles.store(self.storage.as_mut());
+        self.toast(if auto_flag {
+            "🏁 The last mines get flagged for you"
+        } else {
+            "🏁 Auto-flag off"
+        });
+    }
+
+    fn toggle_second_chance(&mut self) {
+        let second_chance = !self.profiles.active().settings.second_chance;
+        self.profiles.save_settings(Settings {
+            second_chance,
+            ..self.profiles.active().settings.clone()
+        });
+        self.profiles.store(self.storage.as_mut());
+        self.toast(if second_chance {
+            "🍀 The first mine you dig each game gets flagged instead"
+        } else {
+            "🍀 Second chance off"
+        });
+    }
+
+    fn toggle_casual(&mut self) {
+        let casual = !self.profiles.active().settings.casual;
+        self.profiles.save_settings(Settings {
+            casual,
+            ..self.profiles.active().settings.clone()
+        });
+        self.profiles.store(self.storage.as_mut());
+        self.toast(if casual {
+            "🐣 Casual: mines you dig cost time instead of the game"
+        } else {
+            "🐣 Casual off"
+        });
+    }
+
+    // Boards keep the cascade they started with, so it's only for new ones.
+    fn toggle_recap(&mut self) {
+        self.recap = match self.recap {
+            Some(_) => None,
+            None => Some(Recap::Current),
         };
-        Self {
-            link,
-            //storage,
-            state,
-        }
     }
 
-    fn update(&mut self, msg: Self::Message) -> ShouldRender {
-        match msg {
-            Msg::ToggleDifficulty => self.toggle_difficulty(),
-            Msg::ToggleMode => self.toggle_mode(),
-            Msg::UpdateBoard { point } => self.update_board(point),
-            Msg::RunRobot => self.run_robot(),
-        }
-        true
+    fn cycle_recap_every(&mut self) {
+        let every = self.profiles.active().settings.recap_every;
+        let next = RECAP_EVERY
+            .iter()
+            .position(|n| *n == every)
+            .map_or(0, |i| (i + 1) % RECAP_EVERY.len());
+        self.profiles.save_settings(Settings {
+            recap_every: RECAP_EVERY[next],
+            ..self.profiles.active().settings.clone()
+        });
+        self.profiles.store(self.storage.as_mut());
     }
 
-    fn view(&self) -> Html {
-        html! {
-            <body class={self.render_body_class()}>
-                <div id="difficulty_button_placeholder" class="flex-container">
-                    <div
-                     id="difficulty-button"
-                     class="clickable item"
-                     onclick=self.link.callback(|_| Msg::ToggleDifficulty) >
-                        { self.render_difficulty() }
-                    </div>
-                    <div
-                     id="mode-button"
-                     class={self.render_mode_class()}
-                     onclick=self.link.callback(|_| Msg::ToggleMode) >
-                        { self.render_mode() }
-                    </div>
-                    <div
-                     id="robot-button"
-                     class={self.render_mode_class()}
-                     onclick=self.link.callback(|_| Msg::RunRobot) >
-                        { self.render_robot()}
-                    </div>
-                    <TimeKeeper op={
-                        match self.state.board.state {
-                            Won => TimeKeeperOp::Stopped,
-                            Failed => TimeKeeperOp::Stopped,
-                            Playing => TimeKeeperOp::Counting,
-                            Ready => TimeKeeperOp::Reset,
-                            NotReady => unreachable!(),
-                        }}/>
-                </div>
-                <div id="board_game_placeholder">
-                    <div id="board_game" class="flex-container">
-                        {
-                            (0..self.state.board.height)
-                                .flat_map(|y| {
-                                                (0..self.state.board.width+1).map(move |x| {
-                                                    if x == self.state.board.width{
-                                                        self.render_break()
-                                                    } else {
-                                                        let board = &self.state.board;
-                                                        html!{
-                                                            <BoardItem
-                                                                x={x}
-                                                                y={y}
-                                                                board_state={board.state.clone()}
-                                                                board_width={board.width}
-                                                                element={board.at(&Point::new(x,y)).unwrap()}
-                                                                update_signal={self.link.callback(|msg:Msg| msg)}/>
-                                                        }
-                                                    }
-                                                })
-                                }).collect::<Html>()
-                        }
-                    </div>
-                </div>
-            </body>
+    fn copy_recap(&mut self) {
+        let text = match &self.recap {
+            Some(Recap::Last(session)) => session.share_text(),
+            _ => self.session.share_text(),
+        };
+        let clipboard = match web_sys::window() {
+            Some(window) => window.navigator().clipboard(),
+            None => return,
+        };
+        let copied = self.link.callback(|ok| Msg::RecapCopied { ok });
+        spawn_local(async move {
+            copied.emit(JsFuture::from(clipboard.write_text(&text)).await.is_ok());
+        });
+    }
+
+    fn cycle_auto_restart(&mut self) {
+        let after = self.profiles.active().settings.auto_restart;
+        let next = AUTO_RESTART_AFTER
+            .iter()
+            .position(|s| *s == after)
+            .map_or(0, |i| (i + 1) % AUTO_RESTART_AFTER.len());
+        let auto_restart = AUTO_RESTART_AFTER[next];
+        self.profiles.save_settings(Settings {
+            auto_restart,
+            ..self.profiles.active().settings.clone()
+        });
+        self.profiles.store(self.storage.as_mut());
+        self.toast(&match auto_restart {
+            0 => String::from("🔁 Auto-restart off"),
+            after => format!("🔁 A new board {}s after a loss", after),
+        });
+    }
+
+    fn toggle_deductions(&mut self) {
+        if !self.robot.is_available() {
+            return self.toast("🧮 Needs 🤖, which isn't in this build");
         }
+        let deductions = !self.profiles.active().settings.deductions;
+        self.profiles.save_settings(Settings {
+            deductions,
+            ..self.profiles.active().settings.clone()
+        });
+        self.profiles.store(self.storage.as_mut());
+        self.schedule_deductions();
     }
-}
 
-impl Model {
-    fn toggle_difficulty(&mut self) {
-        let (new_board, new_difficulty) = match (
-            self.state.board.state.clone(),
-            self.state.difficulty.clone(),
-        ) {
-            (Ready, Difficulty::Easy) => (medium_board(), Difficulty::Medium),
-            (Ready, Difficulty::Medium) => (large_board(), Difficulty::Hard),
-            (Ready, Difficulty::Hard) => (small_board(), Difficulty::Easy),
-            (_, Difficulty::Easy) => (small_board(), Difficulty::Easy),
-            (_, Difficulty::Medium) => (medium_board(), Difficulty::Medium),
-            (_, Difficulty::Hard) => (large_board(), Difficulty::Hard),
-        };
-        self.state = State {
-            difficulty: new_difficulty,
-            board: new_board,
-            ..self.state.clone()
+    fn toggle_trail(&mut self) {
+        let trail = !self.profiles.active().settings.trail;
+        self.profiles.save_settings(Settings {
+            trail,
+            ..self.profiles.active().settings.clone()
+        });
+        self.profiles.store(self.storage.as_mut());
+    }
+
+    fn toggle_auto_retry(&mut self) {
+        let auto_retry = !self.profiles.active().settings.auto_retry;
+        self.profiles.save_settings(Settings {
+            auto_retry,
+            ..self.profiles.active().settings.clone()
+        });
+        self.profiles.store(self.storage.as_mut());
+        self.toast(if auto_retry {
+            "🔂 Auto-restart retries the same board"
+        } else {
+            "🔂 Auto-restart brings a new board"
+        });
+    }
+
+    fn cycle_cascade(&mut self) {
+        let cascade = self.profiles.active().settings.cascade.next();
+        self.profiles.save_settings(Settings {
+            cascade,
+            ..self.profiles.active().settings.clone()
+        });
+        self.profiles.store(self.storage.as_mut());
+        self.toast(&format!(
+            "🌊 Cascade {}, from the next board",
+            cascade.name()
+        ));
+    }
+
+    // A mine was flagged instead of losing, and the clock takes the
+    // penalty.
+    fn forgive(&mut self, penalty: Duration) {
+        let emoji = if self.state.game.casual {
+            "🐣"
+        } else {
+            "🍀"
+        };
+        self.stopwatch.penalize(penalty.as_millis() as f64);
+        self.toast(&format!(
+            "{} That was a mine! Flagged it for you, +{}s",
+            emoji,
+            penalty.as_secs()
+        ));
+    }
+
+    fn toggle_adaptive(&mut self) {
+        let adaptive = !self.profiles.active().settings.adaptive;
+        self.profiles.save_settings(Settings {
+            adaptive,
+            ..self.profiles.active().settings.clone()
+        });
+        self.profiles.store(self.storage.as_mut());
+        self.toast(if adaptive {
+            "🎯 New boards get more mines as you win, and fewer as you lose"
+        } else {
+            "🎯 Adaptive difficulty off"
+        });
+    }
+
+    fn cycle_challenge(&mut self) {
+        let challenge = self.profiles.active().settings.challenge.next();
+        self.profiles.save_settings(Settings {
+            challenge,
+            ..self.profiles.active().settings.clone()
+        });
+        self.profiles.store(self.storage.as_mut());
+        self.toast(&format!("🎚️ New boards: {}", challenge.describe()));
+    }
+
+    fn estimate_win_chance(&mut self) {
+        match self.robot.win_chance(&self.state.game.board) {
+            Some(chance) => self.toast(&format!(
+                "🎲 🤖 wins about {:.0}% of games from here",
+                chance * 100_f64
+            )),
+            None => self.toast("🎲 This position doesn't add up, check the flags"),
         }
     }
-    fn toggle_mode(&mut self) {
-        if matches!(self.state.board.state, Won | Failed) {
+
+    // Runs before the losing move is applied, so the board is the one the
+    // player lost from.
+    fn review_loss(&mut self, before: &Board, lost: &Board) {
+        if !self.robot.is_available() {
             return;
         }
-        self.state = match self.state.mode {
-            Mode::Digging => State {
-                mode: Mode::Flagging,
-                ..self.state.clone()
-            },
-            Mode::Flagging => State {
-                mode: Mode::Digging,
-                ..self.state.clone()
-            },
+        let fatal = heatmap::fatal_mine(lost);
+        let review = fatal.and_then(|p| self.robot.review_loss(before, &p));
+        if let Some(review) = review {
+            self.toast(&format!("☠️ {}", review.summary));
+            self.better_than_loss = review.better;
         }
-    }
-
-    fn render_body_class(&self) -> &str {
-        match self.state.board.state {
-            Ready | Playing => "ongoing",
-            Won => "won",
-            Failed => "failed",
-            NotReady => unreachable!(),
+        if let Some(chance) = self.robot.win_chance(before) {
+            self.toast(&format!(
+                "☠️ Before that move, 🤖 would have won about {:.0}% of games",
+                chance * 100_f64
+            ));
         }
     }
 
-    fn render_difficulty(&self) -> Html {
-        html! {
-            match self.state.difficulty {
-                Difficulty::Easy => "😀",
-                Difficulty::Medium => "🤨",
-                Difficulty::Hard => "🧐",
+    fn run_robot(&mut self) {
+        if self.state.game.is_over() || self.in_match() {
+            return;
+        }
+        let m = self.robot.next_move(&self.state.game.board);
+        let names = self.robot.names();
+        let name = names.get(self.robot.selected()).unwrap_or(&"🤖");
+        debug_log::record(Kind::Solver, format!("{} chose {:?}", name, m));
+        match m {
+            Some(m) => {
+                let marks = self.log_move("🤖", m);
+                self.apply_move(m);
+                self.highlight(marks);
             }
+            None => self.toast("🤖 has no certain moves"),
         }
     }
 
-    fn render_mode_class(&self) -> &str {
-        match &self.state.board.state {
-            Won | Failed => "item",
-            _ => "clickable item",
+    // Plays up to `count` of the moves a single number proves, whichever
+    // strategy is selected, as one action that's drawn once at the end.
+    fn run_robot_steps(&mut self, count: usize) {
+        let before = self.state.game.board.clone();
+        let mut marks = vec![];
+        let mut played = 0;
+        while played < count && self.state.game.board.state == Playing {
+            let m = match self.robot.explain(&self.state.game.board) {
+                Some(step) => step.m,
+                None => break,
+            };
+            marks.extend(self.log_move("🤖", m));
+            self.apply_move(m);
+            played += 1;
+        }
+        debug_log::record(
+            Kind::Solver,
+            format!("🤖 played {} of {} steps", played, count),
+        );
+        if played == 0 {
+            return self.toast("🤖 has no certain moves");
+        }
+        let changed = before.changed_points(&self.state.game.board);
+        self.board_changes = self.board_changes.next(Some(changed));
+        self.highlight(marks);
+        if played < count && self.state.game.board.state == Playing {
+            self.toast(&format!("🤖 ran out of certain moves after {}", played));
         }
     }
 
-    fn render_mode(&self) -> &str {
-        match (&self.state.board.state, self.state.mode.clone()) {
-            (Ready, Mode::Flagging) | (Playing, Mode::Flagging) => "🚩",
-            (Ready, Mode::Digging) | (Playing, Mode::Digging) => "⛏️",
-            (Won, _) => "🏆",
-            (Failed, _) => "☠️",
-            _ => unreachable!(),
+    // The CSV shows where the mines are, so only for finished games.
+    fn export_board(&mut self) {
+        if !matches!(self.state.game.board.state, Won | Failed) {
+            return self.toast("Finish the game to export its board");
         }
+        let csv = self.state.game.board.to_csv();
+        self.download("minesweeper-board.csv", "text/csv", &csv);
     }
 
-    fn render_robot(&self) -> &str {
-        if matches!(&self.state.board.state, Ready | Playing) {
-            "🤖"
-        } else {
-            ""
-        }
+    fn export_stats(&mut self) {
+        let csv = self.profiles.to_csv(&self.presets);
+        self.download("minesweeper-stats.csv", "text/csv", &csv);
     }
 
-    fn render_break(&self) -> Html {
-        html! {
-            <div class="break">
-            </div>
-        }
+    fn export_backup(&mut self) {
+        let backup = backup::export(&self.profiles, &self.history);
+        self.download("minesweeper-backup.json", "application/json", &backup);
     }
 
-    fn update_board(&mut self, p: Point) {
-        match self.state.mode {
-            Mode::Digging => {
-                let new_board = self.state.board.cascade_open_item(&p);
-                if let Some(b) = new_board {
-                    self.state.board = b
-                }
-            }
-            Mode::Flagging => {
-                self.state.board = self.state.board.flag_item(&p);
-            }
-        }
+    // Adds up the counters of profiles with the same name and keeps the best
+    // times of either. Games from the backup join the history.
+    fn merge_backup(&mut self, backup: Backup) {
+        let profiles = self.profiles.merge(backup.profiles);
+        let games = self.history.merge(backup.history);
+        self.profiles.store(self.storage.as_mut());
+        self.history.store(self.storage.as_mut());
+        self.toast(&format!(
+            "📥 Backup merged: {} new profiles, {} new games",
+            profiles, games
+        ));
     }
 
-    fn run_robot(&mut self) {
-        if matches!(self.state.board.state, Won | Failed) {
-            return;
-        }
-        let board = &self.state.board;
-        for x in 0..board.width {
-            for y in 0..board.height {
-                let p = Point::new(x, y);
-                let el = board.at(&p).unwrap();
-                match el {
-                    Number {
-                        state: Open,
-                        count: mine_count,
-                    } if *mine_count > 0 => {
-                        let surrounding_points = board.surrounding_points(&p);
-                        let surrounding_els: Vec<(&Point, MapElement)> = surrounding_points
-                            .iter()
-                            .map(|p| (p, board.at(&p).unwrap().clone()))
-                            .filter(|(_p, el)| {
-                                !matches!(
-                                    el,
-                                    Number {
-                                        state: Open,
-                                        count: 0
-                                    }
-                                )
-                            })
-                            .collect();
-                        let unopened = surrounding_els
-                            .iter()
-                            .filter(|(_p, el)| !matches!(el, Number{state:Open,..}));
-                        let flagged = surrounding_els.iter().filter(
-                            |(_p, el)| matches!(el, Mine{state:Flagged} | Number{state:Flagged,..}),
-                        );
-                        let unopened_count = unopened.clone().count();
-                        let flagged_count = flagged.count();
-
-                        if *mine_count == unopened_count as i32 && flagged_count < unopened_count {
-                            let (p,_el) = unopened.filter(|(_p,el)| !matches!(el, Mine{state:Flagged} | Number{state:Flagged,..})).next().unwrap();
-                            self.state.board = self.state.board.flag_item(&p);
-                            return;
-                        }
+    // Copies the board as the player sees it, for pasting into a discussion.
+    fn copy_position(&mut self) {
+        let clipboard = match web_sys::window() {
+            Some(window) => window.navigator().clipboard(),
+            None => return,
+        };
+        let text = self.state.game.board.to_player_string();
+        let copied = self.link.callback(|ok| Msg::PositionCopied { ok });
+        spawn_local(async move {
+            copied.emit(JsFuture::from(clipboard.write_text(&text)).await.is_ok());
+        });
+    }
 
-                        if *mine_count == flagged_count as i32 && unopened_count - flagged_count > 0
-                        {
-                            let (p,_el) = unopened.filter(|(_p,el)| !matches!(el, Mine{state:Flagged} | Number{state:Flagged,..})).next().unwrap();
-                            if let Some(b) = self.state.board.cascade_open_item(&p) {
-                                self.state.board = b;
-                                return;
-                            }
-                        }
-                    }
-                    _ => (),
-                }
-            }
+    fn download(&mut self, name: &str, mime: &str, text: &str) {
+        if download::download(name, mime, text).is_err() {
+            self.toast("Couldn't download the file");
         }
     }
-}
 
-#[derive(Copy, Clone, Properties, PartialEq)]
-struct TimeKeeperProps {
-    op: TimeKeeperOp,
-}
+    fn download_log(&mut self) {
+        let log = debug_log::dump();
+        self.download("minesweeper-debug-log.jsonl", "application/x-ndjson", &log);
+    }
+
+    fn cant_build(&mut self, difficulty: &Difficulty, error: &BuildBoardError) {
+        self.toast(&format!(
+            "The {} difficulty can't build a board: {}",
+            difficulty.0, error
+        ));
+    }
 
-#[derive(Copy, Clone, PartialEq)]
-enum TimeKeeperOp {
-    Reset,
-    Counting,
-    Stopped,
+    fn toast(&mut self, text: &str) {
+        let dismiss = self.link.callback(|id| Msg::DismissToast { id });
+        self.toasts.show(text, dismiss);
+    }
 }
 
-struct TimeKeeperState {
-    started_at: Option<Date>,
-    stopped_at: Option<Date>,
-    _handle: yew::services::interval::IntervalTask,
+#[derive(Clone, Properties, PartialEq)]
+struct TimeKeeperProps {
+    stopwatch: Stopwatch,
+    timing: Timing,
 }
 
 struct TimeKeeper {
     props: TimeKeeperProps,
-    state: TimeKeeperState,
+    _handle: yew::services::interval::IntervalTask,
 }
 
 enum TimeKeeperMsg {
@@ -391,41 +5777,11 @@ impl Component for TimeKeeper {
         let callback_tick = link.callback(|_| TimeKeeperMsg::Tick);
         let mut interval_service = IntervalService::new();
         let _handle = interval_service.spawn(Duration::from_millis(100), callback_tick);
-
-        let state = TimeKeeperState {
-            started_at: None,
-            stopped_at: None,
-            _handle,
-        };
-        Self { state, props }
+        Self { props, _handle }
     }
 
     fn change(&mut self, props: Self::Properties) -> ShouldRender {
-        let should_render = match (&self.props.op, props.op) {
-            (TimeKeeperOp::Counting, TimeKeeperOp::Reset)
-            | (TimeKeeperOp::Stopped, TimeKeeperOp::Reset) => {
-                self.state.started_at = None;
-                self.state.stopped_at = None;
-                true
-            }
-            (TimeKeeperOp::Reset, TimeKeeperOp::Reset) => false,
-            (TimeKeeperOp::Stopped, TimeKeeperOp::Counting)
-            | (TimeKeeperOp::Reset, TimeKeeperOp::Counting) => {
-                self.state.started_at = Some(Date::new_0());
-                true
-            }
-            (TimeKeeperOp::Counting, TimeKeeperOp::Counting) => true,
-            (TimeKeeperOp::Counting, TimeKeeperOp::Stopped) => {
-                self.state.stopped_at = Some(Date::new_0());
-                true
-            }
-            (TimeKeeperOp::Reset, TimeKeeperOp::Stopped) => {
-                self.state.started_at = Some(Date::new_0());
-                self.state.stopped_at = Some(Date::new_0());
-                true
-            }
-            (TimeKeeperOp::Stopped, TimeKeeperOp::Stopped) => false,
-        };
+        let should_render = self.props != props;
         self.props = props;
         should_render
     }
@@ -440,32 +5796,123 @@ impl Component for TimeKeeper {
 
     fn update(&mut self, msg: Self::Message) -> ShouldRender {
         match msg {
-            TimeKeeperMsg::Tick => {}
+            TimeKeeperMsg::Tick => tab::tick(),
         }
         true
     }
 }
 
 impl TimeKeeper {
+    // Tenths while counting, to the millisecond once stopped. Game time
+    // shows when it's paused.
     fn render_timer(&self) -> String {
-        match (&self.state.started_at, &self.state.stopped_at) {
-            (Some(started_at), None) => {
-                let now = Date::new_0();
-                format!(
-                    "{}",
-                    ((now.get_time() - started_at.get_time()) / 1000_f64)
-                        .round()
-                        .min(999_f64) // make sure we don't run out of space
-                )
-            }
-            (Some(started_at), Some(stopped_at)) => format!(
-                "{}",
-                ((stopped_at.get_time() - started_at.get_time()) / 1000_f64)
-                    .round()
-                    .min(999_f64) // make sure we don't run out of space
-            ),
-            (None, None) => String::from("0"),
-            _ => unreachable!(),
+        let TimeKeeperProps { stopwatch, timing } = &self.props;
+        let seconds = stopwatch.elapsed().on(*timing) / 1000_f64;
+        if stopwatch.stopped_at.is_some() {
+            format!("{:.3}", seconds.min(999.999)) // make sure we don't run out of space
+        } else if *timing == Timing::GameTime && stopwatch.is_paused() {
+            format!("⏸️ {:.1}", seconds.min(999.9))
+        } else {
+            format!("{:.1}", seconds.min(999.9))
+        }
+    }
+}
+
+// Which cells changed since the board was last drawn. Every change gets a
+// new generation, so cells can tell a fresh change-set from one they've
+// already drawn. `points: None` means everything changed.
+#[derive(Clone, PartialEq, Default)]
+struct BoardChanges {
+    generation: u64,
+    points: Option<Rc<Vec<Point>>>,
+}
+
+impl BoardChanges {
+    fn next(&self, points: Option<Vec<Point>>) -> BoardChanges {
+        BoardChanges {
+            generation: self.generation + 1,
+            points: points.map(Rc::new),
+        }
+    }
+
+    // For changes made in the same update as the last ones, which haven't
+    // been drawn yet.
+    fn plus(&self, points: Vec<Point>) -> BoardChanges {
+        match &self.points {
+            Some(last) => self.next(Some(last.iter().copied().chain(points).collect())),
+            None => self.next(None),
+        }
+    }
+
+    fn touches_row(&self, y: usize) -> bool {
+        match &self.points {
+            Some(points) => points.iter().any(|p| p.y == y as i32),
+            None => true,
+        }
+    }
+
+    fn touches(&self, p: &Point) -> bool {
+        match &self.points {
+            Some(points) => points.contains(p),
+            None => true,
+        }
+    }
+}
+
+#[derive(Clone, Properties, PartialEq)]
+struct BoardRowProps {
+    y: usize,
+    board_state: BoardState,
+    elements: Vec<MapElement>,
+    // Classes added to some of the row's cells, by column.
+    marks: Vec<(usize, &'static str)>,
+    changes: BoardChanges,
+    update_signal: Callback<Msg>,
+}
+
+// One row of cells. Only rows the last change-set touched re-render, and
+// within them only the cells it names.
+struct BoardRow {
+    props: BoardRowProps,
+}
+
+impl Component for BoardRow {
+    type Message = ();
+    type Properties = BoardRowProps;
+    fn create(props: Self::Properties, _link: ComponentLink<Self>) -> Self {
+        Self { props }
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        let should_render = self.props.changes.generation != props.changes.generation
+            && props.changes.touches_row(props.y);
+        self.props = props;
+        should_render
+    }
+
+    fn update(&mut self, _msg: Self::Message) -> ShouldRender {
+        false
+    }
+
+    fn view(&self) -> Html {
+        let y = self.props.y;
+        html! {
+            <>
+                {
+                    self.props.elements.iter().enumerate().map(|(x, element)| html! {
+                        <BoardItem
+                            x={x}
+                            y={y}
+                            board_state={self.props.board_state.clone()}
+                            element={element}
+                            mark={self.props.marks.iter().find(|(c, _)| *c == x).map(|(_, mark)| *mark)}
+                            changes={self.props.changes.clone()}
+                            update_signal={self.props.update_signal.clone()}/>
+                    }).collect::<Html>()
+                }
+                <div class="break">
+                </div>
+            </>
         }
     }
 }
@@ -475,8 +5922,9 @@ struct BoardItemProps {
     x: usize,
     y: usize,
     board_state: BoardState,
-    board_width: usize,
     element: MapElement,
+    mark: Option<&'static str>,
+    changes: BoardChanges,
     update_signal: Callback<Msg>,
 }
 
@@ -493,22 +5941,16 @@ impl Component for BoardItem {
     }
 
     fn change(&mut self, props: Self::Properties) -> ShouldRender {
-        if self.props.x == props.x
-            && self.props.y == props.y
-            && self.props.board_state == props.board_state
-            && self.props.board_width == props.board_width
-            && self.props.element == props.element
-        {
-            false
-        } else {
-            self.props = props;
-            true
-        }
+        let point = Point::new(props.x, props.y);
+        let should_render = self.props.changes.generation != props.changes.generation
+            && props.changes.touches(&point);
+        self.props = props;
+        should_render
     }
 
     fn update(&mut self, msg: Self::Message) -> ShouldRender {
         match msg {
-            Msg::UpdateBoard { point } => self.props.update_signal.emit(Msg::UpdateBoard { point }),
+            msg @ Msg::UpdateBoard { .. } => self.props.update_signal.emit(msg),
             _ => unreachable!(),
         }
         true
@@ -519,24 +5961,25 @@ impl Component for BoardItem {
         let y = self.props.y;
         html! {
             <div
-             class={
-                 match(&self.props.board_state, &self.props.element) {
-                     (Ready, Number { state: Closed, .. })
-                         | (Ready, Mine { state: Closed, .. })
-                         | (Playing, Number { state: Closed, .. })
-                         | (Playing, Mine { state: Closed, .. }) => {
-                             String::from("item clickable2")
-                         },
-                     (Playing, Number {state: Open, count: count})
-                         | (Won,Number {count: count, ..})
-                         | (Failed,Number {count: count, ..}) => {
-                         format!("item not-clickable2 mines-{}", count)
-                     },
-                     _ => String::from("item not-clickable2")
-             }},
-                style={self.get_item_style()}
-                onclick=self.link.callback(move |_| {Msg::UpdateBoard {point:Point::new(x,y)}}) >
-                <div style="width:100%; text-align:center"> {
+             class={self.render_class()}
+                onclick=self.link.callback(move |e: MouseEvent| Msg::UpdateBoard {
+                    point: Point::new(x, y),
+                    action: if e.shift_key() {
+                        Action::Flag
+                    } else if e.ctrl_key() || e.meta_key() {
+                        Action::Chord
+                    } else {
+                        Action::FromMode
+                    },
+                })
+                oncontextmenu=self.link.callback(move |e: MouseEvent| {
+                    e.prevent_default();
+                    Msg::UpdateBoard {
+                        point: Point::new(x, y),
+                        action: Action::AgainstMode,
+                    }
+                }) >
+                <div class="item-label"> {
                     match (&self.props.board_state, &self.props.element) {
                         (Ready, Number { state: Flagged, .. })
                             | (Ready, Mine { state: Flagged, .. })
@@ -564,17 +6007,27 @@ impl Component for BoardItem {
 }
 
 impl BoardItem {
-    fn get_item_style(&self) -> String {
-        let square_size: f64 = 100.0 / (self.props.board_width as f64);
-        let margin: f64 = 0.05 * square_size;
-        let width = format!("{:.2}", square_size - 2.0 * margin);
-
-        format!("width: {}%; margin: {}%", width, margin)
+    fn render_class(&self) -> String {
+        let class = match (&self.props.board_state, &self.props.element) {
+            (Ready, Number { state: Closed, .. })
+            | (Ready, Mine { state: Closed, .. })
+            | (Playing, Number { state: Closed, .. })
+            | (Playing, Mine { state: Closed, .. }) => String::from("item clickable2"),
+            (Playing, Number { state: Open, count })
+            | (Won, Number { count, .. })
+            | (Failed, Number { count, .. }) => format!("item not-clickable2 mines-{}", count),
+            _ => String::from("item not-clickable2"),
+        };
+        match self.props.mark {
+            Some(mark) => format!("{} {}", class, mark),
+            None => class,
+        }
     }
 }
 
 #[wasm_bindgen(start)]
 pub fn main() -> Result<(), JsValue> {
+    crash::install_hook();
     yew::initialize();
     App::<Model>::new().mount_as_body();
     let mut console = ConsoleService::new();
@@ -0,0 +1,91 @@
+use lib_minesweeper::{Annotation, Point, Tag};
+use yew::prelude::*;
+
+// How far along each side of a cell its corner badge reaches, in cells.
+const BADGE_SIZE: f64 = 0.3;
+
+// The note being written: the cells picked for it so far, its colour and
+// what it says.
+pub struct Draft {
+    pub cells: Vec<Point>,
+    pub tag: Tag,
+    pub note: String,
+}
+
+impl Default for Draft {
+    fn default() -> Draft {
+        Draft {
+            cells: vec![],
+            tag: Tag::Red,
+            note: String::new(),
+        }
+    }
+}
+
+impl Draft {
+    // Picks `p`, or puts it back if it was picked.
+    pub fn toggle(&mut self, p: Point) {
+        match self.cells.iter().position(|c| *c == p) {
+            Some(i) => {
+                self.cells.remove(i);
+            }
+            None => self.cells.push(p),
+        }
+    }
+
+    // The note as written, leaving the draft empty but for its colour, for
+    // the next one.
+    pub fn take(&mut self) -> Annotation {
+        Annotation {
+            cells: std::mem::take(&mut self.cells),
+            tag: self.tag,
+            note: std::mem::take(&mut self.note),
+        }
+    }
+
+    pub fn marks(&self) -> Vec<(Point, &'static str)> {
+        self.cells.iter().map(|p| (*p, "note-picked")).collect()
+    }
+}
+
+// A "column, row" list of a note's cells, counting from 1.
+pub fn describe_cells(cells: &[Point]) -> String {
+    cells
+        .iter()
+        .map(|p| format!("{},{}", p.x + 1, p.y + 1))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// A triangle in the top right corner of each annotated cell, in its note's
+// colour, drawn over a `width` by `height` board the way the knight's path
+// is. Later notes go on top.
+pub fn render_overlay(annotations: &[Annotation], width: usize, height: usize) -> Html {
+    if annotations.is_empty() {
+        return html! {};
+    }
+    html! {
+        <svg
+         id="notes-overlay"
+         viewBox=format!("0 0 {} {}", width, height)
+         preserveAspectRatio="none">
+            {
+                for annotations.iter().flat_map(|a| a.cells.iter().map(move |p| {
+                    let (right, top) = (p.x as f64 + 1_f64, p.y as f64);
+                    let points = format!(
+                        "{},{} {},{} {},{}",
+                        right - BADGE_SIZE,
+                        top,
+                        right,
+                        top,
+                        right,
+                        top + BADGE_SIZE
+                    );
+                    html! {
+                        <polygon class=format!("tag-{}", a.tag.name()) points=points />
+                    }
+                }))
+            }
+        </svg>
+    }
+}
@@ -0,0 +1,78 @@
+use std::time::Duration;
+
+use serde_derive::{Deserialize, Serialize};
+
+// A power-up is earned each time this much more of the board is cleared.
+const EARN_EVERY: f64 = 0.25;
+// How long a decoy flag shows, and how long a freeze lasts.
+pub const DECOY_FOR: Duration = Duration::from_secs(8);
+pub const FREEZE_FOR: Duration = Duration::from_secs(3);
+
+// Earned in this order, round and round.
+const EARNED: [PowerUp; 3] = [PowerUp::Reveal, PowerUp::Decoy, PowerUp::Freeze];
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum PowerUp {
+    // Opens a random safe cell for whoever plays it.
+    Reveal,
+    // Shows a flag that isn't there on a closed cell of the opponent's
+    // board, for a while.
+    Decoy,
+    // Stops the opponent's board taking moves for a moment, while their
+    // clock keeps running.
+    Freeze,
+}
+
+impl PowerUp {
+    pub fn emoji(self) -> &'static str {
+        match self {
+            PowerUp::Reveal => "🔍",
+            PowerUp::Decoy => "🎭",
+            PowerUp::Freeze => "❄️",
+        }
+    }
+
+    pub fn describe(self) -> &'static str {
+        match self {
+            PowerUp::Reveal => "open a random safe cell",
+            PowerUp::Decoy => "put a fake flag on your opponent's board",
+            PowerUp::Freeze => "freeze your opponent's board for a moment",
+        }
+    }
+
+    // Whether it's sent to the opponent rather than played on the player's
+    // own board.
+    pub fn is_attack(self) -> bool {
+        self != PowerUp::Reveal
+    }
+}
+
+// The power-ups earned in a duel's game and not played yet.
+#[derive(Default)]
+pub struct PowerUps {
+    pub held: Vec<PowerUp>,
+    earned: usize,
+}
+
+impl PowerUps {
+    // Earns one for every EARN_EVERY of the board cleared so far, short of
+    // clearing all of it. Returns what it earned.
+    pub fn reach(&mut self, progress: f64) -> Vec<PowerUp> {
+        let mut new = vec![];
+        while progress < 1_f64 && progress >= (self.earned + 1) as f64 * EARN_EVERY {
+            let power_up = EARNED[self.earned % EARNED.len()];
+            self.earned += 1;
+            self.held.push(power_up);
+            new.push(power_up);
+        }
+        new
+    }
+
+    pub fn take(&mut self, index: usize) -> Option<PowerUp> {
+        if index < self.held.len() {
+            Some(self.held.remove(index))
+        } else {
+            None
+        }
+    }
+}
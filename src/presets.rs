@@ -0,0 +1,308 @@
+use lib_minesweeper::Adjacency;
+use lib_minesweeper::Board;
+use lib_minesweeper::BoardBuilder;
+use lib_minesweeper::BuildBoardError;
+use lib_minesweeper::Replay;
+use lib_minesweeper::Spacing;
+use lib_minesweeper::Symmetry;
+
+use serde_derive::{Deserialize, Serialize};
+
+use crate::storage::{restore_json, store_json, Migration, StorageBackend};
+
+pub const KEY: &str = "jgpaiva.minesweeper.presets";
+
+// For changes to DifficultyPreset.
+const MIGRATIONS: &[Migration] = &[];
+
+// Names a preset. Serializes as the bare name, so games saved when the
+// difficulties were a fixed Easy/Medium/Hard enum still load.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Serialize, Deserialize)]
+pub struct Difficulty(pub String);
+
+impl Default for Difficulty {
+    fn default() -> Difficulty {
+        built_in()[0].difficulty()
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct DifficultyPreset {
+    pub name: String,
+    pub emoji: String,
+    pub width: usize,
+    pub height: usize,
+    pub mines: usize,
+    pub adjacency: Adjacency,
+    #[serde(default, skip_serializing_if = "Symmetry::is_none")]
+    pub symmetry: Symmetry,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spacing: Option<Spacing>,
+    // Cells the first cascade has to be able to open, see
+    // BoardBuilder::min_opening.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_opening: Option<usize>,
+    // A CSS hex colour for the header and the board's edge while playing
+    // this difficulty, so it's clear at a glance which one it is.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub accent: Option<String>,
+}
+
+impl DifficultyPreset {
+    fn new(name: &str, emoji: &str, width: usize, height: usize, mines: usize) -> Self {
+        DifficultyPreset {
+            name: name.to_string(),
+            emoji: emoji.to_string(),
+            width,
+            height,
+            mines,
+            adjacency: Adjacency::Knight,
+            symmetry: Symmetry::None,
+            spacing: None,
+            min_opening: None,
+            accent: None,
+        }
+    }
+
+    pub fn custom(
+        width: usize,
+        height: usize,
+        mines: usize,
+        symmetry: Symmetry,
+        spacing: Option<Spacing>,
+    ) -> Self {
+        let mut name = match symmetry {
+            Symmetry::None => format!("{}×{}, {} mines", width, height, mines),
+            Symmetry::Mirror => format!("{}×{}, {} mirrored mines", width, height, mines),
+            Symmetry::Rotational => format!("{}×{}, {} rotated mines", width, height, mines),
+        };
+        if let Some(spacing) = spacing {
+            name.push_str(&format!(" {} apart", spacing.distance));
+        }
+        DifficultyPreset {
+            symmetry,
+            spacing,
+            ..DifficultyPreset::new(&name, "🛠️", width, height, mines)
+        }
+    }
+
+    pub fn difficulty(&self) -> Difficulty {
+        Difficulty(self.name.clone())
+    }
+
+    // Only "#" and hex digits get through, as it goes into a style.
+    pub fn accent(&self) -> Option<&str> {
+        self.accent.as_deref().filter(|accent| {
+            accent.len() > 1
+                && accent.starts_with('#')
+                && accent[1..].chars().all(|c| c.is_ascii_hexdigit())
+        })
+    }
+
+    pub fn board(&self, seed: Option<u64>) -> Result<Board, BuildBoardError> {
+        match seed {
+            Some(seed) => self.builder().seed(seed),
+            None => self.builder(),
+        }
+        .build()
+    }
+
+    // What can be told about the preset without building a board: the
+    // builder's own checks, and whether the mines fit as far apart as it
+    // asks, so its boards don't have to fall back to closer spacing.
+    pub fn check(&self) -> Result<(), String> {
+        self.builder().check().map_err(|error| error.to_string())?;
+        match self.spacing {
+            Some(spacing) if !spacing.fits(self.width, self.height, self.mines) => Err(format!(
+                "{} mines don't fit {} moves apart",
+                self.mines, spacing.distance
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    fn builder(&self) -> BoardBuilder {
+        let builder = BoardBuilder::new(self.width, self.height)
+            .mine_count(self.mines)
+            .adjacency(self.adjacency)
+            .symmetry(self.symmetry);
+        let builder = match self.spacing {
+            Some(spacing) => builder.spacing(spacing),
+            None => builder,
+        };
+        match self.min_opening {
+            Some(cells) => builder.min_opening(cells),
+            None => builder,
+        }
+    }
+}
+
+fn built_in() -> Vec<DifficultyPreset> {
+    let accent = |preset: DifficultyPreset, accent: &str| DifficultyPreset {
+        accent: Some(accent.to_string()),
+        ..preset
+    };
+    vec![
+        accent(DifficultyPreset::new("Easy", "😀", 10, 10, 10), "#50723c"),
+        accent(DifficultyPreset::new("Medium", "🤨", 16, 16, 40), "#5296a5"),
+        // Redraws the few Hard boards whose biggest opening is tiny.
+        accent(
+            DifficultyPreset {
+                min_opening: Some(40),
+                ..DifficultyPreset::new("Hard", "🧐", 16, 30, 99)
+            },
+            "#ffbc42",
+        ),
+        accent(DifficultyPreset::new("Evil", "😈", 30, 24, 220), "#d81159"),
+        // Mostly there to see how the game copes with big boards.
+        accent(DifficultyPreset::new("Huge", "🐘", 50, 50, 500), "#685369"),
+    ]
+}
+
+// Every difficulty the toggle cycles through: the built-in ones first, then
+// the player's own, in the order they were added. Only the player's own are
+// stored.
+#[derive(Clone)]
+pub struct Presets {
+    presets: Vec<DifficultyPreset>,
+}
+
+impl Presets {
+    pub fn restore(storage: &dyn StorageBackend) -> Presets {
+        let custom: Vec<DifficultyPreset> =
+            restore_json(storage, KEY, MIGRATIONS).unwrap_or_default();
+        let mut presets = Presets {
+            presets: built_in(),
+        };
+        // Saved presets that no longer pass `add`'s checks are dropped.
+        for preset in custom {
+            let _ = presets.add(preset);
+        }
+        presets
+    }
+
+    pub fn store(&self, storage: &mut dyn StorageBackend) {
+        let custom = &self.presets[built_in().len()..];
+        store_json(storage, KEY, MIGRATIONS, &custom)
+    }
+
+    pub fn find(&self, difficulty: &Difficulty) -> Option<&DifficultyPreset> {
+        self.presets.iter().find(|p| p.name == difficulty.0)
+    }
+
+    // Falls back to the first preset for difficulties that no longer exist,
+    // e.g. a custom one saved on another device.
+    pub fn get(&self, difficulty: &Difficulty) -> &DifficultyPreset {
+        self.find(difficulty).unwrap_or(&self.presets[0])
+    }
+
+    pub fn emoji(&self, difficulty: &Difficulty) -> &str {
+        self.find(difficulty).map_or("❔", |p| &p.emoji)
+    }
+
+    pub fn next(&self, difficulty: &Difficulty) -> Difficulty {
+        let index = self.presets.iter().position(|p| p.name == difficulty.0);
+        let next = index.map_or(0, |i| (i + 1) % self.presets.len());
+        self.presets[next].difficulty()
+    }
+
+    // The preset that builds boards like the replay's, if there is one.
+    pub fn for_replay(&self, replay: &Replay) -> Option<Difficulty> {
+        self.presets
+            .iter()
+            .find(|p| {
+                (
+                    p.width,
+                    p.height,
+                    p.mines,
+                    p.adjacency,
+                    p.symmetry,
+                    p.spacing,
+                ) == (
+                    replay.width,
+                    replay.height,
+                    replay.mines,
+                    replay.adjacency,
+                    replay.symmetry,
+                    replay.spacing,
+                )
+            })
+            .map(|p| p.difficulty())
+    }
+
+    // The preset that builds this very board from its seed, if there is one.
+    pub fn for_board(&self, board: &Board) -> Option<Difficulty> {
+        let seed = board.seed?;
+        self.presets
+            .iter()
+            .find(|p| {
+                p.board(Some(seed))
+                    .is_ok_and(|b| b.position_hash() == board.position_hash())
+            })
+            .map(|p| p.difficulty())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &DifficultyPreset> {
+        self.presets.iter()
+    }
+
+    // Presets pass `add`'s checks, but some still can't build every board,
+    // e.g. a symmetric one with no layout its mines fit.
+    pub fn board(
+        &self,
+        difficulty: &Difficulty,
+        seed: Option<u64>,
+    ) -> Result<Board, BuildBoardError> {
+        self.get(difficulty).board(seed)
+    }
+
+    // Custom presets replace earlier ones with the same name, but can't
+    // replace the built-in ones or fail DifficultyPreset::check. No board is
+    // built, so restoring the table stays cheap.
+    pub fn add(&mut self, preset: DifficultyPreset) -> Result<(), String> {
+        preset.check()?;
+        let built_in = built_in().len();
+        match self.presets.iter().position(|p| p.name == preset.name) {
+            Some(i) if i < built_in => Err(format!("{} is a built-in difficulty", preset.name)),
+            Some(i) => {
+                self.presets[i] = preset;
+                Ok(())
+            }
+            None => {
+                self.presets.push(preset);
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_built_in_boards_build() {
+        let presets = Presets {
+            presets: built_in(),
+        };
+        for preset in presets.iter() {
+            assert!(presets.board(&preset.difficulty(), Some(1)).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_board_that_cant_be_built() {
+        // Nine mines can't be laid out around the middle of an even board.
+        let preset = DifficultyPreset::custom(8, 8, 9, Symmetry::Rotational, None);
+        let difficulty = preset.difficulty();
+        let mut presets = Presets {
+            presets: built_in(),
+        };
+        assert_eq!(presets.add(preset), Ok(()));
+        assert_eq!(
+            presets.board(&difficulty, Some(1)).map(|board| board.seed),
+            Err(BuildBoardError::NoSymmetricLayout { mines: 9 })
+        );
+    }
+}
@@ -0,0 +1,629 @@
+use std::collections::HashMap;
+
+use serde_derive::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::adaptive;
+use crate::clicks::Clicks;
+use crate::heatmap::Heatmap;
+use crate::keys::Keybindings;
+use crate::presets::Presets;
+use crate::robot::{AutoOpen, Challenge};
+use crate::sound::Audio;
+use crate::stopwatch::Times;
+use crate::storage::{restore_json, store_json, Migration, StorageBackend};
+use crate::turn_timer::Expiry;
+use crate::{Difficulty, Mode};
+use lib_minesweeper::{Board, Cascade, Point, Timing};
+
+pub const KEY: &str = "jgpaiva.minesweeper.profiles";
+const AVATARS: [&str; 8] = ["🙂", "🦊", "🐢", "🐙", "🦉", "🐝", "🐸", "🦄"];
+
+// Add one when Profile, Settings or Stats change shape.
+const MIGRATIONS: &[Migration] = &[
+    add_mistake_warnings,
+    add_auto_open,
+    add_click_stats,
+    add_layout,
+    add_compact,
+    add_daily_reminder,
+    add_challenge,
+    add_splits,
+    add_game_time,
+    add_heatmap,
+    add_controls,
+    add_auto_flag,
+    add_adaptive,
+    add_audio,
+    add_second_chance,
+    add_casual,
+    add_cascade,
+    add_recap_every,
+    add_auto_restart,
+    add_retries,
+    add_trail,
+    add_deductions,
+    add_turn_limit,
+    add_keys,
+];
+
+// Version 1 adds the mistake warnings setting, off for everyone.
+fn add_mistake_warnings(data: Value) -> Value {
+    add_field(data, "settings", "warn_mistakes", Value::Bool(false))
+}
+
+// Version 2 adds the auto-open assist, off for everyone.
+fn add_auto_open(data: Value) -> Value {
+    add_field(data, "settings", "auto_open", Value::from("Off"))
+}
+
+// Version 3 starts counting clicks, from nothing.
+fn add_click_stats(data: Value) -> Value {
+    let clicks = serde_json::to_value(Clicks::default()).unwrap_or(Value::Null);
+    let data = add_field(data, "stats", "clicks", clicks);
+    add_field(data, "stats", "three_bv", Value::from(0))
+}
+
+// Version 4 adds the layout settings, as they were: right-handed.
+fn add_layout(data: Value) -> Value {
+    let layout = serde_json::to_value(Layout::default()).unwrap_or(Value::Null);
+    add_field(data, "settings", "layout", layout)
+}
+
+// Version 5 adds compact mode, off for everyone.
+fn add_compact(data: Value) -> Value {
+    add_field(data, "settings", "compact", Value::Bool(false))
+}
+
+// Version 6 adds the daily board reminder, off for everyone.
+fn add_daily_reminder(data: Value) -> Value {
+    add_field(data, "settings", "daily_reminder", Value::Bool(false))
+}
+
+// Version 7 adds the challenge new boards are picked for, any for everyone.
+fn add_challenge(data: Value) -> Value {
+    add_field(data, "settings", "challenge", Value::from("Any"))
+}
+
+// Version 8 adds splits, off for everyone and with no best splits yet.
+fn add_splits(data: Value) -> Value {
+    let data = add_field(data, "settings", "splits", Value::Bool(false));
+    add_field(
+        data,
+        "stats",
+        "best_splits",
+        Value::Object(Default::default()),
+    )
+}
+
+// Version 9 adds game time. Clocks stay on real time, and there are no
+// best game times yet.
+fn add_game_time(data: Value) -> Value {
+    let data = add_field(data, "settings", "timing", Value::from("RealTime"));
+    let data = add_field(
+        data,
+        "stats",
+        "best_game_ms",
+        Value::Object(Default::default()),
+    );
+    add_field(
+        data,
+        "stats",
+        "best_game_splits",
+        Value::Object(Default::default()),
+    )
+}
+
+// Version 10 starts the click heatmap, from nothing.
+fn add_heatmap(data: Value) -> Value {
+    let heatmap = serde_json::to_value(Heatmap::default()).unwrap_or(Value::Null);
+    add_field(data, "stats", "heatmap", heatmap)
+}
+
+// Version 11 remembers the d-pad and 🤖's strategy, as they were by
+// default: the d-pad on touch screens only, and the first strategy.
+fn add_controls(data: Value) -> Value {
+    let data = add_field(data, "settings", "pad", Value::Null);
+    add_field(data, "settings", "strategy", Value::from(""))
+}
+
+// Version 12 adds the auto-flag rule, off for everyone.
+fn add_auto_flag(data: Value) -> Value {
+    add_field(data, "settings", "auto_flag", Value::from(false))
+}
+
+// Version 13 adds adaptive difficulty, off for everyone and with no
+// recent games to go by.
+fn add_adaptive(data: Value) -> Value {
+    let data = add_field(data, "settings", "adaptive", Value::from(false));
+    let data = add_field(data, "stats", "recent", Value::Array(vec![]));
+    add_field(data, "stats", "density", Value::Null)
+}
+
+// Version 14 adds the audio settings, with every sound off.
+fn add_audio(data: Value) -> Value {
+    let audio = serde_json::to_value(Audio::default()).unwrap_or(Value::Null);
+    add_field(data, "settings", "audio", audio)
+}
+
+// Version 15 adds the second chance rule, off for everyone.
+fn add_second_chance(data: Value) -> Value {
+    add_field(data, "settings", "second_chance", Value::from(false))
+}
+
+// Version 16 adds casual mode, off for everyone.
+fn add_casual(data: Value) -> Value {
+    add_field(data, "settings", "casual", Value::from(false))
+}
+
+// Version 17 adds the cascade setting, through zeros as before.
+fn add_cascade(data: Value) -> Value {
+    add_field(data, "settings", "cascade", Value::from("Zeros"))
+}
+
+// Version 18 adds how many games the session recap comes up after, never
+// for everyone.
+fn add_recap_every(data: Value) -> Value {
+    add_field(data, "settings", "recap_every", Value::from(0))
+}
+
+// Version 19 adds auto-restart after a loss, off for everyone.
+fn add_auto_restart(data: Value) -> Value {
+    add_field(data, "settings", "auto_restart", Value::from(0))
+}
+
+// Version 20 adds the retries' counts, and whether auto-restart retries
+// the same board, off for everyone.
+fn add_retries(data: Value) -> Value {
+    let data = add_field(data, "stats", "retries", Value::from(0));
+    let data = add_field(data, "stats", "retries_won", Value::from(0));
+    add_field(data, "settings", "auto_retry", Value::Bool(false))
+}
+
+// Version 21 adds the trail of opened cells, off for everyone.
+fn add_trail(data: Value) -> Value {
+    add_field(data, "settings", "trail", Value::Bool(false))
+}
+
+// Version 22 adds the counter of certain moves, off for everyone.
+fn add_deductions(data: Value) -> Value {
+    add_field(data, "settings", "deductions", Value::Bool(false))
+}
+
+// Version 23 adds the time limit on each move in a match, none for
+// everyone, and what running out of time does.
+fn add_turn_limit(data: Value) -> Value {
+    let data = add_field(data, "settings", "turn_limit", Value::from(0));
+    add_field(data, "settings", "turn_expiry", Value::from("Forfeit"))
+}
+
+// Version 24 adds the keybindings, all on their defaults.
+fn add_keys(data: Value) -> Value {
+    add_field(data, "settings", "keys", Value::Object(Default::default()))
+}
+
+// Adds `name` to the `section` of every profile.
+fn add_field(mut data: Value, section: &str, name: &str, value: Value) -> Value {
+    let profiles = data.get_mut("profiles").and_then(Value::as_array_mut);
+    for profile in profiles.into_iter().flatten() {
+        if let Some(fields) = profile.get_mut(section).and_then(Value::as_object_mut) {
+            fields.insert(String::from(name), value.clone());
+        }
+    }
+    data
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Settings {
+    pub difficulty: Difficulty,
+    pub mode: Mode,
+    // Ask before digging a cell 🤖 can prove is a mine.
+    pub warn_mistakes: bool,
+    pub auto_open: AutoOpen,
+    pub layout: Layout,
+    // Thin header and nothing else around the board, for big boards on
+    // small screens.
+    pub compact: bool,
+    // A notification when a new daily board comes out, while on a streak.
+    pub daily_reminder: bool,
+    // How hard new boards should be to win without guessing.
+    pub challenge: Challenge,
+    // Times at 25, 50 and 75% of the board, against the best game's.
+    pub splits: bool,
+    // Which clock the timer, splits and best times go by.
+    pub timing: Timing,
+    // Whether to show the d-pad. None shows it on touch screens only.
+    pub pad: Option<bool>,
+    // 🤖's strategy, by name. Empty for the first one.
+    pub strategy: String,
+    // Flag the last mines once only they are left closed.
+    pub auto_flag: bool,
+    // Nudge the mines on new boards up or down to suit recent results.
+    pub adaptive: bool,
+    pub audio: Audio,
+    // Forgive the first mine set off in each game, for a time penalty.
+    pub second_chance: bool,
+    // Never lose to a mine: every one dug is flagged, for a penalty that
+    // grows with each.
+    pub casual: bool,
+    // How far opening a cell spreads, from the next board on.
+    pub cascade: Cascade,
+    // Show the session recap after every so many games, 0 for never.
+    pub recap_every: u32,
+    // Seconds after a loss before a new board of the same difficulty
+    // starts by itself, 0 for never.
+    pub auto_restart: u32,
+    // Auto-restart starts the lost board again rather than a new one.
+    pub auto_retry: bool,
+    // Tint opened cells by how recently they were opened, in games and
+    // replays.
+    pub trail: bool,
+    // Show how many moves 🤖 can prove, but not which.
+    pub deductions: bool,
+    // Seconds for each move in hot seat and the duels this player hosts, 0
+    // for no limit.
+    pub turn_limit: u32,
+    pub turn_expiry: Expiry,
+    // The keys for the keyboard's actions that aren't on their defaults.
+    pub keys: Keybindings,
+}
+
+// For left-handed players: the header controls in reverse order, the d-pad
+// on the left, and the mouse buttons' actions swapped, each on its own.
+#[derive(Serialize, Deserialize, Clone, Copy, Default, PartialEq)]
+pub struct Layout {
+    pub mirrored: bool,
+    pub pad_left: bool,
+    pub swap_buttons: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct Stats {
+    pub played: u32,
+    pub won: u32,
+    pub streak: u32,
+    pub best_streak: u32,
+    pub best_ms: HashMap<Difficulty, f64>,
+    // The splits of the game that set each best time.
+    pub best_splits: HashMap<Difficulty, Vec<f64>>,
+    // The same in game time, which can be set by a different game.
+    pub best_game_ms: HashMap<Difficulty, f64>,
+    pub best_game_splits: HashMap<Difficulty, Vec<f64>>,
+    // Clicks and the boards' 3BV, over the games won.
+    pub clicks: Clicks,
+    pub three_bv: u64,
+    // Games on boards already played, kept apart from the rest.
+    pub retries: u32,
+    pub retries_won: u32,
+    // Where the player clicks and loses, over every finished game.
+    pub heatmap: Heatmap,
+    // Whether each of the last adaptive::RECENT games was won, oldest first.
+    pub recent: Vec<bool>,
+    // The mine density adaptive difficulty is at. None until it's been used.
+    pub density: Option<f64>,
+}
+
+impl Stats {
+    // The best times on `timing`'s clock.
+    pub fn best_ms(&self, timing: Timing) -> &HashMap<Difficulty, f64> {
+        match timing {
+            Timing::RealTime => &self.best_ms,
+            Timing::GameTime => &self.best_game_ms,
+        }
+    }
+
+    // The splits of the games that set them.
+    pub fn best_splits(&self, timing: Timing) -> &HashMap<Difficulty, Vec<f64>> {
+        match timing {
+            Timing::RealTime => &self.best_splits,
+            Timing::GameTime => &self.best_game_splits,
+        }
+    }
+
+    // Keeps the best real and game times apart. Returns whether the game set
+    // a new best on `timing`'s clock.
+    fn record(
+        &mut self,
+        difficulty: &Difficulty,
+        won: bool,
+        duration: Times,
+        splits: &[Times],
+        timing: Timing,
+    ) -> bool {
+        self.played += 1;
+        self.recent.push(won);
+        if self.recent.len() > adaptive::RECENT {
+            self.recent.remove(0);
+        }
+        if !won {
+            self.streak = 0;
+            return false;
+        }
+        self.won += 1;
+        self.streak += 1;
+        self.best_streak = self.best_streak.max(self.streak);
+        let mut new_best = false;
+        for clock in [Timing::RealTime, Timing::GameTime] {
+            let (best_ms, best_splits) = self.bests_mut(clock);
+            let best = best_ms.entry(difficulty.clone()).or_insert(f64::INFINITY);
+            if duration.on(clock) < *best {
+                *best = duration.on(clock);
+                let splits = splits.iter().map(|s| s.on(clock)).collect();
+                best_splits.insert(difficulty.clone(), splits);
+                new_best |= clock == timing;
+            }
+        }
+        new_best
+    }
+
+    fn bests_mut(
+        &mut self,
+        timing: Timing,
+    ) -> (
+        &mut HashMap<Difficulty, f64>,
+        &mut HashMap<Difficulty, Vec<f64>>,
+    ) {
+        match timing {
+            Timing::RealTime => (&mut self.best_ms, &mut self.best_splits),
+            Timing::GameTime => (&mut self.best_game_ms, &mut self.best_game_splits),
+        }
+    }
+
+    // Adds up the counters and keeps the better of each record. The streak
+    // can't be added up: it's whichever was longer. Recent games and the
+    // density stay as they are here, as they're about this device's play.
+    fn merge(&mut self, other: &Stats) {
+        self.played += other.played;
+        self.won += other.won;
+        self.streak = self.streak.max(other.streak);
+        self.best_streak = self.best_streak.max(other.best_streak);
+        self.clicks.add(&other.clicks);
+        self.three_bv += other.three_bv;
+        self.retries += other.retries;
+        self.retries_won += other.retries_won;
+        self.heatmap.add(&other.heatmap);
+        for timing in [Timing::RealTime, Timing::GameTime] {
+            let splits = other.best_splits(timing);
+            for (difficulty, ms) in other.best_ms(timing) {
+                let (best_ms, best_splits) = self.bests_mut(timing);
+                if best_ms.get(difficulty).is_some_and(|best| best <= ms) {
+                    continue;
+                }
+                best_ms.insert(difficulty.clone(), *ms);
+                match splits.get(difficulty) {
+                    Some(splits) => best_splits.insert(difficulty.clone(), splits.clone()),
+                    None => best_splits.remove(difficulty),
+                };
+            }
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Profile {
+    pub name: String,
+    pub avatar: String,
+    pub settings: Settings,
+    pub stats: Stats,
+}
+
+impl Profile {
+    fn new(name: String, avatar: &str) -> Profile {
+        Profile {
+            name,
+            avatar: avatar.to_string(),
+            settings: Settings {
+                difficulty: Difficulty::default(),
+                mode: Mode::Digging,
+                warn_mistakes: false,
+                auto_open: AutoOpen::Off,
+                layout: Layout::default(),
+                compact: false,
+                daily_reminder: false,
+                challenge: Challenge::Any,
+                splits: false,
+                timing: Timing::RealTime,
+                pad: None,
+                strategy: String::new(),
+                auto_flag: false,
+                adaptive: false,
+                audio: Audio::default(),
+                second_chance: false,
+                casual: false,
+                cascade: Cascade::Zeros,
+                recap_every: 0,
+                auto_restart: 0,
+                auto_retry: false,
+                trail: false,
+                deductions: false,
+                turn_limit: 0,
+                turn_expiry: Expiry::Forfeit,
+                keys: Keybindings::default(),
+            },
+            stats: Stats::default(),
+        }
+    }
+}
+
+// Everyone who plays on this browser. There is always at least one profile
+// and `active` always points at one of them.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Profiles {
+    active: usize,
+    profiles: Vec<Profile>,
+}
+
+impl Default for Profiles {
+    fn default() -> Profiles {
+        Profiles {
+            active: 0,
+            profiles: vec![Profile::new(String::from("Player"), AVATARS[0])],
+        }
+    }
+}
+
+impl Profiles {
+    pub fn restore(storage: &dyn StorageBackend) -> Profiles {
+        match restore_json::<Profiles>(storage, KEY, MIGRATIONS) {
+            Some(profiles) if profiles.active < profiles.profiles.len() => profiles,
+            _ => Profiles::default(),
+        }
+    }
+
+    pub fn store(&self, storage: &mut dyn StorageBackend) {
+        store_json(storage, KEY, MIGRATIONS, self)
+    }
+
+    pub fn active(&self) -> &Profile {
+        &self.profiles[self.active]
+    }
+
+    pub fn active_index(&self) -> usize {
+        self.active
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &Profile)> {
+        self.profiles.iter().enumerate()
+    }
+
+    pub fn switch_to(&mut self, index: usize) -> bool {
+        if index < self.profiles.len() {
+            self.active = index;
+            true
+        } else {
+            false
+        }
+    }
+
+    // Adds a profile with the next unused avatar and makes it the active one.
+    pub fn add(&mut self, name: String) {
+        let avatar = AVATARS
+            .iter()
+            .find(|a| !self.profiles.iter().any(|p| p.avatar == **a))
+            .unwrap_or(&AVATARS[self.profiles.len() % AVATARS.len()]);
+        self.profiles.push(Profile::new(name, avatar));
+        self.active = self.profiles.len() - 1;
+    }
+
+    // Merges the stats of profiles with the same name, and adds the others.
+    // Settings stay as they are here. Returns how many profiles were added.
+    pub fn merge(&mut self, other: Profiles) -> usize {
+        let mut added = 0;
+        for profile in other.profiles {
+            match self.profiles.iter_mut().find(|p| p.name == profile.name) {
+                Some(mine) => mine.stats.merge(&profile.stats),
+                None => {
+                    self.profiles.push(profile);
+                    added += 1;
+                }
+            }
+        }
+        added
+    }
+
+    pub fn save_settings(&mut self, settings: Settings) {
+        self.profiles[self.active].settings = settings;
+    }
+
+    pub fn record_game(
+        &mut self,
+        difficulty: &Difficulty,
+        won: bool,
+        duration: Times,
+        splits: &[Times],
+        timing: Timing,
+    ) -> bool {
+        self.profiles[self.active]
+            .stats
+            .record(difficulty, won, duration, splits, timing)
+    }
+
+    pub fn record_retry(&mut self, won: bool) {
+        let stats = &mut self.profiles[self.active].stats;
+        stats.retries += 1;
+        stats.retries_won += won as u32;
+    }
+
+    // Moves adaptive difficulty's density on from `density`, if it's not
+    // been set yet, after the game just recorded.
+    pub fn nudge_density(&mut self, won: bool, density: f64) -> f64 {
+        let stats = &mut self.profiles[self.active].stats;
+        let density = adaptive::nudge(stats.density.unwrap_or(density), won, &stats.recent);
+        stats.density = Some(density);
+        density
+    }
+
+    pub fn record_clicks(&mut self, clicks: &Clicks, three_bv: usize) {
+        let stats = &mut self.profiles[self.active].stats;
+        stats.clicks.add(clicks);
+        stats.three_bv += three_bv as u64;
+    }
+
+    pub fn record_heatmap(&mut self, clicks: &[Point], board: &Board) {
+        self.profiles[self.active]
+            .stats
+            .heatmap
+            .record(clicks, board);
+    }
+
+    // One row per profile, with a best time column for each preset.
+    pub fn to_csv(&self, presets: &Presets) -> String {
+        let mut header = vec![
+            String::from("profile"),
+            String::from("played"),
+            String::from("won"),
+            String::from("streak"),
+            String::from("best streak"),
+            String::from("efficiency (%)"),
+        ];
+        header.extend(presets.iter().map(|p| format!("{} best (s)", p.name)));
+        header.extend(
+            presets
+                .iter()
+                .map(|p| format!("{} best game time (s)", p.name)),
+        );
+        let mut rows = vec![header];
+        for profile in &self.profiles {
+            let stats = &profile.stats;
+            let mut row = vec![
+                profile.name.clone(),
+                stats.played.to_string(),
+                stats.won.to_string(),
+                stats.streak.to_string(),
+                stats.best_streak.to_string(),
+                stats
+                    .clicks
+                    .efficiency(stats.three_bv)
+                    .map_or(String::new(), |e| format!("{:.0}", e * 100_f64)),
+            ];
+            for timing in [Timing::RealTime, Timing::GameTime] {
+                row.extend(presets.iter().map(|p| {
+                    stats
+                        .best_ms(timing)
+                        .get(&p.difficulty())
+                        .map_or(String::new(), |ms| format!("{:.3}", ms / 1000_f64))
+                }));
+            }
+            rows.push(row);
+        }
+        rows.iter()
+            .map(|row| {
+                row.iter()
+                    .map(|f| csv_field(f))
+                    .collect::<Vec<_>>()
+                    .join(",")
+                    + "\n"
+            })
+            .collect()
+    }
+}
+
+// Quotes fields that would otherwise break the row, e.g. the commas in
+// custom preset names.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
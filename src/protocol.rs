@@ -0,0 +1,59 @@
+use lib_minesweeper::BoardState;
+use lib_minesweeper::Move;
+use lib_minesweeper::Replay;
+
+use serde_derive::{Deserialize, Serialize};
+use yew::format::{Json, Text};
+
+use crate::power_ups::PowerUp;
+use crate::turn_timer::Expiry;
+use crate::Difficulty;
+
+// Messages exchanged between players' browsers, for duels and broadcasts.
+// Sent as JSON text, so any transport that can carry strings can carry them.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum PeerMessage {
+    // Sent by whoever hosts the match: both sides then play this exact board.
+    // `hash` is the board's, in case the two sides' difficulties of that name
+    // differ. The time limit on each move is the host's too.
+    Start {
+        difficulty: Difficulty,
+        seed: u64,
+        hash: u64,
+        #[serde(default)]
+        turn_limit: u32,
+        #[serde(default)]
+        expiry: Expiry,
+    },
+    // Sent after every move that changed the sender's board.
+    Progress { progress: f64, state: BoardState },
+    // Duels: a power-up played against the receiver.
+    PowerUp {
+        power_up: PowerUp,
+    },
+    // Broadcasts: a move made by the player being watched.
+    Move {
+        m: Move,
+    },
+    // Broadcasts: the whole game so far, sent when a game starts and every
+    // few moves so spectators who tune in late can catch up. `hash` lets
+    // them check the board they rebuilt matches the player's.
+    Checkpoint {
+        difficulty: Difficulty,
+        replay: Replay,
+        hash: u64,
+    },
+}
+
+impl PeerMessage {
+    pub fn to_text(&self) -> Option<String> {
+        let text: Text = Json(self).into();
+        text.ok()
+    }
+
+    pub fn from_text(text: String) -> Option<PeerMessage> {
+        let text: Text = Ok(text);
+        let Json(message) = Json::from(text);
+        message.ok()
+    }
+}
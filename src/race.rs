@@ -0,0 +1,97 @@
+use lib_minesweeper::Race;
+use serde_derive::{Deserialize, Serialize};
+
+use crate::stopwatch;
+use crate::storage::{restore_json, store_json, Migration, StorageBackend};
+
+pub const KEY: &str = "jgpaiva.minesweeper.race";
+const MIGRATIONS: &[Migration] = &[];
+
+// Boards in a race started from here. Longer ones can be joined by code.
+pub const NEW_RACE_BOARDS: usize = 5;
+
+// How one board of the race went, in real time so every player's times
+// count the same way.
+#[derive(Serialize, Deserialize, Clone, Copy)]
+pub struct Finish {
+    pub won: bool,
+    pub ms: f64,
+}
+
+// The race the player is in, and how far they've got. Only the first go at
+// each board counts, and only in order.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Run {
+    pub code: String,
+    pub finishes: Vec<Finish>,
+}
+
+impl Run {
+    // None unless `code` is a race code.
+    pub fn new(code: &str) -> Option<Run> {
+        let race = lib_minesweeper::race(code)?;
+        Some(Run {
+            code: race.code,
+            finishes: vec![],
+        })
+    }
+
+    pub fn race(&self) -> Race {
+        lib_minesweeper::race(&self.code).expect("runs only start from valid codes")
+    }
+
+    // The board to play next, or None once they've all been played.
+    pub fn next(&self) -> Option<usize> {
+        Some(self.finishes.len()).filter(|i| *i < self.race().len())
+    }
+
+    pub fn finish(&mut self, finish: Finish) {
+        if self.next().is_some() {
+            self.finishes.push(finish);
+        }
+    }
+
+    pub fn won(&self) -> usize {
+        self.finishes.iter().filter(|f| f.won).count()
+    }
+
+    // Over the boards won: players rank by boards won, then by this.
+    pub fn won_ms(&self) -> f64 {
+        self.finishes.iter().filter(|f| f.won).map(|f| f.ms).sum()
+    }
+
+    // "🏆 4/5 in 80.123s"
+    pub fn score(&self) -> String {
+        format!(
+            "🏆 {}/{} in {}s",
+            self.won(),
+            self.race().len(),
+            stopwatch::seconds(self.won_ms())
+        )
+    }
+
+    // The results as text to send to the other players.
+    pub fn summary(&self) -> String {
+        let mut lines = vec![format!("🏎️ Race {}", self.code)];
+        for (i, finish) in self.finishes.iter().enumerate() {
+            lines.push(format!(
+                "{}. {} {}s",
+                i + 1,
+                if finish.won { "🏆" } else { "☠️" },
+                stopwatch::seconds(finish.ms)
+            ));
+        }
+        lines.push(self.score());
+        lines.join("\n") + "\n"
+    }
+
+    pub fn restore(storage: &dyn StorageBackend) -> Option<Run> {
+        restore_json::<Option<Run>>(storage, KEY, MIGRATIONS)
+            .flatten()
+            .filter(|run| lib_minesweeper::race(&run.code).is_some())
+    }
+
+    pub fn store(run: &Option<Run>, storage: &mut dyn StorageBackend) {
+        store_json(storage, KEY, MIGRATIONS, run)
+    }
+}
@@ -0,0 +1,368 @@
+use lib_minesweeper::Board;
+use lib_minesweeper::Move;
+use lib_minesweeper::Point;
+#[cfg(feature = "robot")]
+use lib_minesweeper::{
+    analyse_loss, certain_mines, certain_safe, make_solvable, mine_odds, win_chance, BasicSolver,
+    BoardView, CellView, Deduction, Fix, Guessing, Rating, SafestGuess, Strategy,
+};
+use lib_minesweeper::{Drill, Pattern};
+use serde_derive::{Deserialize, Serialize};
+
+// Each estimate game costs about the square of the board's cells, so big
+// boards get fewer of them.
+#[cfg(feature = "robot")]
+const ESTIMATE_WORK: usize = 2_000_000;
+
+// The most mines the editor's repair adds or takes away.
+#[cfg(feature = "robot")]
+const MAX_FIXES: usize = 12;
+
+// How many seeds a new game tries for a board of the chosen challenge.
+#[cfg(feature = "robot")]
+const RATED_ATTEMPTS: u64 = 30;
+
+// A move the robot is sure of: the open number it follows from, the cells
+// that number constrains, and the reasoning in words.
+pub struct Step {
+    pub m: Move,
+    pub source: Point,
+    pub constrained: Vec<Point>,
+    pub reason: String,
+}
+
+// Which cells get opened for the player after each of their moves: none,
+// the ones a single number proves safe, or every one that can't be a mine.
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub enum AutoOpen {
+    Off,
+    Basic,
+    Solver,
+}
+
+impl AutoOpen {
+    pub fn next(self) -> AutoOpen {
+        match self {
+            AutoOpen::Off => AutoOpen::Basic,
+            AutoOpen::Basic => AutoOpen::Solver,
+            AutoOpen::Solver => AutoOpen::Off,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            AutoOpen::Off => "off",
+            AutoOpen::Basic => "basic rules",
+            AutoOpen::Solver => "full solver",
+        }
+    }
+}
+
+// How hard new boards should be to win without guessing, whatever their
+// size and mines: any board, or one 🤖 rates as Casual, Tricky or Brutal.
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub enum Challenge {
+    Any,
+    Casual,
+    Tricky,
+    Brutal,
+}
+
+impl Challenge {
+    pub fn next(self) -> Challenge {
+        match self {
+            Challenge::Any => Challenge::Casual,
+            Challenge::Casual => Challenge::Tricky,
+            Challenge::Tricky => Challenge::Brutal,
+            Challenge::Brutal => Challenge::Any,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Challenge::Any => "any",
+            Challenge::Casual => "casual",
+            Challenge::Tricky => "tricky",
+            Challenge::Brutal => "brutal",
+        }
+    }
+
+    pub fn describe(self) -> &'static str {
+        match self {
+            Challenge::Any => "any board",
+            Challenge::Casual => "casual, every move follows from one number",
+            Challenge::Tricky => "tricky, some moves take several numbers",
+            Challenge::Brutal => "brutal, guessing needed",
+        }
+    }
+}
+
+// A look back at the move that lost a game, and the cells that would have
+// been better to open.
+pub struct Review {
+    pub summary: String,
+    pub better: Vec<Point>,
+}
+
+// The strategies the robot button can play, the default first. Builds
+// without the `robot` feature leave the solver out and have none, which
+// hides the button and the picker.
+pub struct Robot {
+    #[cfg(feature = "robot")]
+    strategies: Vec<Box<dyn Strategy>>,
+    selected: usize,
+}
+
+impl Robot {
+    pub fn new() -> Robot {
+        Robot {
+            #[cfg(feature = "robot")]
+            strategies: vec![
+                Box::new(BasicSolver),
+                Box::new(Guessing(BasicSolver)),
+                Box::new(SafestGuess),
+            ],
+            selected: 0,
+        }
+    }
+
+    #[cfg(feature = "robot")]
+    pub fn names(&self) -> Vec<&str> {
+        self.strategies.iter().map(|s| s.name()).collect()
+    }
+
+    #[cfg(not(feature = "robot"))]
+    pub fn names(&self) -> Vec<&str> {
+        vec![]
+    }
+
+    pub fn is_available(&self) -> bool {
+        !self.names().is_empty()
+    }
+
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    pub fn select(&mut self, index: usize) {
+        self.selected = index.min(self.names().len().saturating_sub(1));
+    }
+
+    // Strategies that aren't there any more leave the selection as it is.
+    pub fn select_named(&mut self, name: &str) {
+        if let Some(index) = self.names().iter().position(|n| *n == name) {
+            self.selected = index;
+        }
+    }
+
+    #[cfg(feature = "robot")]
+    pub fn next_move(&self, board: &Board) -> Option<Move> {
+        self.strategies[self.selected].next_move(&BoardView::new(board))
+    }
+
+    #[cfg(not(feature = "robot"))]
+    pub fn next_move(&self, _board: &Board) -> Option<Move> {
+        None
+    }
+
+    // Always explains the basic solver's moves, whichever strategy is
+    // selected, as those are the ones that follow from a single number.
+    #[cfg(feature = "robot")]
+    pub fn explain(&self, board: &Board) -> Option<Step> {
+        BasicSolver.explain(&BoardView::new(board)).map(step)
+    }
+
+    #[cfg(not(feature = "robot"))]
+    pub fn explain(&self, _board: &Board) -> Option<Step> {
+        None
+    }
+
+    // Why `m` is certain on `board`, if a single number proves it.
+    #[cfg(feature = "robot")]
+    pub fn justify(&self, board: &Board, m: &Move) -> Option<Step> {
+        BasicSolver.justify(&BoardView::new(board), m).map(step)
+    }
+
+    #[cfg(not(feature = "robot"))]
+    pub fn justify(&self, _board: &Board, _m: &Move) -> Option<Step> {
+        None
+    }
+
+    // A trainer position on `pattern`, the same one for the same seed.
+    #[cfg(feature = "robot")]
+    pub fn drill(&self, pattern: Pattern, seed: u64) -> Option<Drill> {
+        lib_minesweeper::drill(pattern, seed)
+    }
+
+    #[cfg(not(feature = "robot"))]
+    pub fn drill(&self, _pattern: Pattern, _seed: u64) -> Option<Drill> {
+        None
+    }
+
+    // Roughly how likely the position is to be won, by playing it out on
+    // boards matching what's visible. None if it contradicts itself.
+    #[cfg(feature = "robot")]
+    pub fn win_chance(&self, board: &Board) -> Option<f64> {
+        let cells = board.width * board.height;
+        let games = (ESTIMATE_WORK / (cells * cells)).clamp(5, 50);
+        win_chance(&BoardView::new(board), games, &mut rand::thread_rng())
+    }
+
+    #[cfg(not(feature = "robot"))]
+    pub fn win_chance(&self, _board: &Board) -> Option<f64> {
+        None
+    }
+
+    // Each closed cell's chance of being a mine, and whether that's exact
+    // rather than sampled. None if the position contradicts itself.
+    #[cfg(feature = "robot")]
+    pub fn mine_odds(&self, board: &Board) -> Option<(bool, Vec<(Point, f64)>)> {
+        let odds = mine_odds(&BoardView::new(board))?;
+        Some((odds.exact, odds.iter().copied().collect()))
+    }
+
+    #[cfg(not(feature = "robot"))]
+    pub fn mine_odds(&self, _board: &Board) -> Option<(bool, Vec<(Point, f64)>)> {
+        None
+    }
+
+    // `board` is the position the losing move was played on.
+    #[cfg(feature = "robot")]
+    pub fn review_loss(&self, board: &Board, fatal: &Point) -> Option<Review> {
+        let loss = analyse_loss(&BoardView::new(board), fatal)?;
+        Some(Review {
+            summary: loss.to_string(),
+            better: loss.better().to_vec(),
+        })
+    }
+
+    #[cfg(not(feature = "robot"))]
+    pub fn review_loss(&self, _board: &Board, _fatal: &Point) -> Option<Review> {
+        None
+    }
+
+    // Whether the cell can be proven to be a mine from what's visible.
+    #[cfg(feature = "robot")]
+    pub fn is_certain_mine(&self, board: &Board, p: &Point) -> bool {
+        certain_mines(&BoardView::new(board)).contains(p)
+    }
+
+    #[cfg(not(feature = "robot"))]
+    pub fn is_certain_mine(&self, _board: &Board, _p: &Point) -> bool {
+        false
+    }
+
+    // How many moves can be proven from what's visible: closed cells that
+    // are safe, and ones that are mines but aren't flagged yet. None
+    // without the solver.
+    #[cfg(feature = "robot")]
+    pub fn count_certain_moves(&self, board: &Board) -> Option<usize> {
+        let view = BoardView::new(board);
+        let safe = certain_safe(&view).len();
+        let mines = certain_mines(&view)
+            .iter()
+            .filter(|p| view.at(p) == Some(CellView::Closed))
+            .count();
+        Some(safe + mines)
+    }
+
+    #[cfg(not(feature = "robot"))]
+    pub fn count_certain_moves(&self, _board: &Board) -> Option<usize> {
+        None
+    }
+
+    // The closed cells `level` would open for the player. Both levels trust
+    // the flags, so a wrong one can get a mine opened, as with chording.
+    #[cfg(feature = "robot")]
+    pub fn auto_open(&self, board: &Board, level: AutoOpen) -> Vec<Point> {
+        let view = BoardView::new(board);
+        match level {
+            AutoOpen::Off => vec![],
+            AutoOpen::Basic => BasicSolver.safe_cells(&view),
+            AutoOpen::Solver => certain_safe(&view),
+        }
+    }
+
+    #[cfg(not(feature = "robot"))]
+    pub fn auto_open(&self, _board: &Board, _level: AutoOpen) -> Vec<Point> {
+        vec![]
+    }
+
+    // Whether the board can't be won without guessing, played from the
+    // zero in its biggest opening. None without the solver to tell.
+    #[cfg(feature = "robot")]
+    pub fn needs_guessing(&self, board: &Board) -> Option<bool> {
+        Some(Rating::of(&board.layout()) == Rating::Brutal)
+    }
+
+    #[cfg(not(feature = "robot"))]
+    pub fn needs_guessing(&self, _board: &Board) -> Option<bool> {
+        None
+    }
+
+    // A new board from `build`, trying the seeds after the first one's
+    // until the board is rated as `challenge` asks, and whether it is. Gives
+    // up after RATED_ATTEMPTS seeds and settles for the first board.
+    #[cfg(feature = "robot")]
+    pub fn rated_board<E>(
+        &self,
+        challenge: Challenge,
+        build: impl Fn(Option<u64>) -> Result<Board, E>,
+    ) -> Result<(Board, bool), E> {
+        let wanted = match challenge {
+            Challenge::Any => return Ok((build(None)?, true)),
+            Challenge::Casual => Rating::Casual,
+            Challenge::Tricky => Rating::Tricky,
+            Challenge::Brutal => Rating::Brutal,
+        };
+        let first = build(None)?;
+        if Rating::of(&first) == wanted {
+            return Ok((first, true));
+        }
+        // Seeds that don't build are skipped like ones rated otherwise.
+        let seed = first.seed.unwrap_or_default();
+        Ok((1..RATED_ATTEMPTS)
+            .filter_map(|i| build(Some(seed.wrapping_add(i))).ok())
+            .find(|board| Rating::of(board) == wanted)
+            .map_or((first, false), |board| (board, true)))
+    }
+
+    #[cfg(not(feature = "robot"))]
+    pub fn rated_board<E>(
+        &self,
+        _challenge: Challenge,
+        build: impl Fn(Option<u64>) -> Result<Board, E>,
+    ) -> Result<(Board, bool), E> {
+        Ok((build(None)?, true))
+    }
+
+    // `board` changed as little as the solver can manage to be won from
+    // `start` without guessing, and the cells changed, true where a mine
+    // was added. None if it couldn't within MAX_FIXES, or without the
+    // solver.
+    #[cfg(feature = "robot")]
+    pub fn repair(&self, board: &Board, start: &Point) -> Option<(Board, Vec<(Point, bool)>)> {
+        let repair = make_solvable(board, start, MAX_FIXES)?;
+        let fixes = repair
+            .fixes
+            .iter()
+            .map(|fix| (fix.point(), matches!(fix, Fix::Added(_))))
+            .collect();
+        Some((repair.board, fixes))
+    }
+
+    #[cfg(not(feature = "robot"))]
+    pub fn repair(&self, _board: &Board, _start: &Point) -> Option<(Board, Vec<(Point, bool)>)> {
+        None
+    }
+}
+
+#[cfg(feature = "robot")]
+fn step(deduction: Deduction) -> Step {
+    Step {
+        m: deduction.m,
+        source: deduction.source,
+        constrained: [deduction.closed.clone(), deduction.flagged.clone()].concat(),
+        reason: deduction.to_string(),
+    }
+}
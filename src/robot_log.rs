@@ -0,0 +1,76 @@
+use lib_minesweeper::{Move, Point};
+
+use crate::robot::Step;
+
+// A move 🤖 or an assist played for the player, and the number that proved
+// it, if a single one did.
+pub struct Entry {
+    pub by: &'static str,
+    pub m: Move,
+    pub step: Option<Step>,
+}
+
+impl Entry {
+    pub fn text(&self) -> String {
+        let what = match self.m {
+            Move::Open(p) => format!("opened {}", cell(&p)),
+            Move::Flag(p) => format!("flagged {}", cell(&p)),
+            Move::OpenAllUnflagged => String::from("opened every unflagged cell"),
+            Move::Chord(p) => format!("chorded {}", cell(&p)),
+        };
+        let why = match &self.step {
+            Some(step) => step.reason.clone(),
+            None => String::from("No single number proves it."),
+        };
+        format!("{} {}. {}", self.by, what, why)
+    }
+
+    // The move's cell first, as a cell only takes the first of its marks.
+    fn marks(&self) -> Vec<(Point, &'static str)> {
+        let mut marks = vec![];
+        if let Move::Open(p) | Move::Flag(p) = self.m {
+            marks.push((p, "lesson-move"));
+        }
+        if let Some(step) = &self.step {
+            marks.push((step.source, "lesson-source"));
+            marks.extend(step.constrained.iter().map(|p| (*p, "lesson-constrained")));
+        }
+        marks
+    }
+}
+
+fn cell(p: &Point) -> String {
+    format!("column {}, row {}", p.x + 1, p.y + 1)
+}
+
+// The moves played for the player this game, oldest first. Hovering one
+// marks the cells its reasoning is about.
+#[derive(Default)]
+pub struct RobotLog {
+    entries: Vec<Entry>,
+    hovered: Option<usize>,
+}
+
+impl RobotLog {
+    pub fn push(&mut self, entry: Entry) {
+        self.entries.push(entry);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Entry> {
+        self.entries.iter()
+    }
+
+    pub fn hover(&mut self, index: Option<usize>) {
+        self.hovered = index;
+    }
+
+    pub fn marks(&self) -> Vec<(Point, &'static str)> {
+        self.hovered
+            .and_then(|index| self.entries.get(index))
+            .map_or(vec![], Entry::marks)
+    }
+}
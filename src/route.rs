@@ -0,0 +1,238 @@
+use lib_minesweeper::Pattern;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use yew::Callback;
+
+// Where the player is in the app, as the part of the URL after the #, so
+// screens can be linked to and the back button moves between them.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Route {
+    // A game on the difficulty with this name, e.g. "#/play/hard".
+    Play(String),
+    Daily,
+    History,
+    // A game from the history, by its index there.
+    Replay(usize),
+    Profiles,
+    Featured,
+    // With a code to join, say from a link another racer sent.
+    Race(Option<String>),
+    Duel,
+    Broadcast,
+    Import,
+    Print,
+    Trainer,
+    // The tutorial, drills and intro levels to pick from.
+    Learn,
+    // The pattern reference, showing one pattern's examples first.
+    Patterns(Option<Pattern>),
+    // The analysis board, on the position it was opened with.
+    Analysis,
+    // The performance check, for a board this wide, high and mined. Not
+    // linked from anywhere.
+    Stress(Option<(usize, usize, usize)>),
+    Keys,
+}
+
+impl Route {
+    // None for anything else, including no route at all.
+    pub fn parse(hash: &str) -> Option<Route> {
+        let path = hash.trim_start_matches('#').trim_start_matches('/');
+        let parts: Vec<&str> = path.split('/').collect();
+        Some(match parts[..] {
+            ["play", name] if !name.is_empty() => Route::Play(name.to_string()),
+            ["daily"] => Route::Daily,
+            ["history"] => Route::History,
+            ["replay", index] => Route::Replay(index.parse().ok()?),
+            ["profiles"] => Route::Profiles,
+            ["featured"] => Route::Featured,
+            ["race"] => Route::Race(None),
+            ["race", code] => Route::Race(Some(code.to_string())),
+            ["duel"] => Route::Duel,
+            ["broadcast"] => Route::Broadcast,
+            ["import"] => Route::Import,
+            ["print"] => Route::Print,
+            ["trainer"] => Route::Trainer,
+            ["learn"] => Route::Learn,
+            ["patterns"] => Route::Patterns(None),
+            ["patterns", key] => Route::Patterns(Some(Pattern::from_key(key)?)),
+            ["analysis"] => Route::Analysis,
+            ["keys"] => Route::Keys,
+            ["stress"] => Route::Stress(None),
+            ["stress", size, mines] => {
+                let (width, height) = size.split_once('x')?;
+                Route::Stress(Some((
+                    width.parse().ok()?,
+                    height.parse().ok()?,
+                    mines.parse().ok()?,
+                )))
+            }
+            _ => return None,
+        })
+    }
+
+    pub fn to_hash(&self) -> String {
+        match self {
+            Route::Play(name) => format!("#/play/{}", slug(name)),
+            Route::Daily => String::from("#/daily"),
+            Route::History => String::from("#/history"),
+            Route::Replay(index) => format!("#/replay/{}", index),
+            Route::Profiles => String::from("#/profiles"),
+            Route::Featured => String::from("#/featured"),
+            Route::Race(None) => String::from("#/race"),
+            Route::Race(Some(code)) => format!("#/race/{}", code),
+            Route::Duel => String::from("#/duel"),
+            Route::Broadcast => String::from("#/broadcast"),
+            Route::Import => String::from("#/import"),
+            Route::Print => String::from("#/print"),
+            Route::Trainer => String::from("#/trainer"),
+            Route::Learn => String::from("#/learn"),
+            Route::Patterns(None) => String::from("#/patterns"),
+            Route::Patterns(Some(pattern)) => format!("#/patterns/{}", pattern.key()),
+            Route::Analysis => String::from("#/analysis"),
+            Route::Keys => String::from("#/keys"),
+            Route::Stress(None) => String::from("#/stress"),
+            Route::Stress(Some((width, height, mines))) => {
+                format!("#/stress/{}x{}/{}", width, height, mines)
+            }
+        }
+    }
+}
+
+// "Very Hard" as it goes in a URL: "very-hard". Difficulties are matched on
+// this, so links don't depend on case.
+pub fn slug(name: &str) -> String {
+    name.split_whitespace()
+        .collect::<Vec<_>>()
+        .join("-")
+        .to_lowercase()
+}
+
+// The URL's current #, decoded. Empty if it has none.
+pub fn current() -> String {
+    let hash = web_sys::window()
+        .and_then(|w| w.location().hash().ok())
+        .unwrap_or_default();
+    js_sys::decode_uri_component(&hash)
+        .ok()
+        .and_then(|h| h.as_string())
+        .unwrap_or(hash)
+}
+
+// Moves to `route` as a new step the back button can return from, unless
+// it's where the URL already is.
+pub fn push(route: &Route) {
+    set(route, false)
+}
+
+// Moves to `route` in place of the current step.
+pub fn replace(route: &Route) {
+    set(route, true)
+}
+
+fn set(route: &Route, replace: bool) {
+    let hash = route.to_hash();
+    if current() == hash {
+        return;
+    }
+    let history = match web_sys::window().and_then(|w| w.history().ok()) {
+        Some(history) => history,
+        None => return,
+    };
+    let _ = if replace {
+        history.replace_state_with_url(&JsValue::NULL, "", Some(&hash))
+    } else {
+        history.push_state_with_url(&JsValue::NULL, "", Some(&hash))
+    };
+}
+
+// Tells `callback` the new # when the player goes back or forward, or
+// follows a link within the app.
+pub fn on_change(callback: Callback<String>) {
+    let window = match web_sys::window() {
+        Some(window) => window,
+        None => return,
+    };
+    let on_change = Closure::wrap(Box::new(move || callback.emit(current())) as Box<dyn FnMut()>);
+    window.set_onpopstate(Some(on_change.as_ref().unchecked_ref()));
+    // Listens for as long as the page is open.
+    on_change.forget();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_round_trip() {
+        let routes = vec![
+            Route::Play(String::from("hard")),
+            Route::Daily,
+            Route::History,
+            Route::Replay(12),
+            Route::Profiles,
+            Route::Featured,
+            Route::Race(None),
+            Route::Race(Some(String::from("5-k3j9x2"))),
+            Route::Duel,
+            Route::Broadcast,
+            Route::Import,
+            Route::Print,
+            Route::Trainer,
+            Route::Learn,
+            Route::Patterns(None),
+            Route::Patterns(Some(Pattern::Subset)),
+            Route::Analysis,
+            Route::Keys,
+            Route::Stress(None),
+            Route::Stress(Some((1000, 800, 150_000))),
+        ];
+        for route in routes {
+            assert_eq!(Route::parse(&route.to_hash()), Some(route));
+        }
+    }
+
+    #[test]
+    fn test_play_uses_the_slug() {
+        let route = Route::Play(String::from("Very  Hard"));
+        assert_eq!(route.to_hash(), "#/play/very-hard");
+        assert_eq!(
+            Route::parse(&route.to_hash()),
+            Some(Route::Play(String::from("very-hard")))
+        );
+    }
+
+    #[test]
+    fn test_parse_leniently() {
+        assert_eq!(Route::parse("daily"), Some(Route::Daily));
+        assert_eq!(Route::parse("#daily"), Some(Route::Daily));
+        assert_eq!(Route::parse("#//daily"), Some(Route::Daily));
+    }
+
+    #[test]
+    fn test_parse_malformed() {
+        let hashes = [
+            "",
+            "#",
+            "#/",
+            "#/nowhere",
+            "#/daily/",
+            "#/play",
+            "#/play/",
+            "#/replay",
+            "#/replay/first",
+            "#/replay/-1",
+            "#/race/a/b",
+            "#/patterns/nothing",
+            "#/stress/100",
+            "#/stress/100/10",
+            "#/stress/100x/10",
+            "#/stress/100x100/many",
+            "#/stress/100x100x100/10",
+        ];
+        for hash in &hashes {
+            assert_eq!(Route::parse(hash), None, "{}", hash);
+        }
+    }
+}
@@ -0,0 +1,136 @@
+use serde_derive::{Deserialize, Serialize};
+
+use crate::storage::{restore_json, store_json, Migration, StorageBackend};
+
+// Where the session is kept when the page is hidden, for the recap on the
+// next visit.
+pub const KEY: &str = "jgpaiva.minesweeper.session";
+
+// Add one when Session or SessionEvent change shape.
+const MIGRATIONS: &[Migration] = &[];
+
+// How many games the recap can be set to come up after. 0 is never.
+pub const RECAP_EVERY: [u32; 4] = [0, 5, 10, 20];
+
+// Something that happened since the page was opened. The recap only looks
+// at these, so lifetime stats never leak into it.
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub enum SessionEvent {
+    // A saved game, with its difficulty's emoji.
+    Finished {
+        emoji: String,
+        won: bool,
+        duration_ms: f64,
+    },
+    BestTime {
+        emoji: String,
+    },
+    DailyWon,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct Session {
+    events: Vec<SessionEvent>,
+}
+
+impl Session {
+    // The session the page was left with, if it played any games. It's
+    // taken out of storage so it's only recapped once.
+    pub fn take_last(storage: &mut dyn StorageBackend) -> Option<Session> {
+        let session = restore_json::<Session>(storage, KEY, MIGRATIONS);
+        store_json(storage, KEY, MIGRATIONS, &Session::default());
+        session.filter(|s| s.games() > 0)
+    }
+
+    pub fn store(&self, storage: &mut dyn StorageBackend) {
+        store_json(storage, KEY, MIGRATIONS, self)
+    }
+
+    pub fn push(&mut self, event: SessionEvent) {
+        self.events.push(event);
+    }
+
+    pub fn games(&self) -> usize {
+        self.finished().count()
+    }
+
+    pub fn won(&self) -> usize {
+        self.finished().filter(|(won, _)| *won).count()
+    }
+
+    // As a percentage, 0 with no games.
+    pub fn win_rate(&self) -> f64 {
+        match self.games() {
+            0 => 0_f64,
+            games => self.won() as f64 * 100_f64 / games as f64,
+        }
+    }
+
+    pub fn time_played_ms(&self) -> f64 {
+        self.finished().map(|(_, ms)| ms).sum()
+    }
+
+    // The most games won in a row.
+    pub fn best_streak(&self) -> usize {
+        self.finished()
+            .fold((0, 0), |(streak, best), (won, _)| {
+                let streak = if won { streak + 1 } else { 0 };
+                (streak, best.max(streak))
+            })
+            .1
+    }
+
+    // The best times set and dailies won, one line each, in the order they
+    // happened.
+    pub fn notable(&self) -> Vec<String> {
+        self.events
+            .iter()
+            .filter_map(|e| match e {
+                SessionEvent::BestTime { emoji } => Some(format!("🏁 New best time on {}", emoji)),
+                SessionEvent::DailyWon => Some(String::from("📅 Won the daily board")),
+                SessionEvent::Finished { .. } => None,
+            })
+            .collect()
+    }
+
+    // A few lines for pasting somewhere, e.g.
+    //
+    //     💣 Minesweeper session: 12 games, 75% won
+    //     ⏲️ 18m 04s played · 🔥 best streak 5
+    //     🏁 New best time on 🐣
+    pub fn share_text(&self) -> String {
+        let mut lines = vec![
+            format!(
+                "💣 Minesweeper session: {} games, {:.0}% won",
+                self.games(),
+                self.win_rate()
+            ),
+            format!(
+                "⏲️ {} played · 🔥 best streak {}",
+                format_duration(self.time_played_ms()),
+                self.best_streak()
+            ),
+        ];
+        lines.extend(self.notable());
+        lines.join("\n")
+    }
+
+    fn finished(&self) -> impl Iterator<Item = (bool, f64)> + '_ {
+        self.events.iter().filter_map(|e| match e {
+            SessionEvent::Finished {
+                won, duration_ms, ..
+            } => Some((*won, *duration_ms)),
+            _ => None,
+        })
+    }
+}
+
+// "1h 02m", "18m 04s" or "42s".
+fn format_duration(ms: f64) -> String {
+    let seconds = (ms / 1000_f64) as u64;
+    match (seconds / 3600, seconds / 60 % 60, seconds % 60) {
+        (0, 0, s) => format!("{}s", s),
+        (0, m, s) => format!("{}m {:02}s", m, s),
+        (h, m, _) => format!("{}h {:02}m", h, m),
+    }
+}
@@ -0,0 +1,126 @@
+use serde_derive::{Deserialize, Serialize};
+use web_sys::{AudioContext, OscillatorType};
+
+// How loud the game is, and which of its sounds play. Both kinds start off,
+// so nobody's surprised by a noise they didn't ask for.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct Audio {
+    // From 0 to 100.
+    pub volume: u8,
+    // Short sounds for opening, flagging and hitting a mine.
+    pub effects: bool,
+    // A few notes when a game is won or lost.
+    pub jingles: bool,
+}
+
+impl Default for Audio {
+    fn default() -> Audio {
+        Audio {
+            volume: 50,
+            effects: false,
+            jingles: false,
+        }
+    }
+}
+
+impl Audio {
+    pub fn is_silent(self) -> bool {
+        self.volume == 0 || !(self.effects || self.jingles)
+    }
+
+    fn plays(self, sound: Sound) -> bool {
+        self.volume > 0
+            && match sound {
+                Sound::Open | Sound::Flag | Sound::Explode => self.effects,
+                Sound::Won | Sound::Lost => self.jingles,
+            }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Sound {
+    Open,
+    Flag,
+    Explode,
+    Won,
+    Lost,
+}
+
+impl Sound {
+    // Each note's pitch in Hz and length in seconds, one after the other.
+    fn notes(self) -> &'static [(f32, f64)] {
+        match self {
+            Sound::Open => &[(660_f32, 0.05)],
+            Sound::Flag => &[(440_f32, 0.07)],
+            Sound::Explode => &[(110_f32, 0.3)],
+            Sound::Won => &[
+                (523_f32, 0.12),
+                (659_f32, 0.12),
+                (784_f32, 0.12),
+                (1047_f32, 0.3),
+            ],
+            Sound::Lost => &[(392_f32, 0.18), (330_f32, 0.18), (262_f32, 0.4)],
+        }
+    }
+
+    fn wave(self) -> OscillatorType {
+        match self {
+            Sound::Explode => OscillatorType::Sawtooth,
+            Sound::Won | Sound::Lost => OscillatorType::Triangle,
+            _ => OscillatorType::Sine,
+        }
+    }
+}
+
+// Synthesises the sounds with Web Audio, so there are no files to load.
+// Browsers only let audio start after the player has done something, so the
+// context is made on the first sound rather than up front.
+#[derive(Default)]
+pub struct Speaker {
+    context: Option<AudioContext>,
+}
+
+impl Speaker {
+    pub fn play(&mut self, sound: Sound, audio: Audio) {
+        if !audio.plays(sound) {
+            return;
+        }
+        if self.context.is_none() {
+            self.context = AudioContext::new().ok();
+        }
+        let context = match &self.context {
+            Some(context) => context,
+            None => return,
+        };
+        let _ = context.resume();
+        // Halved, as full volume is harsh for tones this plain.
+        let volume = f32::from(audio.volume) / 200_f32;
+        let mut at = context.current_time();
+        for (pitch, length) in sound.notes() {
+            let _ = note(context, sound.wave(), *pitch, volume, at, *length);
+            at += length;
+        }
+    }
+}
+
+// Fades out over its length, which keeps the notes from clicking.
+fn note(
+    context: &AudioContext,
+    wave: OscillatorType,
+    pitch: f32,
+    volume: f32,
+    at: f64,
+    length: f64,
+) -> Result<(), wasm_bindgen::JsValue> {
+    let oscillator = context.create_oscillator()?;
+    let gain = context.create_gain()?;
+    oscillator.set_type(wave);
+    oscillator.frequency().set_value_at_time(pitch, at)?;
+    gain.gain().set_value_at_time(volume, at)?;
+    gain.gain()
+        .exponential_ramp_to_value_at_time(0.001, at + length)?;
+    oscillator.connect_with_audio_node(&gain)?;
+    gain.connect_with_audio_node(&context.destination())?;
+    oscillator.start_with_when(at)?;
+    oscillator.stop_with_when(at + length)
+}
@@ -0,0 +1,181 @@
+use std::collections::HashMap;
+
+use lib_minesweeper::Board;
+use lib_minesweeper::Symmetry;
+use serde_derive::{Deserialize, Serialize};
+
+use crate::presets::DifficultyPreset;
+use crate::race::Finish;
+use crate::stopwatch;
+use crate::storage::{restore_json, store_json, Migration, StorageBackend};
+
+pub const KEY: &str = "jgpaiva.minesweeper.speed_drill";
+
+// For changes to SpeedRecords.
+const MIGRATIONS: &[Migration] = &[];
+
+// How many boards a drill can be, in the order 🔢 goes through them.
+pub const LENGTHS: [usize; 3] = [5, 10, 20];
+
+// Wins the rolling average is over.
+const ROLLING: usize = 5;
+
+// A new board of the small size every drill plays, so times compare from
+// one drill to the next.
+pub fn board() -> Board {
+    let preset = DifficultyPreset {
+        name: String::from("Speed drill"),
+        emoji: String::from("🏃"),
+        ..DifficultyPreset::custom(8, 8, 10, Symmetry::None, None)
+    };
+    preset.board(None).unwrap()
+}
+
+// Small boards one after another against a clock that only runs while
+// they're played. Lost boards count towards the time but not the average.
+pub struct SpeedDrill {
+    pub length: usize,
+    finishes: Vec<Finish>,
+}
+
+impl SpeedDrill {
+    pub fn new(length: usize) -> SpeedDrill {
+        SpeedDrill {
+            length,
+            finishes: vec![],
+        }
+    }
+
+    pub fn finish(&mut self, finish: Finish) {
+        if !self.is_over() {
+            self.finishes.push(finish);
+        }
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.finishes.len() >= self.length
+    }
+
+    pub fn won(&self) -> usize {
+        self.finishes.iter().filter(|f| f.won).count()
+    }
+
+    pub fn total_ms(&self) -> f64 {
+        self.finishes.iter().map(|f| f.ms).sum()
+    }
+
+    // Over the last ROLLING boards won, or as many as there are.
+    pub fn rolling_ms(&self) -> Option<f64> {
+        let wins: Vec<f64> = self
+            .finishes
+            .iter()
+            .rev()
+            .filter(|f| f.won)
+            .take(ROLLING)
+            .map(|f| f.ms)
+            .collect();
+        match wins.len() {
+            0 => None,
+            count => Some(wins.iter().sum::<f64>() / count as f64),
+        }
+    }
+
+    // "🏃 3/10 · 41.203s · ⌀ 13.734s"
+    pub fn status(&self) -> String {
+        let mut status = format!(
+            "🏃 {}/{} · {}s",
+            self.finishes.len(),
+            self.length,
+            stopwatch::seconds(self.total_ms())
+        );
+        if let Some(ms) = self.rolling_ms() {
+            status.push_str(&format!(" · ⌀ {}s", stopwatch::seconds(ms)));
+        }
+        status
+    }
+
+    // Only a drill with every board won can set the best time for its
+    // length.
+    fn is_clean(&self) -> bool {
+        self.is_over() && self.won() == self.length
+    }
+
+    // Whether the rolling average is over a full ROLLING wins.
+    fn has_full_average(&self) -> bool {
+        self.won() >= ROLLING
+    }
+}
+
+// The best drills, apart from the difficulties' best times: the fastest
+// total for each length, and the best rolling average ever reached.
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct SpeedRecords {
+    // The length last picked, for the next drill.
+    #[serde(default)]
+    length: Option<usize>,
+    #[serde(default)]
+    pub best_ms: HashMap<usize, f64>,
+    #[serde(default)]
+    pub best_average_ms: Option<f64>,
+}
+
+impl SpeedRecords {
+    pub fn restore(storage: &dyn StorageBackend) -> SpeedRecords {
+        restore_json(storage, KEY, MIGRATIONS).unwrap_or_default()
+    }
+
+    pub fn store(&self, storage: &mut dyn StorageBackend) {
+        store_json(storage, KEY, MIGRATIONS, self)
+    }
+
+    pub fn length(&self) -> usize {
+        self.length
+            .filter(|l| LENGTHS.contains(l))
+            .unwrap_or(LENGTHS[1])
+    }
+
+    // Picks the next of LENGTHS, and returns it.
+    pub fn next_length(&mut self) -> usize {
+        let index = LENGTHS.iter().position(|l| *l == self.length());
+        let length = LENGTHS[index.map_or(0, |i| (i + 1) % LENGTHS.len())];
+        self.length = Some(length);
+        length
+    }
+
+    // Keeps whatever `drill` beat after its latest board, and returns
+    // whether it beat anything.
+    pub fn record(&mut self, drill: &SpeedDrill) -> bool {
+        let mut beaten = false;
+        if drill.has_full_average() {
+            let average = drill.rolling_ms().unwrap();
+            if self.best_average_ms.is_none_or(|best| average < best) {
+                self.best_average_ms = Some(average);
+                beaten = true;
+            }
+        }
+        if drill.is_clean() {
+            let total = drill.total_ms();
+            let best = self.best_ms.entry(drill.length).or_insert(f64::INFINITY);
+            if total < *best {
+                *best = total;
+                beaten = true;
+            }
+        }
+        beaten
+    }
+
+    // "🏁 best 52.310s · ⌀ 4.870s", or empty before any.
+    pub fn describe(&self, length: usize) -> String {
+        let mut parts = vec![];
+        if let Some(ms) = self.best_ms.get(&length) {
+            parts.push(format!("best {}s", stopwatch::seconds(*ms)));
+        }
+        if let Some(ms) = self.best_average_ms {
+            parts.push(format!("⌀ {}s", stopwatch::seconds(ms)));
+        }
+        if parts.is_empty() {
+            return String::new();
+        }
+        format!("🏁 {}", parts.join(" · "))
+    }
+}
@@ -0,0 +1,141 @@
+use js_sys::Date;
+use lib_minesweeper::Timing;
+use serde_derive::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use yew::Callback;
+
+// How far through the board, as a share of its safe cells, each split is
+// taken.
+pub const SPLITS: [f64; 3] = [0.25, 0.5, 0.75];
+
+// Milliseconds, with the fractions `performance.now()` gives. Only good for
+// measuring time between two calls: it counts from when the page loaded,
+// not from the epoch.
+pub fn now() -> f64 {
+    web_sys::window()
+        .and_then(|w| w.performance())
+        .map_or_else(Date::now, |p| p.now())
+}
+
+// Tells `callback` whether the page is hidden whenever that changes, e.g.
+// when the player switches tabs.
+pub fn on_hidden_change(callback: Callback<bool>) {
+    let document = match web_sys::window().and_then(|w| w.document()) {
+        Some(document) => document,
+        None => return,
+    };
+    let hidden = document.clone();
+    let on_change =
+        Closure::wrap(Box::new(move || callback.emit(hidden.hidden())) as Box<dyn FnMut()>);
+    document.set_onvisibilitychange(Some(on_change.as_ref().unchecked_ref()));
+    // Listens for as long as the page is open.
+    on_change.forget();
+}
+
+// The same moment of a game on both clocks, in milliseconds from its start.
+#[derive(Serialize, Deserialize, Clone, Copy, Default, PartialEq)]
+pub struct Times {
+    pub real_ms: f64,
+    pub game_ms: f64,
+}
+
+impl Times {
+    pub fn on(&self, timing: Timing) -> f64 {
+        match timing {
+            Timing::RealTime => self.real_ms,
+            Timing::GameTime => self.game_ms,
+        }
+    }
+}
+
+// Times a game from its first move, taking a split the first time it gets
+// past each of SPLITS. Keeps real time and game time side by side: game
+// time leaves out the pauses.
+#[derive(Clone, Default, PartialEq)]
+pub struct Stopwatch {
+    pub started_at: Option<f64>,
+    pub stopped_at: Option<f64>,
+    // The pauses that are over, and when the one going on began.
+    paused_ms: f64,
+    paused_at: Option<f64>,
+    pub splits: Vec<Times>,
+}
+
+impl Stopwatch {
+    pub fn start(&mut self) {
+        *self = Stopwatch {
+            started_at: Some(now()),
+            ..Stopwatch::default()
+        };
+    }
+
+    pub fn stop(&mut self) {
+        if self.started_at.is_some() && self.stopped_at.is_none() {
+            self.stopped_at = Some(now());
+        }
+    }
+
+    // Pauses or resumes game time. Real time keeps going.
+    pub fn pause(&mut self, paused: bool) {
+        if self.started_at.is_none() || self.stopped_at.is_some() {
+            return;
+        }
+        match (paused, self.paused_at) {
+            (true, None) => self.paused_at = Some(now()),
+            (false, Some(paused_at)) => {
+                self.paused_ms += now() - paused_at;
+                self.paused_at = None;
+            }
+            _ => {}
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused_at.is_some()
+    }
+
+    pub fn elapsed(&self) -> Times {
+        let started_at = match self.started_at {
+            Some(started_at) => started_at,
+            None => return Times::default(),
+        };
+        let end = self.stopped_at.unwrap_or_else(now);
+        let pausing = self.paused_at.map_or(0_f64, |paused_at| end - paused_at);
+        Times {
+            real_ms: end - started_at,
+            game_ms: end - started_at - self.paused_ms - pausing,
+        }
+    }
+
+    // Adds `ms` to both clocks, as if the game had started that much
+    // earlier.
+    pub fn penalize(&mut self, ms: f64) {
+        if self.stopped_at.is_none() {
+            if let Some(started_at) = &mut self.started_at {
+                *started_at -= ms;
+            }
+        }
+    }
+
+    // Takes the splits `progress` has just got past.
+    pub fn reach(&mut self, progress: f64) {
+        if self.started_at.is_none() || self.stopped_at.is_some() {
+            return;
+        }
+        let elapsed = self.elapsed();
+        while self.splits.len() < SPLITS.len() && progress >= SPLITS[self.splits.len()] {
+            self.splits.push(elapsed);
+        }
+    }
+}
+
+// "12.345", in seconds.
+pub fn seconds(ms: f64) -> String {
+    format!("{:.3}", ms / 1000_f64)
+}
+
+// "+0.250" or "-1.500", in seconds: how far behind or ahead of `best`.
+pub fn delta(ms: f64, best: f64) -> String {
+    format!("{:+.3}", (ms - best) / 1000_f64)
+}
@@ -0,0 +1,275 @@
+use std::collections::HashMap;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_derive::Deserialize;
+use serde_json::Value;
+use yew::callback::Callback;
+use yew::format::{Nothing, Text};
+use yew::services::fetch::{FetchService, FetchTask, Request, Response};
+use yew::services::storage::{Area, StorageService};
+use yew::services::Task;
+
+// Where the stats and saves subsystems keep their data. Values are opaque
+// strings (usually JSON) under fixed keys.
+pub trait StorageBackend {
+    fn store(&mut self, key: &str, value: String);
+    fn restore(&self, key: &str) -> Option<String>;
+
+    // Asks a backend that syncs with somewhere else for the latest value of
+    // `key`, which arrives through `callback` as (key, value). Backends that
+    // only keep data locally have nothing to fetch.
+    fn pull(&mut self, _key: &str, _callback: Callback<(String, String)>) {}
+
+    // Takes a value that came back from `pull`, without sending it out again.
+    fn accept_pulled(&mut self, key: &str, value: String) {
+        self.store(key, value)
+    }
+}
+
+// Turns data saved at one version of its format into the next version.
+pub type Migration = fn(Value) -> Value;
+
+// Saved values are wrapped as {"version": N, "data": ...}, where N is the
+// number of migrations the caller knows about. Values saved before versioning
+// are the bare data and count as version 0.
+#[derive(Serialize)]
+struct Envelope<'a, T> {
+    version: usize,
+    data: &'a T,
+}
+
+#[derive(Deserialize)]
+struct SavedEnvelope {
+    version: usize,
+    data: Value,
+}
+
+pub fn store_json<T: Serialize>(
+    backend: &mut dyn StorageBackend,
+    key: &str,
+    migrations: &[Migration],
+    value: &T,
+) {
+    let envelope = Envelope {
+        version: migrations.len(),
+        data: value,
+    };
+    if let Ok(value) = serde_json::to_string(&envelope) {
+        backend.store(key, value);
+    }
+}
+
+// Runs the migrations the saved value hasn't been through yet. Values saved by
+// a newer version of the app are tried as they are.
+pub fn restore_json<T: DeserializeOwned>(
+    backend: &dyn StorageBackend,
+    key: &str,
+    migrations: &[Migration],
+) -> Option<T> {
+    let saved: Value = serde_json::from_str(&backend.restore(key)?).ok()?;
+    let (version, data) = match serde_json::from_value::<SavedEnvelope>(saved.clone()) {
+        Ok(envelope) => (envelope.version, envelope.data),
+        Err(_) => (0, saved),
+    };
+    let data = migrations
+        .iter()
+        .skip(version)
+        .fold(data, |data, migrate| migrate(data));
+    serde_json::from_value(data).ok()
+}
+
+pub struct LocalStorage {
+    storage: StorageService,
+}
+
+impl LocalStorage {
+    pub fn new() -> Option<LocalStorage> {
+        let storage = StorageService::new(Area::Local).ok()?;
+        Some(LocalStorage { storage })
+    }
+}
+
+impl StorageBackend for LocalStorage {
+    fn store(&mut self, key: &str, value: String) {
+        let text: Text = Ok(value);
+        self.storage.store(key, text)
+    }
+
+    fn restore(&self, key: &str) -> Option<String> {
+        let text: Text = self.storage.restore(key);
+        text.ok()
+    }
+}
+
+// Used when the browser won't give us local storage: the game still works,
+// but nothing outlives the page.
+#[derive(Default)]
+pub struct InMemory {
+    values: HashMap<String, String>,
+}
+
+impl StorageBackend for InMemory {
+    fn store(&mut self, key: &str, value: String) {
+        self.values.insert(key.to_string(), value);
+    }
+
+    fn restore(&self, key: &str) -> Option<String> {
+        self.values.get(key).cloned()
+    }
+}
+
+// Keeps a local copy and mirrors every write to `{endpoint}/{key}` with a
+// PUT. Pulling GETs the same URL; whichever device wrote last wins.
+pub struct RemoteHttp {
+    endpoint: String,
+    cache: Box<dyn StorageBackend>,
+    fetch: FetchService,
+    tasks: Vec<FetchTask>,
+}
+
+impl RemoteHttp {
+    pub fn new(endpoint: String, cache: Box<dyn StorageBackend>) -> RemoteHttp {
+        RemoteHttp {
+            endpoint: endpoint.trim_end_matches('/').to_string(),
+            cache,
+            fetch: FetchService::new(),
+            tasks: vec![],
+        }
+    }
+
+    fn url(&self, key: &str) -> String {
+        format!("{}/{}", self.endpoint, key)
+    }
+
+    fn keep(&mut self, task: FetchTask) {
+        self.tasks.retain(|t| t.is_active());
+        self.tasks.push(task);
+    }
+}
+
+impl StorageBackend for RemoteHttp {
+    fn store(&mut self, key: &str, value: String) {
+        let request = Request::put(self.url(key))
+            .header("Content-Type", "application/json")
+            .body(Ok(value.clone()));
+        self.cache.store(key, value);
+        if let Ok(request) = request {
+            let callback = Callback::from(|_: Response<Text>| ());
+            if let Ok(task) = self.fetch.fetch(request, callback) {
+                self.keep(task);
+            }
+        }
+    }
+
+    fn restore(&self, key: &str) -> Option<String> {
+        self.cache.restore(key)
+    }
+
+    fn pull(&mut self, key: &str, callback: Callback<(String, String)>) {
+        let request = match Request::get(self.url(key)).body(Nothing) {
+            Ok(request) => request,
+            Err(_) => return,
+        };
+        let key = key.to_string();
+        let on_response = Callback::from(move |response: Response<Text>| {
+            let (meta, body) = response.into_parts();
+            if let (true, Ok(body)) = (meta.status.is_success(), body) {
+                callback.emit((key.clone(), body));
+            }
+        });
+        if let Ok(task) = self.fetch.fetch(request, on_response) {
+            self.keep(task);
+        }
+    }
+
+    fn accept_pulled(&mut self, key: &str, value: String) {
+        self.cache.store(key, value)
+    }
+}
+
+pub fn default_backend() -> Box<dyn StorageBackend> {
+    let local: Box<dyn StorageBackend> = match LocalStorage::new() {
+        Some(local) => Box::new(local),
+        None => Box::new(InMemory::default()),
+    };
+    // Self-hosters enable syncing by adding
+    // <meta name="minesweeper-sync-endpoint" content="https://..."> to index.html.
+    match crate::page_setting("minesweeper-sync-endpoint") {
+        Some(endpoint) => Box::new(RemoteHttp::new(endpoint, local)),
+        None => local,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    // Counts the migrations a value went through.
+    fn bump(mut data: Value) -> Value {
+        let seen = data["migrated"].as_u64().unwrap_or(0);
+        data["migrated"] = Value::from(seen + 1);
+        data
+    }
+
+    const MIGRATIONS: &[Migration] = &[bump, bump];
+
+    fn saved(text: &str) -> InMemory {
+        let mut storage = InMemory::default();
+        storage.store("key", text.to_string());
+        storage
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut storage = InMemory::default();
+        store_json(&mut storage, "key", MIGRATIONS, &json!({"migrated": 0}));
+        assert_eq!(
+            storage.restore("key"),
+            Some(String::from(r#"{"version":2,"data":{"migrated":0}}"#))
+        );
+        let restored: Option<Value> = restore_json(&storage, "key", MIGRATIONS);
+        assert_eq!(restored, Some(json!({"migrated": 0})));
+    }
+
+    #[test]
+    fn test_restore_runs_missing_migrations() {
+        let storage = saved(r#"{"version":1,"data":{"migrated":0}}"#);
+        let restored: Option<Value> = restore_json(&storage, "key", MIGRATIONS);
+        assert_eq!(restored, Some(json!({"migrated": 1})));
+    }
+
+    #[test]
+    fn test_restore_bare_data_as_version_0() {
+        let storage = saved(r#"{"migrated":0}"#);
+        let restored: Option<Value> = restore_json(&storage, "key", MIGRATIONS);
+        assert_eq!(restored, Some(json!({"migrated": 2})));
+    }
+
+    #[test]
+    fn test_restore_newer_version_as_it_is() {
+        let storage = saved(r#"{"version":5,"data":{"migrated":0}}"#);
+        let restored: Option<Value> = restore_json(&storage, "key", MIGRATIONS);
+        assert_eq!(restored, Some(json!({"migrated": 0})));
+    }
+
+    #[test]
+    fn test_restore_bad_values() {
+        let restored: Option<Value> = restore_json(&InMemory::default(), "key", MIGRATIONS);
+        assert_eq!(restored, None);
+        let restored: Option<Value> = restore_json(&saved("{"), "key", MIGRATIONS);
+        assert_eq!(restored, None);
+        // An envelope with the wrong data for the type.
+        let storage = saved(r#"{"version":2,"data":"text"}"#);
+        assert_eq!(restore_json::<Vec<u32>>(&storage, "key", MIGRATIONS), None);
+        // Something that isn't an envelope is taken as bare data.
+        let storage = saved(r#"{"version":"one","data":[1]}"#);
+        let restored: Option<Value> = restore_json(&storage, "key", MIGRATIONS);
+        assert_eq!(
+            restored,
+            Some(json!({"version": "one", "data": [1], "migrated": 2}))
+        );
+    }
+}
@@ -0,0 +1,148 @@
+use lib_minesweeper::Adjacency;
+use lib_minesweeper::Board;
+use lib_minesweeper::BoardBuilder;
+use lib_minesweeper::Move;
+
+use crate::robot::Robot;
+use crate::stopwatch;
+
+// The board the page starts with: big enough that a slow change shows.
+const WIDTH: usize = 200;
+const HEIGHT: usize = 200;
+const MINES: usize = 6000;
+
+// How long each part of a run took, in milliseconds. The render is timed
+// once the board's been drawn, and the solver only with 🤖 in the build.
+pub struct Timings {
+    pub generation_ms: f64,
+    pub cascade_ms: f64,
+    pub opened: usize,
+    pub render_ms: Option<f64>,
+    pub solver_ms: Option<f64>,
+}
+
+// The hidden #/stress page: builds a giant board, opens it and draws it,
+// timing each step, to catch the game getting slower on big boards. The
+// size is typed in, or comes from the URL as #/stress/300x300/13500.
+pub struct Stress {
+    pub width: String,
+    pub height: String,
+    pub mines: String,
+    pub board: Option<Board>,
+    pub timings: Option<Timings>,
+    pub error: Option<String>,
+    // The size of the board last built, for the URL.
+    pub ran: Option<(usize, usize, usize)>,
+    // When the board was handed over to be drawn, while it's being drawn.
+    render_from: Option<f64>,
+}
+
+impl Default for Stress {
+    fn default() -> Stress {
+        Stress {
+            width: WIDTH.to_string(),
+            height: HEIGHT.to_string(),
+            mines: MINES.to_string(),
+            board: None,
+            timings: None,
+            error: None,
+            ran: None,
+            render_from: None,
+        }
+    }
+}
+
+impl Stress {
+    pub fn set_size(&mut self, width: usize, height: usize, mines: usize) {
+        self.width = width.to_string();
+        self.height = height.to_string();
+        self.mines = mines.to_string();
+    }
+
+    // The size as typed, if it's three whole numbers.
+    pub fn size(&self) -> Option<(usize, usize, usize)> {
+        Some((
+            self.width.trim().parse().ok()?,
+            self.height.trim().parse().ok()?,
+            self.mines.trim().parse().ok()?,
+        ))
+    }
+
+    // Builds the board and opens its first cascade, leaving the render to
+    // be timed by `rendered`.
+    pub fn run(&mut self, robot: &Robot) {
+        self.board = None;
+        self.timings = None;
+        self.error = None;
+        let (width, height, mines) = match self.size() {
+            Some(size) => size,
+            None => return self.error = Some(String::from("Sizes are whole numbers")),
+        };
+        let started = stopwatch::now();
+        let board = match BoardBuilder::new(width, height)
+            .mine_count(mines)
+            .adjacency(Adjacency::Knight)
+            .build()
+        {
+            Ok(board) => board,
+            Err(e) => return self.error = Some(format!("That board can't be built: {:?}", e)),
+        };
+        let generation_ms = stopwatch::now() - started;
+        // The biggest cascade there is, which is the one to time.
+        let start = board.opening_start().or_else(|| board.pick_safe_cell(0));
+        let started = stopwatch::now();
+        let opened = match start.and_then(|p| board.apply(&Move::Open(p))) {
+            Some(opened) => opened,
+            None => return self.error = Some(String::from("There's no safe cell to open")),
+        };
+        let cascade_ms = stopwatch::now() - started;
+        let solver_ms = if robot.is_available() {
+            let started = stopwatch::now();
+            robot.next_move(&opened);
+            Some(stopwatch::now() - started)
+        } else {
+            None
+        };
+        self.timings = Some(Timings {
+            generation_ms,
+            cascade_ms,
+            opened: opened.stats().opened,
+            render_ms: None,
+            solver_ms,
+        });
+        self.board = Some(opened);
+        self.ran = Some((width, height, mines));
+        self.render_from = Some(stopwatch::now());
+    }
+
+    // Called the first chance after the board's on the page.
+    pub fn rendered(&mut self) {
+        if let (Some(from), Some(timings)) = (self.render_from.take(), &mut self.timings) {
+            timings.render_ms = Some(stopwatch::now() - from);
+        }
+    }
+
+    // One "Name: 12.345 ms" line per step.
+    pub fn report(&self) -> Vec<String> {
+        let timings = match &self.timings {
+            Some(timings) => timings,
+            None => return vec![],
+        };
+        let ms = |ms: Option<f64>, missing: &str| match ms {
+            Some(ms) => format!("{:.3} ms", ms),
+            None => missing.to_string(),
+        };
+        vec![
+            format!("Generation: {:.3} ms", timings.generation_ms),
+            format!(
+                "First cascade: {:.3} ms, {} cells",
+                timings.cascade_ms, timings.opened
+            ),
+            format!("Render: {}", ms(timings.render_ms, "…")),
+            format!(
+                "Solver step: {}",
+                ms(timings.solver_ms, "no 🤖 in this build")
+            ),
+        ]
+    }
+}
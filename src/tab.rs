@@ -0,0 +1,119 @@
+use std::cell::RefCell;
+
+use js_sys::Date;
+use lib_minesweeper::Board;
+use lib_minesweeper::BoardState::{self, Failed, NotReady, Playing, Ready, Won};
+
+const NAME: &str = "Minesweeper";
+
+// What the browser tab shows about the game in progress.
+struct Tab {
+    state: BoardState,
+    mines_left: i32,
+    started_at: Option<f64>,
+    stopped_at: Option<f64>,
+    title: String,
+    icon: &'static str,
+}
+
+thread_local! {
+    static TAB: RefCell<Option<Tab>> = const { RefCell::new(None) };
+}
+
+// Follows the board through a game, starting the clock on the first move
+// and stopping it when the game ends.
+pub fn board_changed(board: &Board) {
+    TAB.with(|tab| {
+        let mut tab = tab.borrow_mut();
+        let (started_at, stopped_at) = match (tab.as_ref(), &board.state) {
+            (_, Ready) | (_, NotReady) => (None, None),
+            (Some(t), Playing) if t.started_at.is_some() => (t.started_at, None),
+            (_, Playing) => (Some(Date::now()), None),
+            (Some(t), _) if t.stopped_at.is_some() => (t.started_at, t.stopped_at),
+            (Some(t), _) => (t.started_at.or(Some(Date::now())), Some(Date::now())),
+            (None, _) => (None, None),
+        };
+        let (title, icon) = tab
+            .as_ref()
+            .map_or((String::new(), ""), |t| (t.title.clone(), t.icon));
+        *tab = Some(Tab {
+            state: board.state.clone(),
+            mines_left: board.stats().mines_remaining,
+            started_at,
+            stopped_at,
+            title,
+            icon,
+        });
+    });
+    tick();
+}
+
+// Keeps the elapsed time in the title current. Only touches the page when
+// something changed.
+pub fn tick() {
+    TAB.with(|tab| {
+        let mut tab = tab.borrow_mut();
+        let tab = match tab.as_mut() {
+            Some(tab) => tab,
+            None => return,
+        };
+        let seconds = match tab.started_at {
+            Some(started_at) => {
+                ((tab.stopped_at.unwrap_or_else(Date::now) - started_at) / 1000_f64).round()
+            }
+            None => 0_f64,
+        };
+        let (title, icon) = match tab.state {
+            Won => (format!("🏆 Won in {}s · {}", seconds, NAME), "🏆"),
+            Failed => (format!("💣 Lost · {}", NAME), "💣"),
+            Playing => (
+                format!("⏱️ {} · 🚩 {} · {}", seconds, tab.mines_left, NAME),
+                "♞",
+            ),
+            Ready | NotReady => (format!("🚩 {} · {}", tab.mines_left, NAME), "♞"),
+        };
+        let document = match web_sys::window().and_then(|w| w.document()) {
+            Some(document) => document,
+            None => return,
+        };
+        if title != tab.title {
+            document.set_title(&title);
+            tab.title = title;
+        }
+        if icon != tab.icon {
+            set_icon(&document, icon);
+            tab.icon = icon;
+        }
+    });
+}
+
+// The favicon is the emoji drawn in an SVG.
+fn set_icon(document: &web_sys::Document, emoji: &str) {
+    let svg = format!(
+        "<svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 100 100'>\
+         <text y='.9em' font-size='90'>{}</text></svg>",
+        emoji
+    );
+    let href = format!(
+        "data:image/svg+xml,{}",
+        String::from(js_sys::encode_uri_component(&svg))
+    );
+    let link = match document.query_selector("link[rel~='icon']") {
+        Ok(Some(link)) => link,
+        _ => {
+            let link = match document.create_element("link") {
+                Ok(link) => link,
+                Err(_) => return,
+            };
+            let _ = link.set_attribute("rel", "icon");
+            match document.query_selector("head") {
+                Ok(Some(head)) => {
+                    let _ = head.append_child(&link);
+                }
+                _ => return,
+            }
+            link
+        }
+    };
+    let _ = link.set_attribute("href", &href);
+}
@@ -0,0 +1,43 @@
+use std::time::Duration;
+
+use yew::callback::Callback;
+use yew::services::timeout::{TimeoutService, TimeoutTask};
+
+const SHOWN_FOR: Duration = Duration::from_secs(3);
+
+pub struct Toast {
+    pub id: u64,
+    pub text: String,
+    _dismiss: TimeoutTask,
+}
+
+// Short messages shown over the game for a few seconds, e.g. to say why a
+// click didn't do anything.
+#[derive(Default)]
+pub struct Toasts {
+    next_id: u64,
+    shown: Vec<Toast>,
+}
+
+impl Toasts {
+    // `dismiss` gets called with the toast's id once it's been shown for long
+    // enough.
+    pub fn show(&mut self, text: &str, dismiss: Callback<u64>) {
+        let id = self.next_id;
+        self.next_id += 1;
+        let task = TimeoutService::new().spawn(SHOWN_FOR, dismiss.reform(move |_| id));
+        self.shown.push(Toast {
+            id,
+            text: text.to_string(),
+            _dismiss: task,
+        });
+    }
+
+    pub fn dismiss(&mut self, id: u64) {
+        self.shown.retain(|t| t.id != id);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Toast> {
+        self.shown.iter()
+    }
+}
@@ -0,0 +1,50 @@
+use lib_minesweeper::Replay;
+use yew::prelude::*;
+
+// How long a cell's tint takes to fade, for timed games, and over how many
+// moves otherwise.
+const FADE_MS: f64 = 10_000_f64;
+const FADE_MOVES: usize = 10;
+
+// Tints the cells the first `step` moves opened, strongest for the latest
+// move and fading with each cell's age, so the path of play shows through
+// the knight's cascades. Drawn over the board the way the notes are.
+pub fn render_overlay(replay: &Replay, step: usize) -> Html {
+    let last = match step.min(replay.moves.len()).checked_sub(1) {
+        Some(last) => last,
+        None => return html! {},
+    };
+    let timed = replay.times.len() > last;
+    let freshness = |opened: usize| {
+        if timed {
+            1_f64 - (replay.times[last] - replay.times[opened]) / FADE_MS
+        } else {
+            1_f64 - (last - opened) as f64 / FADE_MOVES as f64
+        }
+    };
+    let cells: Vec<(usize, f64)> = replay
+        .opened_by(step)
+        .unwrap_or_default()
+        .into_iter()
+        .enumerate()
+        .filter_map(|(i, opened)| Some((i, freshness(opened?))))
+        .filter(|(_, fresh)| *fresh > 0_f64)
+        .collect();
+    html! {
+        <svg
+         id="trail-overlay"
+         viewBox=format!("0 0 {} {}", replay.width, replay.height)
+         preserveAspectRatio="none">
+            {
+                for cells.into_iter().map(|(i, fresh)| html! {
+                    <rect
+                     x=(i % replay.width).to_string()
+                     y=(i / replay.width).to_string()
+                     width="1"
+                     height="1"
+                     style=format!("fill-opacity: {:.2}", fresh * 0.6) />
+                })
+            }
+        </svg>
+    }
+}
@@ -0,0 +1,113 @@
+use lib_minesweeper::{Answer, Pattern};
+use serde_derive::{Deserialize, Serialize};
+
+use crate::storage::{restore_json, store_json, Migration, StorageBackend};
+
+pub const KEY: &str = "jgpaiva.minesweeper.trainer";
+const MIGRATIONS: &[Migration] = &[];
+
+// Questions until a pattern comes round again, by how many times in a row
+// it's been answered right. A missed one comes back after the next.
+const SPACING: [usize; 4] = [2, 4, 8, 16];
+
+// How the player's doing on one pattern, and when it's next asked.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Card {
+    pub pattern: Pattern,
+    pub streak: usize,
+    pub due: usize,
+    pub right: u32,
+    pub asked: u32,
+}
+
+// Spaced repetition over the patterns: whichever is due soonest is asked
+// next, and missing one brings it back soon.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Trainer {
+    pub asked: usize,
+    pub cards: Vec<Card>,
+}
+
+impl Default for Trainer {
+    fn default() -> Trainer {
+        let mut trainer = Trainer {
+            asked: 0,
+            cards: vec![],
+        };
+        trainer.add_missing();
+        trainer
+    }
+}
+
+impl Trainer {
+    // Patterns new since the trainer was stored are due straight away.
+    fn add_missing(&mut self) {
+        for pattern in Pattern::ALL.iter().copied() {
+            if !self.cards.iter().any(|c| c.pattern == pattern) {
+                self.cards.push(Card {
+                    pattern,
+                    streak: 0,
+                    due: self.asked,
+                    right: 0,
+                    asked: 0,
+                });
+            }
+        }
+    }
+
+    // Ties go to the pattern with the shortest run of right answers.
+    pub fn next(&self) -> Pattern {
+        self.cards
+            .iter()
+            .min_by_key(|c| (c.due, c.streak))
+            .map_or(Pattern::ALL[0], |c| c.pattern)
+    }
+
+    pub fn answer(&mut self, pattern: Pattern, right: bool) {
+        self.asked += 1;
+        let asked = self.asked;
+        let card = match self.cards.iter_mut().find(|c| c.pattern == pattern) {
+            Some(card) => card,
+            None => return,
+        };
+        card.asked += 1;
+        if right {
+            card.right += 1;
+            card.due = asked + SPACING[card.streak.min(SPACING.len() - 1)];
+            card.streak += 1;
+        } else {
+            card.streak = 0;
+            card.due = asked + 1;
+        }
+    }
+
+    // "satisfied number 4/5 · filled number 2/3 · …", over the patterns
+    // asked so far.
+    pub fn summary(&self) -> String {
+        self.cards
+            .iter()
+            .filter(|c| c.asked > 0)
+            .map(|c| format!("{} {}/{}", c.pattern.name(), c.right, c.asked))
+            .collect::<Vec<_>>()
+            .join(" · ")
+    }
+
+    pub fn restore(storage: &dyn StorageBackend) -> Trainer {
+        let mut trainer: Trainer = restore_json(storage, KEY, MIGRATIONS).unwrap_or_default();
+        trainer.add_missing();
+        trainer
+    }
+
+    pub fn store(&self, storage: &mut dyn StorageBackend) {
+        store_json(storage, KEY, MIGRATIONS, self)
+    }
+}
+
+// As the answer buttons put it.
+pub fn answer_name(answer: Answer) -> &'static str {
+    match answer {
+        Answer::Safe => "safe",
+        Answer::Mine => "a mine",
+        Answer::Unknown => "impossible to tell",
+    }
+}
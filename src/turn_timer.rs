@@ -0,0 +1,100 @@
+use lib_minesweeper::Board;
+use lib_minesweeper::MapElement::Mine;
+use lib_minesweeper::MapElementCellState::Closed;
+use lib_minesweeper::Point;
+use serde_derive::{Deserialize, Serialize};
+
+// The seconds a player can get for each move in a match, in the order ⏳
+// goes through them. 0 is no limit.
+pub const LIMITS: [u32; 4] = [0, 10, 20, 30];
+
+// What happens to a player who runs out of time: they dig a mine and lose,
+// or a random closed cell is dug for them.
+#[derive(Debug, PartialEq, Clone, Copy, Default, Serialize, Deserialize)]
+pub enum Expiry {
+    #[default]
+    Forfeit,
+    RandomMove,
+}
+
+impl Expiry {
+    pub fn next(self) -> Expiry {
+        match self {
+            Expiry::Forfeit => Expiry::RandomMove,
+            Expiry::RandomMove => Expiry::Forfeit,
+        }
+    }
+
+    pub fn emoji(self) -> &'static str {
+        match self {
+            Expiry::Forfeit => "🏳️",
+            Expiry::RandomMove => "🎲",
+        }
+    }
+
+    pub fn describe(self) -> &'static str {
+        match self {
+            Expiry::Forfeit => "running out of time loses",
+            Expiry::RandomMove => "running out of time digs a random cell",
+        }
+    }
+
+    // The cell dug for a player out of time. `pick` is any number, e.g. a
+    // random one. A forfeit with every mine flagged falls back to a random
+    // cell.
+    pub fn cell(self, board: &Board, pick: u64) -> Option<Point> {
+        let mine = (0..board.height)
+            .flat_map(|y| (0..board.width).map(move |x| Point::new(x, y)))
+            .find(|p| matches!(board.at(p), Some(Mine { state: Closed })));
+        match self {
+            Expiry::Forfeit => mine.or_else(|| board.pick_closed_cell(pick)),
+            Expiry::RandomMove => board.pick_closed_cell(pick),
+        }
+    }
+}
+
+// A match's clock: whose move it is, and the seconds they've got left for
+// it. Each player's time starts afresh when their move comes round, and
+// their timeouts are counted apart.
+pub struct TurnTimer {
+    pub limit: u32,
+    pub expiry: Expiry,
+    pub player: usize,
+    pub left: u32,
+    pub timeouts: [u32; 2],
+}
+
+impl TurnTimer {
+    // None for no limit.
+    pub fn new(limit: u32, expiry: Expiry) -> Option<TurnTimer> {
+        Some(TurnTimer {
+            limit,
+            expiry,
+            player: 0,
+            left: limit,
+            timeouts: [0, 0],
+        })
+        .filter(|_| limit > 0)
+    }
+
+    pub fn start(&mut self, player: usize) {
+        self.player = player;
+        self.left = self.limit;
+    }
+
+    // A second gone. Returns whether that was the player's last, counting
+    // it against them.
+    pub fn tick(&mut self) -> bool {
+        self.left = self.left.saturating_sub(1);
+        if self.left > 0 {
+            return false;
+        }
+        self.timeouts[self.player] += 1;
+        true
+    }
+
+    // "⏳ 7s 🏳️"
+    pub fn status(&self) -> String {
+        format!("⏳ {}s {}", self.left, self.expiry.emoji())
+    }
+}
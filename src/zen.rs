@@ -0,0 +1,29 @@
+use lib_minesweeper::Board;
+use lib_minesweeper::BoardState;
+
+// One board after another with no clock, for relaxing. Only counts what
+// was cleared, and only until zen mode is turned off or the page closes.
+#[derive(Default)]
+pub struct Zen {
+    pub boards: usize,
+    pub cells: usize,
+}
+
+impl Zen {
+    // Adds a finished board, won or not.
+    pub fn finish(&mut self, board: &Board) {
+        self.cells += board.stats().opened;
+        if board.state == BoardState::Won {
+            self.boards += 1;
+        }
+    }
+
+    // "🧘 3 boards · 412 cells", counting what's open on `board` so far.
+    pub fn status(&self, board: &Board) -> String {
+        let cells = match board.state {
+            BoardState::Ready | BoardState::Playing => self.cells + board.stats().opened,
+            _ => self.cells,
+        };
+        format!("🧘 {} boards · {} cells", self.boards, cells)
+    }
+}